@@ -0,0 +1,75 @@
+//!
+//! A game played on a thread of its own, with its guesses coming
+//! from a second thread and its events read on this one, which
+//! is how a GUI would use `GameThread`. Run it with
+//! `cargo run --example threaded`.
+//!
+//! Every event is checked to have come in the order the game
+//! had them, and the program panics if one didn't.
+//!
+
+use mastermind::mastermind::{ChannelEvent, GameThread};
+use mastermind::{Colour, GameConfig, GameEvent};
+use std::thread;
+
+fn main() {
+    let config = GameConfig {
+        max_tries: Some(3),
+        auto_max_tries: false,
+        ..GameConfig::default()
+    };
+    let (game, events) = GameThread::new_seeded(config, 42).expect("These settings make a game");
+
+    // The handle is moved to the thread doing the input, and the
+    // game itself is on another one again.
+    let feeder = thread::spawn(move || {
+        game.push_string_input("rbwy");
+        game.push_string_input("gx");
+        for colour in [Colour::Orange, Colour::Red, Colour::Blue].iter() {
+            game.step(*colour);
+        }
+        game.push_string_input("ywgo rbwy");
+        // Dropping the handle here ends the game, and with it the
+        // events once they've all been read.
+    });
+
+    let mut position = 0;
+    let mut after_game = false;
+    let mut errors = 0;
+    for event in events {
+        print_event(&event);
+        match event {
+            Ok(GameEvent::PegAccepted { position: x }) => {
+                assert!(!after_game, "A peg came before the next game");
+                assert_eq!(x, position, "The pegs came out of order");
+                position += 1;
+            }
+            Ok(GameEvent::GuessEvaluated { .. }) => {
+                assert_eq!(position, 4, "A guess was scored before it was full");
+                position = 0;
+            }
+            Ok(GameEvent::GameWon) | Ok(GameEvent::GameLost { .. }) => {
+                assert_eq!(position, 0, "A game ended partway through a guess");
+                after_game = true;
+            }
+            Ok(GameEvent::NewGameStarted) => {
+                assert!(after_game, "A new game started before the last one ended");
+                after_game = false;
+            }
+            Err(e) => {
+                assert_eq!(e.buffered, position, "The error lost track of the guess");
+                errors += 1;
+            }
+        }
+    }
+    feeder.join().expect("The feeding thread panicked");
+    assert_eq!(errors, 1, "Only the `x` should have been turned down");
+    println!("Every event came in order");
+}
+
+fn print_event(event: &ChannelEvent) {
+    match event {
+        Ok(x) => println!("{:?}", x),
+        Err(e) => println!("Turned down: {}", e),
+    }
+}
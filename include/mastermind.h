@@ -0,0 +1,101 @@
+/*
+ * The C interface to mastermind, built with
+ * `cargo build --release --features ffi`, which puts the
+ * library in target/release (libmastermind.so on Linux).
+ *
+ * This matches src/mastermind/ffi.rs, and can be generated
+ * again from it with `cbindgen --config cbindgen.toml`.
+ *
+ * Colours are numbered: 0 red, 1 blue, 2 white, 3 yellow,
+ * 4 green, 5 orange, 6 purple, 7 pink. 8 is a blank. Games
+ * made from C only use the first six, so only mm_score accepts
+ * purple, pink and blanks.
+ */
+
+#ifndef MASTERMIND_H
+#define MASTERMIND_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+typedef enum MmStatus {
+  MmStatus_Ok = 0,
+  MmStatus_Won = 1,
+  MmStatus_Lost = 2,
+  MmStatus_NullPointer = 3,
+  MmStatus_InvalidColour = 4,
+  MmStatus_WrongLength = 5,
+  MmStatus_DuplicateColour = 6,
+  MmStatus_OutOfRange = 7,
+  MmStatus_Panic = 8,
+  MmStatus_Pending = 9,
+} MmStatus;
+
+typedef struct MmGame MmGame;
+
+/*
+ * max_tries is 0 for unlimited tries, and allow_duplicates is
+ * 0 for no and anything else for yes.
+ */
+typedef struct MmSettings {
+  size_t pegs;
+  uint8_t allow_duplicates;
+  size_t max_tries;
+} MmSettings;
+
+typedef struct MmFeedback {
+  size_t correct_position;
+  size_t correct_colour;
+} MmFeedback;
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Null if the settings are invalid. Free with mm_free_game. */
+MmGame *mm_new_game(const MmSettings *settings);
+
+/* The same secrets every time for the same seed and settings. */
+MmGame *mm_new_seeded_game(const MmSettings *settings, uint64_t seed);
+
+/* Won and Lost mean the next game has already started. */
+MmStatus mm_guess(MmGame *game, const uint8_t *pegs, size_t len, MmFeedback *out);
+
+/* OutOfRange until a game has finished. */
+MmStatus mm_last_secret(const MmGame *game, uint8_t *pegs, size_t len);
+
+MmStatus mm_score(const uint8_t *secret, const uint8_t *guess, size_t len, MmFeedback *out);
+
+size_t mm_board_len(const MmGame *game);
+
+MmStatus mm_board(const MmGame *game, size_t index, uint8_t *pegs, size_t len, MmFeedback *out);
+
+void mm_free_game(MmGame *game);
+
+/*
+ * A guess a peg at a time, on the same games as above. These
+ * give back an MmStatus as an int.
+ */
+
+/* The same as mm_new_game. Free with mastermind_free. */
+MmGame *mastermind_new(const MmSettings *config);
+
+/* Pending until the guess is full, then the same as mm_guess. */
+int mastermind_push(MmGame *state, char colour);
+
+/* OutOfRange if the guess being entered is empty. */
+int mastermind_undo(MmGame *state);
+
+/* The feedback of the last guess scored, OutOfRange before one. */
+int mastermind_feedback(const MmGame *state, size_t *out_exact, size_t *out_misplaced);
+
+/* The secret of the game being played, WrongLength if len is too short. */
+int mastermind_secret(const MmGame *state, uint8_t *out_buf, size_t len);
+
+void mastermind_free(MmGame *state);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,101 @@
+/*
+ * A Java wrapper around the game, using the native methods in
+ * src/mastermind/java.rs. To build and run the smoke test in
+ * `main` (From the root of the repository):
+ *
+ *     cargo build --release --features java
+ *     javac -d target/java java/Mastermind.java
+ *     java -Djava.library.path=target/release -cp target/java Mastermind
+ *
+ * `cargo test --features java --test java` does the same, with
+ * whichever JDK is on the `PATH`. It fails if anything in `main`
+ * goes wrong, which throws an `AssertionError`.
+ *
+ * Results come back as JSON strings, which keeps this class
+ * free of any dependencies.
+ */
+public final class Mastermind implements AutoCloseable {
+    static {
+        System.loadLibrary("mastermind");
+    }
+
+    private static native long newGame(int pegs, boolean duplicates, int maxTries);
+
+    private static native String guess(long game, String code);
+
+    private static native String board(long game);
+
+    private static native void freeGame(long game);
+
+    /** The pointer to the game on the rust side, or 0 once closed. */
+    private long game;
+
+    /**
+     * Starts a game. `maxTries` is 0 for unlimited tries.
+     *
+     * @throws IllegalArgumentException if the settings are invalid
+     */
+    public Mastermind(int pegs, boolean duplicates, int maxTries) {
+        game = newGame(pegs, duplicates, maxTries);
+    }
+
+    /**
+     * Makes a guess, like "rbgy", and returns its feedback as
+     * JSON with the fields `correct_position`, `correct_colour`
+     * and `outcome` ("in_progress", "won" or "lost"). Once a game
+     * is over, the next one starts.
+     *
+     * @throws IllegalArgumentException if the guess isn't allowed
+     */
+    public String guess(String code) {
+        return guess(game, code);
+    }
+
+    /** Where the game stands, as JSON. */
+    public String board() {
+        return board(game);
+    }
+
+    @Override
+    public void close() {
+        freeGame(game);
+        game = 0;
+    }
+
+    /** A quick check that everything is wired up. */
+    public static void main(String[] args) {
+        try (Mastermind game = new Mastermind(4, false, 10)) {
+            check(game.guess("rbgy"), "\"outcome\":");
+            check(game.guess("owyg"), "\"correct_position\":");
+            check(game.board(), "\"attempts_made\":2");
+            rejects(game, "rrgy");
+            rejects(game, "rbg");
+            // Not a colour, and not ASCII either
+            rejects(game, "rbg\u00e9");
+            rejects(game, null);
+        }
+        try {
+            new Mastermind(9, false, 10);
+            throw new AssertionError("Invalid settings were accepted");
+        } catch (IllegalArgumentException e) {
+            System.out.println("Rejected as expected: " + e.getMessage());
+        }
+        System.out.println("All good");
+    }
+
+    private static void check(String json, String expected) {
+        System.out.println(json);
+        if (!json.contains(expected)) {
+            throw new AssertionError("Expected " + expected + " in " + json);
+        }
+    }
+
+    private static void rejects(Mastermind game, String code) {
+        try {
+            game.guess(code);
+            throw new AssertionError("The guess " + code + " was accepted");
+        } catch (IllegalArgumentException e) {
+            System.out.println("Rejected as expected: " + e.getMessage());
+        }
+    }
+}
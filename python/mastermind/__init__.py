@@ -0,0 +1,237 @@
+"""
+Python bindings for mastermind, for playing with solver
+strategies from a notebook.
+
+These load the C interface (See include/mastermind.h), so the
+library has to be built first:
+
+    cargo build --release --features ffi
+
+The library is looked for in target/release next to this
+folder, or wherever the MASTERMIND_LIB environment variable
+points.
+
+    >>> game = Game(pegs=4, duplicates=False, max_tries=10, seed=1)
+    >>> game.guess("rbgy")
+    (1, 2)
+
+The tests are in tests/, and are run from this folder with
+`python -m pytest`.
+
+This isn't a pyo3 extension module, and there's no `python`
+cargo feature. pyo3 would make the crate need Python to build
+whenever the feature could be turned on, and an optional
+dependency still has to be fetched to build without it. Going
+through the C interface keeps the crate's dependencies as
+they are, and the package is plain Python which pip can
+install, rather than one built with maturin. The cost is that every
+call goes through ctypes, and the library is found at run time
+rather than being part of the package. If the bindings ever
+need speed (Calling `score` in a tight loop, say), moving to
+pyo3 behind a `python` feature would keep the same API.
+"""
+
+import ctypes
+import itertools
+import os
+import sys
+
+# In the same order as the colours in the rust code, so a
+# colour's index here is its number in the C interface.
+COLOURS = ["red", "blue", "white", "yellow", "green", "orange", "purple", "pink"]
+
+# The colours a game is played with. Purple and pink are only
+# for `score`, like the blank, which is number 8 and is written
+# "." or "blank".
+CLASSIC = COLOURS[:6]
+_BLANK = 8
+
+_OK, _WON, _LOST = 0, 1, 2
+_ERRORS = {
+    3: (ValueError, "a null pointer was passed"),
+    4: (ValueError, "not a colour"),
+    5: (ValueError, "wrong number of colours"),
+    6: (ValueError, "duplicates aren't allowed in this game"),
+    7: (IndexError, "out of range"),
+    8: (RuntimeError, "the game panicked"),
+}
+
+
+class _Settings(ctypes.Structure):
+    _fields_ = [
+        ("pegs", ctypes.c_size_t),
+        ("allow_duplicates", ctypes.c_uint8),
+        ("max_tries", ctypes.c_size_t),
+    ]
+
+
+class _Feedback(ctypes.Structure):
+    _fields_ = [
+        ("correct_position", ctypes.c_size_t),
+        ("correct_colour", ctypes.c_size_t),
+    ]
+
+
+def _library_path():
+    if "MASTERMIND_LIB" in os.environ:
+        return os.environ["MASTERMIND_LIB"]
+    name = {"win32": "mastermind.dll", "darwin": "libmastermind.dylib"}.get(
+        sys.platform, "libmastermind.so"
+    )
+    here = os.path.dirname(os.path.abspath(__file__))
+    return os.path.join(here, "..", "..", "target", "release", name)
+
+
+_lib = ctypes.CDLL(_library_path())
+_pegs = ctypes.POINTER(ctypes.c_uint8)
+_lib.mm_new_game.argtypes = [ctypes.POINTER(_Settings)]
+_lib.mm_new_game.restype = ctypes.c_void_p
+_lib.mm_new_seeded_game.argtypes = [ctypes.POINTER(_Settings), ctypes.c_uint64]
+_lib.mm_new_seeded_game.restype = ctypes.c_void_p
+_lib.mm_guess.argtypes = [ctypes.c_void_p, _pegs, ctypes.c_size_t, ctypes.POINTER(_Feedback)]
+_lib.mm_guess.restype = ctypes.c_int
+_lib.mm_last_secret.argtypes = [ctypes.c_void_p, _pegs, ctypes.c_size_t]
+_lib.mm_last_secret.restype = ctypes.c_int
+_lib.mm_score.argtypes = [_pegs, _pegs, ctypes.c_size_t, ctypes.POINTER(_Feedback)]
+_lib.mm_score.restype = ctypes.c_int
+_lib.mm_board_len.argtypes = [ctypes.c_void_p]
+_lib.mm_board_len.restype = ctypes.c_size_t
+_lib.mm_board.argtypes = [
+    ctypes.c_void_p,
+    ctypes.c_size_t,
+    _pegs,
+    ctypes.c_size_t,
+    ctypes.POINTER(_Feedback),
+]
+_lib.mm_board.restype = ctypes.c_int
+_lib.mm_free_game.argtypes = [ctypes.c_void_p]
+_lib.mm_free_game.restype = None
+
+
+def _check(status):
+    if status in _ERRORS:
+        kind, message = _ERRORS[status]
+        raise kind(message)
+    return status
+
+
+def _to_numbers(code):
+    """
+    Accepts a string of first letters ("rbgy") or a list of
+    names (["red", "blue", ...]). Purple and pink start with the
+    same letter, so they can only be given by name.
+    """
+    if isinstance(code, str):
+        names = []
+        for c in code:
+            if c in "._":
+                names.append("blank")
+                continue
+            found = [x for x in COLOURS if x[0] == c.lower()]
+            if len(found) > 1:
+                raise ValueError("%r could be %s" % (c, " or ".join(found)))
+            names.append(found[0] if found else None)
+    else:
+        names = [str(x).lower() for x in code]
+    numbers = []
+    for name in names:
+        if name == "blank":
+            numbers.append(_BLANK)
+        elif name in COLOURS:
+            numbers.append(COLOURS.index(name))
+        else:
+            raise ValueError("not a code: %r" % (code,))
+    return (ctypes.c_uint8 * len(numbers))(*numbers)
+
+
+def _to_names(numbers):
+    return ["blank" if x == _BLANK else COLOURS[x] for x in numbers]
+
+
+def score(secret, guess):
+    """
+    The feedback `guess` gets against `secret`, as the number
+    in the correct position and the number of the right colour.
+    """
+    secret, guess = _to_numbers(secret), _to_numbers(guess)
+    if len(secret) != len(guess):
+        raise ValueError("the secret and guess are different lengths")
+    out = _Feedback()
+    _check(_lib.mm_score(secret, guess, len(secret), ctypes.byref(out)))
+    return (out.correct_position, out.correct_colour)
+
+
+def all_codes(pegs=4, duplicates=False):
+    """
+    Every code a game with these settings could have, as lists
+    of names.
+    """
+    if duplicates:
+        codes = itertools.product(CLASSIC, repeat=pegs)
+    else:
+        codes = itertools.permutations(CLASSIC, pegs)
+    return [list(x) for x in codes]
+
+
+class Game:
+    """
+    One game. Once it's won or lost, `secret` shows what the
+    code was, and any further guesses start on the next game.
+
+    With a `seed`, the secrets are the same every time for the
+    same seed and settings, which is handy for comparing
+    strategies on the same codes.
+    """
+
+    def __init__(self, pegs=4, duplicates=False, max_tries=10, seed=None):
+        settings = _Settings(pegs, 1 if duplicates else 0, max_tries or 0)
+        if seed is None:
+            self._game = _lib.mm_new_game(ctypes.byref(settings))
+        else:
+            self._game = _lib.mm_new_seeded_game(ctypes.byref(settings), seed)
+        if not self._game:
+            raise ValueError("invalid settings")
+        self.pegs = pegs
+        self.over = False
+
+    def __del__(self):
+        if getattr(self, "_game", None):
+            _lib.mm_free_game(self._game)
+            self._game = None
+
+    def guess(self, code):
+        """
+        Makes a guess, and returns its feedback.
+        """
+        numbers = _to_numbers(code)
+        out = _Feedback()
+        status = _check(_lib.mm_guess(self._game, numbers, len(numbers), ctypes.byref(out)))
+        self.over = status in (_WON, _LOST)
+        self.won = status == _WON
+        return (out.correct_position, out.correct_colour)
+
+    @property
+    def board(self):
+        """
+        The guesses made so far this game, with their feedback.
+        """
+        rows = []
+        for idx in range(_lib.mm_board_len(self._game)):
+            pegs = (ctypes.c_uint8 * self.pegs)()
+            out = _Feedback()
+            _check(_lib.mm_board(self._game, idx, pegs, self.pegs, ctypes.byref(out)))
+            rows.append((_to_names(pegs), (out.correct_position, out.correct_colour)))
+        return rows
+
+    @property
+    def secret(self):
+        """
+        The code of the last game to finish, or `None` while the
+        first game is still going.
+        """
+        pegs = (ctypes.c_uint8 * self.pegs)()
+        status = _lib.mm_last_secret(self._game, pegs, self.pegs)
+        if status == 7:
+            return None
+        _check(status)
+        return _to_names(pegs)
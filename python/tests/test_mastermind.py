@@ -0,0 +1,98 @@
+"""
+Tests for the python bindings. The library has to be built
+first, with `cargo build --release --features ffi`.
+"""
+
+import pytest
+
+import mastermind
+from mastermind import COLOURS, Game, all_codes, score
+
+
+def test_colours_are_numbered_like_the_rust_code():
+    assert len(COLOURS) == 8
+    assert COLOURS[6:] == ["purple", "pink"]
+    assert mastermind.CLASSIC == COLOURS[:6]
+
+
+def test_score_accepts_letters_and_names():
+    assert score("rbwy", "rbwy") == (4, 0)
+    assert score("rbwy", "yrbw") == (0, 4)
+    assert score("rbwy", ["red", "blue", "Yellow", "white"]) == (2, 2)
+    assert score("rbgy", "rrrr") == (1, 0)
+
+
+def test_score_knows_every_colour_and_blanks():
+    assert score(["purple", "pink"], ["pink", "purple"]) == (0, 2)
+    assert score("r.", ["blank", "red"]) == (0, 2)
+
+
+def test_bad_codes_raise_value_error():
+    with pytest.raises(ValueError):
+        score("rbwx", "rbwy")
+    with pytest.raises(ValueError):
+        score("rbw", "rbwy")
+    with pytest.raises(ValueError):
+        # Purple or pink?
+        score("p", "r")
+    with pytest.raises(ValueError):
+        score(["red", "teal"], ["red", "blue"])
+
+
+def test_all_codes_uses_the_classic_colours():
+    codes = all_codes(pegs=4, duplicates=False)
+    assert len(codes) == 6 * 5 * 4 * 3
+    assert all(len(set(x)) == 4 for x in codes)
+    assert len(all_codes(pegs=4, duplicates=True)) == 6 ** 4
+    assert all(x in mastermind.CLASSIC for code in codes for x in code)
+
+
+def test_invalid_settings_raise_value_error():
+    with pytest.raises(ValueError):
+        Game(pegs=0)
+    with pytest.raises(ValueError):
+        Game(pegs=7, duplicates=False)
+
+
+def test_the_same_seed_gives_the_same_secrets():
+    secrets = []
+    for _ in range(2):
+        game = Game(max_tries=1, seed=42)
+        game.guess("rbwy")
+        assert game.over
+        secrets.append(game.secret)
+    assert secrets[0] == secrets[1]
+    assert len(secrets[0]) == 4
+
+
+def test_a_scripted_game():
+    # Seed 1 picks blue, red, orange, yellow first
+    game = Game(pegs=4, duplicates=False, max_tries=10, seed=1)
+    assert game.secret is None
+    assert game.guess("rbgy") == (1, 2)
+    assert not game.over
+    assert game.guess(["blue", "red", "white", "green"]) == (2, 0)
+    assert game.board == [
+        (["red", "blue", "green", "yellow"], (1, 2)),
+        (["blue", "red", "white", "green"], (2, 0)),
+    ]
+    with pytest.raises(ValueError):
+        # Not in this game's colours
+        game.guess(["blue", "red", "purple", "yellow"])
+    with pytest.raises(ValueError):
+        game.guess("rrby")
+    assert len(game.board) == 2
+    assert game.guess("broy") == (4, 0)
+    assert game.over and game.won
+    assert game.secret == ["blue", "red", "orange", "yellow"]
+    # The next game has started, with an empty board
+    assert game.board == []
+
+
+def test_a_lost_game_shows_the_secret():
+    game = Game(max_tries=2, seed=1)
+    game.guess("wgry")
+    assert not game.over
+    game.guess("wgro")
+    assert game.over and not game.won
+    assert game.secret == ["blue", "red", "orange", "yellow"]
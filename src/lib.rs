@@ -0,0 +1,44 @@
+//!
+//! The library half of the crate. `main.rs` is a thin program
+//! on top of this, and anything else which wants to play (Like
+//! a C++ GUI through the `ffi` feature) can link to it too.
+//!
+//! Without the `std` feature (Which is on unless it's turned
+//! off with `--no-default-features`) only `rules` is built, for
+//! programs which don't have an operating system underneath.
+//! That's checked by building for a microcontroller, which has
+//! no `std` to fall back on, which `tests/no_std.rs` does too:
+//! ```text
+//! rustup target add thumbv6m-none-eabi
+//! cargo build --lib --no-default-features --target thumbv6m-none-eabi
+//! ```
+//!
+
+// `no_std` leaves out the standard library, and `alloc` is the
+// part of it which only needs memory, for `Vec` and `String`.
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+///
+/// The rules of the game on their own, without `std`, see
+/// `rules.rs`.
+///
+pub mod rules;
+
+///
+/// Everything else lives in here, see `mastermind/mod.rs`.
+///
+#[cfg(feature = "std")]
+pub mod mastermind;
+
+//
+// What a game is played with, brought up to the top of the
+// crate so that another program only needs `use mastermind::State`
+// rather than `mastermind::mastermind::State`. The rest is still
+// there under `mastermind::mastermind` for anything more involved.
+//
+#[cfg(feature = "std")]
+pub use mastermind::{
+    Attempt, Colour, ConfigError, Feedback, GameConfig, GameEvent, GameRecord, GameSummary, Guess,
+    GuessError, InputError, MastermindError, Outcome, State, StateBuilder, TurnOutcome, COLOURS,
+};
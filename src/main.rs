@@ -8,44 +8,79 @@
 //!
 
 ///
-/// This is a module declaration, for the main mastermind
-/// implementation. In rust, the main module, referred to
-/// as `crate` (for example it can be used as `crate::main`
+/// This is an import of the main mastermind implementation.
+/// In rust, the main module of a crate is referred to as
+/// `crate` (for example it can be used as `crate::main`
 /// to call the main function below). It owns more modules
 /// each of which can own more modules, therefore creating
-/// bit of a module tree. In this case, `crate` owns one
-/// module, called `mastermind`.
+/// bit of a module tree.
 ///
-mod mastermind;
+/// The tree our game lives in belongs to the library crate
+/// (See `lib.rs`), which is also called `mastermind`, so that
+/// other programs can use it too. This program is one of
+/// them, and the library's `mastermind` module is brought in
+/// here under the same name.
+///
+use mastermind::mastermind;
 
 ///
 /// The main method, AKA the entrypoint of the program.
 /// This is a global function, because there is no state
 /// (Or static state) associated with the global entrypoint
 ///
-/// This function returns (Denoted with the arrow), a
+/// `mastermind::main` returns (Denoted with the arrow), a
 /// `Result<(), Box<dyn Error>>`. This means that it will
 /// return either nothing (On success, represented by the
 /// `()`, equivalent to Java's `void`) or, on an error,
-/// a boxed up `Error`, which essentially means, it will
-/// print an error should it fail to run.
+/// a boxed up `Error`.
 ///
 /// Returning a result is similar to Java's `throws Exception`,
-/// it allows us to propagate an error.
+/// it allows us to propagate an error. We catch it here and
+/// print it, much like a `try { } catch { }` around
+/// everything.
 ///
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() {
+    //
+    // If whoever was reading our output goes away (Like
+    // `mastermind stats | head -1`, once `head` has its line),
+    // `println!` panics, since it has nowhere to print. Nobody is
+    // left to read about it though, so we stop quietly instead.
+    // A panic "hook" is run whenever something panics, and
+    // `take_hook` gives back the normal one for everything else.
+    //
+    let report = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<String>()
+            .map_or("", |x| x.as_str());
+        if message.starts_with("failed printing to stdout") {
+            std::process::exit(0);
+        }
+        report(info)
+    }));
     //
-    // The `mastermind::main` function returns the same
-    // type as this function, and because the last statement
-    // of a block of code (AKA surrounded with curly braces
-    // `{}`) is returned from that block if it doesn't end
-    // with a semicolon
+    // `if let` is a `match` with only one branch we care
+    // about. In this case, we only do something if we
+    // got an `Err` back, and call the error inside of it `e`.
     //
-    mastermind::main()
+    if let Err(e) = mastermind::main() {
+        // The same goes for flushing, which gives an error
+        // rather than panicking
+        let closed = e
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|x| x.kind() == std::io::ErrorKind::BrokenPipe);
+        if closed {
+            std::process::exit(0);
+        }
+        eprintln!("Error: {}", e);
+        // Exit with a non-zero code so scripts know we failed
+        std::process::exit(1);
+    }
 }
 
 //
 // To continue please navigate to the mastermind directory
-// and read the `mod.rs` file. This is what we declared
+// and read the `mod.rs` file. This is what we imported
 // above.
 //
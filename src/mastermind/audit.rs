@@ -0,0 +1,623 @@
+//!
+//! `mastermind audit`, which makes lots of secrets the same way
+//! a game does and checks that they come out as evenly as they
+//! should.
+//!
+//! Every colour should be as likely as every other, both
+//! overall and in each position, whatever the settings are.
+//! With duplicates allowed, how often colours repeat within a
+//! code (Like two of one colour and one each of two others) is
+//! compared against the odds worked out by trying every code.
+//!
+//! Each comparison is a chi-squared test: the squared
+//! difference between what was counted and what was expected,
+//! over what was expected, added up. A fair generator only goes
+//! over the limit printed next to it about once in a thousand
+//! audits, so going over it is worth a closer look.
+//!
+//! Settings with colour weights or required colours change the
+//! odds, which are only worked out for weights in duplicates
+//! mode. The rest are counted but not tested.
+//!
+//! `--samples N` picks how many secrets to make, `--seed N`
+//! makes the same ones every time, `--pegs`, `--duplicates`,
+//! `--distinct-colours`, `--min-distinct-colours`,
+//! `--max-per-colour`, `--palette-size` and `--blanks` override
+//! the settings from the config file, and `--json` prints the
+//! results as JSON.
+//!
+
+use super::json::Json;
+use super::settings::{self, SettingsLayer};
+use super::{Colour, GameConfig, State};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+const DEFAULT_SAMPLES: usize = 10_000;
+
+//
+// How far above zero a standard normal value has to be to
+// happen once in a thousand tries, for working out the limits.
+//
+const Z: f64 = 3.09;
+
+//
+// Categories expected to come up fewer times than this are left
+// out of a test, the chi-squared test is no good at them.
+//
+const MIN_EXPECTED: f64 = 5.0;
+
+///
+/// What was counted from `samples` secrets.
+///
+struct Counts {
+    config: GameConfig,
+    samples: usize,
+    ///
+    /// How often each colour came up anywhere, indexed by
+    /// `Colour as usize`.
+    ///
+    overall: Vec<usize>,
+    ///
+    /// The same, but for each position.
+    ///
+    positions: Vec<Vec<usize>>,
+    ///
+    /// How often each pattern of repeats came up, where a
+    /// pattern is how many of each colour the code has, most
+    /// first; `[2, 1, 1]` is a pair and two others.
+    ///
+    patterns: BTreeMap<Vec<usize>, usize>,
+}
+
+///
+/// The result of one chi-squared test.
+///
+struct Test {
+    name: String,
+    chi_squared: f64,
+    limit: f64,
+}
+
+impl Test {
+    fn suspicious(&self) -> bool {
+        self.chi_squared > self.limit
+    }
+}
+
+fn sample(config: GameConfig, samples: usize, rng: &mut impl Rng) -> Counts {
+    // The palette is in `Colour as usize` order, blank last
+    let colours = config.palette().len();
+    let mut counts = Counts {
+        config,
+        samples,
+        overall: vec![0; colours],
+        positions: vec![vec![0; colours]; config.pegs],
+        patterns: BTreeMap::new(),
+    };
+    for _ in 0..samples {
+        let secret = State::generate_pegs_with(config, rng);
+        for (position, &colour) in secret.iter().enumerate() {
+            counts.overall[colour as usize] += 1;
+            counts.positions[position][colour as usize] += 1;
+        }
+        *counts.patterns.entry(pattern(&secret)).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn pattern(code: &[Colour]) -> Vec<usize> {
+    let mut colours = code.to_vec();
+    colours.sort_unstable();
+    colours.dedup();
+    let mut pattern = colours
+        .iter()
+        .map(|x| code.iter().filter(|y| *y == x).count())
+        .collect::<Vec<_>>();
+    pattern.sort_unstable_by(|a, b| b.cmp(a));
+    pattern
+}
+
+//
+// The chance of each pattern of repeats when every code allowed
+// by `config` is as likely as the others, found by going through
+// all of them (At most 7^7, which doesn't take long).
+//
+fn expected_patterns(config: GameConfig) -> BTreeMap<Vec<usize>, f64> {
+    let palette = config.palette();
+    let codes = (0..palette.len().pow(config.pegs as u32))
+        .map(|mut idx| {
+            (0..config.pegs)
+                .map(|_| {
+                    let colour = palette[idx % palette.len()];
+                    idx /= palette.len();
+                    colour
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|code| config.over_limit(code).is_none() && config.enough_colours(code))
+        .collect::<Vec<_>>();
+    let mut patterns = BTreeMap::new();
+    for code in &codes {
+        *patterns.entry(pattern(code)).or_insert(0.0) += 1.0 / codes.len() as f64;
+    }
+    patterns
+}
+
+//
+// Adds up one test over pairs of (Counted, expected), or gives
+// back `None` if too few categories are left to test.
+//
+fn chi_squared(name: String, cells: impl Iterator<Item = (usize, f64)>) -> Option<Test> {
+    let (chi_squared, categories) = cells
+        .filter(|&(_, expected)| expected >= MIN_EXPECTED)
+        .fold((0.0, 0), |(sum, count), (observed, expected)| {
+            let difference = observed as f64 - expected;
+            (sum + difference * difference / expected, count + 1)
+        });
+    if categories < 2 {
+        return None;
+    }
+    Some(Test {
+        name,
+        chi_squared,
+        limit: limit(categories - 1),
+    })
+}
+
+//
+// The value a chi-squared statistic with `df` degrees of
+// freedom only goes over once in a thousand tries, using the
+// Wilson-Hilferty approximation, which is close enough for
+// flagging things.
+//
+fn limit(df: usize) -> f64 {
+    let df = df as f64;
+    let spread = 2.0 / (9.0 * df);
+    df * (1.0 - spread + Z * spread.sqrt()).powi(3)
+}
+
+//
+// The chance of each peg being each colour, indexed by `Colour
+// as usize`, if it's simple enough to work out. Without weights
+// or required colours every colour is as likely as the others.
+// With weights, that's only easy to say when every peg is
+// picked from every colour on its own, in duplicates mode. A
+// blank is as likely as a colour with a weight of 1.
+//
+fn expected_chances(config: GameConfig) -> Option<Vec<f64>> {
+    if config.must_include.contains(&true) {
+        return None;
+    }
+    // Picking again when the code is over the limit for a colour,
+    // or has too few colours, keeps every colour as likely as the
+    // others, but with weights the picks are made another way
+    // (See `State::generate_weighted`).
+    let most = config.max_per_colour.unwrap_or(config.pegs);
+    let fewest = config.min_distinct_colours.unwrap_or(0);
+    match config.colour_weights {
+        None => {
+            let colours = config.palette().len();
+            Some(vec![1.0 / colours as f64; colours])
+        }
+        Some(weights)
+            if config.allow_duplicates
+                && config.max_distinct_colours.is_none()
+                && most >= config.pegs
+                && fewest <= 1 =>
+        {
+            let mut weights = weights.to_vec();
+            weights.truncate(config.palette_size.unwrap_or(weights.len()));
+            if config.allow_blanks {
+                weights.push(1);
+            }
+            let total = weights.iter().sum::<u32>() as f64;
+            Some(weights.iter().map(|&x| x as f64 / total).collect())
+        }
+        Some(_) => None,
+    }
+}
+
+fn tests(counts: &Counts) -> Vec<Test> {
+    let mut tests = Vec::new();
+    let chances = match expected_chances(counts.config) {
+        Some(x) => x,
+        None => return tests,
+    };
+    let samples = counts.samples as f64;
+    let pegs = counts.config.pegs as f64;
+    tests.extend(chi_squared(
+        "Colours overall".into(),
+        counts
+            .overall
+            .iter()
+            .zip(&chances)
+            .map(|(&x, chance)| (x, samples * pegs * chance)),
+    ));
+    for (position, colours) in counts.positions.iter().enumerate() {
+        tests.extend(chi_squared(
+            format!("Colours in position {}", position + 1),
+            colours
+                .iter()
+                .zip(&chances)
+                .map(|(&x, chance)| (x, samples * chance)),
+        ));
+    }
+    // Limiting the colours in use changes the odds of repeats,
+    // and so do weights, so only plain duplicates mode has odds
+    // to compare with. A limit on each colour just leaves out
+    // the patterns which go over it.
+    if counts.config.allow_duplicates
+        && counts.config.max_distinct_colours.is_none()
+        && counts.config.colour_weights.is_none()
+    {
+        tests.extend(chi_squared(
+            "Repeats".into(),
+            expected_patterns(counts.config)
+                .into_iter()
+                .map(|(pattern, chance)| {
+                    (
+                        counts.patterns.get(&pattern).copied().unwrap_or(0),
+                        chance * counts.samples as f64,
+                    )
+                }),
+        ));
+    }
+    tests
+}
+
+fn pattern_name(pattern: &[usize]) -> String {
+    pattern
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn print_report(counts: &Counts, tests: &[Test], seed: u64) {
+    let percent = |x: usize, of: usize| 100.0 * x as f64 / of.max(1) as f64;
+    println!(
+        "{} secrets with {} pegs, duplicates {}, seed {}",
+        counts.samples,
+        counts.config.pegs,
+        if counts.config.allow_duplicates {
+            "allowed"
+        } else {
+            "not allowed"
+        },
+        seed
+    );
+    let chances = expected_chances(counts.config);
+    print!("\n{:<8} {:>8}", "Colour", "Overall");
+    for position in 1..=counts.config.pegs {
+        print!(" {:>7}", format!("Pos {}", position));
+    }
+    println!(" {:>8}", "Expected");
+    for (idx, colour) in counts.config.palette().iter().enumerate() {
+        print!(
+            "{:<8} {:>7.2}%",
+            format!("{:?}", colour),
+            percent(counts.overall[idx], counts.samples * counts.config.pegs)
+        );
+        for position in &counts.positions {
+            print!(" {:>6.2}%", percent(position[idx], counts.samples));
+        }
+        match &chances {
+            Some(chances) => println!(" {:>7.2}%", 100.0 * chances[idx]),
+            None => println!(" {:>8}", "?"),
+        }
+    }
+    if chances.is_none() {
+        println!("\nThe odds of each colour are too fiddly to work out with these settings, so there's nothing to test against.");
+    }
+    if counts.config.allow_duplicates {
+        println!("\nRepeats  (How many of each colour in a code)");
+        for (pattern, &count) in &counts.patterns {
+            println!(
+                "{:<12} {:>6.2}%",
+                pattern_name(pattern),
+                percent(count, counts.samples)
+            );
+        }
+    }
+    println!();
+    for test in tests {
+        println!(
+            "{:<22} chi-squared {:>8.2}, limit {:>6.2}{}",
+            test.name,
+            test.chi_squared,
+            test.limit,
+            if test.suspicious() {
+                "  <- badly off!"
+            } else {
+                ""
+            }
+        );
+    }
+}
+
+//
+// JSON only has whole numbers here, so this has the counts
+// rather than percentages, and the test results rounded.
+//
+fn report_json(counts: &Counts, tests: &[Test], seed: u64) -> Json {
+    Json::object(vec![
+        ("samples", counts.samples.into()),
+        ("seed", seed.into()),
+        ("settings", Json::from_serde(&counts.config)),
+        (
+            "colours",
+            Json::Array(
+                counts
+                    .config
+                    .palette()
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, colour)| {
+                        Json::object(vec![
+                            ("colour", colour.name().into()),
+                            ("overall", counts.overall[idx].into()),
+                            (
+                                "positions",
+                                Json::Array(
+                                    counts.positions.iter().map(|x| x[idx].into()).collect(),
+                                ),
+                            ),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "repeats",
+            Json::Array(
+                counts
+                    .patterns
+                    .iter()
+                    .map(|(pattern, &count)| {
+                        Json::object(vec![
+                            ("pattern", pattern_name(pattern).into()),
+                            ("count", count.into()),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "tests",
+            Json::Array(
+                tests
+                    .iter()
+                    .map(|test| {
+                        Json::object(vec![
+                            ("name", test.name.as_str().into()),
+                            ("chi_squared", (test.chi_squared.round() as usize).into()),
+                            ("limit", (test.limit.round() as usize).into()),
+                            ("suspicious", test.suspicious().into()),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut samples = DEFAULT_SAMPLES;
+    let mut seed = None;
+    let mut json = false;
+    let mut given = SettingsLayer::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--samples" => {
+                samples = args
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .ok_or("`--samples` needs a number")?
+            }
+            "--seed" => {
+                seed = Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--seed` needs a number")?,
+                )
+            }
+            "--pegs" => {
+                given.pegs = Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--pegs` needs a number")?,
+                )
+            }
+            "--duplicates" => {
+                given.allow_duplicates = Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--duplicates` needs `true` or `false`")?,
+                )
+            }
+            "--blanks" => given.allow_blanks = Some(true),
+            "--palette-size" => {
+                given.palette_size = Some(Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--palette-size` needs a number")?,
+                ))
+            }
+            "--min-distinct-colours" => {
+                given.min_distinct_colours = Some(Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--min-distinct-colours` needs a number")?,
+                ))
+            }
+            "--max-per-colour" => {
+                given.max_per_colour = Some(Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--max-per-colour` needs a number")?,
+                ))
+            }
+            "--distinct-colours" => {
+                given.max_distinct_colours = Some(Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--distinct-colours` needs a number")?,
+                ))
+            }
+            "--json" => json = true,
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    let config = settings::resolve(settings::load_config_file()?, None, given);
+    config.validate()?;
+    // Without `--seed` one is picked, and printed, so that a run
+    // which looks off can be made again.
+    let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let counts = sample(config, samples, &mut StdRng::seed_from_u64(seed));
+    let tests = tests(&counts);
+    if json {
+        println!("{}", report_json(&counts, &tests, seed));
+    } else {
+        print_report(&counts, &tests, seed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts `secrets` the same way `sample` counts the ones it
+    // makes.
+    fn count(config: GameConfig, secrets: impl Iterator<Item = Vec<Colour>>) -> Counts {
+        let colours = config.palette().len();
+        let mut counts = Counts {
+            config,
+            samples: 0,
+            overall: vec![0; colours],
+            positions: vec![vec![0; colours]; config.pegs],
+            patterns: BTreeMap::new(),
+        };
+        for secret in secrets {
+            counts.samples += 1;
+            for (position, &colour) in secret.iter().enumerate() {
+                counts.overall[colour as usize] += 1;
+                counts.positions[position][colour as usize] += 1;
+            }
+            *counts.patterns.entry(pattern(&secret)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn duplicates() -> GameConfig {
+        GameConfig {
+            allow_duplicates: true,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn patterns_count_repeats_most_first() {
+        use Colour::*;
+        assert_eq!(pattern(&[Red, Blue, Red, Green]), vec![2, 1, 1]);
+        assert_eq!(pattern(&[Blue, Blue, Blue, Blue]), vec![4]);
+        assert_eq!(pattern(&[Red, Blue, White, Green]), vec![1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn expected_patterns_are_the_odds_of_each() {
+        let patterns = expected_patterns(duplicates());
+        let total = patterns.values().sum::<f64>();
+        assert!((total - 1.0).abs() < 1e-9);
+        // 6 × 5 × 4 × 3 of the 6^4 codes have no repeats
+        let none = patterns[&vec![1, 1, 1, 1]];
+        assert!((none - 360.0 / 1296.0).abs() < 1e-9);
+        assert!((patterns[&vec![4]] - 6.0 / 1296.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn limits_match_the_chi_squared_tables() {
+        // Values for one in a thousand, from the tables
+        for &(df, table) in &[(1, 10.83), (5, 20.52), (10, 29.59), (35, 66.62)] {
+            let approximation = limit(df);
+            assert!(
+                (approximation - table).abs() < 0.6,
+                "{} for {}",
+                approximation,
+                df
+            );
+        }
+    }
+
+    #[test]
+    fn fair_secrets_pass_every_test() {
+        let configs = [
+            GameConfig::default(),
+            duplicates(),
+            GameConfig {
+                pegs: 5,
+                palette_size: Some(8),
+                ..duplicates()
+            },
+            // Which colours are in use is picked first here
+            GameConfig {
+                pegs: 5,
+                max_distinct_colours: Some(3),
+                ..duplicates()
+            },
+        ];
+        for &config in &configs {
+            let counts = sample(config, 20_000, &mut StdRng::seed_from_u64(1));
+            let tests = tests(&counts);
+            let repeats = config.allow_duplicates && config.max_distinct_colours.is_none();
+            assert_eq!(tests.len(), config.pegs + 1 + repeats as usize);
+            for test in &tests {
+                assert!(!test.suspicious(), "{} with {:?}", test.name, config);
+            }
+        }
+    }
+
+    #[test]
+    fn the_same_seed_makes_the_same_secrets() {
+        let config = duplicates();
+        let first = sample(config, 1000, &mut StdRng::seed_from_u64(9));
+        let second = sample(config, 1000, &mut StdRng::seed_from_u64(9));
+        assert_eq!(first.positions, second.positions);
+        assert_eq!(first.patterns, second.patterns);
+    }
+
+    #[test]
+    fn modulo_bias_is_flagged() {
+        // Taking a random number from 0 to 31 modulo 6 picks the
+        // first two colours a little more often than the rest
+        let config = duplicates();
+        let palette = config.palette();
+        let mut rng = StdRng::seed_from_u64(1);
+        let biased = (0..20_000).map(|_| {
+            (0..config.pegs)
+                .map(|_| palette[(rng.gen::<u8>() >> 3) as usize % palette.len()])
+                .collect()
+        });
+        let tests = tests(&count(config, biased));
+        assert!(tests
+            .iter()
+            .any(|x| x.name == "Colours overall" && x.suspicious()));
+    }
+
+    #[test]
+    fn settings_with_fiddly_odds_are_not_tested() {
+        let config = GameConfig {
+            must_include: {
+                let mut must = GameConfig::default().must_include;
+                must[0] = true;
+                must
+            },
+            ..GameConfig::default()
+        };
+        assert!(expected_chances(config).is_none());
+        assert!(tests(&count(config, std::iter::empty())).is_empty());
+    }
+}
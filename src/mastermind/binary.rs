@@ -0,0 +1,219 @@
+//!
+//! A compact binary encoding of `Json` values, used for save
+//! files which would be large as text.
+//!
+//! It encodes the `Json` a save is, rather than having a layout
+//! of its own, and that `Json` comes from the serde derives of
+//! `StateSnapshot` (See `save.rs`). So a binary save has the
+//! same version, checksum and fields as a JSON one, and the two
+//! can't drift apart. Each value is a single tag byte followed
+//! by its contents:
+//!
+//! | Tag | Value  | Followed by                            |
+//! |-----|--------|----------------------------------------|
+//! | 0   | null   | Nothing                                |
+//! | 1   | false  | Nothing                                |
+//! | 2   | true   | Nothing                                |
+//! | 3   | number | A zigzag varint                        |
+//! | 4   | string | A varint length, then UTF-8 bytes      |
+//! | 5   | array  | A varint count, then that many values  |
+//! | 6   | object | A varint count, then string/value pairs|
+//!
+//! Varints store seven bits per byte, with the top bit set
+//! on every byte but the last.
+//!
+
+use super::json::{Json, MAX_DEPTH};
+
+///
+/// Every binary file starts with these bytes, which is how we
+/// tell it apart from a JSON file (Which starts with `{`).
+///
+pub const MAGIC: &[u8] = b"MMSV";
+
+///
+/// Encodes `value`, including the `MAGIC` header.
+///
+pub fn encode(value: &Json) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    write_value(&mut out, value);
+    out
+}
+
+///
+/// The opposite of `encode`.
+///
+pub fn decode(bytes: &[u8]) -> Result<Json, String> {
+    if !bytes.starts_with(MAGIC) {
+        return Err("missing the binary save header".into());
+    }
+    let mut reader = Reader {
+        bytes,
+        position: MAGIC.len(),
+        depth: 0,
+    };
+    let value = reader.value()?;
+    if reader.position != bytes.len() {
+        return Err(format!("unexpected data at byte {}", reader.position));
+    }
+    Ok(value)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_string(out: &mut Vec<u8>, text: &str) {
+    write_varint(out, text.len() as u64);
+    out.extend_from_slice(text.as_bytes());
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Json) {
+    match value {
+        Json::Null => out.push(0),
+        Json::Bool(false) => out.push(1),
+        Json::Bool(true) => out.push(2),
+        Json::Number(x) => {
+            out.push(3);
+            // Zigzag encoding keeps small negative numbers small
+            write_varint(out, ((x << 1) ^ (x >> 63)) as u64);
+        }
+        Json::String(x) => {
+            out.push(4);
+            write_string(out, x);
+        }
+        Json::Array(items) => {
+            out.push(5);
+            write_varint(out, items.len() as u64);
+            for item in items {
+                write_value(out, item);
+            }
+        }
+        Json::Object(pairs) => {
+            out.push(6);
+            write_varint(out, pairs.len() as u64);
+            for (key, value) in pairs {
+                write_string(out, key);
+                write_value(out, value);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    ///
+    /// How many arrays and objects we're inside of, which is
+    /// limited for the same reason as in `json.rs`.
+    ///
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or("unexpected end of data")?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("number is too long".into())
+    }
+
+    fn length(&mut self) -> Result<usize, String> {
+        let length = self.varint()? as usize;
+        // A length can never be more than the bytes we have
+        // left, this stops a corrupted file from making us
+        // allocate a huge list.
+        if length > self.bytes.len() - self.position {
+            return Err("length is longer than the data".into());
+        }
+        Ok(length)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let length = self.length()?;
+        let text = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        String::from_utf8(text.to_vec()).map_err(|_| "invalid UTF-8 in string".into())
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        match self.byte()? {
+            0 => Ok(Json::Null),
+            1 => Ok(Json::Bool(false)),
+            2 => Ok(Json::Bool(true)),
+            3 => {
+                let x = self.varint()?;
+                Ok(Json::Number(((x >> 1) as i64) ^ -((x & 1) as i64)))
+            }
+            4 => self.string().map(Json::String),
+            x @ 5 | x @ 6 => {
+                if self.depth == MAX_DEPTH {
+                    return Err("values are nested too deeply".into());
+                }
+                self.depth += 1;
+                let value = self.nested(x == 5);
+                self.depth -= 1;
+                value
+            }
+            x => Err(format!("unknown value tag {}", x)),
+        }
+    }
+
+    //
+    // The contents of an array, or of an object if it isn't one.
+    //
+    fn nested(&mut self, array: bool) -> Result<Json, String> {
+        let count = self.length()?;
+        if array {
+            (0..count)
+                .map(|_| self.value())
+                .collect::<Result<_, _>>()
+                .map(Json::Array)
+        } else {
+            (0..count)
+                .map(|_| Ok((self.string()?, self.value()?)))
+                .collect::<Result<_, String>>()
+                .map(Json::Object)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deep_nesting_is_an_error_rather_than_a_crash() {
+        // Arrays of one item, each inside the one before
+        let mut bytes = MAGIC.to_vec();
+        for _ in 0..4000 {
+            bytes.extend_from_slice(&[5, 1]);
+        }
+        bytes.push(0);
+        assert!(decode(&bytes).unwrap_err().contains("nested too deeply"));
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let value = Json::parse(r#"{"a":[1,-2,null,true,"text"],"b":{}}"#).unwrap();
+        assert_eq!(decode(&encode(&value)), Ok(value));
+    }
+}
@@ -0,0 +1,271 @@
+//!
+//! The guesses of a game laid out as a table, one guess to a
+//! row, with the colours lined up in columns and the feedback
+//! next to them:
+//! ```text
+//! #  Guess                        Exact  Misplaced
+//! 1  Red    Blue   White  Yellow      1          2
+//! 2  Green  Red    Orange White       0          3
+//! ```
+//! `format_history` does this for the game being played, and a
+//! `BoardFormatter` can be set up to show the feedback in words
+//! (See `wording.rs`), paint the colours, or hide the guesses.
+//!
+
+use super::wording::FeedbackStyle;
+use super::{Attempt, Colour, Feedback, State, Symbol};
+use std::fmt::Display;
+
+///
+/// Lays out guesses as a table, see the top of this file. It's
+/// set up like `StateBuilder`, with each setting taking the
+/// formatter and giving it back:
+/// ```
+/// let table = BoardFormatter::new().hide_guesses(true).format(&guesses);
+/// ```
+///
+pub struct BoardFormatter<'a, P = Colour> {
+    paint: Option<&'a Paint<'a, P>>,
+    feedback: Option<&'a FeedbackStyle>,
+    hide_guesses: bool,
+}
+
+///
+/// How `BoardFormatter::paint` writes a peg, given the peg and
+/// the text for it.
+///
+pub type Paint<'a, P> = dyn Fn(P, &str) -> String + 'a;
+
+impl<P> Default for BoardFormatter<'_, P> {
+    fn default() -> Self {
+        Self {
+            paint: None,
+            feedback: None,
+            hide_guesses: false,
+        }
+    }
+}
+
+impl<'a, P: Symbol + Display> BoardFormatter<'a, P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Writes each peg with `paint`, which is given the peg and
+    /// its name already padded out to the width of the column,
+    /// like `terminal::paint` to show it in its own colour.
+    ///
+    pub fn paint(mut self, paint: &'a Paint<'a, P>) -> Self {
+        self.paint = Some(paint);
+        self
+    }
+
+    ///
+    /// Writes the feedback in words, as one column, instead of
+    /// the two columns of numbers.
+    ///
+    pub fn feedback(mut self, style: &'a FeedbackStyle) -> Self {
+        self.feedback = Some(style);
+        self
+    }
+
+    ///
+    /// Leaves the guesses out and only shows their feedback, for
+    /// memory mode (See `GameConfig::memory`).
+    ///
+    pub fn hide_guesses(mut self, hide: bool) -> Self {
+        self.hide_guesses = hide;
+        self
+    }
+
+    ///
+    /// The table for `guesses`, oldest first, each with its
+    /// feedback if it has any (Like `GameSummary::guesses`). It
+    /// ends with a newline, and is only the heading if there are
+    /// no guesses.
+    ///
+    pub fn format(&self, guesses: &[(Vec<P>, Option<Feedback>)]) -> String {
+        // Every peg gets as much room as the longest name, so
+        // that the columns line up whatever the guesses are.
+        let width = P::all()
+            .iter()
+            .chain(P::blank().as_ref())
+            .map(|x| x.to_string().chars().count())
+            .max()
+            .unwrap_or(1);
+        let pegs = guesses.iter().map(|(x, _)| x.len()).max().unwrap_or(0);
+        let guess_width = if self.hide_guesses {
+            HIDDEN.len()
+        } else {
+            (pegs * (width + 1)).saturating_sub(1)
+        }
+        .max("Guess".len());
+        let number_width = guesses.len().to_string().len();
+
+        let mut table = format!("{:>1$}  {2:<3$}  ", "#", number_width, "Guess", guess_width);
+        table.push_str(match self.feedback {
+            Some(_) => "Feedback",
+            None => "Exact  Misplaced",
+        });
+        table.push('\n');
+        for (idx, (guess, feedback)) in guesses.iter().enumerate() {
+            let mut row = format!("{:>1$}  ", idx + 1, number_width);
+            let written = if self.hide_guesses {
+                row.push_str(HIDDEN);
+                HIDDEN.len()
+            } else {
+                for (at, &peg) in guess.iter().enumerate() {
+                    if at > 0 {
+                        row.push(' ');
+                    }
+                    let cell = format!("{:<1$}", peg.to_string(), width);
+                    match self.paint {
+                        Some(paint) => row.push_str(&paint(peg, &cell)),
+                        None => row.push_str(&cell),
+                    }
+                }
+                (guess.len() * (width + 1)).saturating_sub(1)
+            };
+            row.push_str(&" ".repeat(guess_width.saturating_sub(written) + 2));
+            match (feedback, self.feedback) {
+                (Some(feedback), Some(style)) => row.push_str(&style.describe(*feedback)),
+                (Some(feedback), None) => {
+                    row.push_str(&format!("{:>5}  {:>9}", feedback.exact, feedback.misplaced))
+                }
+                (None, _) => {}
+            }
+            table.push_str(row.trim_end());
+            table.push('\n');
+        }
+        table
+    }
+
+    ///
+    /// `format`, for guesses which have all been scored, like
+    /// those in a `GameRecord`.
+    ///
+    pub fn format_attempts(&self, attempts: &[Attempt<P>]) -> String {
+        let guesses = attempts
+            .iter()
+            .map(|x| (x.guess.to_vec(), Some(x.feedback)))
+            .collect::<Vec<_>>();
+        self.format(&guesses)
+    }
+}
+
+//
+// What's written in place of each guess when they're hidden.
+//
+const HIDDEN: &str = "(hidden)";
+
+///
+/// The guesses of the game `state` is playing so far, and their
+/// feedback, as a table, see the top of this file.
+///
+pub fn format_history<P: Symbol + Display>(state: &State<P>) -> String {
+    BoardFormatter::new().format_attempts(state.history())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::StateBuilder;
+    use Colour::*;
+
+    fn three_guesses() -> State<'static> {
+        let mut state = StateBuilder::new().build().unwrap();
+        state.set_secret(vec![Red, Blue, Green, White]);
+        for line in &["rbyw", "grow", "bryg"] {
+            state.push_string_input(line).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn colours_are_written_by_name() {
+        assert_eq!(Red.to_string(), "Red");
+        assert_eq!(Orange.to_string(), "Orange");
+        assert_eq!(format!("{:<7}|", Pink), "Pink   |");
+    }
+
+    #[test]
+    fn three_guess_game() {
+        assert_eq!(
+            format_history(&three_guesses()),
+            "\
+#  Guess                        Exact  Misplaced
+1  Red    Blue   Yellow White       3          0
+2  Green  Red    Orange White       1          2
+3  Blue   Red    Yellow Green       0          3
+"
+        );
+    }
+
+    #[test]
+    fn feedback_in_words() {
+        let state = three_guesses();
+        let style = FeedbackStyle::BullsCows;
+        assert_eq!(
+            BoardFormatter::new()
+                .feedback(&style)
+                .format_attempts(state.history()),
+            "\
+#  Guess                        Feedback
+1  Red    Blue   Yellow White   3 bulls and 0 cows
+2  Green  Red    Orange White   1 bull and 2 cows
+3  Blue   Red    Yellow Green   0 bulls and 3 cows
+"
+        );
+    }
+
+    #[test]
+    fn hidden_guesses() {
+        let state = three_guesses();
+        assert_eq!(
+            BoardFormatter::new()
+                .hide_guesses(true)
+                .format_attempts(state.history()),
+            "\
+#  Guess     Exact  Misplaced
+1  (hidden)      3          0
+2  (hidden)      1          2
+3  (hidden)      0          3
+"
+        );
+    }
+
+    #[test]
+    fn painted_pegs_keep_their_columns() {
+        let paint = |peg: Colour, cell: &str| format!("<{}{}>", peg.name().len(), cell);
+        let guesses = vec![(vec![Red, Blue], None)];
+        assert_eq!(
+            BoardFormatter::new().paint(&paint).format(&guesses),
+            "#  Guess          Exact  Misplaced\n1  <3Red   > <4Blue  >\n"
+        );
+    }
+
+    #[test]
+    fn no_guesses_is_only_the_heading() {
+        assert_eq!(
+            BoardFormatter::<Colour>::new().format(&[]),
+            "#  Guess  Exact  Misplaced\n"
+        );
+    }
+
+    #[test]
+    fn numbers_line_up_past_nine() {
+        let guess = (
+            vec![Red, Blue],
+            Some(Feedback {
+                exact: 1,
+                misplaced: 0,
+            }),
+        );
+        let table = BoardFormatter::new().format(&vec![guess; 10]);
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], " #  Guess          Exact  Misplaced");
+        assert_eq!(lines[1], " 1  Red    Blue        1          0");
+        assert_eq!(lines[10], "10  Red    Blue        1          0");
+    }
+}
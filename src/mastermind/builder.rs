@@ -0,0 +1,305 @@
+//!
+//! `StateBuilder`, for setting up a `State` one named setting at
+//! a time instead of with a row of positional arguments.
+//!
+//! `State::new(config, |_| {}, |_| {}, SilentOutput)` only says
+//! what each argument is by where it goes, and every new one
+//! makes that worse. The builder names each one as it's set:
+//! ```
+//! let state = StateBuilder::new()
+//!     .pegs(5)
+//!     .allow_duplicates(true)
+//!     .max_tries(10)
+//!     .on_lose(|secret| println!("The code was {:?}", secret))
+//!     .build()?;
+//! ```
+//! Nothing is checked until `build`, so the settings can be
+//! given in any order.
+//!
+
+use super::{
+    CodeMaker, Colour, ConfigError, GameConfig, GameOutput, GameSummary, OnGameOver,
+    RandomCodeMaker, SilentOutput, State, Symbol, TerminalOutput,
+};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+///
+/// The settings and callbacks for a `State` which hasn't been
+/// made yet, see the top of this file.
+///
+/// Each setter takes `self` and gives it back, which is what
+/// lets them be chained. Unlike a Java builder, which changes
+/// itself and returns `this`, the old builder is moved into
+/// the call and can't be used again afterwards.
+///
+/// Like `State`, this makes a game of colours unless it's told
+/// otherwise, see `for_symbols`.
+///
+pub struct StateBuilder<'a, P = Colour> {
+    config: GameConfig,
+    win: OnGameOver<'a, P>,
+    lose: OnGameOver<'a, P>,
+    output: Box<dyn GameOutput + 'a>,
+    maker: Box<dyn CodeMaker<P> + 'a>,
+    seed: Option<u64>,
+}
+
+impl<'a> StateBuilder<'a> {
+    ///
+    /// A builder for four pegs without duplicates and no limit
+    /// on tries, which does nothing when a game is won or lost
+    /// and prints nothing. Building it straight away gives a
+    /// playable game.
+    ///
+    pub fn new() -> Self {
+        let builder = Self::for_symbols();
+        let config = GameConfig {
+            palette_size: GameConfig::default().palette_size,
+            ..builder.config
+        };
+        builder.config(config)
+    }
+}
+
+impl<'a, P: Symbol> StateBuilder<'a, P> {
+    ///
+    /// `new`, for a game whose pegs aren't colours (See
+    /// `symbol.rs`), which are named on the type instead, like
+    /// `StateBuilder::<Digit>::for_symbols()`. `new` can't do both,
+    /// since rust wouldn't know which pegs were meant by
+    /// `StateBuilder::new()` on its own.
+    ///
+    /// Every symbol is in play, since only colours have a
+    /// classic set to start with (See `GameConfig::palette_size`).
+    ///
+    pub fn for_symbols() -> Self {
+        Self {
+            config: GameConfig {
+                max_tries: None,
+                auto_max_tries: false,
+                palette_size: None,
+                ..GameConfig::default()
+            },
+            win: Box::new(|_| {}),
+            lose: Box::new(|_| {}),
+            output: Box::new(SilentOutput),
+            maker: Box::new(RandomCodeMaker::new()),
+            seed: None,
+        }
+    }
+
+    ///
+    /// Every setting at once, replacing any given before. The
+    /// setters below can still change them afterwards.
+    ///
+    pub fn config(self, config: GameConfig) -> Self {
+        Self { config, ..self }
+    }
+
+    ///
+    /// How many pegs the code has.
+    ///
+    pub fn pegs(mut self, pegs: usize) -> Self {
+        self.config.pegs = pegs;
+        self
+    }
+
+    ///
+    /// Whether a colour can be in the code more than once.
+    ///
+    pub fn allow_duplicates(mut self, allow: bool) -> Self {
+        self.config.allow_duplicates = allow;
+        self
+    }
+
+    ///
+    /// How many guesses a game has before it's lost. This turns
+    /// off the limit being worked out from the other settings
+    /// (See `GameConfig::auto_max_tries`).
+    ///
+    pub fn max_tries(mut self, tries: usize) -> Self {
+        self.config.max_tries = Some(tries);
+        self.config.auto_max_tries = false;
+        self
+    }
+
+    ///
+    /// Called whenever a game is won, with a summary of it.
+    ///
+    pub fn on_win(self, win: impl FnMut(&GameSummary<P>) + 'a) -> Self {
+        Self {
+            win: Box::new(win),
+            ..self
+        }
+    }
+
+    ///
+    /// Called whenever a game is lost, with a summary of it
+    /// (Which has the secret).
+    ///
+    pub fn on_lose(self, lose: impl FnMut(&GameSummary<P>) + 'a) -> Self {
+        Self {
+            lose: Box::new(lose),
+            ..self
+        }
+    }
+
+    ///
+    /// Whether the state prints its own messages, for a game
+    /// with nothing else doing the talking. This is the same as
+    /// `output` with a `TerminalOutput` or a `SilentOutput`.
+    ///
+    pub fn terminal(self, terminal: bool) -> Self {
+        if terminal {
+            self.output(TerminalOutput)
+        } else {
+            self.output(SilentOutput)
+        }
+    }
+
+    ///
+    /// Where the state's messages go, see `output.rs`.
+    ///
+    pub fn output(self, output: impl GameOutput + 'a) -> Self {
+        Self {
+            output: Box::new(output),
+            ..self
+        }
+    }
+
+    ///
+    /// Who answers the guesses, see `codemaker.rs`. This is a
+    /// `RandomCodeMaker` unless it's set.
+    ///
+    pub fn code_maker(self, maker: impl CodeMaker<P> + 'a) -> Self {
+        Self {
+            maker: Box::new(maker),
+            ..self
+        }
+    }
+
+    ///
+    /// Picks the secrets from random numbers seeded with `seed`,
+    /// like `State::new_seeded`.
+    ///
+    pub fn seed(self, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    ///
+    /// Makes the `State`, or gives back what's wrong with the
+    /// settings if they can't make a code. This is the only
+    /// place they're checked.
+    ///
+    pub fn build(self) -> Result<State<'a, P>, ConfigError> {
+        let rng: Box<dyn RngCore + 'a> = match self.seed {
+            Some(seed) => Box::new(StdRng::seed_from_u64(seed)),
+            None => Box::new(rand::thread_rng()),
+        };
+        // A limit worked out from the settings has to wait until
+        // all of them have been given.
+        let config = self.config.with_auto_tries();
+        State::build(config, self.win, self.lose, self.output, rng, self.maker)
+    }
+}
+
+impl<P: Symbol> Default for StateBuilder<'_, P> {
+    fn default() -> Self {
+        Self::for_symbols()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Guess, MAX_TRIES};
+    use super::*;
+
+    #[test]
+    fn the_defaults_build_a_playable_game() {
+        let mut state = StateBuilder::new().build().unwrap();
+        let config = *state.config();
+        assert_eq!(config.pegs, 4);
+        assert!(!config.allow_duplicates);
+        assert_eq!(config.max_tries, None);
+        assert!(!config.auto_max_tries);
+        assert_eq!(config.palette().len(), 6);
+        let secret = state.reveal_secret().to_vec();
+        let guess = Guess::for_config(secret, config).unwrap();
+        assert!(state.enter_guess(&guess).unwrap().is_some());
+    }
+
+    #[test]
+    fn the_setters_end_up_in_the_settings() {
+        let state = StateBuilder::new()
+            .pegs(5)
+            .allow_duplicates(true)
+            .max_tries(10)
+            .build()
+            .unwrap();
+        let config = state.config();
+        assert_eq!(config.pegs, 5);
+        assert!(config.allow_duplicates);
+        assert_eq!(config.max_tries, Some(10));
+        // Later setters win over an earlier `config`
+        let state = StateBuilder::new()
+            .config(GameConfig::default())
+            .pegs(3)
+            .build()
+            .unwrap();
+        assert_eq!(state.size(), 3);
+    }
+
+    #[test]
+    fn the_game_over_callbacks_are_called() {
+        let mut won = 0;
+        let mut lost = 0;
+        {
+            let mut state = StateBuilder::new()
+                .max_tries(1)
+                .on_win(|_| won += 1)
+                .on_lose(|_| lost += 1)
+                .seed(283)
+                .build()
+                .unwrap();
+            let config = *state.config();
+            let secret = state.reveal_secret().to_vec();
+            state
+                .enter_guess(&Guess::for_config(secret, config).unwrap())
+                .unwrap();
+            let mut other = state.reveal_secret().to_vec();
+            other.rotate_left(1);
+            state
+                .enter_guess(&Guess::for_config(other, config).unwrap())
+                .unwrap();
+            state.forfeit();
+        }
+        assert_eq!((won, lost), (1, 2));
+    }
+
+    #[test]
+    fn bad_settings_fail_to_build() {
+        assert_eq!(
+            StateBuilder::new().pegs(7).build().err(),
+            Some(ConfigError::TooManyPegs {
+                requested: 7,
+                available: 6
+            })
+        );
+        assert_eq!(
+            StateBuilder::new().max_tries(0).build().err(),
+            Some(ConfigError::NoTries)
+        );
+        assert_eq!(
+            StateBuilder::new().max_tries(MAX_TRIES + 1).build().err(),
+            Some(ConfigError::TooManyTries { most: MAX_TRIES })
+        );
+        assert_eq!(
+            StateBuilder::new().pegs(1).build().err(),
+            Some(ConfigError::TooFewPegs { requested: 1 })
+        );
+    }
+}
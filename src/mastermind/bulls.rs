@@ -0,0 +1,306 @@
+//!
+//! Bulls and Cows, the older game Mastermind came from, played
+//! with the digits 0 to 9 instead of colours. The secret is
+//! usually four different digits, and each guess is told how
+//! many of its digits are in the right place (Bulls) and how
+//! many are in the number somewhere else (Cows).
+//!
+//! It's the same `State` underneath, with `Digit` pegs (See
+//! `symbol.rs`), so a guess is typed as the digits themselves,
+//! like `1234`. Picked with "digits" when a new game is set up.
+//!
+
+use super::wording::FeedbackStyle;
+use super::{prompt, GameConfig, GameEvent, State, StateBuilder, Symbol, MIN_PEGS};
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+///
+/// One digit of a number, from 0 to 9. This is only a `u8`
+/// underneath, but one which is known to be a single digit,
+/// so it can always be written as one.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digit(u8);
+
+///
+/// Every digit, in order.
+///
+pub static DIGITS: &[Digit] = &[
+    Digit(0),
+    Digit(1),
+    Digit(2),
+    Digit(3),
+    Digit(4),
+    Digit(5),
+    Digit(6),
+    Digit(7),
+    Digit(8),
+    Digit(9),
+];
+
+impl Digit {
+    ///
+    /// The digit `value`, or `None` if it's more than 9.
+    ///
+    pub fn new(value: u8) -> Option<Self> {
+        Some(Digit(value)).filter(|_| value <= 9)
+    }
+
+    pub fn value(self) -> u8 {
+        self.0
+    }
+}
+
+impl fmt::Display for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+//
+// Written the same way as `Display`, so that a guess in an
+// error message reads `[1, 2, 3]` and not `[Digit(1), ...]`.
+//
+impl fmt::Debug for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Digit {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut chars = text.chars();
+        match (chars.next().and_then(|x| x.to_digit(10)), chars.next()) {
+            (Some(x), None) => Ok(Digit(x as u8)),
+            _ => Err(format!("`{}` isn't a digit from 0 to 9", text)),
+        }
+    }
+}
+
+impl Symbol for Digit {
+    fn all() -> &'static [Self] {
+        DIGITS
+    }
+
+    fn noun() -> &'static str {
+        "digit"
+    }
+
+    fn name(self) -> String {
+        self.0.to_string()
+    }
+
+    fn from_letter(letter: char) -> Option<Self> {
+        letter.to_digit(10).map(|x| Digit(x as u8))
+    }
+
+    fn index(self) -> Option<usize> {
+        Some(self.0 as usize)
+    }
+}
+
+///
+/// The settings a game of Bulls and Cows starts with: four
+/// different digits out of all ten, and as many tries as it
+/// takes.
+///
+pub fn preset() -> GameConfig {
+    GameConfig {
+        pegs: 4,
+        allow_duplicates: false,
+        palette_size: None,
+        max_tries: None,
+        auto_max_tries: false,
+        ..GameConfig::default()
+    }
+}
+
+///
+/// Plays Bulls and Cows on the terminal until the player types
+/// `quit` or their input runs out, asking how long the number
+/// is first.
+///
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let defaults = preset();
+    let pegs = prompt::ask(
+        &format!(
+            "How many digits long should the number be? ({}-{}, inclusive, blank for {}): ",
+            MIN_PEGS,
+            DIGITS.len(),
+            defaults.pegs
+        ),
+        Some(defaults.pegs),
+        |&x| {
+            if (MIN_PEGS..=DIGITS.len()).contains(&x) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Enter a valid positive integer from {}-{} inclusive.",
+                    MIN_PEGS,
+                    DIGITS.len()
+                ))
+            }
+        },
+    )?;
+    let config = GameConfig { pegs, ..defaults };
+    let mut state: State<Digit> = StateBuilder::for_symbols().config(config).build()?;
+    let style = FeedbackStyle::BullsCows;
+
+    println!(
+        "I'm thinking of a number {} digits long, with no digit in it twice. \
+         Type \"quit\" to give up.",
+        pegs
+    );
+    let mut input = String::new();
+    loop {
+        print!("Guess #{} > ", state.history().len() + 1);
+        std::io::stdout().flush()?;
+        input.clear();
+        if std::io::stdin().read_line(&mut input)? == 0 || input.trim() == "quit" {
+            let number = state.reveal_secret().iter().map(|x| x.to_string());
+            println!("The number was {}", number.collect::<String>());
+            return Ok(());
+        }
+        // Each line is a whole guess, so whatever was entered of
+        // one which didn't work is taken back again.
+        let events = match state.push_string_input(input.trim()) {
+            Ok(_) if !state.buffered().is_empty() => {
+                println!(
+                    "That's only {} of the {} digits",
+                    state.buffered().len(),
+                    pegs
+                );
+                while state.undo_input().is_some() {}
+                continue;
+            }
+            Ok(x) => x,
+            Err(e) => {
+                println!("{}", e);
+                while state.undo_input().is_some() {}
+                continue;
+            }
+        };
+        for event in events {
+            match event {
+                GameEvent::GuessEvaluated { feedback } => println!("{}", style.describe(feedback)),
+                GameEvent::GameWon => {
+                    let game = state.last_game().expect("A game was just won");
+                    let number = game.secret.iter().flatten().map(|x| x.to_string());
+                    println!(
+                        "You got it in {}, the number was {}!",
+                        game.attempts_made,
+                        number.collect::<String>()
+                    );
+                }
+                GameEvent::NewGameStarted => println!("I'm thinking of a new number."),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::{parse_symbols, Feedback};
+    use std::collections::HashSet;
+
+    fn digits(text: &str) -> Vec<Digit> {
+        text.chars()
+            .map(|x| x.to_string().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parses_digits() {
+        assert_eq!("7".parse::<Digit>(), Ok(Digit(7)));
+        assert_eq!("0".parse::<Digit>(), Ok(Digit(0)));
+        assert!("10".parse::<Digit>().is_err());
+        assert!("a".parse::<Digit>().is_err());
+        assert!("".parse::<Digit>().is_err());
+        assert_eq!(Digit::new(9), Some(Digit(9)));
+        assert_eq!(Digit::new(10), None);
+        assert_eq!(Digit::from_letter('3'), Some(Digit(3)));
+        assert_eq!(Digit::from_letter('x'), None);
+        assert_eq!(parse_symbols::<Digit>("1234"), Ok(digits("1234")));
+        assert_eq!(parse_symbols::<Digit>("5 6 7"), Ok(digits("567")));
+        assert!(parse_symbols::<Digit>("12a4").is_err());
+    }
+
+    #[test]
+    fn written_as_the_digit() {
+        assert_eq!(Digit(4).to_string(), "4");
+        assert_eq!(format!("{:?}", digits("120")), "[1, 2, 0]");
+        assert_eq!(Digit(4).name(), "4");
+        assert_eq!(Digit::noun(), "digit");
+    }
+
+    #[test]
+    fn preset_is_four_different_digits() {
+        let config = preset();
+        assert_eq!(config.pegs, 4);
+        assert!(!config.allow_duplicates);
+        assert_eq!(config.max_tries, None);
+        assert_eq!(config.symbols::<Digit>(), DIGITS.to_vec());
+    }
+
+    #[test]
+    fn secrets_use_all_ten_digits() {
+        let mut seen = HashSet::<Digit>::new();
+        for seed in 0..100 {
+            let state: State<Digit> = StateBuilder::for_symbols()
+                .config(preset())
+                .seed(seed)
+                .build()
+                .unwrap();
+            let secret = state.reveal_secret();
+            assert_eq!(secret.len(), 4);
+            assert_eq!(secret.iter().collect::<HashSet<_>>().len(), 4);
+            seen.extend(secret);
+        }
+        assert_eq!(seen.len(), 10);
+    }
+
+    #[test]
+    fn scores_digit_guesses() {
+        let mut state: State<Digit> = StateBuilder::for_symbols()
+            .config(preset())
+            .build()
+            .unwrap();
+        state.set_secret(digits("1243"));
+        state.push_string_input("1234").unwrap();
+        state.push_string_input("5670").unwrap();
+        state.push_string_input("3412").unwrap();
+        let feedback = state
+            .history()
+            .iter()
+            .map(|x| x.feedback)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            feedback,
+            vec![
+                Feedback {
+                    exact: 2,
+                    misplaced: 2
+                },
+                Feedback {
+                    exact: 0,
+                    misplaced: 0
+                },
+                Feedback {
+                    exact: 0,
+                    misplaced: 4
+                },
+            ]
+        );
+        let style = FeedbackStyle::BullsCows;
+        assert_eq!(style.describe(feedback[0]), "2 bulls and 2 cows");
+        let events = state.push_string_input("1243").unwrap();
+        assert!(events.contains(&GameEvent::GameWon));
+    }
+}
@@ -0,0 +1,168 @@
+//!
+//! Remembering the feedback of (Secret, guess) pairs which have
+//! been scored before, so that the solver doesn't score the
+//! same pair again in every turn and every game.
+//!
+//! This only pays off when scoring is slower than looking a
+//! pair up in a `HashMap`, which, for codes as short as ours,
+//! it usually isn't: `mastermind simulate --verbose` shows the
+//! solver running about twice as slowly with `--cache` on six
+//! peg games, even with nearly every pair found in the cache.
+//! So only `mastermind simulate --cache` uses it, for trying
+//! out other settings (Or a slower `score_guess`), and games
+//! people play never do.
+//!
+//! The cache can be shared between threads: it's split into
+//! shards, each with a lock of its own, so two threads only
+//! wait for each other when they want the same shard. The
+//! counters are atomics, which can be added to from any thread
+//! without a lock (Like `AtomicInteger` in Java).
+//!
+
+use super::packed::{try_pack, BITS, MAX_PACKED_PEGS};
+use super::{score_guess, Colour, Feedback};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const SHARDS: usize = 16;
+
+//
+// An odd number with its bits spread out, for multiplying the
+// bits of a key around.
+//
+const MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+///
+/// A bounded cache of feedback. Once a shard is full it's
+/// emptied and starts over, which is crude, but never does
+/// worse than not having a cache for long.
+///
+pub struct FeedbackCache {
+    shards: Vec<Mutex<HashMap<u64, Feedback>>>,
+    shard_capacity: usize,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl FeedbackCache {
+    ///
+    /// A cache which holds at most about `capacity` pairs.
+    ///
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+            shard_capacity: (capacity / SHARDS).max(1),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    ///
+    /// The same as `score_guess`, but from the cache if this
+    /// pair has been scored before.
+    ///
+    pub fn score(&self, secret: &[Colour], guess: &[Colour]) -> Feedback {
+        let key = match key(secret, guess) {
+            Some(x) => x,
+            None => return score_guess(secret, guess),
+        };
+        // Mix the bits up so that codes which only differ in the
+        // last peg don't all land in the same shard.
+        let shard = &self.shards[(key.wrapping_mul(MIX) >> 60) as usize % SHARDS];
+        // A thread which panicked while holding the lock can't
+        // have left a wrong answer behind, so poisoning is ignored.
+        let mut shard = shard.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(&feedback) = shard.get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return feedback;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let feedback = score_guess(secret, guess);
+        if shard.len() >= self.shard_capacity {
+            shard.clear();
+        }
+        shard.insert(key, feedback);
+        feedback
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+//
+// Both codes packed (See `packed.rs`) side by side, with their
+// length above them, since a `PackedCode` doesn't say how long
+// it is. Codes which can't be packed, like those with blanks,
+// or of different lengths, aren't cached at all.
+//
+fn key(secret: &[Colour], guess: &[Colour]) -> Option<u64> {
+    if secret.len() != guess.len() {
+        return None;
+    }
+    let width = MAX_PACKED_PEGS as u32 * BITS;
+    let length = secret.len() as u64;
+    let (secret, guess) = (try_pack(secret)?, try_pack(guess)?);
+    Some(length << (2 * width) | u64::from(secret.bits()) << width | u64::from(guess.bits()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::{all_codes, COLOURS};
+
+    #[test]
+    fn codes_with_blanks_never_share_a_key() {
+        // With 3 bits a peg, a blank (8) spilled over into the
+        // peg before it, so `[Red, Blank]` and `[Blue, Red]` were
+        // the same key
+        let cache = FeedbackCache::new(1 << 16);
+        assert_eq!(
+            cache.score(&[Colour::Blue, Colour::Red], &[Colour::Red, Colour::Blue]),
+            Feedback {
+                exact: 0,
+                misplaced: 2
+            }
+        );
+        assert_eq!(
+            cache.score(&[Colour::Red, Colour::Blank], &[Colour::Red, Colour::Blank]),
+            Feedback {
+                exact: 2,
+                misplaced: 0
+            }
+        );
+    }
+
+    #[test]
+    fn cached_feedback_always_agrees_with_scoring() {
+        let palette = COLOURS
+            .iter()
+            .copied()
+            .chain(Some(Colour::Blank))
+            .collect::<Vec<_>>();
+        let codes = all_codes(&palette, 2, true).collect::<Vec<_>>();
+        let cache = FeedbackCache::new(1 << 16);
+        // Twice, so the second time round is from the cache
+        for _ in 0..2 {
+            for secret in &codes {
+                for guess in &codes {
+                    assert_eq!(cache.score(secret, guess), score_guess(secret, guess));
+                }
+            }
+        }
+        assert!(cache.hits() > 0);
+    }
+
+    #[test]
+    fn codes_of_different_lengths_never_share_a_key() {
+        let short = [Colour::Red, Colour::Red];
+        let long = [Colour::Red, Colour::Red, Colour::Red];
+        assert_ne!(key(&short, &short), key(&long, &long));
+        assert_eq!(key(&short, &long), None);
+    }
+}
@@ -0,0 +1,253 @@
+//!
+//! `GameThread`, a game played on a thread of its own which sends
+//! everything that happens in it down a channel.
+//!
+//! A GUI usually has one thread drawing the window, which can't
+//! stop to play the game, so the game goes somewhere else and the
+//! window picks up what happened whenever it's ready:
+//! ```
+//! let (game, events) = GameThread::new(GameConfig::default())?;
+//! game.push_string_input("rgby");
+//! for event in events.try_iter() {
+//!     // Draw it
+//! }
+//! ```
+//!
+//! A `State` can't be moved to another thread itself, since its
+//! callbacks, output and random numbers are boxed up without
+//! promising they're safe to (`Send` in Rust terms). Instead the
+//! `State` is made on the thread which plays it and never leaves,
+//! so it has no callbacks at all and stays quiet. Everything it
+//! would have said comes down the channel as `GameEvent`s.
+//!
+
+use super::{Colour, ConfigError, GameConfig, GameEvent, InputError, StateBuilder};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+///
+/// What comes down the channel: every `GameEvent` the game had,
+/// in the order it had them, or why some input was turned down.
+///
+pub type ChannelEvent = Result<GameEvent, InputError>;
+
+//
+// What the handle asks the thread playing the game to do.
+//
+enum Input {
+    Colour(Colour),
+    Line(String),
+    Stop,
+}
+
+///
+/// A game being played on another thread, see the top of this
+/// file. This is only a handle to it, which can itself be sent
+/// to whichever thread is doing the input.
+///
+/// The input methods are the same as `State`'s, but they don't
+/// wait for the game or give anything back: what they did turns
+/// up on the receiver made alongside the handle instead. Dropping
+/// the handle ends the game and waits for its thread to finish.
+///
+pub struct GameThread {
+    inputs: Sender<Input>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl GameThread {
+    ///
+    /// Starts a game with these settings on a new thread, and
+    /// gives back the handle to it and where its events come
+    /// out, or what's wrong with the settings.
+    ///
+    pub fn new(config: GameConfig) -> Result<(Self, Receiver<ChannelEvent>), ConfigError> {
+        Self::start(config, None)
+    }
+
+    ///
+    /// Like `new`, but picking the secrets from random numbers
+    /// seeded with `seed`, like `State::new_seeded`.
+    ///
+    pub fn new_seeded(
+        config: GameConfig,
+        seed: u64,
+    ) -> Result<(Self, Receiver<ChannelEvent>), ConfigError> {
+        Self::start(config, Some(seed))
+    }
+
+    //
+    // The builder can't go to the thread either, so it's the
+    // settings and seed that are sent, and the thread says
+    // whether they made a game before anything else happens.
+    //
+    fn start(
+        config: GameConfig,
+        seed: Option<u64>,
+    ) -> Result<(Self, Receiver<ChannelEvent>), ConfigError> {
+        let (inputs, input_receiver) = mpsc::channel();
+        let (events, event_receiver) = mpsc::channel();
+        let (ready, ready_receiver) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            let builder = StateBuilder::new().config(config);
+            let builder = match seed {
+                Some(seed) => builder.seed(seed),
+                None => builder,
+            };
+            let mut state = match builder.build() {
+                Ok(x) => x,
+                Err(e) => {
+                    let _ = ready.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready.send(Ok(()));
+            for input in input_receiver {
+                let result = match input {
+                    Input::Colour(colour) => state.step(colour).map_err(|error| InputError {
+                        error,
+                        position: None,
+                        guesses_finished: 0,
+                        buffered: state.buffered().len(),
+                        game_ended: false,
+                    }),
+                    Input::Line(text) => state.push_string_input(&text),
+                    Input::Stop => break,
+                };
+                let sent = match result {
+                    Ok(x) => x.into_iter().all(|event| events.send(Ok(event)).is_ok()),
+                    Err(e) => events.send(Err(e)).is_ok(),
+                };
+                // Nobody is listening any more.
+                if !sent {
+                    break;
+                }
+            }
+        });
+        match ready_receiver.recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                let _ = worker.join();
+                return Err(e);
+            }
+            // The thread can only have gone without saying anything
+            // by panicking.
+            Err(_) => panic!("The game's thread stopped before it started"),
+        }
+        Ok((
+            Self {
+                inputs,
+                worker: Some(worker),
+            },
+            event_receiver,
+        ))
+    }
+
+    ///
+    /// Enters one colour, like `State::step`.
+    ///
+    pub fn step(&self, colour: Colour) {
+        self.send(Input::Colour(colour));
+    }
+
+    ///
+    /// Enters a line of colours, like `State::push_string_input`.
+    /// If it goes wrong, the events for the part which worked
+    /// come before the error.
+    ///
+    pub fn push_string_input(&self, text: &str) {
+        self.send(Input::Line(text.to_string()));
+    }
+
+    //
+    // The thread only stops early if the receiver was dropped,
+    // in which case there's nobody to tell about this input
+    // anyway.
+    //
+    fn send(&self, input: Input) {
+        let _ = self.inputs.send(input);
+    }
+}
+
+impl Drop for GameThread {
+    fn drop(&mut self) {
+        self.send(Input::Stop);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Feedback;
+    use super::*;
+
+    #[test]
+    fn events_come_out_in_the_order_the_game_had_them() {
+        let config = GameConfig::default();
+        // The same seed picks the same secret here as on the thread
+        let secret = StateBuilder::new()
+            .config(config)
+            .seed(290)
+            .build()
+            .unwrap()
+            .reveal_secret()
+            .to_vec();
+        let mut wrong = secret.clone();
+        wrong.rotate_left(1);
+
+        let (game, events) = GameThread::new_seeded(config, 290).unwrap();
+        // The guesses come from another thread again, and dropping
+        // the handle there ends the game
+        let feeder = thread::spawn(move || {
+            for &colour in &wrong {
+                game.step(colour);
+            }
+            game.push_string_input("zebra");
+            let names: Vec<_> = secret.iter().map(|x| x.name()).collect();
+            game.push_string_input(&names.join(" "));
+        });
+        let events: Vec<ChannelEvent> = events.iter().collect();
+        feeder.join().unwrap();
+
+        let peg = |position| Ok(GameEvent::PegAccepted { position });
+        assert_eq!(events[..4], [peg(0), peg(1), peg(2), peg(3)]);
+        assert!(matches!(
+            events[4],
+            Ok(GameEvent::GuessEvaluated {
+                feedback: Feedback { exact: 0, .. }
+            })
+        ));
+        assert!(events[5].is_err());
+        assert_eq!(
+            events[6..],
+            [
+                peg(0),
+                peg(1),
+                peg(2),
+                peg(3),
+                Ok(GameEvent::GuessEvaluated {
+                    feedback: Feedback {
+                        exact: 4,
+                        misplaced: 0
+                    }
+                }),
+                Ok(GameEvent::GameWon),
+                Ok(GameEvent::NewGameStarted),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_settings_are_found_before_the_thread_plays() {
+        let config = GameConfig {
+            pegs: 1,
+            ..GameConfig::default()
+        };
+        assert_eq!(
+            GameThread::new(config).err(),
+            Some(ConfigError::TooFewPegs { requested: 1 })
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//!
+//! `CodeMaker`, whoever answers the guesses in a game.
+//!
+//! Usually that's a secret picked at random before the game
+//! starts, which is what `RandomCodeMaker` is. There's also a
+//! well known way to cheat at Mastermind without ever being
+//! caught: don't pick a code at all, and answer each guess with
+//! whatever feedback leaves the most codes it could still be.
+//! That's `AdversarialCodeMaker`, which makes every game as long
+//! as it can be for the guesses made:
+//! ```
+//! let state = StateBuilder::new()
+//!     .code_maker(AdversarialCodeMaker::new())
+//!     .build()?;
+//! ```
+//! Either way, every answer agrees with every other one, so the
+//! game is still fair in the sense that it can be won.
+//!
+
+use super::{score_guess, solver, Colour, Feedback, GameConfig, Symbol};
+
+///
+/// Answers the guesses in a game, like an interface in Java.
+/// `State` keeps one as a `Box<dyn CodeMaker>` and asks it for
+/// the feedback to each guess.
+///
+/// Whatever `reveal` gives back is used as the secret whenever
+/// `State` needs one, like when the game is lost, so it has to
+/// be a code which would have given every answer so far.
+///
+/// `P` is what the pegs are, see `symbol.rs`.
+///
+pub trait CodeMaker<P = Colour> {
+    ///
+    /// A new game is starting with `config`. `drawn` is the
+    /// secret picked at random for it, the same way it always
+    /// has been, which a codemaker can use or ignore.
+    ///
+    fn new_game(&mut self, config: &GameConfig, drawn: Vec<P>);
+
+    ///
+    /// The feedback for `guess`. Once this is a win, the game is
+    /// over and there won't be any more until `new_game`.
+    ///
+    fn feedback(&mut self, guess: &[P]) -> Feedback;
+
+    ///
+    /// The secret, or a code which could be it if there isn't
+    /// one yet. `State` asks after every answer, so this can
+    /// change as the game goes on.
+    ///
+    fn reveal(&self) -> Vec<P>;
+
+    ///
+    /// Something about the secret has been given away, like the
+    /// colour in one of its positions, and `fits` says which
+    /// codes agree with it. It's always true of what `reveal`
+    /// gave back last. A codemaker with a secret can ignore it,
+    /// which is what this does unless it's overridden.
+    ///
+    fn narrow(&mut self, _fits: &dyn Fn(&[P]) -> bool) {}
+}
+
+///
+/// Answers with a secret picked at random before the game
+/// starts, which is how the game has always been played.
+///
+#[derive(Clone, Debug)]
+pub struct RandomCodeMaker<P = Colour> {
+    secret: Vec<P>,
+}
+
+impl<P> RandomCodeMaker<P> {
+    pub fn new() -> Self {
+        Self { secret: Vec::new() }
+    }
+}
+
+impl<P> Default for RandomCodeMaker<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Symbol> CodeMaker<P> for RandomCodeMaker<P> {
+    fn new_game(&mut self, _: &GameConfig, drawn: Vec<P>) {
+        self.secret = drawn;
+    }
+
+    fn feedback(&mut self, guess: &[P]) -> Feedback {
+        score_guess(&self.secret, guess)
+    }
+
+    fn reveal(&self) -> Vec<P> {
+        self.secret.clone()
+    }
+}
+
+///
+/// Never picks a secret, see the top of this file. It keeps
+/// every code which agrees with the answers so far, and gives
+/// each guess the answer which the most of them would have.
+///
+/// This has to go through every possible code for each guess,
+/// like the solver does, so it's only quick with the usual four
+/// or five pegs.
+///
+#[derive(Clone, Debug)]
+pub struct AdversarialCodeMaker<P = Colour> {
+    ///
+    /// The codes which could still be the secret, in the order
+    /// `solver::candidates` gives them. There's always at least
+    /// one once a game has started.
+    ///
+    remaining: Vec<Vec<P>>,
+}
+
+impl<P> AdversarialCodeMaker<P> {
+    pub fn new() -> Self {
+        Self {
+            remaining: Vec::new(),
+        }
+    }
+
+    ///
+    /// How many codes the secret could still be.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.remaining.len()
+    }
+}
+
+impl<P> Default for AdversarialCodeMaker<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Symbol> CodeMaker<P> for AdversarialCodeMaker<P> {
+    fn new_game(&mut self, config: &GameConfig, drawn: Vec<P>) {
+        self.remaining = solver::candidates(*config);
+        // The solver doesn't know every setting which limits the
+        // codes, so if it thinks there aren't any, the one which
+        // was drawn at least is one.
+        if self.remaining.is_empty() {
+            self.remaining.push(drawn);
+        }
+    }
+
+    fn feedback(&mut self, guess: &[P]) -> Feedback {
+        // Sort the codes by the answer they'd give, keeping the
+        // answers in the order they first came up so that the
+        // same guesses always get the same answers.
+        let mut answers = Vec::<(Feedback, Vec<Vec<P>>)>::new();
+        for code in self.remaining.drain(..) {
+            let feedback = score_guess(&code, guess);
+            match answers.iter_mut().find(|(x, _)| *x == feedback) {
+                Some((_, codes)) => codes.push(code),
+                None => answers.push((feedback, vec![code])),
+            }
+        }
+        // `max_by_key` gives the last of the biggest, and the
+        // first is wanted, so this goes through them backwards.
+        let (feedback, codes) = answers
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, codes)| codes.len())
+            .expect("There's always at least one code left");
+        self.remaining = codes;
+        feedback
+    }
+
+    fn reveal(&self) -> Vec<P> {
+        self.remaining[0].clone()
+    }
+
+    fn narrow(&mut self, fits: &dyn Fn(&[P]) -> bool) {
+        self.remaining.retain(|code| fits(code));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::{play_with_guesser, GamePhase, RandomGuesser, StateBuilder};
+
+    fn config() -> GameConfig {
+        GameConfig {
+            max_tries: None,
+            auto_max_tries: false,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn random_code_maker_answers_for_the_drawn_code() {
+        let secret = vec![Colour::Red, Colour::Blue, Colour::Green, Colour::White];
+        let mut maker = RandomCodeMaker::new();
+        maker.new_game(&config(), secret.clone());
+        let guess = [Colour::Blue, Colour::Red, Colour::Green, Colour::Yellow];
+        assert_eq!(maker.feedback(&guess), score_guess(&secret, &guess));
+        assert_eq!(maker.reveal(), secret);
+    }
+
+    #[test]
+    fn adversarial_code_maker_never_contradicts_itself() {
+        let config = config();
+        let codes = solver::candidates::<Colour>(config);
+        let mut maker = AdversarialCodeMaker::new();
+        maker.new_game(&config, codes[0].clone());
+        assert_eq!(maker.remaining(), codes.len());
+        let mut replies = Vec::new();
+        // Guessing every code in turn has to hit the secret at
+        // some point, however the answers go.
+        for guess in &codes {
+            let before = maker.remaining();
+            let feedback = maker.feedback(guess);
+            replies.push((guess.clone(), feedback));
+            assert!(maker.remaining() >= 1);
+            assert!(maker.remaining() <= before);
+            let secret = maker.reveal();
+            for (guess, feedback) in &replies {
+                assert_eq!(score_guess(&secret, guess), *feedback);
+            }
+            if feedback.exact == config.pegs {
+                break;
+            }
+        }
+        assert_eq!(replies.last().unwrap().1.exact, config.pegs);
+        assert_eq!(replies.last().unwrap().0, maker.reveal());
+    }
+
+    #[test]
+    fn adversarial_code_maker_answers_the_same_each_time() {
+        let config = config();
+        let guess = [Colour::Red, Colour::Blue, Colour::Green, Colour::White];
+        let answer = || {
+            let mut maker = AdversarialCodeMaker::new();
+            maker.new_game(&config, guess.to_vec());
+            (maker.feedback(&guess), maker.remaining(), maker.reveal())
+        };
+        let first = answer();
+        assert_eq!(first, answer());
+        // The drawn code isn't picked, so the first guess can't be
+        // right while there are other codes left.
+        assert_ne!(first.0.exact, config.pegs);
+    }
+
+    #[test]
+    fn adversarial_code_maker_in_a_state() {
+        let mut state = StateBuilder::new()
+            .config(config())
+            .code_maker(AdversarialCodeMaker::new())
+            .seed(4)
+            .build()
+            .unwrap();
+        let summary = play_with_guesser(&mut state, &mut RandomGuesser::new_seeded(4)).unwrap();
+        assert_eq!(summary.phase, GamePhase::Won);
+        let secret = &state.games()[0].secret;
+        assert_eq!(summary.guesses.len(), summary.attempts_made);
+        for (guess, feedback) in &summary.guesses {
+            assert_eq!(Some(score_guess(secret, guess)), *feedback);
+        }
+    }
+
+    #[test]
+    fn narrow_keeps_the_codes_which_fit() {
+        let config = config();
+        let mut maker = AdversarialCodeMaker::<Colour>::new();
+        maker.new_game(&config, Vec::new());
+        maker.narrow(&|code| code[0] == Colour::Red);
+        assert!(maker.remaining() > 0);
+        assert_eq!(maker.reveal()[0], Colour::Red);
+    }
+}
@@ -0,0 +1,294 @@
+//!
+//! Going through every code there is, for anything which needs
+//! to look at all of them, like the solver.
+//!
+//! There can be a lot of codes (6 colours and 6 pegs is already
+//! 46656), so `all_codes` doesn't make a list of them. It gives
+//! back an iterator, which works out each code as it's asked
+//! for, like an `Iterator` in Java:
+//! ```
+//! for code in all_codes(COLOURS, 4, false) {
+//!     println!("{:?}", code);
+//! }
+//! ```
+//! `code_space_size` says how many there'll be, without going
+//! through them.
+//!
+
+use super::Colour;
+use std::ops::Deref;
+
+///
+/// One whole code, which unlike a `Guess` hasn't been checked
+/// against any settings, for putting codes in sets and maps.
+///
+/// Codes are ordered peg by peg from the first, so a palette
+/// which is in order (Like `COLOURS`) gives them in order from
+/// `all_codes`: sorting a list of codes, or keeping them in a
+/// `BTreeSet`, puts them back in the order they were gone
+/// through.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Code<P = Colour>(pub Vec<P>);
+
+impl<P> Code<P> {
+    pub fn into_inner(self) -> Vec<P> {
+        self.0
+    }
+}
+
+impl<P> From<Vec<P>> for Code<P> {
+    fn from(pegs: Vec<P>) -> Self {
+        Self(pegs)
+    }
+}
+
+impl<P> Deref for Code<P> {
+    type Target = [P];
+
+    fn deref(&self) -> &[P] {
+        &self.0
+    }
+}
+
+///
+/// Every code of `size` pegs made from `palette`, once each, in
+/// order: the first peg changes slowest, and each peg goes
+/// through `palette` in the order it's given. Without duplicates
+/// a code never has the same peg twice, so there are none at all
+/// if `size` is more than there are in `palette`.
+///
+pub fn all_codes<P: Copy>(palette: &[P], size: usize, allow_duplicates: bool) -> AllCodes<P> {
+    let mut codes = AllCodes {
+        palette: palette.to_vec(),
+        allow_duplicates,
+        next: Some(Vec::with_capacity(size)),
+        left: code_space_size(palette.len(), size, allow_duplicates),
+    };
+    // The first code is the one with the first pegs it can have
+    // everywhere, if there's a code at all.
+    if !codes.fill(size) {
+        codes.next = None;
+    }
+    codes
+}
+
+///
+/// How many codes `all_codes` gives for a palette of
+/// `palette_len` pegs, which is `palette_len` to the power of
+/// `size` with duplicates and the number of ways of picking
+/// `size` of them in order without. This saturates at
+/// `usize::MAX` rather than overflowing.
+///
+pub fn code_space_size(palette_len: usize, size: usize, allow_duplicates: bool) -> usize {
+    if allow_duplicates {
+        return (0..size).fold(1usize, |total, _| total.saturating_mul(palette_len));
+    }
+    if size > palette_len {
+        return 0;
+    }
+    (palette_len - size + 1..=palette_len).fold(1usize, |total, x| total.saturating_mul(x))
+}
+
+///
+/// The iterator from `all_codes`.
+///
+#[derive(Clone, Debug)]
+pub struct AllCodes<P = Colour> {
+    palette: Vec<P>,
+    allow_duplicates: bool,
+    ///
+    /// Where in `palette` each peg of the next code is, or `None`
+    /// once every code has been given.
+    ///
+    next: Option<Vec<usize>>,
+    left: usize,
+}
+
+impl<P> AllCodes<P> {
+    //
+    // Fills `next` up to `size` pegs, each with the first peg in
+    // the palette it can be. This is `false` if it couldn't be
+    // filled, because there weren't enough different pegs.
+    //
+    fn fill(&mut self, size: usize) -> bool {
+        let (pegs, allow_duplicates) = (self.palette.len(), self.allow_duplicates);
+        let next = match &mut self.next {
+            Some(next) => next,
+            None => return false,
+        };
+        while next.len() < size {
+            match (0..pegs).find(|x| allow_duplicates || !next.contains(x)) {
+                Some(x) => next.push(x),
+                None => return false,
+            }
+        }
+        true
+    }
+
+    //
+    // Moves `next` on to the code after it. Like counting, the
+    // last peg which can be moved on is, and every peg after it
+    // starts again from the first it can be.
+    //
+    fn advance(&mut self) {
+        let (pegs, allow_duplicates) = (self.palette.len(), self.allow_duplicates);
+        let next = match &mut self.next {
+            Some(next) => next,
+            None => return,
+        };
+        let size = next.len();
+        while let Some(last) = next.pop() {
+            let allowed = |x: &usize| allow_duplicates || !next.contains(x);
+            if let Some(x) = (last + 1..pegs).find(allowed) {
+                next.push(x);
+                self.fill(size);
+                return;
+            }
+        }
+        self.next = None;
+    }
+}
+
+impl<P: Copy> Iterator for AllCodes<P> {
+    type Item = Vec<P>;
+
+    fn next(&mut self) -> Option<Vec<P>> {
+        let code = self
+            .next
+            .as_ref()?
+            .iter()
+            .map(|&x| self.palette[x])
+            .collect();
+        self.left = self.left.saturating_sub(1);
+        self.advance();
+        Some(code)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+impl<P: Copy> ExactSizeIterator for AllCodes<P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::COLOURS;
+    use std::collections::{BTreeSet, HashSet};
+
+    #[test]
+    fn colours_are_ordered_like_colours_is() {
+        assert!(COLOURS.windows(2).all(|x| x[0] < x[1]));
+        assert!(COLOURS.iter().all(|&x| x < Colour::Blank));
+    }
+
+    #[test]
+    fn codes_come_out_in_their_own_order() {
+        for &allow_duplicates in &[false, true] {
+            let codes: Vec<_> = all_codes(&COLOURS[..6], 4, allow_duplicates).collect();
+            assert!(codes.windows(2).all(|x| x[0] < x[1]));
+            let mut sorted = codes.clone();
+            sorted.sort();
+            assert_eq!(sorted, codes);
+        }
+    }
+
+    #[test]
+    fn a_set_of_codes_is_in_the_order_they_come_out_in() {
+        for &allow_duplicates in &[false, true] {
+            let codes: Vec<_> = all_codes(&COLOURS[..6], 4, allow_duplicates)
+                .map(Code::from)
+                .collect();
+            assert!(codes.windows(2).all(|x| x[0] < x[1]));
+            // Backwards, and every code twice
+            let set: BTreeSet<_> = codes.iter().rev().chain(&codes).cloned().collect();
+            assert_eq!(set.into_iter().collect::<Vec<_>>(), codes);
+            let set: HashSet<_> = codes.iter().chain(&codes).collect();
+            assert_eq!(set.len(), codes.len());
+        }
+    }
+
+    #[test]
+    fn equal_codes_hash_the_same() {
+        let codes: HashSet<_> = all_codes(&COLOURS[..6], 4, true).collect();
+        assert_eq!(codes.len(), 6 * 6 * 6 * 6);
+        assert!(codes.contains(&vec![Colour::Red; 4]));
+        let again: HashSet<_> = all_codes(&COLOURS[..6], 4, true)
+            .chain(all_codes(&COLOURS[..6], 4, false))
+            .collect();
+        assert_eq!(again, codes);
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(all_codes(&COLOURS[..6], 4, true).count(), 1296);
+        assert_eq!(all_codes(&COLOURS[..6], 4, false).count(), 360);
+        assert_eq!(code_space_size(6, 4, true), 1296);
+        assert_eq!(code_space_size(6, 4, false), 360);
+        assert_eq!(code_space_size(8, 5, false), 8 * 7 * 6 * 5 * 4);
+        assert_eq!(code_space_size(3, 4, false), 0);
+        assert_eq!(all_codes(&COLOURS[..3], 4, false).count(), 0);
+        // There's exactly one way to have no pegs at all
+        assert_eq!(
+            all_codes(&COLOURS[..3], 0, false).collect::<Vec<_>>(),
+            [vec![]]
+        );
+        assert_eq!(code_space_size(3, 0, true), 1);
+        assert_eq!(code_space_size(0, 2, true), 0);
+        assert_eq!(code_space_size(usize::MAX, 3, true), usize::MAX);
+    }
+
+    #[test]
+    fn every_code_once_on_small_configurations() {
+        for colours in 0..=4 {
+            for size in 0..=4 {
+                for &allow_duplicates in &[false, true] {
+                    let codes: Vec<_> =
+                        all_codes(&COLOURS[..colours], size, allow_duplicates).collect();
+                    let unique: HashSet<_> = codes.iter().cloned().collect();
+                    assert_eq!(unique.len(), codes.len());
+                    assert_eq!(
+                        codes.len(),
+                        code_space_size(colours, size, allow_duplicates)
+                    );
+                    for code in &codes {
+                        assert_eq!(code.len(), size);
+                        assert!(code.iter().all(|x| COLOURS[..colours].contains(x)));
+                        if !allow_duplicates {
+                            assert_eq!(code.iter().collect::<HashSet<_>>().len(), size);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn size_hint_counts_down() {
+        let mut codes = all_codes(&COLOURS[..4], 2, false);
+        for left in (0..=12).rev() {
+            assert_eq!(codes.len(), left);
+            assert_eq!(codes.next().is_some(), left > 0);
+        }
+        assert_eq!(codes.next(), None);
+    }
+
+    #[test]
+    fn codes_are_worked_out_as_they_are_asked_for() {
+        // 8 to the power of 10 codes would never fit in memory
+        let mut codes = all_codes(COLOURS, 10, true);
+        assert_eq!(codes.len(), 1 << 30);
+        assert_eq!(codes.next(), Some(vec![Colour::Red; 10]));
+        let mut second = vec![Colour::Red; 10];
+        second[9] = Colour::Blue;
+        assert_eq!(codes.next(), Some(second));
+    }
+
+    #[test]
+    fn any_pegs_can_be_gone_through() {
+        let codes: Vec<_> = all_codes(&[1u8, 2, 3], 2, false).collect();
+        assert_eq!(codes, [[1, 2], [1, 3], [2, 1], [2, 3], [3, 1], [3, 2]]);
+    }
+}
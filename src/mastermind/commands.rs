@@ -0,0 +1,416 @@
+//!
+//! The commands which do something other than play a game,
+//! like `mastermind saves list` or `mastermind stats`.
+//!
+
+use super::history::{self, Stats};
+use super::json::Json;
+use super::profile;
+use super::save;
+use super::settings;
+use super::storage;
+use super::wording::FeedbackStyle;
+use super::{parse_code, parse_colours, score_guess, Colour};
+use std::collections::HashSet;
+use std::error::Error;
+use std::io::BufRead;
+
+///
+/// Runs `command` with the rest of the command line `args`,
+/// or gives back `None` if `command` isn't one of ours (In
+/// which case a game should be played instead).
+///
+pub fn run(
+    command: &str,
+    args: impl Iterator<Item = String>,
+) -> Option<Result<(), Box<dyn Error>>> {
+    Some(match command {
+        "saves" => saves_command(args),
+        "profiles" => profiles_command(args),
+        "stats" => stats_command(args),
+        "leaderboard" => leaderboard_command(args),
+        "relay" => super::relay::run(args),
+        "tutorial" => super::tutorial::run(),
+        "demo" => super::demo::run(args),
+        "simulate" => super::simulate::run(args),
+        "audit" => super::audit::run(args),
+        "quiz" => super::quiz::run(args),
+        "host" => super::local::host(args),
+        "join" => super::local::join(args),
+        "serve" => super::serve::run(args),
+        "score" => score_command(args),
+        _ => return None,
+    })
+}
+
+///
+/// `mastermind saves list` and `mastermind saves delete NAME`.
+///
+fn saves_command(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    match args.next().as_deref() {
+        Some("list") => {
+            let slots = storage::list_slots()?;
+            if slots.is_empty() {
+                println!("There are no save slots");
+            }
+            for name in slots {
+                let path = storage::autosave_path(&name)?;
+                if !save::has_autosave(&path) {
+                    println!("{}: no unfinished game", name);
+                    continue;
+                }
+                match save::describe(&path) {
+                    Ok(info) => println!(
+                        "{}: game #{} with {} pegs, duplicates {}, {} attempt(s) made{}",
+                        name,
+                        info.games_played + 1,
+                        info.config.pegs,
+                        if info.config.allow_duplicates {
+                            "allowed"
+                        } else {
+                            "not allowed"
+                        },
+                        info.attempts,
+                        info.saved_at
+                            .map(|x| format!(", saved {}", describe_age(x)))
+                            .unwrap_or_default()
+                    ),
+                    Err(e) => println!("{}: {}", name, e),
+                }
+            }
+            Ok(())
+        }
+        Some("delete") => {
+            let name = args
+                .next()
+                .ok_or("`saves delete` needs the name of a slot")?;
+            storage::validate_slot_name(&name)?;
+            if !storage::list_slots()?.contains(&name) {
+                return Err(format!("There is no save slot called `{}`", name).into());
+            }
+            storage::delete_slot(&name)?;
+            println!("Deleted the `{}` save slot", name);
+            Ok(())
+        }
+        _ => Err("Usage: `mastermind saves list` or `mastermind saves delete NAME`".into()),
+    }
+}
+
+//
+// How long ago `time` was, in rough human terms.
+//
+fn describe_age(time: std::time::SystemTime) -> String {
+    let seconds = time.elapsed().map(|x| x.as_secs()).unwrap_or(0);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} minute(s) ago", seconds / 60),
+        3600..=86399 => format!("{} hour(s) ago", seconds / 3600),
+        _ => format!("{} day(s) ago", seconds / 86400),
+    }
+}
+
+///
+/// `mastermind profiles list`, `mastermind profiles rename OLD NEW`
+/// and `mastermind profiles delete NAME`.
+///
+fn profiles_command(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    match args.next().as_deref() {
+        Some("list") => {
+            let profiles = profile::list()?;
+            if profiles.is_empty() {
+                println!("There are no players yet");
+            }
+            let last = profile::last_used();
+            for profile in profiles {
+                println!(
+                    "{}{}",
+                    profile.name,
+                    if Some(profile.id) == last {
+                        " (played last)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            Ok(())
+        }
+        Some("rename") => {
+            let (old, new) = match (args.next(), args.next()) {
+                (Some(old), Some(new)) => (old, new),
+                _ => return Err("`profiles rename` needs the old and new names".into()),
+            };
+            profile::rename(&old, &new)?;
+            println!("Renamed `{}` to `{}`", old, new);
+            Ok(())
+        }
+        Some("delete") => {
+            let name = args
+                .next()
+                .ok_or("`profiles delete` needs the name of a player")?;
+            profile::delete(&name)?;
+            println!("Deleted the player `{}`", name);
+            Ok(())
+        }
+        _ => Err(
+            "Usage: `mastermind profiles list`, `mastermind profiles rename OLD NEW` \
+                  or `mastermind profiles delete NAME`"
+                .into(),
+        ),
+    }
+}
+
+///
+/// `mastermind stats`, optionally with `--profile NAME` to only
+/// show one player, and `--label TEXT` to only count the games
+/// with labels like `TEXT` (Which are listed too).
+///
+fn stats_command(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut only = None;
+    let mut label = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" => only = Some(args.next().ok_or("`--profile` needs a name")?),
+            "--label" => label = Some(args.next().ok_or("`--label` needs some text")?),
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    let profiles = match only {
+        Some(name) => {
+            vec![profile::find(&name)?
+                .ok_or_else(|| format!("There is no player called `{}`", name))?]
+        }
+        None => profile::list()?,
+    };
+    let mut entries = history::load()?;
+    if let Some(label) = &label {
+        entries.retain(|x| x.label_matches(label));
+        if entries.is_empty() {
+            println!("No games have a label like `{}`", label);
+            return Ok(());
+        }
+    }
+    for profile in profiles {
+        let stats = Stats::for_profile(&entries, profile.id);
+        // Everyone who didn't play one of the labelled games
+        // would only be noise.
+        if label.is_some() && stats.played == 0 {
+            continue;
+        }
+        print!(
+            "{}: {} game(s) played, {} won ({:.0}%)",
+            profile.name,
+            stats.played,
+            stats.won,
+            stats.win_rate() * 100.0
+        );
+        if stats.assisted > 0 {
+            print!(", {} of them with help", stats.assisted);
+        }
+        if stats.hardcore > 0 {
+            print!(", {} in hardcore mode", stats.hardcore);
+        }
+        if stats.memory > 0 {
+            print!(", {} in memory mode", stats.memory);
+        }
+        if stats.wagered > 0 {
+            print!(
+                ", {} points from {} game(s) with wagers",
+                stats.points, stats.wagered
+            );
+        }
+        if stats.speed_bonus > 0 {
+            print!(", {} speed bonus points", stats.speed_bonus);
+        }
+        println!();
+        let (asked, right) = history::load_quiz(profile.id)?
+            .iter()
+            .fold((0, 0), |(asked, right), x| {
+                (asked + x.asked, right + x.right)
+            });
+        if label.is_none() && asked > 0 {
+            println!(
+                "  Quiz: {} of {} answers right ({:.0}%)",
+                right,
+                asked,
+                right as f64 / asked as f64 * 100.0
+            );
+        }
+        if label.is_some() {
+            for entry in entries.iter().filter(|x| x.profile == profile.id) {
+                println!(
+                    "  {:?}: {} with {} attempts",
+                    entry.record.label.as_deref().unwrap_or_default(),
+                    entry.record.outcome.name(),
+                    entry.record.attempts
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+///
+/// `mastermind leaderboard`, every player who has finished a game
+/// ranked by the fraction of games they've won, or with
+/// `--by speed` by their total speed bonus (See `speed.rs`).
+///
+fn leaderboard_command(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut by_speed = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--by" => {
+                by_speed = match args.next().as_deref() {
+                    Some("wins") => false,
+                    Some("speed") => true,
+                    _ => return Err("`--by` needs `wins` or `speed`".into()),
+                }
+            }
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    let entries = history::load()?;
+    let mut rows = profile::list()?
+        .into_iter()
+        .map(|x| {
+            let stats = Stats::for_profile(&entries, x.id);
+            (x.name, stats)
+        })
+        .filter(|(_, stats)| stats.played > 0)
+        .collect::<Vec<_>>();
+    // Best win rate first, and more wins first among equal rates.
+    rows.sort_by(|(_, a), (_, b)| {
+        b.win_rate()
+            .partial_cmp(&a.win_rate())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.won.cmp(&a.won))
+    });
+    if by_speed {
+        // Sorting keeps the order of equal elements, so ties
+        // are still broken by win rate.
+        rows.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.speed_bonus));
+    }
+    if rows.is_empty() {
+        println!("Nobody has finished a game yet");
+    }
+    for (idx, (name, stats)) in rows.iter().enumerate() {
+        if by_speed {
+            println!(
+                "{:>2}. {:<32} {:>6} points from {} game(s)",
+                idx + 1,
+                name,
+                stats.speed_bonus,
+                stats.played
+            );
+        } else {
+            println!(
+                "{:>2}. {:<32} {:>3.0}% of {} game(s)",
+                idx + 1,
+                name,
+                stats.win_rate() * 100.0,
+                stats.played
+            );
+        }
+    }
+    Ok(())
+}
+
+///
+/// `mastermind score --secret CODE --guess CODE`, which prints
+/// the feedback the guess would get and nothing else.
+///
+/// `--guess -` reads a guess from each line of stdin instead,
+/// printing one answer per line. Lines which can't be scored
+/// are reported on stderr, and make the command fail once the
+/// rest have been scored. `--duplicates false` turns away codes
+/// with a colour in them twice, `--feedback` picks the wording
+/// (See `wording.rs`), and `--json` prints JSON instead.
+///
+fn score_command(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut secret = None;
+    let mut guess = None;
+    let mut duplicates = true;
+    let mut style = None;
+    let mut json = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--secret" => secret = Some(args.next().ok_or("`--secret` needs a code")?),
+            "--guess" => guess = Some(args.next().ok_or("`--guess` needs a code, or `-`")?),
+            "--duplicates" => {
+                duplicates = args
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .ok_or("`--duplicates` needs `true` or `false`")?
+            }
+            "--feedback" => {
+                style = Some(
+                    args.next()
+                        .ok_or("`--feedback` needs a style")?
+                        .parse::<FeedbackStyle>()?,
+                )
+            }
+            "--json" => json = true,
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    let secret = parse_colours(&secret.ok_or("`score` needs a `--secret`")?)
+        .map_err(|e| format!("The secret: {}", e))?;
+    if secret.is_empty() {
+        return Err("The secret needs at least one colour".into());
+    }
+    let style = match style {
+        Some(x) => x,
+        None => settings::load_feedback_style()?,
+    };
+    let no_repeats = |code: &[Colour]| {
+        let mut seen = HashSet::new();
+        if duplicates || code.iter().all(|x| seen.insert(*x)) {
+            Ok(())
+        } else {
+            Err("Duplicates were turned off with `--duplicates false`".to_string())
+        }
+    };
+    no_repeats(&secret).map_err(|e| format!("The secret: {}", e))?;
+    let check = |text: &str| {
+        let code = parse_code(text, secret.len())?;
+        no_repeats(&code).map(|()| code)
+    };
+    let answer = |guess: &[Colour]| {
+        let feedback = score_guess(&secret, guess);
+        if json {
+            Json::object(vec![
+                (
+                    "guess",
+                    Json::Array(guess.iter().map(|x| x.name().into()).collect()),
+                ),
+                ("correct_position", feedback.exact.into()),
+                ("correct_colour", feedback.misplaced.into()),
+            ])
+            .to_string()
+        } else {
+            style.describe(feedback)
+        }
+    };
+    match guess.ok_or("`score` needs a `--guess`")?.as_str() {
+        "-" => {
+            let mut failed = 0;
+            for (idx, line) in std::io::stdin().lock().lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match check(&line) {
+                    Ok(guess) => println!("{}", answer(&guess)),
+                    Err(e) => {
+                        eprintln!("Line {}: {}", idx + 1, e);
+                        failed += 1;
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(format!("{} guess(es) couldn't be scored", failed).into());
+            }
+        }
+        guess => println!("{}", answer(&check(guess)?)),
+    }
+    Ok(())
+}
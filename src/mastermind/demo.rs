@@ -0,0 +1,256 @@
+//!
+//! `mastermind demo`, a game which plays itself while
+//! explaining what's going on, for showing the game off.
+//!
+//! The game is played from a script: a secret, and a list of
+//! guesses with something to say after each one. A script is
+//! built in, but `--script FILE` plays another one instead.
+//! The format is one instruction per line:
+//! ```text
+//! # Lines starting with `#` are ignored
+//! secret bgry
+//! say Something to print
+//! guess rbwy
+//! ```
+//! Guesses are typed out and fed to a normal `State`, so the
+//! demo always looks the same as a real game does.
+//!
+//! Pressing any key stops the demo, and `--fast` skips all of
+//! the pauses.
+//!
+
+use super::{terminal, Colour, GameConfig, GameEvent, State, TerminalOutput, GUESS_PROMPT};
+use std::error::Error;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+///
+/// The script played if no other one is given.
+///
+const BUILT_IN: &str = "\
+secret bgry
+say This game has 4 pegs, and no colour is used twice.
+say With nothing to go on yet, any four colours make a good start.
+guess rbwy
+say Three of those four colours are in the code, and one of them is
+say in the right place. Let's swap White for Orange and move the rest.
+guess bryo
+say Still three colours and one in place, so White and Orange are either
+say both in the code or both out. If they're out, the code has to be
+say Green, Red, Blue and Yellow. Let's try that, in all new places.
+guess gybr
+say All four colours are right, but none are in the right place! Only two
+say codes fit everything so far: Red Green Yellow Blue, or Blue Green Red
+say Yellow. We'll try the first one.
+guess rgyb
+say Not that one, so it has to be the other.
+guess bgry
+";
+
+#[derive(Debug)]
+enum Step {
+    Say(String),
+    Guess(String),
+}
+
+#[derive(Debug)]
+struct Script {
+    secret: Vec<Colour>,
+    steps: Vec<Step>,
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut fast = false;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fast" => fast = true,
+            "--script" => path = Some(args.next().ok_or("`--script` needs a file name")?),
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    let script = match path {
+        Some(path) => parse_script(&std::fs::read_to_string(&path)?)
+            .map_err(|e| format!("Could not read {}: {}", path, e))?,
+        None => parse_script(BUILT_IN)?,
+    };
+    let config = GameConfig {
+        pegs: script.secret.len(),
+        allow_duplicates: (1..script.secret.len())
+            .any(|idx| script.secret[..idx].contains(&script.secret[idx])),
+        max_tries: None,
+        auto_max_tries: false,
+        ..GameConfig::default()
+    };
+    let mut state = State::new(
+        config,
+        |game| println!("You won in {} tries!", game.attempts_made),
+        |game| {
+            println!(
+                "Uh-oh, you lost after {} tries. The code was {:?}",
+                game.attempts_made,
+                game.secret.as_deref().unwrap_or_default()
+            )
+        },
+        TerminalOutput,
+    )
+    .map_err(|e| format!("The secret in the script isn't a valid code: {}", e))?;
+    state.set_secret(script.secret.clone());
+
+    // Listen for a key press on another thread, so that we can
+    // keep playing in the meantime. An `Arc` lets both threads
+    // share the flag, and an `AtomicBool` can be changed by one
+    // thread while the other is reading it.
+    let stop = Arc::new(AtomicBool::new(false));
+    let _raw = terminal::RawMode::enable().inspect(|_| {
+        let stop = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            if let Ok(Some(_)) = terminal::read_key() {
+                stop.store(true, Ordering::SeqCst);
+            }
+        });
+        println!("(Press any key to stop the demo)");
+    });
+    let pause = |millis| {
+        let mut left = if fast { 0 } else { millis };
+        // Sleep a little at a time, so a key press is noticed soon
+        while left > 0 && !stop.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(left.min(50)));
+            left -= left.min(50);
+        }
+        !stop.load(Ordering::SeqCst)
+    };
+
+    let mut won = false;
+    for step in &script.steps {
+        if !pause(0) {
+            break;
+        }
+        match step {
+            Step::Say(text) => {
+                println!("{}", text);
+                pause(1200);
+            }
+            Step::Guess(code) => {
+                print!("{}", GUESS_PROMPT);
+                for c in code.chars() {
+                    print!("{}", c);
+                    std::io::stdout().flush()?;
+                    pause(150);
+                }
+                println!();
+                match state.push_string_input(code) {
+                    Ok(events) => won = events.iter().any(GameEvent::ends_game),
+                    Err(e) => println!("Error encountered: {}", e),
+                }
+                pause(800);
+            }
+        }
+        if won {
+            break;
+        }
+    }
+    if !won {
+        println!("The demo was stopped before the end.");
+    }
+    println!("The code was {:?}", script.secret);
+    Ok(())
+}
+
+fn parse_script(text: &str) -> Result<Script, String> {
+    let mut secret = None;
+    let mut steps = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (instruction, rest) = line.split_at(line.find(' ').unwrap_or(line.len()));
+        let rest = rest.trim();
+        match instruction {
+            "secret" => {
+                secret = Some(
+                    rest.char_indices()
+                        .map(|(idx, _)| rest[idx..].parse())
+                        .collect::<Result<Vec<Colour>, _>>()
+                        .map_err(|e| e.to_string())?,
+                )
+            }
+            "say" => steps.push(Step::Say(rest.to_string())),
+            "guess" => steps.push(Step::Guess(rest.to_string())),
+            _ => {
+                return Err(format!(
+                    "line {}: expected `secret`, `say` or `guess`",
+                    idx + 1
+                ))
+            }
+        }
+    }
+    Ok(Script {
+        secret: secret.ok_or("the script needs a `secret` line")?,
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SilentOutput;
+    use super::*;
+
+    // Plays `script` without any of the typing or pauses, giving
+    // whether each guess ended the game.
+    fn play(script: &Script) -> Vec<bool> {
+        let config = GameConfig {
+            pegs: script.secret.len(),
+            max_tries: None,
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let mut state = State::new(config, |_| {}, |_| {}, SilentOutput).unwrap();
+        state.set_secret(script.secret.clone());
+        script
+            .steps
+            .iter()
+            .filter_map(|step| match step {
+                Step::Guess(code) => Some(code),
+                Step::Say(_) => None,
+            })
+            .map(|code| {
+                let events = state.push_string_input(code).unwrap();
+                events.iter().any(GameEvent::ends_game)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_built_in_script_wins_on_its_last_guess() {
+        let script = parse_script(BUILT_IN).unwrap();
+        assert_eq!(
+            script.secret,
+            vec![Colour::Blue, Colour::Green, Colour::Red, Colour::Yellow]
+        );
+        assert_eq!(play(&script), vec![false, false, false, false, true]);
+    }
+
+    #[test]
+    fn scripts_skip_comments_and_blank_lines() {
+        let script =
+            parse_script("# A comment\n\nsecret rr\n  say Hi there  \nguess rr\n").unwrap();
+        assert_eq!(script.secret, vec![Colour::Red, Colour::Red]);
+        assert!(matches!(&script.steps[0], Step::Say(text) if text == "Hi there"));
+        assert!(matches!(&script.steps[1], Step::Guess(code) if code == "rr"));
+    }
+
+    #[test]
+    fn bad_scripts_say_what_is_wrong() {
+        assert!(parse_script("say No secret")
+            .unwrap_err()
+            .contains("secret"));
+        assert!(parse_script("secret rgby\nshout Hi")
+            .unwrap_err()
+            .contains("line 2"));
+        assert!(parse_script("secret rgbq").is_err());
+    }
+}
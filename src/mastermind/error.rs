@@ -0,0 +1,368 @@
+//!
+//! `MastermindError`, everything that can go wrong while reading
+//! and entering guesses, and `ConfigError`, everything that can
+//! be wrong with a game's settings.
+//!
+//! These used to be `String`s, which are fine for printing but
+//! leave anything else to pick the message apart to find out
+//! what happened. An enum can be matched on instead, and its
+//! `Display` still gives the same messages as before.
+//!
+
+use super::wording::FeedbackStyle;
+use super::{Colour, Feedback, GuessError, Symbol, MIN_PEGS};
+use std::fmt;
+
+///
+/// What went wrong, see the top of this file. The guesses and
+/// attempts named here count from zero, like the rest of
+/// `State` does, and are only counted from one when written out.
+///
+/// The colours in it are whatever the game's pegs are, see
+/// `symbol.rs`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum MastermindError<P = Colour> {
+    ///
+    /// There was nothing to read a colour from.
+    ///
+    InputTooShort,
+    ///
+    /// This letter isn't the first letter of any colour.
+    ///
+    InvalidColourChar(char),
+    ///
+    /// A word which isn't a colour, or the start of one. When it
+    /// was read as a run of letters, `letter` is the first one
+    /// which didn't work.
+    ///
+    UnknownColour { word: String, letter: Option<char> },
+    ///
+    /// A word which is the start of more than one colour, and
+    /// which colours those are.
+    ///
+    AmbiguousColour { word: String, could_be: Vec<P> },
+    ///
+    /// A blank, in a game without them. `so_far` is the guess it
+    /// would have gone into, here and below.
+    ///
+    BlankNotAllowed { so_far: Vec<P> },
+    ///
+    /// A colour which isn't one of the colours in play, see
+    /// `GameConfig::palette_size`.
+    ///
+    NotInPlay(P),
+    ///
+    /// This colour is already in the guess, without duplicates.
+    ///
+    DuplicateNotAllowed { colour: P, so_far: Vec<P> },
+    ///
+    /// There's already as many of this colour in the guess as
+    /// `GameConfig::max_per_colour` allows.
+    ///
+    TooManyOfColour {
+        colour: P,
+        most: usize,
+        so_far: Vec<P>,
+    },
+    ///
+    /// The guess is full and waiting for `State::commit_guess`.
+    ///
+    GuessFull { so_far: Vec<P> },
+    ///
+    /// `State::commit_guess` was asked to score a guess which
+    /// only has `got` of its `needed` colours.
+    ///
+    GuessTooShort { got: usize, needed: usize },
+    ///
+    /// A line of `got` colours, with `GameConfig::strict_input`,
+    /// when there's only room for `max` more.
+    ///
+    GuessTooLong { got: usize, max: usize },
+    ///
+    /// There's no attempt with this index this game.
+    ///
+    NoSuchAttempt(usize),
+    ///
+    /// The guess is the same as the attempt at this index, with
+    /// `GameConfig::block_repeats`.
+    ///
+    RepeatedGuess { attempt: usize, feedback: Feedback },
+    ///
+    /// The whole guess isn't allowed with these settings.
+    ///
+    InvalidGuess(GuessError<P>),
+}
+
+impl<P: Symbol> fmt::Display for MastermindError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MastermindError::InputTooShort => write!(f, "Input too short!"),
+            MastermindError::InvalidColourChar(c) => {
+                write!(f, "Invalid initial character: `{}`", c)
+            }
+            MastermindError::UnknownColour { word, letter } => {
+                match letter {
+                    Some(c) => write!(f, "`{}` in `{}`", c, word)?,
+                    None => write!(f, "`{}`", word)?,
+                }
+                let names = P::all().iter().map(|x| x.name()).collect::<Vec<_>>();
+                let noun = P::noun();
+                write!(
+                    f,
+                    " isn't a {}, the {}s are {}",
+                    noun,
+                    noun,
+                    names.join(", ")
+                )
+            }
+            MastermindError::AmbiguousColour { word, could_be } => {
+                let names = could_be.iter().map(|x| x.name()).collect::<Vec<_>>();
+                write!(f, "`{}` could be {}", word, names.join(" or "))
+            }
+            MastermindError::BlankNotAllowed { so_far } => write!(
+                f,
+                "There are no blanks in this game, so carry on from {:?} with a {}",
+                so_far,
+                P::noun()
+            ),
+            MastermindError::NotInPlay(colour) => {
+                write!(f, "{:?} is not in play this game", colour)
+            }
+            MastermindError::DuplicateNotAllowed { colour, so_far } => write!(
+                f,
+                "{:?} is already in this guess and duplicates aren't allowed, so carry on \
+                 from {:?} with a different {}",
+                colour,
+                so_far,
+                P::noun()
+            ),
+            MastermindError::TooManyOfColour {
+                colour,
+                most,
+                so_far,
+            } => write!(
+                f,
+                "{:?} is already in this guess {} times, which is as many as one {} \
+                 can be, so carry on from {:?} with a different {}",
+                colour,
+                most,
+                P::noun(),
+                so_far,
+                P::noun()
+            ),
+            MastermindError::GuessFull { so_far } => write!(
+                f,
+                "The guess {:?} is already full, so make it or take a {} back first",
+                so_far,
+                P::noun()
+            ),
+            MastermindError::GuessTooShort { got, needed } => write!(
+                f,
+                "That guess only has {} of its {} {}s so far",
+                got,
+                needed,
+                P::noun()
+            ),
+            MastermindError::GuessTooLong { got, max } => write!(
+                f,
+                "That's {} {}s, but there's only room for {} more in this guess",
+                got,
+                P::noun(),
+                max
+            ),
+            MastermindError::NoSuchAttempt(idx) => {
+                write!(f, "There is no guess {} this game", idx + 1)
+            }
+            MastermindError::RepeatedGuess { attempt, feedback } => write!(
+                f,
+                "You already tried that on attempt {}, which got {}",
+                attempt + 1,
+                FeedbackStyle::default().describe(*feedback)
+            ),
+            MastermindError::InvalidGuess(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<P: Symbol> std::error::Error for MastermindError<P> {}
+
+impl<P> From<GuessError<P>> for MastermindError<P> {
+    fn from(e: GuessError<P>) -> Self {
+        MastermindError::InvalidGuess(e)
+    }
+}
+
+///
+/// Why a set of settings can't make a secret code, from
+/// `GameConfig::validate`. `State::new` gives one of these back
+/// instead of a game, and leaves it to whoever asked to say so.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    ///
+    /// Fewer than `MIN_PEGS` pegs.
+    ///
+    TooFewPegs { requested: usize },
+    ///
+    /// More pegs than there are colours to fill them with. A
+    /// blank counts as a colour here, in a game with them.
+    ///
+    TooManyPegs { requested: usize, available: usize },
+    ///
+    /// A limit of zero tries.
+    ///
+    NoTries,
+    ///
+    /// A limit of more than `MAX_TRIES` tries.
+    ///
+    TooManyTries { most: usize },
+    ///
+    /// `max_per_colour` of zero.
+    ///
+    NoneOfAnyColour,
+    ///
+    /// `colours` used at most `most` times each, which isn't
+    /// enough to fill the code.
+    ///
+    CantFillPegs {
+        colours: usize,
+        most: usize,
+        pegs: usize,
+    },
+    ///
+    /// `reveal_every` of zero.
+    ///
+    RevealEveryZero,
+    ///
+    /// A colour with a weight of zero.
+    ///
+    ZeroWeight,
+    ///
+    /// More colours in `must_include` than there are pegs.
+    ///
+    TooManyRequired { pegs: usize, required: usize },
+    ///
+    /// `max_distinct_colours` is less than the number of colours
+    /// in `must_include`.
+    ///
+    RequiredOverLimit { required: usize },
+    ///
+    /// `min_distinct_colours` of zero.
+    ///
+    FewestColoursZero,
+    ///
+    /// `min_distinct_colours` is more than there are pegs.
+    ///
+    FewestOverPegs { pegs: usize, fewest: usize },
+    ///
+    /// A number of different colours which is more than there
+    /// are to choose from.
+    ///
+    NotEnoughColours { available: usize },
+    ///
+    /// `min_distinct_colours` is more than `max_distinct_colours`.
+    ///
+    FewestOverMost { fewest: usize },
+    ///
+    /// `max_distinct_colours` of zero.
+    ///
+    MostColoursZero,
+    ///
+    /// A `palette_size` of zero, or of more colours than there
+    /// are.
+    ///
+    PaletteSize { available: usize },
+    ///
+    /// A colour in `must_include` which isn't in play.
+    ///
+    RequiredNotInPlay,
+    ///
+    /// `max_distinct_colours` is less than the pegs, without
+    /// duplicates.
+    ///
+    TooFewColoursForPegs { pegs: usize },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::TooFewPegs { .. } => {
+                write!(f, "Choose at least {} pegs to play with!", MIN_PEGS)
+            }
+            ConfigError::TooManyPegs { available, .. } => write!(
+                f,
+                "Choose less than or equal to {} pegs to play with!",
+                available
+            ),
+            ConfigError::NoTries => {
+                write!(f, "A game needs at least one try, or no limit at all!")
+            }
+            ConfigError::TooManyTries { most } => write!(
+                f,
+                "A game can have at most {} tries, or no limit at all!",
+                most
+            ),
+            ConfigError::NoneOfAnyColour => {
+                write!(f, "Every colour has to be allowed at least once!")
+            }
+            ConfigError::CantFillPegs {
+                colours,
+                most,
+                pegs,
+            } => write!(
+                f,
+                "{} colours used at most {} times each can't fill {} pegs!",
+                colours, most, pegs
+            ),
+            ConfigError::RevealEveryZero => {
+                write!(f, "Positions can't be revealed after zero wrong guesses!")
+            }
+            ConfigError::ZeroWeight => write!(f, "Every colour needs a weight of at least one!"),
+            ConfigError::TooManyRequired { pegs, required } => write!(
+                f,
+                "{} pegs can't fit all {} colours the code must include!",
+                pegs, required
+            ),
+            ConfigError::RequiredOverLimit { required } => write!(
+                f,
+                "The code must include {} colours, so it can't be limited to fewer!",
+                required
+            ),
+            ConfigError::FewestColoursZero => {
+                write!(f, "The code always uses at least one colour!")
+            }
+            ConfigError::FewestOverPegs { pegs, fewest } => {
+                write!(f, "{} pegs can't have {} different colours!", pegs, fewest)
+            }
+            ConfigError::NotEnoughColours { available } => {
+                write!(f, "There are only {} colours to choose from!", available)
+            }
+            ConfigError::FewestOverMost { fewest } => write!(
+                f,
+                "The code can't use at least {} colours when it's limited to fewer!",
+                fewest
+            ),
+            ConfigError::MostColoursZero => {
+                write!(f, "The code needs to use at least one colour!")
+            }
+            ConfigError::PaletteSize { available } => {
+                write!(f, "Choose from 1 to {} colours to have in play!", available)
+            }
+            ConfigError::RequiredNotInPlay => {
+                write!(f, "The code can't include a colour which isn't in play!")
+            }
+            ConfigError::TooFewColoursForPegs { pegs } => write!(
+                f,
+                "{} pegs without duplicates needs at least {} colours!",
+                pegs, pegs
+            ),
+        }
+    }
+}
+
+///
+/// Like `SaveError`, this lets `?` turn a `ConfigError` into a
+/// `Box<dyn Error>`, which is how `main` gets to print it.
+///
+impl std::error::Error for ConfigError {}
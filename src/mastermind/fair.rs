@@ -0,0 +1,262 @@
+//!
+//! A fair number of tries for some settings, which is what the
+//! setup questions offer and what `--max-tries auto` picks.
+//! Ten tries is plenty with three pegs, but not many with six
+//! pegs and duplicates, so the limit is worked out from how
+//! many codes the secret could be.
+//!
+//! It starts from how many guesses a good solver needs at worst:
+//!
+//! - For the classic game (Four pegs, duplicates, every colour)
+//!   that's known to be 5, found by Knuth in 1977.
+//! - Otherwise, each guess gets one of `F` possible feedbacks,
+//!   so `n` guesses can tell at most `F^n` codes apart, and
+//!   `ceil(log_F(codes))` guesses are needed at the very least.
+//!   Solvers need a couple more than that, and `MARGIN` is
+//!   picked so that this gives 5 for the classic game too.
+//!
+//! A person isn't a solver, and keeping track of what each bit
+//! of feedback means gets harder the longer the code is, so the
+//! limit is that worst case plus one try for each peg past the
+//! first:
+//!
+//! ```text
+//! fair = worst case + (pegs - 1)
+//! ```
+//!
+//! Which is 8 for the classic game, 7 for three pegs and 11 for
+//! six pegs with duplicates.
+//!
+
+use super::{GameConfig, CLASSIC_PALETTE_SIZE};
+
+///
+/// Tries a solver needs on top of the least any guesser could
+/// need, see the top of this file.
+///
+pub const MARGIN: usize = 2;
+
+///
+/// The most guesses a good solver needs for the classic game.
+///
+pub const CLASSIC_WORST_CASE: usize = 5;
+
+///
+/// The fair number of tries for games with `config`, see the
+/// top of this file. Only the settings which change what the
+/// secret could be matter, `max_tries` itself is ignored.
+///
+pub fn max_tries(config: GameConfig) -> usize {
+    worst_case(config) + config.pegs.saturating_sub(1)
+}
+
+///
+/// How many guesses a good solver needs at worst, worked out as
+/// described at the top of this file.
+///
+pub fn worst_case(config: GameConfig) -> usize {
+    let classic = config.pegs == 4
+        && config.allow_duplicates
+        && !config.allow_blanks
+        && config.palette().len() == CLASSIC_PALETTE_SIZE
+        && config.max_per_colour.is_none_or(|x| x >= config.pegs)
+        && config
+            .max_distinct_colours
+            .is_none_or(|x| x >= CLASSIC_PALETTE_SIZE);
+    if classic {
+        return CLASSIC_WORST_CASE;
+    }
+    let codes = code_space(config);
+    if codes <= 1 {
+        return 1;
+    }
+    // Every (exact, misplaced) pair with exact + misplaced at
+    // most the number of pegs, less "all but one exact and one
+    // misplaced", which can't happen.
+    let feedbacks = (config.pegs + 1) * (config.pegs + 2) / 2 - 1;
+    let least = (codes as f64).ln() / (feedbacks as f64).ln();
+    // Floating point can land a hair over a whole number
+    (least - 1e-9).ceil() as usize + MARGIN
+}
+
+///
+/// How many codes the secret could be with `config`, the same
+/// ones `solver::candidates` lists but without listing them.
+///
+pub fn code_space(config: GameConfig) -> usize {
+    let colours = config.palette().len();
+    let pegs = config.pegs;
+    if !config.allow_duplicates {
+        // A limit on colours can only make things impossible
+        // when every peg has its own colour
+        return match config.max_distinct_colours {
+            Some(x) if x < pegs => 0,
+            _ if pegs > colours => 0,
+            _ => (colours - pegs + 1..=colours).product(),
+        };
+    }
+    let most = config.max_per_colour.unwrap_or(pegs);
+    match config.max_distinct_colours {
+        // The secret uses exactly this many colours (See
+        // `GameConfig::max_distinct_colours`), so pick which ones
+        // and then every way of filling the pegs with all of them.
+        Some(x) if x <= pegs => choose(colours, x) * between(pegs, x, 1, most),
+        // Otherwise add up the codes with each number of colours
+        // there can be, from the fewest allowed
+        _ => (config.min_distinct_colours.unwrap_or(0)..=pegs.min(colours))
+            .map(|x| choose(colours, x) * between(pegs, x, 1, most))
+            .sum(),
+    }
+}
+
+//
+// The number of ways to pick `k` things out of `n`.
+//
+fn choose(n: usize, k: usize) -> usize {
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+//
+// The number of ways to fill `pegs` pegs with `colours` colours,
+// using each one from `least` to `most` times. The colours are
+// added one at a time: `ways[n]` is how many ways there are to
+// fill `n` of the pegs with the colours so far, and using the
+// next one `k` times means picking which `k` of the `n + k`
+// pegs it goes in.
+//
+fn between(pegs: usize, colours: usize, least: usize, most: usize) -> usize {
+    let mut ways = vec![0; pegs + 1];
+    ways[0] = 1;
+    for _ in 0..colours {
+        let mut next = vec![0; pegs + 1];
+        for (filled, &count) in ways.iter().enumerate() {
+            for times in least..=most.min(pegs - filled) {
+                next[filled + times] += count * choose(filled + times, times);
+            }
+        }
+        ways = next;
+    }
+    ways[pegs]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::json::Json;
+
+    fn config(pegs: usize, allow_duplicates: bool) -> GameConfig {
+        GameConfig {
+            pegs,
+            allow_duplicates,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn the_documented_limits() {
+        assert_eq!(worst_case(config(4, true)), CLASSIC_WORST_CASE);
+        assert_eq!(max_tries(config(4, true)), 8);
+        assert_eq!(max_tries(config(3, true)), 7);
+        assert_eq!(max_tries(config(6, true)), 11);
+        // What `GameConfig::default` is pinned to
+        assert_eq!(max_tries(config(4, false)), 8);
+    }
+
+    #[test]
+    fn longer_codes_never_get_fewer_tries() {
+        for &duplicates in &[false, true] {
+            let limits = (1..=6)
+                .map(|pegs| max_tries(config(pegs, duplicates)))
+                .collect::<Vec<_>>();
+            assert!(limits.windows(2).all(|x| x[0] <= x[1]), "{:?}", limits);
+        }
+    }
+
+    #[test]
+    fn one_possible_code_needs_one_guess() {
+        let config = GameConfig {
+            pegs: 1,
+            palette_size: Some(1),
+            ..config(1, false)
+        };
+        assert_eq!(code_space(config), 1);
+        assert_eq!(worst_case(config), 1);
+    }
+
+    #[test]
+    fn code_spaces_match_counting_every_code() {
+        let configs = [
+            config(4, false),
+            config(4, true),
+            config(5, true),
+            GameConfig {
+                max_per_colour: Some(2),
+                ..config(5, true)
+            },
+            GameConfig {
+                max_distinct_colours: Some(2),
+                ..config(4, true)
+            },
+            GameConfig {
+                min_distinct_colours: Some(3),
+                palette_size: Some(8),
+                ..config(4, true)
+            },
+            GameConfig {
+                allow_blanks: true,
+                ..config(3, true)
+            },
+        ];
+        for &config in &configs {
+            let palette = config.palette();
+            let counted =
+                crate::mastermind::all_codes(&palette, config.pegs, config.allow_duplicates)
+                    .filter(|code| {
+                        let colours = code.iter().collect::<std::collections::HashSet<_>>().len();
+                        config.over_limit(code).is_none()
+                            && config.enough_colours(code)
+                            && config.max_distinct_colours.is_none_or(|x| colours == x)
+                    })
+                    .count();
+            assert_eq!(code_space(config), counted, "{:?}", config);
+        }
+    }
+
+    #[test]
+    fn impossible_settings_have_no_codes() {
+        assert_eq!(code_space(config(7, false)), 0);
+        let config = GameConfig {
+            max_distinct_colours: Some(3),
+            ..config(4, false)
+        };
+        assert_eq!(code_space(config), 0);
+    }
+
+    #[test]
+    fn choosing() {
+        assert_eq!(choose(6, 0), 1);
+        assert_eq!(choose(6, 2), 15);
+        assert_eq!(choose(8, 8), 1);
+        assert_eq!(between(4, 2, 1, 4), 14);
+        assert_eq!(between(4, 2, 2, 2), 6);
+    }
+
+    #[test]
+    fn saved_settings_say_whether_the_limit_was_picked_for_them() {
+        let auto = GameConfig {
+            auto_max_tries: true,
+            ..config(6, true)
+        }
+        .with_auto_tries();
+        assert_eq!(auto.max_tries, Some(11));
+        let json = Json::from_serde(&auto);
+        assert_eq!(json.get("auto_max_tries").unwrap().as_bool(), Some(true));
+        let picked = GameConfig {
+            auto_max_tries: false,
+            ..auto
+        };
+        let loaded: GameConfig = Json::from_serde(&picked).to_serde().unwrap();
+        assert!(!loaded.auto_max_tries);
+        assert_eq!(loaded.max_tries, Some(11));
+    }
+}
@@ -0,0 +1,850 @@
+//!
+//! A C interface to the game, for embedding it in programs
+//! written in other languages (Like a C++ GUI). This is built
+//! with `cargo build --features ffi`, and `include/mastermind.h`
+//! declares everything here for C.
+//!
+//! This is similar in spirit to JNI in Java: the functions are
+//! `extern "C"`, so they're called the way C calls functions,
+//! and `#[no_mangle]`, so that they keep their names in the
+//! library. C can't see rust's types, so everything crossing
+//! over is a plain number, a `#[repr(C)]` struct (Laid out
+//! the way C would lay it out), or a pointer.
+//!
+//! Colours are numbered in the order of `COLOURS`: red is 0,
+//! then blue, white, yellow, green and orange is 5, and purple
+//! and pink are 6 and 7. A blank is 8. Games made from C are
+//! played with the classic six colours and no blanks, so only
+//! `mm_score` accepts the others.
+//!
+//! Nothing here lets a panic escape into C, which would be
+//! undefined behaviour. Every function catches them and turns
+//! them into `MmStatus::Panic` (Or a null pointer).
+//!
+//! The `mastermind_` functions are for a program which takes a
+//! guess a peg at a time (Like a kiosk with a button for each
+//! colour). They work on the same games as the `mm_` ones, so
+//! a game from `mastermind_new` can be given to `mm_board`, and
+//! freed with either `mastermind_free` or `mm_free_game`. They
+//! give back their `MmStatus` as a plain `int`.
+//!
+
+use super::{
+    score_guess, Colour, Feedback, GameConfig, Guess, GuessError, MastermindError, SilentOutput,
+    State, TurnOutcome, COLOURS,
+};
+use std::convert::TryFrom;
+use std::os::raw::{c_char, c_int};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+///
+/// The settings for a new game. `max_tries` is 0 for unlimited
+/// tries.
+///
+/// `allow_duplicates` is a `u8` which is 0 for no and anything
+/// else for yes, rather than a `bool`. A rust `bool` which isn't
+/// exactly 0 or 1 is undefined behaviour, and C (Or python) can
+/// put anything in a byte.
+///
+#[repr(C)]
+pub struct MmSettings {
+    pub pegs: usize,
+    pub allow_duplicates: u8,
+    pub max_tries: usize,
+}
+
+///
+/// The feedback for a guess, the same as `score_guess`.
+///
+#[repr(C)]
+pub struct MmFeedback {
+    pub correct_position: usize,
+    pub correct_colour: usize,
+}
+
+impl From<Feedback> for MmFeedback {
+    fn from(feedback: Feedback) -> Self {
+        Self {
+            correct_position: feedback.exact,
+            correct_colour: feedback.misplaced,
+        }
+    }
+}
+
+///
+/// What happened, returned by every function which can fail.
+///
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MmStatus {
+    ///
+    /// The guess was scored and the game carries on.
+    ///
+    Ok = 0,
+    ///
+    /// The guess won the game, and the next game has started.
+    ///
+    Won = 1,
+    ///
+    /// That was the last try, and the next game has started.
+    ///
+    Lost = 2,
+    NullPointer = 3,
+    InvalidColour = 4,
+    WrongLength = 5,
+    DuplicateColour = 6,
+    OutOfRange = 7,
+    Panic = 8,
+    ///
+    /// The colour from `mm_push` went into the guess, which
+    /// isn't full yet.
+    ///
+    Pending = 9,
+}
+
+///
+/// A game, which C only ever sees through a pointer. The
+/// `'static` means the state doesn't borrow anything, which
+/// it has to be so that C can hold on to it for as long as
+/// it likes.
+///
+pub type MmGame = State<'static>;
+
+///
+/// Starts a new game, or gives back null if the settings are
+/// invalid. The game must be given back to `mm_free_game`.
+///
+/// # Safety
+///
+/// `settings` must be null or point to a valid `MmSettings`.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mm_new_game(settings: *const MmSettings) -> *mut MmGame {
+    new_game(settings, None)
+}
+
+///
+/// Like `mm_new_game`, but with the secrets picked from random
+/// numbers seeded with `seed`, so that two games with the same
+/// seed and settings have the same secrets, game after game.
+///
+/// # Safety
+///
+/// `settings` must be null or point to a valid `MmSettings`.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mm_new_seeded_game(settings: *const MmSettings, seed: u64) -> *mut MmGame {
+    new_game(settings, Some(seed))
+}
+
+//
+// Both of the above, seeded from the system unless there's a
+// `seed`.
+//
+unsafe fn new_game(settings: *const MmSettings, seed: Option<u64>) -> *mut MmGame {
+    if settings.is_null() {
+        return std::ptr::null_mut();
+    }
+    let settings = &*settings;
+    let config = GameConfig {
+        pegs: settings.pegs,
+        allow_duplicates: settings.allow_duplicates != 0,
+        max_tries: match settings.max_tries {
+            0 => None,
+            x => Some(x),
+        },
+        auto_max_tries: false,
+        ..GameConfig::default()
+    };
+    catch_unwind(|| match seed {
+        Some(seed) => State::new_seeded(config, seed, |_| {}, |_| {}).ok(),
+        None => State::new(config, |_| {}, |_| {}, SilentOutput).ok(),
+    })
+    .ok()
+    .flatten()
+    .map(|state| Box::into_raw(Box::new(state)))
+    .unwrap_or(std::ptr::null_mut())
+}
+
+///
+/// Makes a guess of `len` colours, and writes its feedback to
+/// `out`. A guess which isn't allowed is rejected without using
+/// up a try.
+///
+/// # Safety
+///
+/// `game` must have come from `mm_new_game`, `pegs` must point
+/// to `len` bytes, and `out` must be null or point to an
+/// `MmFeedback` which can be written to.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mm_guess(
+    game: *mut MmGame,
+    pegs: *const u8,
+    len: usize,
+    out: *mut MmFeedback,
+) -> MmStatus {
+    if game.is_null() || pegs.is_null() {
+        return MmStatus::NullPointer;
+    }
+    let state = &mut *game;
+    let pegs = std::slice::from_raw_parts(pegs, len);
+    guard(|| {
+        let guess = match read_colours(pegs) {
+            Some(x) => x,
+            None => return MmStatus::InvalidColour,
+        };
+        // `enter_guess` checks this too, but this way C can tell
+        // what was wrong.
+        let guess = match Guess::for_config(guess, state.config) {
+            Ok(x) => x,
+            Err(GuessError::WrongLength { .. }) => return MmStatus::WrongLength,
+            Err(GuessError::Blank)
+            | Err(GuessError::NotInPlay(_))
+            | Err(GuessError::Unreadable(_)) => return MmStatus::InvalidColour,
+            Err(GuessError::Duplicate(_)) | Err(GuessError::TooMany { .. }) => {
+                return MmStatus::DuplicateColour
+            }
+        };
+        let outcome = match state.enter_guess(&guess) {
+            Ok(x) => x,
+            Err(_) => return MmStatus::DuplicateColour,
+        };
+        if let (false, Some(feedback)) = (out.is_null(), state.last_scored()) {
+            *out = feedback.into();
+        }
+        match outcome {
+            None => MmStatus::Ok,
+            Some(super::Outcome::Won) => MmStatus::Won,
+            Some(super::Outcome::Lost) => MmStatus::Lost,
+        }
+    })
+}
+
+///
+/// Copies the secret of the game which finished most recently
+/// into `pegs`, which has room for `len` colours. This is
+/// `OutOfRange` if no game has finished yet.
+///
+/// # Safety
+///
+/// `game` must have come from `mm_new_game`, and `pegs` must
+/// point to `len` writable bytes.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mm_last_secret(
+    game: *const MmGame,
+    pegs: *mut u8,
+    len: usize,
+) -> MmStatus {
+    if game.is_null() || pegs.is_null() {
+        return MmStatus::NullPointer;
+    }
+    let state = &*game;
+    let pegs = std::slice::from_raw_parts_mut(pegs, len);
+    guard(|| match state.previous_games.last() {
+        Some(record) if record.secret.len() <= pegs.len() => {
+            write_colours(&record.secret, pegs);
+            MmStatus::Ok
+        }
+        Some(_) => MmStatus::WrongLength,
+        None => MmStatus::OutOfRange,
+    })
+}
+
+///
+/// Scores `guess` against `secret` without a game, both of
+/// them `len` colours long.
+///
+/// # Safety
+///
+/// `secret` and `guess` must point to `len` bytes, and `out`
+/// must point to an `MmFeedback` which can be written to.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mm_score(
+    secret: *const u8,
+    guess: *const u8,
+    len: usize,
+    out: *mut MmFeedback,
+) -> MmStatus {
+    if secret.is_null() || guess.is_null() || out.is_null() {
+        return MmStatus::NullPointer;
+    }
+    let secret = std::slice::from_raw_parts(secret, len);
+    let guess = std::slice::from_raw_parts(guess, len);
+    guard(|| match (read_colours(secret), read_colours(guess)) {
+        (Some(secret), Some(guess)) => {
+            *out = score_guess(&secret, &guess).into();
+            MmStatus::Ok
+        }
+        _ => MmStatus::InvalidColour,
+    })
+}
+
+//
+// Runs the body of one of the functions above, turning a panic
+// into `MmStatus::Panic` instead of letting it unwind into C.
+// The `AssertUnwindSafe` promises that nothing the body shares
+// with us is left half changed by a panic in a way that matters,
+// which holds since C is only ever handed a status afterwards.
+//
+fn guard<F: FnOnce() -> MmStatus>(body: F) -> MmStatus {
+    catch_unwind(AssertUnwindSafe(body)).unwrap_or(MmStatus::Panic)
+}
+
+//
+// Colours from C, or `None` if any of them aren't a colour.
+//
+fn read_colours(pegs: &[u8]) -> Option<Vec<Colour>> {
+    pegs.iter()
+        .map(|&x| match COLOURS.get(x as usize) {
+            Some(&colour) => Some(colour),
+            None if x as usize == COLOURS.len() => Some(Colour::Blank),
+            None => None,
+        })
+        .collect()
+}
+
+//
+// Colours for C, `pegs` must have room for all of `colours`.
+//
+fn write_colours(colours: &[Colour], pegs: &mut [u8]) {
+    for (slot, colour) in pegs.iter_mut().zip(colours) {
+        // `COLOURS` is in declaration order, with blank after it
+        *slot = *colour as u8;
+    }
+}
+
+///
+/// The number of guesses on the board of the current game.
+///
+/// # Safety
+///
+/// `game` must be null or have come from `mm_new_game`.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mm_board_len(game: *const MmGame) -> usize {
+    if game.is_null() {
+        return 0;
+    }
+    (*game).attempts()
+}
+
+///
+/// Copies guess number `index` (From 0) of the current game
+/// into `pegs`, which has room for `len` colours, and its
+/// feedback into `out`.
+///
+/// # Safety
+///
+/// `game` must have come from `mm_new_game`, `pegs` must point
+/// to `len` writable bytes, and `out` must be null or point to
+/// an `MmFeedback` which can be written to.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mm_board(
+    game: *const MmGame,
+    index: usize,
+    pegs: *mut u8,
+    len: usize,
+    out: *mut MmFeedback,
+) -> MmStatus {
+    if game.is_null() || pegs.is_null() {
+        return MmStatus::NullPointer;
+    }
+    let state = &*game;
+    let pegs = std::slice::from_raw_parts_mut(pegs, len);
+    guard(|| {
+        let attempt = match state.history().get(index) {
+            Some(x) => x,
+            None => return MmStatus::OutOfRange,
+        };
+        if attempt.guess.len() > pegs.len() {
+            return MmStatus::WrongLength;
+        }
+        write_colours(&attempt.guess, pegs);
+        if !out.is_null() {
+            *out = attempt.feedback.into();
+        }
+        MmStatus::Ok
+    })
+}
+
+///
+/// Frees a game made by `mm_new_game`. Passing null does
+/// nothing.
+///
+/// # Safety
+///
+/// `game` must be null or have come from `mm_new_game`, and
+/// not be used again afterwards.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mm_free_game(game: *mut MmGame) {
+    if !game.is_null() {
+        // Turning it back into a `Box` means it's dropped (Freed)
+        // at the end of this block, like any other `Box`.
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(game))));
+    }
+}
+
+///
+/// Starts a new game, the same as `mm_new_game`. It must be
+/// given back to `mastermind_free`.
+///
+/// # Safety
+///
+/// `config` must be null or point to a valid `MmSettings`.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mastermind_new(config: *const MmSettings) -> *mut State<'static> {
+    mm_new_game(config)
+}
+
+///
+/// Adds colour number `colour` to the guess being entered. The
+/// guess is scored once it's full, which is `Ok`, `Won` or
+/// `Lost` as for `mm_guess`, and `mastermind_feedback` has its
+/// feedback. Until then this is `Pending`. A colour which isn't
+/// allowed is turned away on its own, leaving the rest of the
+/// guess.
+///
+/// `colour` is a `char` only because it's the smallest number C
+/// has, it's never read as a letter.
+///
+/// # Safety
+///
+/// `state` must be null or have come from `mastermind_new`.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mastermind_push(state: *mut State<'static>, colour: c_char) -> c_int {
+    if state.is_null() {
+        return MmStatus::NullPointer as c_int;
+    }
+    let state = &mut *state;
+    // A `char` is signed on most platforms, so anything past 127
+    // comes in negative, and isn't a colour either way.
+    let colour = match u8::try_from(colour) {
+        Ok(x) => x,
+        Err(_) => return MmStatus::InvalidColour as c_int,
+    };
+    guard(|| {
+        let colour = match read_colours(&[colour]) {
+            Some(x) => x[0],
+            None => return MmStatus::InvalidColour,
+        };
+        match state.input_buffer(colour) {
+            Ok(TurnOutcome::Pending) => MmStatus::Pending,
+            Ok(TurnOutcome::RoundComplete { .. }) => MmStatus::Ok,
+            Ok(TurnOutcome::Won { .. }) => MmStatus::Won,
+            Ok(TurnOutcome::Lost { .. }) => MmStatus::Lost,
+            Err(MastermindError::DuplicateNotAllowed { .. })
+            | Err(MastermindError::TooManyOfColour { .. }) => MmStatus::DuplicateColour,
+            Err(_) => MmStatus::InvalidColour,
+        }
+    }) as c_int
+}
+
+///
+/// Takes the last colour back out of the guess being entered.
+/// This is `OutOfRange` if there aren't any.
+///
+/// # Safety
+///
+/// `state` must be null or have come from `mastermind_new`.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mastermind_undo(state: *mut State<'static>) -> c_int {
+    if state.is_null() {
+        return MmStatus::NullPointer as c_int;
+    }
+    let state = &mut *state;
+    guard(|| match state.undo_input() {
+        Some(_) => MmStatus::Ok,
+        None => MmStatus::OutOfRange,
+    }) as c_int
+}
+
+///
+/// Writes the feedback of the guess which was scored most
+/// recently, even if it finished a game, to `out_exact` (The
+/// pegs in the right place) and `out_misplaced` (The right
+/// colour in the wrong place). This is `OutOfRange` if nothing
+/// has been scored yet.
+///
+/// # Safety
+///
+/// `state` must be null or have come from `mastermind_new`, and
+/// `out_exact` and `out_misplaced` must be null or point to a
+/// `size_t` which can be written to.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mastermind_feedback(
+    state: *const State<'static>,
+    out_exact: *mut usize,
+    out_misplaced: *mut usize,
+) -> c_int {
+    if state.is_null() || out_exact.is_null() || out_misplaced.is_null() {
+        return MmStatus::NullPointer as c_int;
+    }
+    let state = &*state;
+    guard(|| match state.last_scored() {
+        Some(feedback) => {
+            *out_exact = feedback.exact;
+            *out_misplaced = feedback.misplaced;
+            MmStatus::Ok
+        }
+        None => MmStatus::OutOfRange,
+    }) as c_int
+}
+
+///
+/// Copies the secret of the game being played into `out_buf`,
+/// which has room for `len` colours, for a kiosk to show once
+/// the player gives up. It's `WrongLength` if they don't fit.
+/// The secret of a game which has just finished is in
+/// `mm_last_secret` instead, since the next one has started.
+///
+/// # Safety
+///
+/// `state` must be null or have come from `mastermind_new`, and
+/// `out_buf` must be null or point to `len` writable bytes.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mastermind_secret(
+    state: *const State<'static>,
+    out_buf: *mut u8,
+    len: usize,
+) -> c_int {
+    if state.is_null() || out_buf.is_null() {
+        return MmStatus::NullPointer as c_int;
+    }
+    let state = &*state;
+    let out_buf = std::slice::from_raw_parts_mut(out_buf, len);
+    guard(|| {
+        let secret = state.reveal_secret();
+        if secret.len() > out_buf.len() {
+            return MmStatus::WrongLength;
+        }
+        write_colours(secret, out_buf);
+        MmStatus::Ok
+    }) as c_int
+}
+
+///
+/// Frees a game made by `mastermind_new`, the same as
+/// `mm_free_game`. Passing null does nothing.
+///
+/// # Safety
+///
+/// `state` must be null or have come from `mastermind_new`, and
+/// not be used again afterwards.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mastermind_free(state: *mut State<'static>) {
+    mm_free_game(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    fn classic() -> MmSettings {
+        MmSettings {
+            pegs: 4,
+            allow_duplicates: 0,
+            max_tries: 10,
+        }
+    }
+
+    unsafe fn secret(state: *const State<'static>) -> [u8; 4] {
+        let mut pegs = [0; 4];
+        let status = mastermind_secret(state, pegs.as_mut_ptr(), pegs.len());
+        assert_eq!(status, MmStatus::Ok as c_int);
+        pegs
+    }
+
+    unsafe fn feedback(state: *const State<'static>) -> (c_int, usize, usize) {
+        let (mut exact, mut misplaced) = (99, 99);
+        let status = mastermind_feedback(state, &mut exact, &mut misplaced);
+        (status, exact, misplaced)
+    }
+
+    #[test]
+    fn a_game_can_be_won_a_peg_at_a_time() {
+        unsafe {
+            let state = mastermind_new(&classic());
+            assert!(!state.is_null());
+            let pegs = secret(state);
+            for &peg in &pegs[..3] {
+                assert_eq!(
+                    mastermind_push(state, peg as c_char),
+                    MmStatus::Pending as c_int
+                );
+            }
+            assert_eq!(
+                mastermind_push(state, pegs[3] as c_char),
+                MmStatus::Won as c_int
+            );
+            assert_eq!(feedback(state), (MmStatus::Ok as c_int, 4, 0));
+            mastermind_free(state);
+        }
+    }
+
+    #[test]
+    fn a_wrong_guess_is_scored_and_the_game_carries_on() {
+        unsafe {
+            let state = mastermind_new(&classic());
+            let pegs = secret(state);
+            // The secret shifted along by one has every colour
+            // right, and none of them in the right place.
+            let mut status = 0;
+            for i in 0..4 {
+                status = mastermind_push(state, pegs[(i + 1) % 4] as c_char);
+            }
+            assert_eq!(status, MmStatus::Ok as c_int);
+            assert_eq!(feedback(state), (MmStatus::Ok as c_int, 0, 4));
+            assert_eq!(secret(state), pegs);
+            assert_eq!(mm_board_len(state), 1);
+            mastermind_free(state);
+        }
+    }
+
+    #[test]
+    fn undo_takes_back_the_last_peg() {
+        unsafe {
+            let state = mastermind_new(&classic());
+            assert_eq!(mastermind_undo(state), MmStatus::OutOfRange as c_int);
+            let pegs = secret(state);
+            assert_eq!(
+                mastermind_push(state, pegs[1] as c_char),
+                MmStatus::Pending as c_int
+            );
+            assert_eq!(mastermind_undo(state), MmStatus::Ok as c_int);
+            for &peg in &pegs {
+                mastermind_push(state, peg as c_char);
+            }
+            assert_eq!(feedback(state), (MmStatus::Ok as c_int, 4, 0));
+            mastermind_free(state);
+        }
+    }
+
+    #[test]
+    fn bad_arguments_are_statuses() {
+        unsafe {
+            assert!(mastermind_new(ptr::null()).is_null());
+            let invalid = MmSettings {
+                pegs: 0,
+                ..classic()
+            };
+            assert!(mastermind_new(&invalid).is_null());
+            mastermind_free(ptr::null_mut());
+
+            let null = MmStatus::NullPointer as c_int;
+            assert_eq!(mastermind_push(ptr::null_mut(), 0), null);
+            assert_eq!(mastermind_undo(ptr::null_mut()), null);
+            assert_eq!(feedback(ptr::null()).0, null);
+            assert_eq!(
+                mastermind_secret(ptr::null(), [0u8; 4].as_mut_ptr(), 4),
+                null
+            );
+
+            let state = mastermind_new(&classic());
+            assert_eq!(mastermind_secret(state, ptr::null_mut(), 4), null);
+            let mut exact = 0;
+            assert_eq!(
+                mastermind_feedback(state, &mut exact, ptr::null_mut()),
+                null
+            );
+            assert_eq!(feedback(state).0, MmStatus::OutOfRange as c_int);
+
+            let invalid = MmStatus::InvalidColour as c_int;
+            // Purple, a blank, past the end, and a negative `char`
+            for &colour in &[6, 8, 9, -1] {
+                assert_eq!(mastermind_push(state, colour), invalid);
+            }
+            let mut short = [0u8; 3];
+            assert_eq!(
+                mastermind_secret(state, short.as_mut_ptr(), short.len()),
+                MmStatus::WrongLength as c_int
+            );
+            // Nothing was entered, so the undo has nothing to take.
+            assert_eq!(mastermind_undo(state), MmStatus::OutOfRange as c_int);
+            mastermind_free(state);
+        }
+    }
+
+    #[test]
+    fn a_repeated_colour_is_turned_away_on_its_own() {
+        unsafe {
+            let state = mastermind_new(&classic());
+            let pegs = secret(state);
+            mastermind_push(state, pegs[0] as c_char);
+            assert_eq!(
+                mastermind_push(state, pegs[0] as c_char),
+                MmStatus::DuplicateColour as c_int
+            );
+            for &peg in &pegs[1..] {
+                mastermind_push(state, peg as c_char);
+            }
+            assert_eq!(feedback(state), (MmStatus::Ok as c_int, 4, 0));
+            mastermind_free(state);
+        }
+    }
+
+    #[test]
+    fn a_panic_is_caught_at_the_boundary() {
+        assert_eq!(guard(|| panic!("on purpose")), MmStatus::Panic);
+        assert_eq!(guard(|| MmStatus::Won), MmStatus::Won);
+    }
+
+    #[test]
+    fn null_handles_are_statuses() {
+        unsafe {
+            let pegs = [0u8; 4];
+            let mut out = MmFeedback::from(Feedback::default());
+            let null = MmStatus::NullPointer;
+            assert!(mm_new_game(ptr::null()).is_null());
+            assert_eq!(mm_guess(ptr::null_mut(), pegs.as_ptr(), 4, &mut out), null);
+            assert_eq!(mm_last_secret(ptr::null(), [0u8; 4].as_mut_ptr(), 4), null);
+            assert_eq!(mm_score(ptr::null(), pegs.as_ptr(), 4, &mut out), null);
+            assert_eq!(
+                mm_score(pegs.as_ptr(), pegs.as_ptr(), 4, ptr::null_mut()),
+                null
+            );
+            assert_eq!(mm_board_len(ptr::null()), 0);
+            assert_eq!(
+                mm_board(ptr::null(), 0, [0u8; 4].as_mut_ptr(), 4, &mut out),
+                null
+            );
+            mm_free_game(ptr::null_mut());
+
+            let game = mm_new_game(&classic());
+            assert_eq!(mm_guess(game, ptr::null(), 4, &mut out), null);
+            assert_eq!(mm_board(game, 0, ptr::null_mut(), 4, &mut out), null);
+            mm_free_game(game);
+        }
+    }
+
+    #[test]
+    fn bad_lengths_and_colours_use_up_no_tries() {
+        unsafe {
+            let game = mm_new_game(&classic());
+            let pegs = secret(game);
+            let mut out = MmFeedback::from(Feedback::default());
+            assert_eq!(
+                mm_guess(game, pegs.as_ptr(), 3, &mut out),
+                MmStatus::WrongLength
+            );
+            assert_eq!(
+                mm_guess(game, [0, 1, 2, 3, 4].as_ptr(), 5, &mut out),
+                MmStatus::WrongLength
+            );
+            assert_eq!(
+                mm_guess(game, [0, 1, 2, 42].as_ptr(), 4, &mut out),
+                MmStatus::InvalidColour
+            );
+            assert_eq!(
+                mm_guess(game, [0, 0, 1, 2].as_ptr(), 4, &mut out),
+                MmStatus::DuplicateColour
+            );
+            assert_eq!(mm_board_len(game), 0);
+
+            let miss = [pegs[1], pegs[2], pegs[3], pegs[0]];
+            assert_eq!(mm_guess(game, miss.as_ptr(), 4, &mut out), MmStatus::Ok);
+            assert_eq!((out.correct_position, out.correct_colour), (0, 4));
+            assert_eq!(mm_board_len(game), 1);
+            let mut short = [0u8; 3];
+            assert_eq!(
+                mm_board(game, 0, short.as_mut_ptr(), short.len(), &mut out),
+                MmStatus::WrongLength
+            );
+            let mut board = [0u8; 4];
+            assert_eq!(
+                mm_board(game, 0, board.as_mut_ptr(), 4, ptr::null_mut()),
+                MmStatus::Ok
+            );
+            assert_eq!(board, miss);
+            assert_eq!(
+                mm_board(game, 1, board.as_mut_ptr(), 4, &mut out),
+                MmStatus::OutOfRange
+            );
+            mm_free_game(game);
+        }
+    }
+
+    #[test]
+    fn the_last_secret_is_kept_after_a_loss() {
+        unsafe {
+            let settings = MmSettings {
+                max_tries: 1,
+                ..classic()
+            };
+            let game = mm_new_game(&settings);
+            let pegs = secret(game);
+            let mut last = [0u8; 4];
+            assert_eq!(
+                mm_last_secret(game, last.as_mut_ptr(), 4),
+                MmStatus::OutOfRange
+            );
+            let miss = [pegs[1], pegs[2], pegs[3], pegs[0]];
+            assert_eq!(
+                mm_guess(game, miss.as_ptr(), 4, ptr::null_mut()),
+                MmStatus::Lost
+            );
+            assert_eq!(
+                mm_last_secret(game, last.as_mut_ptr(), 2),
+                MmStatus::WrongLength
+            );
+            assert_eq!(mm_last_secret(game, last.as_mut_ptr(), 4), MmStatus::Ok);
+            assert_eq!(last, pegs);
+            mm_free_game(game);
+        }
+    }
+
+    #[test]
+    fn any_nonzero_byte_allows_duplicates() {
+        unsafe {
+            let settings = MmSettings {
+                allow_duplicates: 2,
+                ..classic()
+            };
+            let game = mm_new_game(&settings);
+            let status = mm_guess(game, [0, 0, 0, 0].as_ptr(), 4, ptr::null_mut());
+            assert!(status == MmStatus::Ok || status == MmStatus::Won);
+            mm_free_game(game);
+        }
+    }
+
+    #[test]
+    fn scoring_without_a_game() {
+        unsafe {
+            let mut out = MmFeedback::from(Feedback::default());
+            let secret = [0, 1, 2, 8];
+            assert_eq!(
+                mm_score(secret.as_ptr(), [0, 0, 8, 0].as_ptr(), 4, &mut out),
+                MmStatus::Ok
+            );
+            assert_eq!((out.correct_position, out.correct_colour), (1, 1));
+            assert_eq!(
+                mm_score(secret.as_ptr(), [0, 0, 9, 0].as_ptr(), 4, &mut out),
+                MmStatus::InvalidColour
+            );
+        }
+    }
+
+    #[test]
+    fn the_same_seed_picks_the_same_secrets() {
+        unsafe {
+            let first = mm_new_seeded_game(&classic(), 5);
+            let second = mm_new_seeded_game(&classic(), 5);
+            assert!(!first.is_null() && !second.is_null());
+            assert_eq!(secret(first), secret(second));
+            assert!(mm_new_seeded_game(ptr::null(), 5).is_null());
+            mm_free_game(first);
+            mm_free_game(second);
+        }
+    }
+}
@@ -0,0 +1,1411 @@
+//!
+//! Everything the game needs from whoever is playing it. The
+//! terminal, a GUI, or a server talking to a browser all ask
+//! for the same things (Settings, guesses) and show the same
+//! things (Feedback, who won), so `play` runs the session once
+//! for all of them, and each one only implements `Frontend`.
+//!
+
+use super::hints::{Hint, HintAnswer};
+use super::json::Json;
+use super::render::{self, Renderer};
+use super::wording::FeedbackStyle;
+use super::{
+    history, save, wager, BoardFormatter, Colour, Feedback, GameConfig, GameRecord, GameSummary,
+    InputError, MastermindError, SettingsLayer, State, StateBuilder, TurnOutcome,
+};
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+///
+/// What the player did when asked for their next move.
+///
+pub enum PlayerInput {
+    ///
+    /// Some colours. This might be part of a guess, or a few
+    /// guesses one after the other.
+    ///
+    Guess(String),
+    ///
+    /// Save the game to a file, or to `save::DEFAULT_PATH`.
+    ///
+    Save(Option<String>),
+    ///
+    /// The player was away for this long (In a menu, say), which
+    /// shouldn't count towards how long the game took.
+    ///
+    Paused(Duration),
+    ///
+    /// Give up on this game, which counts as a loss.
+    ///
+    Forfeit,
+    ///
+    /// Stop playing, keeping the game for next time.
+    ///
+    SaveAndQuit,
+    ///
+    /// Pay for a hint, see `hints.rs`.
+    ///
+    Hint(Hint),
+    ///
+    /// Make the guess which is waiting to be confirmed, see
+    /// `GameConfig::confirm_guesses`.
+    ///
+    Commit,
+    ///
+    /// Take the last colour back out of the guess being entered.
+    ///
+    Undo,
+    ///
+    /// The player's input ran out (Like at the end of a piped
+    /// file, or after Ctrl-D), so nothing more can be played. The
+    /// session ends, keeping the game in progress like
+    /// `SaveAndQuit`.
+    ///
+    Abandon,
+    ///
+    /// The player used a command the frontend took care of by
+    /// itself (Like `status`), which only needs counting.
+    ///
+    Command,
+}
+
+///
+/// Everything the session tells the player about. Each frontend
+/// decides how (And whether) to show each of these.
+///
+pub enum Output {
+    ///
+    /// Game `number` (Counting from one) has started.
+    ///
+    NewGame { number: usize, config: GameConfig },
+    ///
+    /// How many points there are to wager this game, and the
+    /// score so far. Only sent when playing with wagers.
+    ///
+    Points { points: usize, score: usize },
+    ///
+    /// A wager was settled, leaving this many points.
+    ///
+    WagerSettled { won: bool, points: usize },
+    ///
+    /// The feedback for a guess which was just finished, unless
+    /// it won the game. The last guess of a lost game gets this
+    /// just before `GameOver`.
+    ///
+    Feedback {
+        guess: Vec<Colour>,
+        exact: usize,
+        colour: usize,
+    },
+    ///
+    /// A position of the secret was revealed to help out.
+    ///
+    Reveal { position: usize, colour: Colour },
+    ///
+    /// The answer to a hint the player paid for, and the hint
+    /// points they have left.
+    ///
+    Hint {
+        answer: HintAnswer,
+        points_left: usize,
+    },
+    ///
+    /// Where the game stands after a line of guesses, if it's
+    /// still going.
+    ///
+    Progress(GameSummary),
+    ///
+    /// A game is over, one way or another.
+    ///
+    GameOver(GameRecord),
+    ///
+    /// The session score after a game played with wagers.
+    ///
+    Score(usize),
+    ///
+    /// Something went wrong with what the player entered.
+    ///
+    Error(String),
+    ///
+    /// Anything else worth telling the player, like where a
+    /// game was saved.
+    ///
+    Message(String),
+    ///
+    /// Every game played this session, at the end of it, and
+    /// how many attempts the game still in progress had if the
+    /// session was left in the middle of one.
+    ///
+    SessionOver {
+        games: Vec<GameRecord>,
+        abandoned: Option<usize>,
+    },
+    ///
+    /// The numbers behind the session, after `SessionOver`. Only
+    /// sent when playing with `--verbose`.
+    ///
+    Metrics(SessionMetrics),
+}
+
+///
+/// Counts of what the player did over a whole session, kept up
+/// to date by `play`.
+///
+#[derive(Clone, Debug, Default)]
+pub struct SessionMetrics {
+    pub duration: Duration,
+    pub games_played: usize,
+    pub guesses_entered: usize,
+    ///
+    /// Lines with something in them which wasn't a colour, or
+    /// which made a guess that isn't allowed.
+    ///
+    pub parse_errors: usize,
+    ///
+    /// Everything typed which wasn't a guess, like `status` or
+    /// `save`.
+    ///
+    pub commands_used: usize,
+    ///
+    /// Positions of the secret revealed to help out.
+    ///
+    pub hints_taken: usize,
+}
+
+impl SessionMetrics {
+    ///
+    /// How long the session took per guess, or `None` if no
+    /// guesses were made.
+    ///
+    pub fn time_per_guess(&self) -> Option<Duration> {
+        match self.guesses_entered {
+            0 => None,
+            x => Some(self.duration / x as u32),
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            // Our JSON only has whole numbers
+            ("milliseconds", (self.duration.as_millis() as u64).into()),
+            ("games_played", self.games_played.into()),
+            ("guesses_entered", self.guesses_entered.into()),
+            ("parse_errors", self.parse_errors.into()),
+            ("commands_used", self.commands_used.into()),
+            ("hints_taken", self.hints_taken.into()),
+            (
+                "milliseconds_per_guess",
+                self.time_per_guess().map(|x| x.as_millis() as u64).into(),
+            ),
+        ])
+    }
+}
+
+impl Output {
+    ///
+    /// Converts the output to JSON, with a `"kind"` saying which
+    /// one it is, for the JSON lines renderer.
+    ///
+    pub fn to_json(&self) -> Json {
+        let colours = |code: &[Colour]| Json::Array(code.iter().map(|x| x.name().into()).collect());
+        match self {
+            Output::NewGame { number, config } => Json::object(vec![
+                ("kind", "new_game".into()),
+                ("number", (*number).into()),
+                ("settings", Json::from_serde(config)),
+            ]),
+            Output::Points { points, score } => Json::object(vec![
+                ("kind", "points".into()),
+                ("points", (*points).into()),
+                ("score", (*score).into()),
+            ]),
+            Output::WagerSettled { won, points } => Json::object(vec![
+                ("kind", "wager_settled".into()),
+                ("won", (*won).into()),
+                ("points", (*points).into()),
+            ]),
+            Output::Feedback {
+                guess,
+                exact,
+                colour,
+            } => Json::object(vec![
+                ("kind", "feedback".into()),
+                ("guess", colours(guess)),
+                ("correct_position", (*exact).into()),
+                ("correct_colour", (*colour).into()),
+            ]),
+            Output::Reveal { position, colour } => Json::object(vec![
+                ("kind", "reveal".into()),
+                ("position", (*position).into()),
+                ("colour", colour.name().into()),
+            ]),
+            Output::Hint {
+                answer,
+                points_left,
+            } => {
+                let mut pairs = vec![
+                    ("kind", "hint".into()),
+                    ("points_left", (*points_left).into()),
+                ];
+                match answer {
+                    HintAnswer::Revealed { position, colour } => {
+                        pairs.push(("hint", "reveal".into()));
+                        pairs.push(("position", (*position).into()));
+                        pairs.push(("colour", colour.name().into()));
+                    }
+                    HintAnswer::Suggestion(guess) => {
+                        pairs.push(("hint", "suggest".into()));
+                        pairs.push(("guess", colours(guess)));
+                    }
+                    HintAnswer::Count { colour, count } => {
+                        pairs.push(("hint", "count".into()));
+                        pairs.push(("colour", colour.name().into()));
+                        pairs.push(("count", (*count).into()));
+                    }
+                }
+                Json::object(pairs)
+            }
+            Output::Progress(summary) => Json::object(vec![
+                ("kind", "progress".into()),
+                ("summary", summary.to_json()),
+            ]),
+            Output::GameOver(record) => Json::object(vec![
+                ("kind", "game_over".into()),
+                ("record", Json::from_serde(record)),
+            ]),
+            Output::Score(score) => {
+                Json::object(vec![("kind", "score".into()), ("score", (*score).into())])
+            }
+            Output::Error(text) => Json::object(vec![
+                ("kind", "error".into()),
+                ("message", text.as_str().into()),
+            ]),
+            Output::Message(text) => Json::object(vec![
+                ("kind", "message".into()),
+                ("message", text.as_str().into()),
+            ]),
+            Output::SessionOver { games, abandoned } => Json::object(vec![
+                ("kind", "session_over".into()),
+                ("games", Json::from_serde(games)),
+                ("abandoned", (*abandoned).into()),
+            ]),
+            Output::Metrics(metrics) => Json::object(vec![
+                ("kind", "metrics".into()),
+                ("metrics", metrics.to_json()),
+            ]),
+        }
+    }
+}
+
+///
+/// A trait is sort of like an interface in Java. Anything which
+/// implements these methods can be handed to `play`, which is
+/// generic over it (Kind of like `<T extends Frontend>`).
+///
+pub trait Frontend {
+    ///
+    /// Picks the settings for a new game, starting from
+    /// `defaults`.
+    ///
+    fn configure(&mut self, defaults: GameConfig) -> io::Result<GameConfig>;
+
+    ///
+    /// Asks for the player's next move in the game described by
+    /// `summary`.
+    ///
+    fn next_input(&mut self, summary: &GameSummary) -> io::Result<PlayerInput>;
+
+    ///
+    /// Shows `output` to the player.
+    ///
+    fn present(&mut self, output: &Output) -> io::Result<()>;
+
+    ///
+    /// Asks for a wager on the next guess, one of `points` at
+    /// most. This has a default body, so frontends which don't
+    /// do wagers can leave it out, and never bet anything.
+    ///
+    fn ask_wager(&mut self, points: usize) -> io::Result<Option<wager::Wager>> {
+        let _ = points;
+        Ok(None)
+    }
+
+    ///
+    /// Asks the player for a label for the game in `record`,
+    /// which has just finished. This has a default body too, for
+    /// frontends which never label anything.
+    ///
+    fn ask_label(&mut self, record: &GameRecord) -> io::Result<Option<String>> {
+        let _ = record;
+        Ok(None)
+    }
+}
+
+///
+/// What `play` should do besides playing, all of which can be
+/// left off (For example, when nothing should touch the disk).
+///
+#[derive(Default)]
+pub struct Session<'p> {
+    ///
+    /// Lets the player bet points on their guesses.
+    ///
+    pub wagers: bool,
+    ///
+    /// The profile to record finished games for.
+    ///
+    pub player: Option<u64>,
+    ///
+    /// Where to keep the autosave up to date.
+    ///
+    pub autosave: Option<&'p Path>,
+    ///
+    /// Overrides the format picked from the extension when the
+    /// player saves to a file.
+    ///
+    pub save_format: Option<save::SaveFormat>,
+    ///
+    /// Ends the session with its `SessionMetrics`.
+    ///
+    pub verbose: bool,
+}
+
+///
+/// Asks `frontend` for settings and sets up a game with them.
+///
+pub fn new_game<'a>(
+    frontend: &mut impl Frontend,
+    defaults: GameConfig,
+) -> Result<State<'a>, Box<dyn Error>> {
+    let config = frontend.configure(defaults)?;
+    // The frontend does all of the talking, so the state stays
+    // quiet and doesn't need to do anything when a game ends.
+    Ok(StateBuilder::new().config(config).build()?)
+}
+
+///
+/// Plays out the rest of the session in `state` through
+/// `frontend`. This returns `true` if every game was played, or
+/// `false` if the player saved and quit part way through.
+///
+pub fn play<F: Frontend>(
+    frontend: &mut F,
+    state: &mut State,
+    session: &Session,
+) -> io::Result<bool> {
+    let started = Instant::now();
+    let mut metrics = SessionMetrics::default();
+    // The points left over from each game wagered on
+    let mut score = 0;
+    let mut finished = true;
+    let mut abandoned = None;
+    'games: for i in state.previous_games.len()..2 {
+        frontend.present(&Output::NewGame {
+            number: i + 1,
+            config: state.config,
+        })?;
+        if state.repeated_secret() {
+            frontend.present(&Output::Message(
+                "Nearly every secret these settings allow has come up this session, \
+                 so this one might be a repeat"
+                    .into(),
+            ))?;
+        }
+        let mut points = wager::STARTING_POINTS;
+        let mut current_wager = None;
+        if session.wagers {
+            frontend.present(&Output::Points { points, score })?;
+        }
+        loop {
+            if session.wagers && current_wager.is_none() && points > 0 {
+                current_wager = frontend.ask_wager(points)?;
+            }
+            let finished_before = state.previous_games.len();
+            let guesses_before = state.attempts();
+            let (entered, result) = match frontend.next_input(&state.summary())? {
+                PlayerInput::Guess(text) => {
+                    let result = enter(frontend, state, &text, &mut metrics)?
+                        .map_err(|e| (e.to_string(), e.game_ended));
+                    (!text.is_empty(), result)
+                }
+                PlayerInput::Commit => {
+                    let guess = state.buffered().to_vec();
+                    let result = show_outcome(frontend, state, guess, &mut metrics, |state| {
+                        state.commit_guess()
+                    })?
+                    .map(|x| x.ends_game())
+                    .map_err(|e| (e.to_string(), false));
+                    (true, result)
+                }
+                PlayerInput::Undo => {
+                    metrics.commands_used += 1;
+                    let message = match state.undo_input() {
+                        Some(colour) => format!(
+                            "Took back {:?}, the guess is now {:?}",
+                            colour,
+                            state.buffered()
+                        ),
+                        None => "There's nothing to take back".to_string(),
+                    };
+                    frontend.present(&Output::Message(message))?;
+                    continue;
+                }
+                PlayerInput::Command => {
+                    metrics.commands_used += 1;
+                    continue;
+                }
+                PlayerInput::Forfeit => {
+                    metrics.commands_used += 1;
+                    state.forfeit();
+                    let record = state.previous_games[finished_before].clone();
+                    frontend.present(&Output::GameOver(record))?;
+                    (true, Ok(true))
+                }
+                PlayerInput::Hint(hint) => {
+                    metrics.commands_used += 1;
+                    let output = match state.take_hint(hint) {
+                        Ok(answer) => {
+                            metrics.hints_taken += 1;
+                            Output::Hint {
+                                answer,
+                                points_left: state.summary().hint_points_left.unwrap_or(0),
+                            }
+                        }
+                        Err(e) => Output::Error(e),
+                    };
+                    frontend.present(&output)?;
+                    continue;
+                }
+                PlayerInput::Paused(paused) => {
+                    metrics.commands_used += 1;
+                    state.pause_clock(paused);
+                    continue;
+                }
+                PlayerInput::Save(path) => {
+                    metrics.commands_used += 1;
+                    let path = path.as_deref().unwrap_or(save::DEFAULT_PATH);
+                    let message =
+                        match save::save_to_file(state, Path::new(path), session.save_format) {
+                            Ok(()) => format!("Saved the game to {}", path),
+                            Err(e) => format!("Could not save the game: {}", e),
+                        };
+                    frontend.present(&Output::Message(message))?;
+                    continue;
+                }
+                PlayerInput::SaveAndQuit => {
+                    metrics.commands_used += 1;
+                    let message = match session.autosave.map(|x| save::autosave(state, x)) {
+                        Some(Err(e)) => format!("Could not save the game: {}", e),
+                        _ => {
+                            "Saved, this game will be offered again next time you play".to_string()
+                        }
+                    };
+                    frontend.present(&Output::Message(message))?;
+                    finished = false;
+                    break 'games;
+                }
+                PlayerInput::Abandon => {
+                    if let Some(Err(e)) = session.autosave.map(|x| save::autosave(state, x)) {
+                        frontend
+                            .present(&Output::Message(format!("Could not save the game: {}", e)))?;
+                    }
+                    let why = if super::interrupt::happened() {
+                        "Interrupted"
+                    } else {
+                        "Ran out of input"
+                    };
+                    frontend.present(&Output::Message(format!(
+                        "{}, so the session was abandoned",
+                        why
+                    )))?;
+                    abandoned = Some(state.attempts());
+                    finished = false;
+                    break 'games;
+                }
+            };
+            let game_over = state.previous_games.len() != finished_before;
+            metrics.games_played += state.previous_games.len() - finished_before;
+            // A wager covers the next guess, or the last one if a
+            // few were entered on one line.
+            if game_over || state.attempts() != guesses_before {
+                if let (Some(wager), Some(feedback)) = (current_wager.take(), state.last_scored()) {
+                    let won = wager.won(feedback.exact, state.config.pegs);
+                    points = wager.settle(points, feedback.exact, state.config.pegs);
+                    frontend.present(&Output::WagerSettled { won, points })?;
+                }
+            }
+            if !game_over && state.attempts() != guesses_before {
+                frontend.present(&Output::Progress(state.summary()))?;
+            }
+            if game_over {
+                if let Some(record) = state.previous_games.last_mut() {
+                    record.label = frontend.ask_label(record)?;
+                }
+            }
+            if game_over && session.wagers {
+                score += points;
+                frontend.present(&Output::Score(score))?;
+            }
+            if let Some(player) = session.player {
+                for record in &state.previous_games[finished_before..] {
+                    let points = if session.wagers { Some(points) } else { None };
+                    if let Err(e) = history::append(player, record, points) {
+                        frontend.present(&Output::Message(format!(
+                            "Could not record the game in the history: {}",
+                            e
+                        )))?;
+                    }
+                }
+            }
+            // Keep the autosave up to date, so that nothing is
+            // lost if the program is closed in the middle of a game.
+            if let (true, Some(path)) = (entered, session.autosave) {
+                if let Err(e) = save::autosave(state, path) {
+                    frontend.present(&Output::Message(format!(
+                        "Could not autosave the game: {}",
+                        e
+                    )))?;
+                }
+            }
+            match result {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err((text, f)) => {
+                    metrics.parse_errors += 1;
+                    frontend.present(&Output::Error(text))?;
+                    if f {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    frontend.present(&Output::SessionOver {
+        games: state.games().to_vec(),
+        abandoned,
+    })?;
+    if session.verbose {
+        metrics.duration = started.elapsed();
+        frontend.present(&Output::Metrics(metrics))?;
+    }
+    Ok(finished)
+}
+
+///
+/// Pushes `text` into the game one colour at a time, like
+/// `State::push_string_input`, telling `frontend` about each
+/// guess as it's finished (And counting them in `metrics`).
+///
+fn enter<F: Frontend>(
+    frontend: &mut F,
+    state: &mut State,
+    text: &str,
+    metrics: &mut SessionMetrics,
+) -> io::Result<Result<bool, InputError>> {
+    let mut should_reset = false;
+    let mut guesses_finished = 0;
+    let error = |state: &State, error, position, guesses_finished, game_ended| InputError {
+        error,
+        position,
+        guesses_finished,
+        buffered: state.buffered().len(),
+        game_ended,
+    };
+    let colours = match super::locate_colours(text) {
+        Ok(x) => x,
+        Err((position, e)) => return Ok(Err(error(state, e, Some(position), 0, false))),
+    };
+    if let Err(e) = state.check_line(colours.len()) {
+        return Ok(Err(error(state, e, None, 0, false)));
+    }
+    if let Err((position, e)) = state.check_whole_guess(&colours) {
+        return Ok(Err(error(state, e, position, 0, false)));
+    }
+    // A blank line (Or one of just spaces) hasn't gone wrong, but
+    // it's worth saying what's still wanted.
+    if colours.is_empty() {
+        let needed = state.config.pegs - state.buffered().len();
+        let plural = if needed == 1 { "" } else { "s" };
+        let reminder = match state.buffered() {
+            [] => format!(
+                "A guess is {} colour{}, typed by name or by first letter",
+                needed, plural
+            ),
+            so_far => format!(
+                "The guess so far is {:?}, it needs {} more colour{}",
+                so_far, needed, plural
+            ),
+        };
+        frontend.present(&Output::Message(reminder))?;
+        return Ok(Ok(false));
+    }
+    for (position, colour) in colours {
+        let mut guess = state.buffered().to_vec();
+        guess.push(colour);
+        match show_outcome(frontend, state, guess, metrics, |state| {
+            state.input_buffer(colour)
+        })? {
+            Ok(TurnOutcome::Pending) => {}
+            Ok(outcome) => {
+                guesses_finished += 1;
+                should_reset |= outcome.ends_game();
+            }
+            Err(e) => {
+                let e = error(state, e, Some(position), guesses_finished, should_reset);
+                return Ok(Err(e));
+            }
+        }
+    }
+    if state.config.confirm_guesses && state.buffered().len() == state.config.pegs {
+        frontend.present(&Output::Message(
+            "Enter a blank line or \"ok\" to make that guess, or \"undo\" to take a \
+             colour back"
+                .into(),
+        ))?;
+    }
+    Ok(Ok(should_reset))
+}
+
+//
+// Does `action` to `state`, which might finish `guess`, and
+// tells `frontend` what came of it: the feedback, the end of
+// the game or any positions that were revealed.
+//
+fn show_outcome<F: Frontend>(
+    frontend: &mut F,
+    state: &mut State,
+    guess: Vec<Colour>,
+    metrics: &mut SessionMetrics,
+    action: impl FnOnce(&mut State) -> Result<TurnOutcome, MastermindError>,
+) -> io::Result<Result<TurnOutcome, MastermindError>> {
+    let games = state.previous_games.len();
+    let revealed = state.revealed.clone();
+    let outcome = match action(state) {
+        Ok(x) => x,
+        Err(e) => return Ok(Err(e)),
+    };
+    if outcome != TurnOutcome::Pending {
+        metrics.guesses_entered += 1;
+    }
+    // A guess which lost the game still gets its feedback,
+    // only a winning one doesn't need any.
+    let feedback = match outcome {
+        TurnOutcome::Pending | TurnOutcome::Won { .. } => None,
+        TurnOutcome::RoundComplete { feedback } => Some(feedback),
+        TurnOutcome::Lost { .. } => state.last_scored(),
+    };
+    if let Some(Feedback {
+        exact,
+        misplaced: colour,
+    }) = feedback
+    {
+        frontend.present(&Output::Feedback {
+            guess,
+            exact,
+            colour,
+        })?;
+    }
+    if outcome.ends_game() {
+        frontend.present(&Output::GameOver(state.previous_games[games].clone()))?;
+    } else {
+        for &position in state.revealed.iter().filter(|x| !revealed.contains(x)) {
+            metrics.hints_taken += 1;
+            frontend.present(&Output::Reveal {
+                position,
+                colour: state.reveal_secret()[position],
+            })?;
+        }
+    }
+    Ok(Ok(outcome))
+}
+
+///
+/// Playing in the terminal, by typing in answers to questions.
+///
+pub struct TerminalFrontend {
+    ///
+    /// Settings picked on the command line, which we don't ask
+    /// about.
+    ///
+    given: SettingsLayer,
+    input: String,
+    ///
+    /// Everything the session has to say goes through here. A
+    /// `Box<dyn Renderer>` can hold any type which implements
+    /// `Renderer`, picked while the program is running.
+    ///
+    renderer: Box<dyn Renderer>,
+    ///
+    /// How feedback is worded in what's printed without the
+    /// renderer, like the `history` command.
+    ///
+    feedback: FeedbackStyle,
+    ///
+    /// Whether to stay away from anything a screen reader would
+    /// trip over, like the arrow key menu, see `accessible`.
+    ///
+    accessible: bool,
+    ///
+    /// Where the player's moves are read from, which is standard
+    /// input unless `reading_from` says otherwise. The settings
+    /// questions are always asked on the terminal.
+    ///
+    reader: Option<Box<dyn BufRead>>,
+}
+
+impl TerminalFrontend {
+    pub fn new(given: SettingsLayer, renderer: Box<dyn Renderer>, feedback: FeedbackStyle) -> Self {
+        Self {
+            given,
+            input: String::new(),
+            renderer,
+            feedback,
+            accessible: false,
+            reader: None,
+        }
+    }
+
+    ///
+    /// Reads the player's moves from `reader` instead of standard
+    /// input, like a file of guesses, or some text in a test.
+    ///
+    pub fn reading_from(self, reader: impl BufRead + 'static) -> Self {
+        Self {
+            reader: Some(Box::new(reader)),
+            ..self
+        }
+    }
+
+    //
+    // Reads the next line into `input`, giving back how long it
+    // was like `read_line` does, so 0 is the end of the input.
+    //
+    fn read_line(&mut self) -> io::Result<usize> {
+        self.input.clear();
+        match &mut self.reader {
+            Some(reader) => reader.read_line(&mut self.input),
+            None => io::stdin().read_line(&mut self.input),
+        }
+    }
+
+    ///
+    /// Turns on accessible mode, which should go along with
+    /// `render::AccessibleRenderer`. The settings are asked for
+    /// as questions instead of with the menu (Which redraws
+    /// itself), nothing typed is rubbed out in memory mode, and
+    /// the board and rules are written as sentences.
+    ///
+    pub fn accessible(self) -> Self {
+        Self {
+            accessible: true,
+            ..self
+        }
+    }
+}
+
+impl Frontend for TerminalFrontend {
+    ///
+    /// On a terminal this is done with the menu in `menu.rs`,
+    /// unless the player would rather answer the questions.
+    ///
+    fn configure(&mut self, defaults: GameConfig) -> io::Result<GameConfig> {
+        if !self.accessible {
+            if let Some(config) = super::menu::choose_settings(defaults)? {
+                return Ok(config);
+            }
+        }
+        // Blank answers take the value from `defaults`
+        let given = self.given;
+        let duplicates = match given.allow_duplicates {
+            Some(x) => x,
+            None => super::ask_duplicates(defaults.allow_duplicates)?,
+        };
+        let palette_size = match given.palette_size {
+            Some(x) => x,
+            None => super::ask_palette_size(defaults.palette_size)?,
+        };
+        let pegs = match given.pegs {
+            Some(x) => x,
+            None => {
+                let colours = GameConfig {
+                    palette_size,
+                    ..defaults
+                };
+                super::ask_pegs(defaults.pegs, colours.palette().len())?
+            }
+        };
+        // Only duplicates can go over the limit on each colour
+        let max_per_colour = match given.max_per_colour {
+            Some(x) => x,
+            None if duplicates => super::ask_max_per_colour(defaults.max_per_colour, pegs)?,
+            None => defaults.max_per_colour,
+        };
+        let config = GameConfig {
+            pegs,
+            allow_duplicates: duplicates,
+            max_per_colour,
+            palette_size,
+            ..defaults
+        };
+        match (given.auto_max_tries, given.max_tries) {
+            (Some(true), _) => Ok(GameConfig {
+                auto_max_tries: true,
+                ..config
+            }
+            .with_auto_tries()),
+            (_, Some(max_tries)) => Ok(GameConfig {
+                max_tries,
+                auto_max_tries: false,
+                ..config
+            }),
+            _ => super::ask_max_tries(config),
+        }
+    }
+
+    fn next_input(&mut self, summary: &GameSummary) -> io::Result<PlayerInput> {
+        print!("{}", super::GUESS_PROMPT);
+        io::stdout().flush()?;
+        if self.read_line()? == 0 {
+            println!();
+            return Ok(PlayerInput::Abandon);
+        }
+        let line = self.input.trim();
+        // In memory mode, rub out what was typed (It's still on
+        // screen, since the terminal prints it as it's typed).
+        if summary.settings.memory
+            && !self.accessible
+            && super::terminal::echoes_input()
+            && !line.is_empty()
+        {
+            super::terminal::erase_previous_line()?;
+            println!("Enter next colours > (hidden)");
+        }
+        Ok(match line {
+            "" | "ok" if summary.settings.confirm_guesses => PlayerInput::Commit,
+            "undo" => PlayerInput::Undo,
+            "status" => {
+                println!("{}", summary.describe(&self.feedback));
+                PlayerInput::Command
+            }
+            "history" => {
+                print_history(summary, &self.feedback, self.accessible);
+                PlayerInput::Command
+            }
+            // The pause menu either goes back to the game, or
+            // ends it the same way finishing it normally would.
+            "menu" => {
+                let paused_at = Instant::now();
+                match self.pause_menu(summary)? {
+                    PauseChoice::Resume => PlayerInput::Paused(paused_at.elapsed()),
+                    PauseChoice::Forfeit => PlayerInput::Forfeit,
+                    PauseChoice::SaveAndQuit => PlayerInput::SaveAndQuit,
+                }
+            }
+            x if x == "hint" || x.starts_with("hint ") => match x["hint".len()..].parse() {
+                Ok(hint) => PlayerInput::Hint(hint),
+                Err(e) => {
+                    println!("Please try again! {}", e);
+                    PlayerInput::Command
+                }
+            },
+            x if x == "save" || x.starts_with("save ") => {
+                let path = x["save".len()..].trim();
+                PlayerInput::Save(if path.is_empty() {
+                    None
+                } else {
+                    Some(path.to_string())
+                })
+            }
+            x => PlayerInput::Guess(x.to_string()),
+        })
+    }
+
+    fn present(&mut self, output: &Output) -> io::Result<()> {
+        self.renderer.render(output)
+    }
+
+    ///
+    /// Asks if the player would like to wager some of their
+    /// `points` on the next guess.
+    ///
+    fn ask_wager(&mut self, points: usize) -> io::Result<Option<wager::Wager>> {
+        loop {
+            print!("Wager on this guess? (Like \"20 exact\" or \"10 win\", blank to skip) > ");
+            io::stdout().flush()?;
+            // Running out of input skips the wager, and then the
+            // guess finds out there's nothing more to play
+            if self.read_line()? == 0 || self.input.trim().is_empty() {
+                return Ok(None);
+            }
+            match wager::place(&self.input, points) {
+                Ok(x) => {
+                    println!("Wagered {}", x);
+                    return Ok(Some(x));
+                }
+                Err(e) => println!("Please try again! {}", e),
+            }
+        }
+    }
+
+    ///
+    /// Asks for a label at the end of every game, which the
+    /// player can skip by just pressing enter.
+    ///
+    fn ask_label(&mut self, _: &GameRecord) -> io::Result<Option<String>> {
+        print!("Label this game? (Blank to skip) > ");
+        io::stdout().flush()?;
+        self.read_line()?;
+        Ok(super::clean_label(&self.input))
+    }
+}
+
+///
+/// What the player picked in the pause menu.
+///
+enum PauseChoice {
+    Resume,
+    SaveAndQuit,
+    Forfeit,
+}
+
+impl TerminalFrontend {
+    ///
+    /// The pause menu, opened with the `menu` command. The player
+    /// can look around as much as they like, until they pick
+    /// something which leaves the menu.
+    ///
+    fn pause_menu(&mut self, summary: &GameSummary) -> io::Result<PauseChoice> {
+        loop {
+            println!(
+                "Paused. 1: Resume, 2: Board, 3: Rules, 4: Settings, 5: Save and quit, 6: Forfeit"
+            );
+            print!("Menu > ");
+            io::stdout().flush()?;
+            // Running out of input leaves the menu the same way as
+            // it does everywhere else, by carrying on with the game.
+            if self.read_line()? == 0 {
+                return Ok(PauseChoice::Resume);
+            }
+            match self.input.trim() {
+                "1" | "resume" | "" => return Ok(PauseChoice::Resume),
+                "2" | "board" => print_history(summary, &self.feedback, self.accessible),
+                "3" | "rules" => println!(
+                    "{}",
+                    super::rules(self.accessible, summary.settings.allow_blanks)
+                ),
+                "4" | "settings" => print_settings(summary.settings),
+                "5" | "quit" => return Ok(PauseChoice::SaveAndQuit),
+                "6" | "forfeit" => return Ok(PauseChoice::Forfeit),
+                x => println!("Please try again! `{}` isn't one of the options.", x),
+            }
+        }
+    }
+}
+
+///
+/// Lists the settings of the current game, for the pause menu.
+///
+fn print_settings(config: GameConfig) {
+    let limit = |x: Option<usize>, none: &str| x.map_or(none.to_string(), |x| x.to_string());
+    println!("Pegs: {}", config.pegs);
+    println!("Duplicates: {}", config.allow_duplicates);
+    if config.auto_max_tries {
+        println!(
+            "Tries: {} (A fair limit for these settings)",
+            limit(config.max_tries, "unlimited")
+        );
+    } else {
+        println!("Tries: {}", limit(config.max_tries, "unlimited"));
+    }
+    println!("Colours in play: {}", limit(config.palette_size, "all"));
+    println!(
+        "Colours in code: {}",
+        limit(config.max_distinct_colours, "all")
+    );
+    println!(
+        "Fewest colours in code: {}",
+        limit(config.min_distinct_colours, "any")
+    );
+    println!(
+        "Most of a colour: {}",
+        limit(config.max_per_colour, "no limit")
+    );
+    println!("Reveal every: {}", limit(config.reveal_every, "off"));
+    println!("Hardcore: {}", config.hardcore);
+    println!("Memory: {}", config.memory);
+    println!("Block repeats: {}", config.block_repeats);
+    println!("Strict input: {}", config.strict_input);
+    println!("Confirm guesses: {}", config.confirm_guesses);
+    println!("Hint points: {}", config.hint_points);
+    println!("Fresh secrets: {}", config.fresh_secrets);
+    println!("Blanks: {}", config.allow_blanks);
+    if let Some(weights) = config.colour_weights {
+        let weights = super::COLOURS
+            .iter()
+            .map(|&x| format!("{:?} {}", x, weights[x as usize]))
+            .collect::<Vec<_>>();
+        println!("Colour weights: {}", weights.join(", "));
+    }
+    let required = config.required_colours();
+    if !required.is_empty() {
+        println!("Always includes: {:?}", required);
+    }
+    println!("(Settings can't be changed in the middle of a game)");
+}
+
+///
+/// Lists the guesses made so far this game, for the `history`
+/// command.
+///
+fn print_history(summary: &GameSummary, style: &FeedbackStyle, accessible: bool) {
+    if summary.settings.hardcore {
+        println!("The history is hidden in hardcore mode!");
+        return;
+    }
+    if accessible {
+        if summary.guesses.is_empty() {
+            println!("There are no guesses yet.");
+        }
+        for (idx, (guess, feedback)) in summary.guesses.iter().enumerate() {
+            let guess = if summary.settings.memory {
+                None
+            } else {
+                Some(&guess[..])
+            };
+            println!("{}", render::spoken_attempt(idx + 1, guess, *feedback));
+        }
+        return;
+    }
+    if summary.guesses.is_empty() {
+        println!("There are no guesses yet.");
+        return;
+    }
+    // Memory mode still shows the feedback, just not the guess.
+    let board = BoardFormatter::new()
+        .feedback(style)
+        .hide_guesses(summary.settings.memory)
+        .format(&summary.guesses);
+    print!("{}", board);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    // A frontend which plays from a list of inputs, and keeps
+    // everything it's shown as JSON. Once the inputs run out it
+    // gives up, the way running out of stdin does.
+    #[derive(Default)]
+    struct Scripted {
+        inputs: VecDeque<PlayerInput>,
+        wagers: VecDeque<Option<wager::Wager>>,
+        shown: Vec<Json>,
+    }
+
+    impl Scripted {
+        fn new(inputs: Vec<PlayerInput>) -> Self {
+            Self {
+                inputs: inputs.into(),
+                ..Self::default()
+            }
+        }
+
+        fn kinds(&self) -> Vec<&str> {
+            self.shown
+                .iter()
+                .filter_map(|x| x.get("kind")?.as_str())
+                .collect()
+        }
+
+        fn last(&self, kind: &str) -> &Json {
+            self.shown
+                .iter()
+                .rev()
+                .find(|x| x.get("kind").and_then(Json::as_str) == Some(kind))
+                .unwrap()
+        }
+    }
+
+    impl Frontend for Scripted {
+        fn configure(&mut self, defaults: GameConfig) -> io::Result<GameConfig> {
+            Ok(defaults)
+        }
+
+        fn next_input(&mut self, _: &GameSummary) -> io::Result<PlayerInput> {
+            Ok(self.inputs.pop_front().unwrap_or(PlayerInput::Abandon))
+        }
+
+        fn present(&mut self, output: &Output) -> io::Result<()> {
+            self.shown.push(output.to_json());
+            Ok(())
+        }
+
+        fn ask_wager(&mut self, _: usize) -> io::Result<Option<wager::Wager>> {
+            Ok(self.wagers.pop_front().flatten())
+        }
+    }
+
+    fn state() -> State<'static> {
+        StateBuilder::new().seed(7).build().unwrap()
+    }
+
+    fn typed(code: &[Colour]) -> PlayerInput {
+        let names: Vec<_> = code.iter().map(|x| x.name()).collect();
+        PlayerInput::Guess(names.join(" "))
+    }
+
+    // Some code which isn't `secret`, made by moving every peg
+    // along one place.
+    fn not(secret: &[Colour]) -> Vec<Colour> {
+        let mut code = secret.to_vec();
+        code.rotate_left(1);
+        code
+    }
+
+    #[test]
+    fn a_whole_session_plays_without_any_io() {
+        let mut state = state();
+        let secret = state.reveal_secret().to_vec();
+        let mut frontend = Scripted::new(vec![
+            PlayerInput::Guess("zebra".into()),
+            typed(&not(&secret)),
+            PlayerInput::Command,
+            typed(&secret),
+            PlayerInput::Forfeit,
+        ]);
+        let finished = play(&mut frontend, &mut state, &Session::default()).unwrap();
+
+        assert!(finished);
+        assert_eq!(
+            frontend.kinds(),
+            vec![
+                "new_game",
+                "error",
+                "feedback",
+                "progress",
+                "game_over",
+                "new_game",
+                "game_over",
+                "session_over",
+            ]
+        );
+        let games = state.games();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].attempts, 2);
+        assert_eq!(games[0].outcome, super::super::Outcome::Won);
+        assert_eq!(games[1].outcome, super::super::Outcome::Lost);
+        let feedback = frontend.last("feedback");
+        assert_eq!(
+            feedback.get("correct_position").unwrap().as_usize(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn running_out_of_input_abandons_the_session() {
+        let mut state = state();
+        let secret = state.reveal_secret().to_vec();
+        let mut frontend = Scripted::new(vec![typed(&not(&secret))]);
+        let session = Session {
+            verbose: true,
+            ..Session::default()
+        };
+        let finished = play(&mut frontend, &mut state, &session).unwrap();
+
+        assert!(!finished);
+        let over = frontend.last("session_over");
+        assert_eq!(over.get("abandoned").unwrap().as_usize(), Some(1));
+        assert_eq!(over.get("games").unwrap().as_array().unwrap().len(), 0);
+        let metrics = frontend.last("metrics").get("metrics").unwrap();
+        assert_eq!(metrics.get("guesses_entered").unwrap().as_usize(), Some(1));
+        assert_eq!(frontend.kinds().last(), Some(&"metrics"));
+    }
+
+    #[test]
+    fn wagers_are_settled_and_added_to_the_score() {
+        let mut state = state();
+        let secret = state.reveal_secret().to_vec();
+        let mut frontend = Scripted::new(vec![typed(&secret), PlayerInput::Forfeit]);
+        frontend.wagers = vec![Some(wager::place("10 win", 100).unwrap()), None].into();
+        let session = Session {
+            wagers: true,
+            ..Session::default()
+        };
+        play(&mut frontend, &mut state, &session).unwrap();
+
+        let settled = frontend.last("wager_settled");
+        assert_eq!(settled.get("won").unwrap().as_bool(), Some(true));
+        assert_eq!(settled.get("points").unwrap().as_usize(), Some(150));
+        // 150 from the first game, and the untouched 100 from the
+        // one given up on
+        let score = frontend.last("score").get("score").unwrap().as_usize();
+        assert_eq!(score, Some(250));
+    }
+
+    #[test]
+    fn metrics_count_typos_guesses_and_commands() {
+        let mut state = state();
+        let secret = state.reveal_secret().to_vec();
+        let mut frontend = Scripted::new(vec![
+            PlayerInput::Guess("rde blu".into()),
+            typed(&not(&secret)),
+            PlayerInput::Command,
+            PlayerInput::Guess("purple".into()),
+            PlayerInput::Undo,
+            typed(&not(&not(&secret))),
+            PlayerInput::Hint(Hint::Reveal),
+            typed(&secret),
+            PlayerInput::Forfeit,
+        ]);
+        let session = Session {
+            verbose: true,
+            ..Session::default()
+        };
+        play(&mut frontend, &mut state, &session).unwrap();
+
+        let metrics = frontend.last("metrics").get("metrics").unwrap();
+        let count = |key: &str| metrics.get(key).and_then(Json::as_usize);
+        assert_eq!(count("games_played"), Some(2));
+        assert_eq!(count("guesses_entered"), Some(3));
+        assert_eq!(count("parse_errors"), Some(2));
+        // The command, undo, hint and forfeit
+        assert_eq!(count("commands_used"), Some(4));
+        // There were no hint points to pay for it with
+        assert_eq!(count("hints_taken"), Some(0));
+        assert!(metrics
+            .get("milliseconds_per_guess")
+            .unwrap()
+            .as_usize()
+            .is_some());
+    }
+
+    #[test]
+    fn a_confirmed_guess_is_scored_on_commit() {
+        let config = GameConfig {
+            confirm_guesses: true,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).seed(7).build().unwrap();
+        let secret = state.reveal_secret().to_vec();
+        let mut typo = secret.clone();
+        typo[3] = *config
+            .palette()
+            .iter()
+            .find(|x| !secret.contains(x))
+            .unwrap();
+        let mut frontend = Scripted::new(vec![
+            typed(&typo),
+            PlayerInput::Undo,
+            PlayerInput::Guess(secret[3].name().into()),
+            PlayerInput::Commit,
+        ]);
+        play(&mut frontend, &mut state, &Session::default()).unwrap();
+
+        let games = state.games();
+        assert_eq!(games[0].outcome, super::super::Outcome::Won);
+        assert_eq!(games[0].attempts, 1);
+        assert!(!frontend.kinds().contains(&"feedback"));
+    }
+
+    // A terminal frontend reading `input` instead of standard
+    // input, and saying nothing
+    fn reading(input: &str) -> TerminalFrontend {
+        let given = SettingsLayer::default();
+        TerminalFrontend::new(
+            given,
+            Box::new(render::SilentRenderer),
+            FeedbackStyle::default(),
+        )
+        .reading_from(io::Cursor::new(input.to_string()))
+    }
+
+    #[test]
+    fn the_end_of_the_input_ends_the_session_cleanly() {
+        let mut state = state();
+        let secret = state.reveal_secret().to_vec();
+        let names: Vec<_> = not(&secret).iter().map(|x| x.name()).collect();
+        // A guess, a command, and then nothing more
+        let mut frontend = reading(&format!("{}\nundo\n", names.join(" ")));
+        let finished = play(&mut frontend, &mut state, &Session::default()).unwrap();
+        assert!(!finished);
+        assert!(state.games().is_empty());
+        assert_eq!(state.history().len(), 1);
+
+        // Nothing at all, not even a first guess
+        let mut state = self::state();
+        let finished = play(&mut reading(""), &mut state, &Session::default()).unwrap();
+        assert!(!finished);
+        assert!(state.history().is_empty());
+    }
+
+    #[test]
+    fn the_end_of_the_input_skips_the_questions_at_the_end_of_a_move() {
+        let mut frontend = reading("");
+        assert_eq!(frontend.ask_wager(100).unwrap(), None);
+        let mut state = state();
+        state.forfeit();
+        let record = state.games()[0].clone();
+        assert_eq!(frontend.ask_label(&record).unwrap(), None);
+        let summary = state.summary();
+        assert!(matches!(
+            frontend.pause_menu(&summary).unwrap(),
+            PauseChoice::Resume
+        ));
+    }
+
+    // Every message shown, in order
+    fn messages(frontend: &Scripted) -> Vec<&str> {
+        frontend
+            .shown
+            .iter()
+            .filter(|x| x.get("kind").and_then(Json::as_str) == Some("message"))
+            .filter_map(|x| x.get("message")?.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn a_blank_line_is_answered_with_what_is_needed() {
+        let mut state = state();
+        let secret = state.reveal_secret().to_vec();
+        let first = secret[0].name().to_string();
+        let mut frontend = Scripted::new(vec![
+            PlayerInput::Guess("".into()),
+            PlayerInput::Guess("   ".into()),
+            PlayerInput::Guess("\t \t".into()),
+            PlayerInput::Guess(first.clone()),
+            PlayerInput::Guess("".into()),
+        ]);
+        play(&mut frontend, &mut state, &Session::default()).unwrap();
+
+        let reminder = "A guess is 4 colours, typed by name or by first letter";
+        let so_far = format!(
+            "The guess so far is [{:?}], it needs 3 more colours",
+            secret[0]
+        );
+        assert_eq!(
+            messages(&frontend)[..4],
+            [reminder, reminder, reminder, so_far.as_str()]
+        );
+        assert!(!frontend.kinds().contains(&"error"));
+    }
+
+    #[test]
+    fn spaces_and_tabs_between_colours_are_skipped() {
+        use super::super::Outcome;
+        for line in &["r g b y", "  r g  b y  ", "r\tg\tb\ty", "rg\t by"] {
+            let mut state = StateBuilder::new().build().unwrap();
+            state.set_secret(vec![
+                Colour::Red,
+                Colour::Green,
+                Colour::Blue,
+                Colour::Yellow,
+            ]);
+            state.push_string_input(line).unwrap();
+            assert_eq!(state.games()[0].outcome, Outcome::Won, "{:?}", line);
+        }
+    }
+}
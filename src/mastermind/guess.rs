@@ -0,0 +1,349 @@
+//!
+//! A whole guess at the secret, as a type of its own instead of
+//! a bare list of colours.
+//!
+//! Whether a guess is allowed depends on the settings: how many
+//! pegs there are, whether duplicates are allowed and so on.
+//! Those checks all live here, in `Guess::new` and
+//! `Guess::for_config`, so that typing a guess, entering one
+//! from another program and loading one from a save all agree
+//! on what's allowed.
+//!
+//! Parsing a guess with `FromStr` only reads the colours, since
+//! a line of text doesn't know what it's meant to be checked
+//! against. `Guess::parse` reads one and checks it too, and
+//! `State` checks every guess again when it's entered.
+//!
+
+use super::{parse_symbols, Colour, GameConfig, MastermindError, Symbol};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+///
+/// The colours of one guess, in order.
+///
+/// This is what Java calls a wrapper class, and what rust calls
+/// a newtype: a struct with a single unnamed field, which
+/// costs nothing at runtime but can't be mixed up with any other
+/// `Vec<Colour>`. Like `State`, it can be of other pegs than
+/// colours, see `symbol.rs`.
+///
+/// Serde writes it as the list it wraps.
+/// Reading one back doesn't check it against any settings, and
+/// `State` checks it again when it's entered anyway.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Guess<P = Colour>(Vec<P>);
+
+///
+/// Why a guess wasn't allowed.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum GuessError<P = Colour> {
+    ///
+    /// The text couldn't be read as colours, with why.
+    ///
+    Unreadable(Box<MastermindError<P>>),
+    ///
+    /// The guess doesn't have as many colours as the code.
+    ///
+    WrongLength { expected: usize, found: usize },
+    ///
+    /// This colour is in the guess more than once, without
+    /// duplicates.
+    ///
+    Duplicate(P),
+    ///
+    /// The guess has a blank, in a game without them.
+    ///
+    Blank,
+    ///
+    /// This colour isn't one of the colours in play, see
+    /// `GameConfig::palette_size`.
+    ///
+    NotInPlay(P),
+    ///
+    /// This colour is in the guess more often than
+    /// `GameConfig::max_per_colour` allows.
+    ///
+    TooMany { colour: P, most: usize },
+}
+
+impl<P: Symbol> fmt::Display for GuessError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuessError::Unreadable(e) => write!(f, "{}", e),
+            GuessError::WrongLength { expected, found } => write!(
+                f,
+                "That guess has {} {}s, but the code has {}",
+                found,
+                P::noun(),
+                expected
+            ),
+            GuessError::Duplicate(colour) => write!(
+                f,
+                "{:?} is in that guess twice, but duplicates aren't allowed",
+                colour
+            ),
+            GuessError::Blank => write!(f, "There are no blanks in this game"),
+            GuessError::NotInPlay(colour) => write!(f, "{:?} is not in play this game", colour),
+            GuessError::TooMany { colour, most } => write!(
+                f,
+                "{:?} can't be in a guess more than {} times",
+                colour, most
+            ),
+        }
+    }
+}
+
+impl<P: Symbol> std::error::Error for GuessError<P> {}
+
+impl<P: Symbol> GuessError<P> {
+    ///
+    /// Which of `pegs` this is about, if it's about one of them:
+    /// the first which isn't allowed where it is, like the second
+    /// of two reds without duplicates. `pegs` is the guess which
+    /// gave this error.
+    ///
+    pub fn position(&self, pegs: &[P]) -> Option<usize> {
+        // How many of the colour are allowed before the one at fault
+        let (colour, allowed) = match *self {
+            GuessError::Duplicate(colour) => (colour, 1),
+            GuessError::TooMany { colour, most } => (colour, most),
+            GuessError::NotInPlay(colour) => (colour, 0),
+            GuessError::Blank => (P::blank()?, 0),
+            GuessError::Unreadable(_) | GuessError::WrongLength { .. } => return None,
+        };
+        pegs.iter()
+            .enumerate()
+            .filter(|&(_, &x)| x == colour)
+            .nth(allowed)
+            .map(|(at, _)| at)
+    }
+}
+
+impl<P: Symbol> Guess<P> {
+    ///
+    /// Checks that `pegs` is `expected_len` colours long, and
+    /// that no colour is in it twice unless `allow_duplicates`.
+    ///
+    pub fn new(
+        pegs: Vec<P>,
+        expected_len: usize,
+        allow_duplicates: bool,
+    ) -> Result<Self, GuessError<P>> {
+        if pegs.len() != expected_len {
+            return Err(GuessError::WrongLength {
+                expected: expected_len,
+                found: pegs.len(),
+            });
+        }
+        if !allow_duplicates {
+            let mut seen = HashSet::new();
+            // `insert` returns `false` if the colour was already there
+            if let Some(&colour) = pegs.iter().find(|x| !seen.insert(**x)) {
+                return Err(GuessError::Duplicate(colour));
+            }
+        }
+        Ok(Self(pegs))
+    }
+
+    ///
+    /// `new` with everything else `config` says about guesses
+    /// checked as well: blanks, and how many of each colour a
+    /// guess can have.
+    ///
+    pub fn for_config(pegs: Vec<P>, config: GameConfig) -> Result<Self, GuessError<P>> {
+        let guess = Self::new(pegs, config.pegs, config.allow_duplicates)?;
+        if !config.allow_blanks && P::blank().is_some_and(|x| guess.contains(&x)) {
+            return Err(GuessError::Blank);
+        }
+        if let Some(&colour) = guess.iter().find(|&&x| !config.in_play(x)) {
+            return Err(GuessError::NotInPlay(colour));
+        }
+        if let (Some(colour), Some(most)) = (config.over_limit(&guess), config.max_per_colour) {
+            return Err(GuessError::TooMany { colour, most });
+        }
+        Ok(guess)
+    }
+
+    ///
+    /// Reads a whole line as a guess, like `rgby`, `r g b y` or
+    /// `red, green, blue, yellow` (See `parse_colours`), and checks
+    /// it the same way as `new`. If a word can't be read, the
+    /// error (`GuessError::Unreadable`) has the word in it.
+    ///
+    pub fn parse(
+        text: &str,
+        expected_len: usize,
+        allow_duplicates: bool,
+    ) -> Result<Self, GuessError<P>> {
+        let guess = text.parse::<Self>()?;
+        Self::new(guess.0, expected_len, allow_duplicates)
+    }
+
+    ///
+    /// Gives back the colours, for anything which needs to own
+    /// them.
+    ///
+    pub fn into_colours(self) -> Vec<P> {
+        self.0
+    }
+}
+
+///
+/// `Deref` lets a `&Guess` be used anywhere a `&[Colour]` is
+/// wanted, so that reading a guess doesn't need unwrapping it.
+/// There's deliberately no `DerefMut`, since changing a guess
+/// could make it one which isn't allowed.
+///
+impl<P> Deref for Guess<P> {
+    type Target = [P];
+
+    fn deref(&self) -> &[P] {
+        &self.0
+    }
+}
+
+///
+/// So that a `Game` can keep its guesses as `Guess`es, see
+/// `State`.
+///
+impl<P> AsRef<[P]> for Guess<P> {
+    fn as_ref(&self) -> &[P] {
+        &self.0
+    }
+}
+
+///
+/// Reads a whole line, like `rbwy` or `red blue white yellow`,
+/// see `parse_colours`.
+///
+impl<P: Symbol> FromStr for Guess<P> {
+    type Err = GuessError<P>;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        parse_symbols(text)
+            .map(Self)
+            .map_err(|e| GuessError::Unreadable(Box::new(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Colour::*;
+
+    #[test]
+    fn a_guess_has_to_be_the_right_length() {
+        assert_eq!(
+            Guess::<Colour>::parse("rgb", 4, false),
+            Err(GuessError::WrongLength {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            Guess::<Colour>::parse("rgbyw", 4, false),
+            Err(GuessError::WrongLength {
+                expected: 4,
+                found: 5
+            })
+        );
+        assert_eq!(
+            Guess::<Colour>::parse("", 4, true),
+            Err(GuessError::WrongLength {
+                expected: 4,
+                found: 0
+            })
+        );
+    }
+
+    #[test]
+    fn a_duplicate_is_only_allowed_with_duplicates() {
+        let error = Guess::<Colour>::parse("rgbg", 4, false).unwrap_err();
+        assert_eq!(error, GuessError::Duplicate(Green));
+        assert_eq!(error.position(&[Red, Green, Blue, Green]), Some(3));
+        let guess = Guess::<Colour>::parse("rgbg", 4, true).unwrap();
+        assert_eq!(&*guess, &[Red, Green, Blue, Green][..]);
+    }
+
+    #[test]
+    fn a_valid_guess_reads_however_it_was_typed() {
+        let expected = [Red, Green, Blue, Yellow];
+        for text in &["rgby", "r g b y", "red, green, blue, yellow"] {
+            let guess = Guess::<Colour>::parse(text, 4, false).unwrap();
+            assert_eq!(&*guess, &expected[..], "{}", text);
+            assert_eq!(guess.into_colours(), expected.to_vec());
+        }
+    }
+
+    #[test]
+    fn an_unreadable_word_is_kept_in_the_error() {
+        match "rgbz".parse::<Guess>() {
+            Err(GuessError::Unreadable(error)) => {
+                assert!(matches!(*error, MastermindError::UnknownColour { .. }));
+            }
+            other => panic!("{:?}", other),
+        }
+        // Reading a line doesn't check it against anything
+        assert_eq!("rr".parse::<Guess>().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn the_settings_are_checked_too() {
+        let config = GameConfig::default();
+        assert_eq!(
+            Guess::for_config(vec![Red, Green, Blank, Yellow], config),
+            Err(GuessError::Blank)
+        );
+        assert_eq!(
+            Guess::for_config(vec![Red, Green, Purple, Yellow], config),
+            Err(GuessError::NotInPlay(Purple))
+        );
+        let limited = GameConfig {
+            allow_duplicates: true,
+            max_per_colour: Some(2),
+            ..config
+        };
+        assert_eq!(
+            Guess::for_config(vec![Red, Red, Red, Yellow], limited),
+            Err(GuessError::TooMany {
+                colour: Red,
+                most: 2
+            })
+        );
+        assert!(Guess::for_config(vec![Red, Red, Green, Yellow], limited).is_ok());
+    }
+
+    #[test]
+    fn a_malformed_line_names_the_word() {
+        let word = |text: &str| match Guess::<Colour>::parse(text, 4, false) {
+            Err(GuessError::Unreadable(error)) => match *error {
+                MastermindError::UnknownColour { word, .. } => word,
+                MastermindError::AmbiguousColour { word, .. } => word,
+                other => panic!("{:?}", other),
+            },
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(word("red blue purpe green"), "purpe");
+        assert_eq!(word("red-blue green yellow"), "red-blue");
+        assert_eq!(word("rzby"), "rzby");
+        assert_eq!(word("r p b y"), "p");
+        assert_eq!(word("red, green, 7, yellow"), "7");
+    }
+
+    #[test]
+    fn spacing_and_commas_dont_matter() {
+        let expected = [Red, Green, Blue, Yellow];
+        for text in &["  rgby  ", "r g, b  y", "red,green,blue,yellow", "r,,g b y"] {
+            let guess = Guess::<Colour>::parse(text, 4, false).unwrap();
+            assert_eq!(&*guess, &expected[..], "{:?}", text);
+        }
+        // A run of letters and a full name can be mixed
+        let guess = Guess::<Colour>::parse("rg blue yel", 4, false).unwrap();
+        assert_eq!(&*guess, &[Red, Green, Blue, Yellow][..]);
+    }
+}
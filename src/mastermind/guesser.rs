@@ -0,0 +1,281 @@
+//!
+//! `Guesser`, for anything which can play a game by itself: a
+//! solver, a bot to practise against, or a simulation playing
+//! thousands of games to see how the settings hold up.
+//!
+//! A guesser only has to come up with the next guess from what
+//! happened so far. `play_with_guesser` does the rest, entering
+//! its guesses into a `State` until the game is over:
+//! ```
+//! let mut guesser = RandomGuesser::new();
+//! let summary = play_with_guesser(&mut state, &mut guesser)?;
+//! println!("That took {} guesses", summary.attempts_made);
+//! ```
+//!
+
+use super::{solver, Colour, Feedback, GameConfig, GameSummary, MastermindError, State, Symbol};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+///
+/// Something which makes guesses, like an interface in Java.
+/// `State` never asks one for anything itself, it's always
+/// through `play_with_guesser`.
+///
+/// `P` is what the pegs are, see `symbol.rs`.
+///
+pub trait Guesser<P = Colour> {
+    ///
+    /// The next guess for a game played with `config`, which has
+    /// had the guesses in `history` so far (Oldest first), each
+    /// with the feedback it got.
+    ///
+    fn next_guess(&mut self, history: &[(Vec<P>, Feedback)], config: &GameConfig) -> Vec<P>;
+
+    ///
+    /// Forgets anything kept from the last game, before the
+    /// next one starts.
+    ///
+    fn reset(&mut self);
+}
+
+///
+/// Plays the rest of the game in `state` with the guesses from
+/// `guesser`, and gives back the summary of it once it's over.
+///
+/// Any colours already entered for the current guess are taken
+/// back first, since the guesser makes whole guesses. If it
+/// makes one which isn't allowed, that guess is taken back too,
+/// and what was wrong with it is given back instead.
+///
+pub fn play_with_guesser<P: Symbol>(
+    state: &mut State<P>,
+    guesser: &mut dyn Guesser<P>,
+) -> Result<GameSummary<P>, MastermindError<P>> {
+    while state.undo_input().is_some() {}
+    guesser.reset();
+    // Games only ever get added to, so the one we're playing is
+    // over once there's one more of them.
+    let games = state.games().len();
+    while state.games().len() == games {
+        let history = state
+            .history()
+            .iter()
+            .map(|x| (x.guess.to_vec(), x.feedback))
+            .collect::<Vec<_>>();
+        let config = *state.config();
+        for colour in guesser.next_guess(&history, &config) {
+            if let Err(e) = state.input_buffer(colour) {
+                while state.undo_input().is_some() {}
+                return Err(e);
+            }
+        }
+        // A guess of the wrong length would otherwise carry on
+        // into the next one.
+        if !state.buffered().is_empty() {
+            let needed = state.size();
+            let got = state.buffered().len();
+            while state.undo_input().is_some() {}
+            return Err(MastermindError::GuessTooShort { got, needed });
+        }
+    }
+    Ok(state
+        .last_game()
+        .cloned()
+        .expect("A game just ended, so there's a last game"))
+}
+
+///
+/// Guesses any code it hasn't guessed yet, picked at random.
+/// This doesn't look at the feedback at all, so it plays badly,
+/// but it always finishes in the end: without a limit on tries,
+/// the secret is one of the codes it works through.
+///
+pub struct RandomGuesser<P = Colour> {
+    rng: StdRng,
+    ///
+    /// The codes not guessed yet this game, which are only
+    /// worked out once it's known what the game is played with.
+    ///
+    untried: Option<Vec<Vec<P>>>,
+}
+
+impl<P> RandomGuesser<P> {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+            untried: None,
+        }
+    }
+
+    ///
+    /// A guesser which makes the same guesses every time, given
+    /// the same `seed` and the same game.
+    ///
+    pub fn new_seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            untried: None,
+        }
+    }
+}
+
+impl<P> Default for RandomGuesser<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Symbol> Guesser<P> for RandomGuesser<P> {
+    fn next_guess(&mut self, history: &[(Vec<P>, Feedback)], config: &GameConfig) -> Vec<P> {
+        // Anything guessed before this guesser started playing is
+        // taken out once, and after that only the newest guess can
+        // be one it hasn't taken out yet.
+        let untried = match &mut self.untried {
+            Some(untried) => {
+                if let Some((guess, _)) = history.last() {
+                    untried.retain(|code| code != guess);
+                }
+                untried
+            }
+            None => {
+                let mut untried = solver::candidates(*config);
+                untried.retain(|code| history.iter().all(|(guess, _)| guess != code));
+                self.untried.insert(untried)
+            }
+        };
+        // Every code has been tried, which can only happen if the
+        // game isn't one `candidates` knows how to make. Trying
+        // the first again is as good as anything.
+        if untried.is_empty() {
+            return history.first().map_or_else(Vec::new, |x| x.0.clone());
+        }
+        let pick = self.rng.gen_range(0, untried.len());
+        untried.swap_remove(pick)
+    }
+
+    fn reset(&mut self) {
+        self.untried = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::{GamePhase, Outcome, StateBuilder};
+
+    fn small() -> GameConfig {
+        GameConfig {
+            pegs: 2,
+            allow_duplicates: true,
+            palette_size: Some(4),
+            max_tries: None,
+            auto_max_tries: false,
+            ..GameConfig::default()
+        }
+    }
+
+    ///
+    /// Makes the guesses it's given in order, and keeps what it
+    /// was shown so that it can be checked afterwards.
+    ///
+    struct Scripted {
+        guesses: Vec<Vec<Colour>>,
+        seen: Vec<usize>,
+        resets: usize,
+    }
+
+    impl Guesser for Scripted {
+        fn next_guess(
+            &mut self,
+            history: &[(Vec<Colour>, Feedback)],
+            _: &GameConfig,
+        ) -> Vec<Colour> {
+            self.seen.push(history.len());
+            self.guesses.remove(0)
+        }
+
+        fn reset(&mut self) {
+            self.resets += 1;
+        }
+    }
+
+    #[test]
+    fn random_guesser_finishes_without_a_limit() {
+        for seed in 0..20 {
+            let mut state = StateBuilder::new()
+                .config(small())
+                .seed(seed)
+                .build()
+                .unwrap();
+            assert_eq!(state.config().max_tries, None);
+            let mut guesser = RandomGuesser::new_seeded(seed);
+            let summary = play_with_guesser(&mut state, &mut guesser).unwrap();
+            assert_eq!(summary.phase, GamePhase::Won);
+            // It never guesses the same code twice, so it can't
+            // take more tries than there are codes.
+            assert!(summary.attempts_made <= 16);
+            assert_eq!(state.games().len(), 1);
+            assert_eq!(state.games()[0].outcome, Outcome::Won);
+            assert_eq!(state.games()[0].attempts, summary.attempts_made);
+        }
+    }
+
+    #[test]
+    fn random_guesser_is_repeatable_with_a_seed() {
+        let play = || {
+            let mut state = StateBuilder::new().config(small()).seed(7).build().unwrap();
+            play_with_guesser(&mut state, &mut RandomGuesser::new_seeded(3)).unwrap()
+        };
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn random_guesser_plays_a_second_game_after_reset() {
+        let mut state = StateBuilder::new().config(small()).seed(1).build().unwrap();
+        let mut guesser = RandomGuesser::new_seeded(1);
+        play_with_guesser(&mut state, &mut guesser).unwrap();
+        play_with_guesser(&mut state, &mut guesser).unwrap();
+        assert_eq!(state.games().len(), 2);
+        assert!(state.games().iter().all(|x| x.outcome == Outcome::Won));
+    }
+
+    #[test]
+    fn scripted_guesser_sees_the_history() {
+        let mut state = StateBuilder::new().config(small()).build().unwrap();
+        state.set_secret(vec![Colour::Red, Colour::Blue]);
+        // Something entered by hand is taken back before playing.
+        state.input_buffer(Colour::White).unwrap();
+        let mut guesser = Scripted {
+            guesses: vec![
+                vec![Colour::Blue, Colour::Red],
+                vec![Colour::Red, Colour::White],
+                vec![Colour::Red, Colour::Blue],
+            ],
+            seen: Vec::new(),
+            resets: 0,
+        };
+        let summary = play_with_guesser(&mut state, &mut guesser).unwrap();
+        assert_eq!(guesser.resets, 1);
+        assert_eq!(guesser.seen, vec![0, 1, 2]);
+        assert_eq!(summary.phase, GamePhase::Won);
+        assert_eq!(summary.attempts_made, 3);
+    }
+
+    #[test]
+    fn short_guess_is_taken_back() {
+        let mut state = StateBuilder::new().config(small()).build().unwrap();
+        state.set_secret(vec![Colour::Red, Colour::Blue]);
+        let mut guesser = Scripted {
+            guesses: vec![vec![Colour::Red]],
+            seen: Vec::new(),
+            resets: 0,
+        };
+        assert_eq!(
+            play_with_guesser(&mut state, &mut guesser),
+            Err(MastermindError::GuessTooShort { got: 1, needed: 2 })
+        );
+        assert!(state.buffered().is_empty());
+        assert!(state.history().is_empty());
+    }
+}
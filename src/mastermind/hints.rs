@@ -0,0 +1,213 @@
+//!
+//! Hints, paid for out of a budget of hint points which each
+//! game starts with (`GameConfig::hint_points`, zero turns them
+//! off). The hints, and what they cost, are:
+//!
+//! - `hint reveal`: 3 points, shows one position of the secret.
+//! - `hint suggest`: 2 points, the guess the solver would make
+//!   next (See `solver.rs`).
+//! - `hint count COLOUR`: 1 point, how many pegs of that colour
+//!   the secret has.
+//!
+//! Every hint goes through `State::take_hint`, which checks the
+//! budget with `check` here before giving anything away. Points
+//! spent are kept on the `GameRecord`, and come off the speed
+//! bonus (See `speed.rs`).
+//!
+
+use super::{parse_colours, Colour};
+use std::str::FromStr;
+
+///
+/// One of the hints above.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hint {
+    Reveal,
+    Suggest,
+    Count(Colour),
+}
+
+impl Hint {
+    ///
+    /// How many hint points this hint costs.
+    ///
+    pub fn cost(self) -> usize {
+        match self {
+            Hint::Reveal => 3,
+            Hint::Suggest => 2,
+            Hint::Count(_) => 1,
+        }
+    }
+}
+
+///
+/// Reads what comes after `hint`, like `reveal` or `count red`.
+///
+impl FromStr for Hint {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut words = text.split_whitespace();
+        let hint = match (words.next(), words.next()) {
+            (Some("reveal"), None) => Hint::Reveal,
+            (Some("suggest"), None) => Hint::Suggest,
+            (Some("count"), Some(colour)) => {
+                match parse_colours(colour).map_err(|e| e.to_string())?[..] {
+                    [colour] => Hint::Count(colour),
+                    _ => return Err("`hint count` needs a single colour".into()),
+                }
+            }
+            (Some("count"), None) => return Err("`hint count` needs a colour".into()),
+            _ => {
+                return Err("Hints are `hint reveal`, `hint suggest` or `hint count COLOUR`".into())
+            }
+        };
+        match words.next() {
+            None => Ok(hint),
+            Some(x) => Err(format!("`{}` is one word too many for a hint", x)),
+        }
+    }
+}
+
+///
+/// What a hint told the player.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum HintAnswer {
+    ///
+    /// `position` (Counting from zero) of the secret is
+    /// `colour`.
+    ///
+    Revealed {
+        position: usize,
+        colour: Colour,
+    },
+    Suggestion(Vec<Colour>),
+    ///
+    /// The secret has `count` pegs of `colour`.
+    ///
+    Count {
+        colour: Colour,
+        count: usize,
+    },
+}
+
+///
+/// Whether `hint` can be paid for from a budget of `budget`
+/// points, `spent` of which are already gone. The error says
+/// why not, for showing to the player.
+///
+pub fn check(budget: usize, spent: usize, hint: Hint) -> Result<(), String> {
+    let left = budget.saturating_sub(spent);
+    if budget == 0 {
+        Err("Hints are turned off for this game".into())
+    } else if left == 0 {
+        Err("You've used up all of your hint points for this game".into())
+    } else if hint.cost() > left {
+        Err(format!(
+            "That hint costs {} points, but you only have {} left",
+            hint.cost(),
+            left
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{GameConfig, Guess, StateBuilder};
+    use super::*;
+
+    fn with_points(hint_points: usize) -> GameConfig {
+        GameConfig {
+            hint_points,
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn hints_are_read_after_the_word_hint() {
+        assert_eq!(" reveal".parse(), Ok(Hint::Reveal));
+        assert_eq!("suggest".parse(), Ok(Hint::Suggest));
+        assert_eq!("count red".parse(), Ok(Hint::Count(Colour::Red)));
+        assert!("count".parse::<Hint>().is_err());
+        assert!("count red blue".parse::<Hint>().is_err());
+        assert!("count rb".parse::<Hint>().is_err());
+        assert!("reveal now".parse::<Hint>().is_err());
+        assert!("".parse::<Hint>().is_err());
+    }
+
+    #[test]
+    fn a_budget_of_zero_turns_hints_off() {
+        let error = check(0, 0, Hint::Count(Colour::Red)).unwrap_err();
+        assert!(error.contains("turned off"));
+    }
+
+    #[test]
+    fn the_exact_budget_can_be_spent() {
+        assert_eq!(check(3, 0, Hint::Reveal), Ok(()));
+        assert_eq!(check(3, 2, Hint::Count(Colour::Red)), Ok(()));
+        let error = check(3, 3, Hint::Count(Colour::Red)).unwrap_err();
+        assert!(error.contains("used up"));
+    }
+
+    #[test]
+    fn an_unaffordable_hint_says_what_is_left() {
+        let error = check(4, 2, Hint::Reveal).unwrap_err();
+        assert_eq!(error, "That hint costs 3 points, but you only have 2 left");
+        assert_eq!(check(4, 2, Hint::Suggest), Ok(()));
+    }
+
+    #[test]
+    fn spending_is_kept_on_the_game_record() {
+        let mut state = StateBuilder::new()
+            .config(with_points(6))
+            .seed(6)
+            .build()
+            .unwrap();
+        let secret = state.reveal_secret().to_vec();
+        match state.take_hint(Hint::Reveal).unwrap() {
+            HintAnswer::Revealed { position, colour } => assert_eq!(secret[position], colour),
+            x => panic!("expected a reveal, got {:?}", x),
+        }
+        let red = secret.iter().filter(|&&x| x == Colour::Red).count();
+        assert_eq!(
+            state.take_hint(Hint::Count(Colour::Red)),
+            Ok(HintAnswer::Count {
+                colour: Colour::Red,
+                count: red
+            })
+        );
+        assert_eq!(state.summary().hint_points_left, Some(2));
+        assert!(state.take_hint(Hint::Reveal).is_err());
+        assert!(state.take_hint(Hint::Suggest).is_ok());
+        assert!(state.take_hint(Hint::Count(Colour::Blue)).is_err());
+
+        let guess = Guess::for_config(secret, state.config).unwrap();
+        state.enter_guess(&guess).unwrap();
+        let record = &state.games()[0];
+        assert_eq!(record.hint_points, 6);
+        // Paid for reveals aren't what stops a speed bonus
+        let unaided = crate::mastermind::speed::bonus(Some(9), record.duration, 0, 0);
+        assert_eq!(
+            record.speed_bonus,
+            unaided - 6 * crate::mastermind::speed::HINT_PENALTY
+        );
+    }
+
+    #[test]
+    fn budgets_are_per_game() {
+        let mut state = StateBuilder::new()
+            .config(with_points(3))
+            .seed(6)
+            .build()
+            .unwrap();
+        state.take_hint(Hint::Reveal).unwrap();
+        assert!(state.take_hint(Hint::Reveal).is_err());
+        state.forfeit();
+        assert_eq!(state.summary().hint_points_left, Some(3));
+        assert!(state.take_hint(Hint::Reveal).is_ok());
+    }
+}
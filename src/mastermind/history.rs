@@ -0,0 +1,316 @@
+//!
+//! Every game anyone has finished, kept in `history.jsonl` in
+//! the data directory. Each line of the file is one game as a
+//! JSON object, so finishing a game only ever adds a line
+//! rather than rewriting the whole file.
+//!
+//! Rounds of `mastermind quiz` (See `quiz.rs`) are kept the same
+//! way in `quiz.jsonl`, apart from the games so that they don't
+//! count towards anyone's wins.
+//!
+
+use super::json::Json;
+use super::storage;
+use super::GameRecord;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+///
+/// One finished game, and who played it.
+///
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    ///
+    /// The `id` of the player's profile.
+    ///
+    pub profile: u64,
+    pub record: GameRecord,
+    pub finished_at: SystemTime,
+    ///
+    /// The points left at the end of the game, if it was
+    /// played with wagers.
+    ///
+    pub points: Option<usize>,
+}
+
+impl HistoryEntry {
+    ///
+    /// Whether the game's label has `search` somewhere in it,
+    /// ignoring case, so that "birthday" finds "Mum's birthday".
+    ///
+    pub fn label_matches(&self, search: &str) -> bool {
+        self.record
+            .label
+            .as_ref()
+            .is_some_and(|x| x.to_lowercase().contains(&search.to_lowercase()))
+    }
+}
+
+fn history_path() -> PathBuf {
+    storage::data_dir().join("history.jsonl")
+}
+
+///
+/// Adds a finished game to the history, with the points left
+/// over if it was played with wagers.
+///
+pub fn append(profile: u64, record: &GameRecord, points: Option<usize>) -> io::Result<()> {
+    let mut pairs = vec![
+        ("profile", profile.into()),
+        ("finished_at", now().into()),
+        ("game", Json::from_serde(record)),
+    ];
+    if let Some(points) = points {
+        pairs.push(("points", points.into()));
+    }
+    append_line(history_path(), Json::object(pairs))
+}
+
+//
+// Seconds since 1970, which is how times are written down in
+// both files.
+//
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or(0)
+}
+
+fn append_line(path: PathBuf, line: Json) -> io::Result<()> {
+    std::fs::create_dir_all(storage::data_dir())?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)
+}
+
+//
+// The lines of `path`, where a file that isn't there yet has
+// none.
+//
+fn read_lines(path: PathBuf) -> io::Result<Vec<Json>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(x) => x,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(text.lines().filter_map(|x| Json::parse(x).ok()).collect())
+}
+
+///
+/// Every game in the history, oldest first. Lines which can't
+/// be read are skipped.
+///
+pub fn load() -> io::Result<Vec<HistoryEntry>> {
+    Ok(entries(read_lines(history_path())?))
+}
+
+fn entries(lines: Vec<Json>) -> Vec<HistoryEntry> {
+    lines
+        .into_iter()
+        .filter_map(|json| {
+            Some(HistoryEntry {
+                profile: json.get("profile")?.as_usize()? as u64,
+                record: json.get("game")?.to_serde().ok()?,
+                finished_at: UNIX_EPOCH
+                    + Duration::from_secs(json.get("finished_at")?.as_usize()? as u64),
+                points: json.get("points").and_then(Json::as_usize),
+            })
+        })
+        .collect()
+}
+
+///
+/// Totals for a set of games.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub played: usize,
+    pub won: usize,
+    ///
+    /// How many of the games won had positions revealed along
+    /// the way.
+    ///
+    pub assisted: usize,
+    ///
+    /// How many of the games won were played in hardcore mode.
+    ///
+    pub hardcore: usize,
+    ///
+    /// How many of the games won were played in memory mode.
+    ///
+    pub memory: usize,
+    ///
+    /// How many games were played with wagers, and the total
+    /// points left at the end of them.
+    ///
+    pub wagered: usize,
+    pub points: usize,
+    ///
+    /// The speed bonus of every game added up, see `speed.rs`.
+    ///
+    pub speed_bonus: usize,
+}
+
+impl Stats {
+    ///
+    /// Adds up the games in `entries` played by `profile`.
+    ///
+    pub fn for_profile(entries: &[HistoryEntry], profile: u64) -> Self {
+        entries
+            .iter()
+            .filter(|x| x.profile == profile)
+            .fold(Self::default(), |mut stats, entry| {
+                stats.played += 1;
+                if entry.record.outcome == super::Outcome::Won {
+                    stats.won += 1;
+                    if entry.record.reveals > 0 {
+                        stats.assisted += 1;
+                    }
+                    if entry.record.hardcore {
+                        stats.hardcore += 1;
+                    }
+                    if entry.record.memory {
+                        stats.memory += 1;
+                    }
+                }
+                stats.speed_bonus += entry.record.speed_bonus;
+                if let Some(points) = entry.points {
+                    stats.wagered += 1;
+                    stats.points += points;
+                }
+                stats
+            })
+    }
+
+    ///
+    /// The fraction of games won, from `0.0` to `1.0`.
+    ///
+    pub fn win_rate(&self) -> f64 {
+        if self.played == 0 {
+            0.0
+        } else {
+            self.won as f64 / self.played as f64
+        }
+    }
+}
+
+///
+/// One round of `mastermind quiz`.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuizEntry {
+    ///
+    /// How many pegs the codes had, which is what the difficulty
+    /// picks.
+    ///
+    pub pegs: usize,
+    pub asked: usize,
+    pub right: usize,
+}
+
+impl QuizEntry {
+    ///
+    /// The fraction of answers which were right, from `0.0` to
+    /// `1.0`.
+    ///
+    pub fn accuracy(&self) -> f64 {
+        if self.asked == 0 {
+            0.0
+        } else {
+            self.right as f64 / self.asked as f64
+        }
+    }
+}
+
+fn quiz_path() -> PathBuf {
+    storage::data_dir().join("quiz.jsonl")
+}
+
+///
+/// Adds a finished round of the quiz, played by `profile`.
+///
+pub fn append_quiz(profile: u64, entry: QuizEntry) -> io::Result<()> {
+    let line = Json::object(vec![
+        ("profile", profile.into()),
+        ("finished_at", now().into()),
+        ("pegs", entry.pegs.into()),
+        ("asked", entry.asked.into()),
+        ("right", entry.right.into()),
+    ]);
+    append_line(quiz_path(), line)
+}
+
+///
+/// Every round of the quiz `profile` has played, oldest first.
+///
+pub fn load_quiz(profile: u64) -> io::Result<Vec<QuizEntry>> {
+    Ok(read_lines(quiz_path())?
+        .into_iter()
+        .filter(|x| x.get("profile").and_then(Json::as_usize) == Some(profile as usize))
+        .filter_map(|x| {
+            Some(QuizEntry {
+                pegs: x.get("pegs")?.as_usize()?,
+                asked: x.get("asked")?.as_usize()?,
+                right: x.get("right")?.as_usize()?,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The history in `tests/fixtures`, which has labelled games
+    // for two players, a line from before labels, and a line
+    // which isn't JSON at all.
+    fn fixture() -> Vec<HistoryEntry> {
+        let text = include_str!("../../tests/fixtures/history.jsonl");
+        entries(text.lines().filter_map(|x| Json::parse(x).ok()).collect())
+    }
+
+    #[test]
+    fn every_readable_line_is_loaded() {
+        let entries = fixture();
+        assert_eq!(entries.len(), 5);
+        // Older lines don't have labels, or any of the later fields
+        assert_eq!(entries[3].record.label, None);
+        assert_eq!(entries[3].record.duration, None);
+        assert_eq!(entries[0].record.label.as_deref(), Some("Mum's Birthday"));
+        assert_eq!(entries[2].points, Some(140));
+    }
+
+    #[test]
+    fn labels_are_searched_ignoring_case() {
+        let entries = fixture();
+        let found = entries
+            .iter()
+            .filter(|x| x.label_matches("birthday"))
+            .map(|x| x.record.label.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec!["Mum's Birthday", "birthday rematch", "BIRTHDAY"]
+        );
+        assert!(entries.iter().all(|x| !x.label_matches("easter")));
+        // A game without a label doesn't match even an empty search
+        assert!(!entries[3].label_matches(""));
+    }
+
+    #[test]
+    fn stats_only_count_the_labelled_games_kept() {
+        let mut entries = fixture();
+        entries.retain(|x| x.label_matches("birthday"));
+        let first = Stats::for_profile(&entries, 1);
+        assert_eq!((first.played, first.won), (2, 1));
+        assert_eq!(first.speed_bonus, 573);
+        let second = Stats::for_profile(&entries, 2);
+        assert_eq!((second.played, second.won, second.hardcore), (1, 1, 1));
+        assert_eq!((second.wagered, second.points), (1, 140));
+        assert_eq!(Stats::for_profile(&entries, 3), Stats::default());
+    }
+}
@@ -0,0 +1,87 @@
+//!
+//! Ending a session with Ctrl-C the same way as running out of
+//! input, so that the game in progress is autosaved and the
+//! session's games are still listed on the way out.
+//!
+//! A signal handler can interrupt the program anywhere, even in
+//! the middle of an allocation, so it can't do much more than
+//! flip a flag. Instead of printing anything itself, the handler
+//! swaps stdin for `/dev/null`. The read the game is waiting on
+//! then finds the end of its input, and the session finishes up
+//! like it would at the end of a piped file.
+//!
+//! A second Ctrl-C exits straight away.
+//!
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+///
+/// Whether the session was ended with Ctrl-C.
+///
+pub fn happened() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+pub use unix::catch;
+
+///
+/// There's no `/dev/null` to swap in outside of Unix, so Ctrl-C
+/// is left to end the program the usual way.
+///
+#[cfg(not(unix))]
+pub fn catch() {}
+
+#[cfg(unix)]
+mod unix {
+    use super::INTERRUPTED;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::OnceLock;
+
+    //
+    // Everything the handler needs is made ahead of time, since
+    // it can only look at globals: `/dev/null` already opened,
+    // and how the terminal was set up before any raw mode.
+    //
+    static NULL: AtomicI32 = AtomicI32::new(-1);
+    static TERMINAL: OnceLock<libc::termios> = OnceLock::new();
+
+    extern "C" fn on_interrupt(_: libc::c_int) {
+        // All of these are safe to call from a signal handler
+        unsafe {
+            if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                libc::_exit(130);
+            }
+            // Raw mode puts the terminal back when it's dropped,
+            // but that can't happen once stdin is `/dev/null`.
+            if let Some(terminal) = TERMINAL.get() {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, terminal);
+            }
+            libc::dup2(NULL.load(Ordering::SeqCst), libc::STDIN_FILENO);
+        }
+    }
+
+    ///
+    /// Starts catching Ctrl-C, see the top of this file. If
+    /// `/dev/null` can't be opened, Ctrl-C isn't caught at all.
+    ///
+    pub fn catch() {
+        unsafe {
+            let null = libc::open(b"/dev/null\0".as_ptr().cast(), libc::O_RDONLY);
+            if null < 0 {
+                return;
+            }
+            NULL.store(null, Ordering::SeqCst);
+            let mut terminal = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut terminal) == 0 {
+                let _ = TERMINAL.set(terminal);
+            }
+            libc::signal(
+                libc::SIGINT,
+                on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//!
+//! The native half of `java/Mastermind.java`, built with
+//! `cargo build --features java`. Since this whole project is
+//! a tour of rust for Java programmers, this lets the game be
+//! played from Java too.
+//!
+//! Java finds these functions by name when it loads the
+//! library: a `native` method `newGame` in the class
+//! `Mastermind` is the C function `Java_Mastermind_newGame`.
+//!
+//! Everything Java hands us goes through a `JNIEnv`, which is
+//! a table of function pointers. The `jni` crate wraps it up
+//! in rust types, like `JString` for a `java.lang.String`, and
+//! converts strings to and from the modified UTF-8 Java uses
+//! (Which writes some characters differently from the UTF-8
+//! rust uses).
+//!
+//! Games are kept in a `Box`, and Java holds on to the pointer
+//! as a `long` until it calls `freeGame`. Errors (And panics)
+//! are thrown as Java exceptions instead of crossing back into
+//! Java as a crash. `tests/java.rs` runs the smoke test in
+//! `java/Mastermind.java` against the library.
+//!
+
+use super::{GameConfig, SilentOutput, State};
+use jni::objects::{JClass, JString};
+use jni::sys::{jboolean, jint, jlong, jstring};
+use jni::JNIEnv;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+//
+// Runs `body`, throwing whatever it fails with (Or panics with)
+// as an exception and giving Java `failed` instead.
+//
+fn guarded<'local, T>(
+    env: &mut JNIEnv<'local>,
+    failed: T,
+    body: impl FnOnce(&mut JNIEnv<'local>) -> Result<T, String>,
+) -> T {
+    let result = catch_unwind(AssertUnwindSafe(|| body(env)));
+    let (class, message) = match result {
+        Ok(Ok(x)) => return x,
+        Ok(Err(e)) => ("java/lang/IllegalArgumentException", e),
+        Err(_) => (
+            "java/lang/RuntimeException",
+            "The game panicked".to_string(),
+        ),
+    };
+    // If even throwing fails, Java already has an exception on
+    // its way (Like running out of memory), which will do.
+    let _ = env.throw_new(class, message);
+    failed
+}
+
+///
+/// `static native long newGame(int pegs, boolean duplicates, int maxTries)`,
+/// where `maxTries` is 0 for unlimited tries.
+///
+#[no_mangle]
+pub extern "system" fn Java_Mastermind_newGame(
+    mut env: JNIEnv,
+    _class: JClass,
+    pegs: jint,
+    duplicates: jboolean,
+    max_tries: jint,
+) -> jlong {
+    guarded(&mut env, 0, |_| {
+        let config = GameConfig {
+            pegs: pegs.max(0) as usize,
+            allow_duplicates: duplicates != 0,
+            max_tries: match max_tries {
+                x if x <= 0 => None,
+                x => Some(x as usize),
+            },
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let state = State::new(config, |_| {}, |_| {}, SilentOutput).map_err(|e| e.to_string())?;
+        Ok(Box::into_raw(Box::new(state)) as jlong)
+    })
+}
+
+///
+/// `static native String guess(long game, String code)`, which
+/// gives back the feedback and whether the game is over, as
+/// JSON. The code is the first letter of each colour.
+///
+/// # Safety
+///
+/// Only Java should call this, with a `game` from `newGame`
+/// which hasn't been freed.
+///
+#[no_mangle]
+pub unsafe extern "system" fn Java_Mastermind_guess(
+    mut env: JNIEnv,
+    _class: JClass,
+    game: jlong,
+    code: JString,
+) -> jstring {
+    guarded(&mut env, std::ptr::null_mut(), |env| {
+        let state = (game as *mut State<'static>)
+            .as_mut()
+            .ok_or("The game has been closed")?;
+        if code.is_null() {
+            return Err("The code can't be null".to_string());
+        }
+        let text: String = env.get_string(&code).map_err(|e| e.to_string())?.into();
+        let guess =
+            super::Guess::for_config(super::parse_code(&text, state.config.pegs)?, state.config)
+                .map_err(|e| e.to_string())?;
+        let outcome = state
+            .enter_guess(&guess)
+            .map_err(|e| e.to_string())?
+            .map_or("in_progress", |x| x.name());
+        let feedback = state.last_scored().unwrap_or_default();
+        let json = super::json::Json::object(vec![
+            ("correct_position", feedback.exact.into()),
+            ("correct_colour", feedback.misplaced.into()),
+            ("outcome", outcome.into()),
+        ]);
+        new_string(env, &json.to_string())
+    })
+}
+
+///
+/// `static native String board(long game)`, the summary of the
+/// game being played as JSON (See `GameSummary::to_json`).
+///
+/// # Safety
+///
+/// Only Java should call this, with a `game` from `newGame`
+/// which hasn't been freed.
+///
+#[no_mangle]
+pub unsafe extern "system" fn Java_Mastermind_board(
+    mut env: JNIEnv,
+    _class: JClass,
+    game: jlong,
+) -> jstring {
+    guarded(&mut env, std::ptr::null_mut(), |env| {
+        let state = (game as *const State<'static>)
+            .as_ref()
+            .ok_or("The game has been closed")?;
+        new_string(env, &state.summary().to_json().to_string())
+    })
+}
+
+///
+/// `static native void freeGame(long game)`.
+///
+/// # Safety
+///
+/// Only Java should call this, with a `game` from `newGame`
+/// which isn't used again afterwards (Or 0).
+///
+#[no_mangle]
+pub unsafe extern "system" fn Java_Mastermind_freeGame(
+    mut env: JNIEnv,
+    _class: JClass,
+    game: jlong,
+) {
+    guarded(&mut env, (), |_| {
+        if game != 0 {
+            drop(Box::from_raw(game as *mut State<'static>));
+        }
+        Ok(())
+    })
+}
+
+//
+// Makes a Java string out of a rust one, for giving back to
+// Java (Which is why it's a raw `jstring`).
+//
+fn new_string(env: &mut JNIEnv, text: &str) -> Result<jstring, String> {
+    env.new_string(text)
+        .map(|x| x.into_raw())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,606 @@
+//!
+//! A very small JSON value type, used to hand snapshots of
+//! the game to anything which isn't Rust (A frontend, a file
+//! on disk, a socket, etc.).
+//!
+//! Messages which are only ever written, like the events a
+//! frontend is sent, are built up by hand out of `Json`
+//! values. Anything which is read back in too (Saves, settings
+//! and records) is laid out by its serde derives instead, so
+//! that there is only one description of it, and `from_serde`
+//! and `to_serde` go between those and a `Json`.
+//!
+//! We keep a value of our own rather than using
+//! `serde_json::Value` because objects here keep their keys in
+//! the order they were written, which makes files easier to
+//! read, and because `parse` limits how deep a value can be.
+//!
+
+use serde::de::{self, DeserializeOwned, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+
+///
+/// How many arrays and objects deep a value can be read. Each
+/// level is read by a function calling itself, so without a
+/// limit a line of a few thousand `[`s would use up the whole
+/// stack and crash, rather than being an error. Nothing we
+/// write ever comes close.
+///
+pub const MAX_DEPTH: usize = 64;
+
+///
+/// A JSON value. Each variant maps onto one of the
+/// kinds of value the JSON spec allows.
+///
+/// Objects are stored as a list of pairs instead of a
+/// `HashMap` so that the keys come out in the same order
+/// we put them in, which makes the output easier to read.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    ///
+    /// Reads a value back in from text. The whole string must be
+    /// a single value (Surrounding whitespace is fine).
+    ///
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            position: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position != parser.text.len() {
+            return Err(parser.error("Unexpected text after the value"));
+        }
+        Ok(value)
+    }
+
+    ///
+    /// Looks up `key` if this is an object.
+    ///
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(pairs) => pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Number(x) if *x >= 0 => Some(*x as usize),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Shorthand for building an object out of `(key, value)`
+    /// pairs, for example `Json::object(vec![("a", Json::Null)])`.
+    ///
+    pub fn object(pairs: Vec<(&str, Json)>) -> Self {
+        Json::Object(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    ///
+    /// `value` laid out the way its serde derives say, with the
+    /// fields in the order they're declared in.
+    ///
+    pub fn from_serde<T: Serialize + ?Sized>(value: &T) -> Self {
+        // Through text, because `serde_json::Value` would sort
+        // the keys of every object
+        let text = serde_json::to_string(value).expect("Everything we save can be written");
+        serde_json::from_str(&text).expect("serde_json writes JSON we can read")
+    }
+
+    ///
+    /// The opposite of `from_serde`, giving why if this isn't
+    /// laid out like a `T`.
+    ///
+    pub fn to_serde<T: DeserializeOwned>(&self) -> Result<T, String> {
+        T::deserialize(self).map_err(|e| e.to_string())
+    }
+}
+
+///
+/// Conversions so that we can write `Json::from(3)` or
+/// `some_option.into()` instead of spelling out variants.
+///
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Self {
+        Json::Number(value as i64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value as i64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(Json::Null)
+    }
+}
+
+///
+/// So that a `Json` can be written with any serde format, and
+/// be part of a type which has serde derives.
+///
+impl Serialize for Json {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Json::Null => serializer.serialize_unit(),
+            Json::Bool(x) => serializer.serialize_bool(*x),
+            Json::Number(x) => serializer.serialize_i64(*x),
+            Json::String(x) => serializer.serialize_str(x),
+            Json::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Json::Object(pairs) => {
+                let mut map = serializer.serialize_map(Some(pairs.len()))?;
+                for (key, value) in pairs {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Json {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a JSON value, with only whole numbers")
+    }
+
+    fn visit_unit<E>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Json, D::Error> {
+        Json::deserialize(deserializer)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Json, E> {
+        Ok(Json::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Json, E> {
+        Ok(Json::Number(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Json, E> {
+        i64::try_from(value)
+            .map(Json::Number)
+            .map_err(|_| E::custom(format!("{} is too big a number", value)))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Json, E> {
+        Ok(Json::String(value.to_string()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Json, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Json::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error> {
+        let mut pairs = Vec::new();
+        while let Some(pair) = map.next_entry()? {
+            pairs.push(pair);
+        }
+        Ok(Json::Object(pairs))
+    }
+}
+
+///
+/// A `Json` can also be read from like a file, so that
+/// `to_serde` doesn't have to write it out as text and parse
+/// it again.
+///
+impl<'de> Deserializer<'de> for &'de Json {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Json::Null => visitor.visit_unit(),
+            Json::Bool(x) => visitor.visit_bool(*x),
+            Json::Number(x) => visitor.visit_i64(*x),
+            Json::String(x) => visitor.visit_borrowed_str(x),
+            Json::Array(values) => {
+                visitor.visit_seq(de::value::SeqDeserializer::new(values.iter()))
+            }
+            Json::Object(pairs) => visitor.visit_map(de::value::MapDeserializer::new(
+                pairs.iter().map(|(key, value)| (key.as_str(), value)),
+            )),
+        }
+    }
+
+    // `null` is `None` and anything else is `Some`, like in
+    // `serde_json`
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Json::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    // A newtype (Like `Guess`) is written as what it wraps
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Variants without anything in them (Like colours) are
+    // written as their name, and the rest as an object with the
+    // name as its only key
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            Json::String(name) => {
+                visitor.visit_enum(de::IntoDeserializer::into_deserializer(name.as_str()))
+            }
+            Json::Object(pairs) => visitor.visit_enum(de::value::MapAccessDeserializer::new(
+                de::value::MapDeserializer::new(
+                    pairs.iter().map(|(key, value)| (key.as_str(), value)),
+                ),
+            )),
+            _ => Err(de::Error::custom("expected a name, or an object with one")),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> de::IntoDeserializer<'de> for &'de Json {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+///
+/// Writing a `Json` value out as text. This is what you get
+/// when you `println!("{}", value)` or call `.to_string()`.
+///
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(x) => write!(f, "{}", x),
+            Json::Number(x) => write!(f, "{}", x),
+            Json::String(x) => write_string(f, x),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(pairs) => {
+                write!(f, "{{")?;
+                for (idx, (key, value)) in pairs.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+//
+// Strings need their quotes and control characters escaped.
+//
+fn write_string(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+///
+/// Walks over the text one byte at a time. Everything JSON
+/// uses for its structure is plain ASCII, so we only need to
+/// think about UTF-8 inside of strings.
+///
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+    ///
+    /// How many arrays and objects we're inside of.
+    ///
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at byte {}", message, self.position)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\n') | Some(b'\r') | Some(b'\t') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        if self.text[self.position..].starts_with(expected.as_bytes()) {
+            self.position += expected.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected `{}`", expected)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(x @ b'[') | Some(x @ b'{') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("Nested too deeply"));
+                }
+                self.depth += 1;
+                let value = if x == b'[' {
+                    self.array()
+                } else {
+                    self.object()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("Unexpected character")),
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect("[")?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("Expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect("{")?;
+        let mut pairs = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Json::Object(pairs));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            pairs.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(pairs));
+                }
+                _ => return Err(self.error("Expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.position += 1;
+        }
+        // We only ever write whole numbers, so that's all we read.
+        std::str::from_utf8(&self.text[start..self.position])
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .map(Json::Number)
+            .ok_or_else(|| self.error("Invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect("\"")?;
+        let mut bytes = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("Unterminated string")),
+                Some(b'"') => {
+                    self.position += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.position += 1;
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'u') => {
+                            let digits = self
+                                .text
+                                .get(self.position + 1..self.position + 5)
+                                .and_then(|x| std::str::from_utf8(x).ok())
+                                .and_then(|x| u32::from_str_radix(x, 16).ok())
+                                .and_then(std::char::from_u32)
+                                .ok_or_else(|| self.error("Invalid unicode escape"))?;
+                            self.position += 4;
+                            digits
+                        }
+                        _ => return Err(self.error("Invalid escape")),
+                    };
+                    self.position += 1;
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut buffer).as_bytes());
+                }
+                Some(x) => {
+                    self.position += 1;
+                    bytes.push(x);
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("Invalid UTF-8 in string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deep_nesting_is_an_error_rather_than_a_crash() {
+        let deep = "[".repeat(4000);
+        assert!(Json::parse(&deep)
+            .unwrap_err()
+            .contains("Nested too deeply"));
+        let deep = "{\"a\":".repeat(4000);
+        assert!(Json::parse(&deep)
+            .unwrap_err()
+            .contains("Nested too deeply"));
+    }
+
+    #[test]
+    fn nesting_up_to_the_limit_is_fine() {
+        let text = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        let value = Json::parse(&text).unwrap();
+        assert_eq!(value.to_string(), text);
+        let text = format!("[{}{}]", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(Json::parse(&text).is_err());
+    }
+}
@@ -0,0 +1,611 @@
+//!
+//! `mastermind host --socket PATH` and `mastermind join --socket PATH`,
+//! for two players on the same machine (Say, in two terminals).
+//! The host makes up the code and watches, and whoever joins
+//! guesses it. They talk over a Unix domain socket, which is a
+//! file standing in for a network address, so there are no
+//! ports involved.
+//!
+//! `join` can also connect to a `mastermind serve` with
+//! `--address HOST:PORT` instead, and ask for settings of its
+//! own with `--pegs`, `--duplicates` and `--max-tries`.
+//!
+
+use super::protocol::{self, Message};
+use super::settings::SettingsLayer;
+use super::{max_tries_flag, parse_code, Feedback};
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+//
+// `host` only takes `--socket PATH`.
+//
+fn socket_path(mut args: impl Iterator<Item = String>) -> Result<PathBuf, Box<dyn Error>> {
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--socket" => path = Some(args.next().ok_or("`--socket` needs a path")?),
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    Ok(PathBuf::from(
+        path.ok_or("Pick a socket with `--socket PATH`")?,
+    ))
+}
+
+#[cfg(unix)]
+pub fn host(args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    unix::host(socket_path(args)?)
+}
+
+//
+// Windows has sockets like these as well, but not in the
+// standard library, so for now this just says so.
+//
+#[cfg(not(unix))]
+pub fn host(args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    socket_path(args)?;
+    Err("Hosting over a socket file is only supported on Unix-like systems for now".into())
+}
+
+pub fn join(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut path = None;
+    let mut address = None;
+    let mut wanted = SettingsLayer::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--socket" => path = Some(PathBuf::from(args.next().ok_or("`--socket` needs a path")?)),
+            "--address" => address = Some(args.next().ok_or("`--address` needs HOST:PORT")?),
+            "--pegs" => {
+                wanted.pegs = Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--pegs` needs a number")?,
+                )
+            }
+            "--duplicates" => {
+                wanted.allow_duplicates = Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--duplicates` needs `true` or `false`")?,
+                )
+            }
+            "--max-tries" => {
+                let tries = args.next().ok_or("`--max-tries` needs a number")?;
+                max_tries_flag(&mut wanted, &tries)?;
+            }
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    match (path, address) {
+        (_, Some(address)) => play_remote(
+            || {
+                let stream = TcpStream::connect(&address)
+                    .map_err(|e| format!("Could not connect to {}: {}", address, e))?;
+                Ok((BufReader::new(stream.try_clone()?), stream))
+            },
+            wanted,
+            &mut io::stdin().lock(),
+        ),
+        #[cfg(unix)]
+        (Some(path), None) => play_remote(
+            || {
+                let stream = unix::connect(&path)?;
+                Ok((BufReader::new(stream.try_clone()?), stream))
+            },
+            wanted,
+            &mut io::stdin().lock(),
+        ),
+        #[cfg(not(unix))]
+        (Some(_), None) => {
+            Err("Joining over a socket file is only supported on Unix-like systems for now".into())
+        }
+        (None, None) => Err("Pick a game with `--socket PATH` or `--address HOST:PORT`".into()),
+    }
+}
+
+fn vanished() -> Box<dyn Error> {
+    "The other player left the game".into()
+}
+
+//
+// How many times `join` tries to get back into a game after
+// the connection drops, and how long it waits before each try.
+//
+const RECONNECT_ATTEMPTS: usize = 10;
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+///
+/// One end of a connection to a host, which remembers the token
+/// the host gave it (If any) for getting back into the game.
+///
+struct Connection<R, W> {
+    reader: R,
+    writer: W,
+    token: Option<String>,
+}
+
+impl<R: BufRead, W: Write> Connection<R, W> {
+    //
+    // The next message, or `None` if the connection has dropped.
+    //
+    fn receive(&mut self) -> Result<Option<Message>, Box<dyn Error>> {
+        loop {
+            match protocol::receive(&mut self.reader) {
+                Ok(Some(Message::Token(token))) => self.token = Some(token),
+                Ok(x) => return Ok(x),
+                Err(e) if dropped(&e) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    //
+    // Sends `message`, giving back `false` if the connection has
+    // dropped.
+    //
+    fn send(&mut self, message: &Message) -> Result<bool, Box<dyn Error>> {
+        match protocol::send(&mut self.writer, message) {
+            Ok(()) => Ok(true),
+            Err(e) if dropped(&e) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn dropped(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+//
+// Guesses at the game on the other end of whatever `connect`
+// connects to, with the guesses typed into `input`, until it's
+// over. If the host handed out a token, a dropped connection is
+// made again and the game carries on.
+//
+fn play_remote<R: BufRead, W: Write>(
+    connect: impl Fn() -> Result<(R, W), Box<dyn Error>>,
+    wanted: SettingsLayer,
+    input: &mut impl BufRead,
+) -> Result<(), Box<dyn Error>> {
+    let (reader, writer) = connect()?;
+    let mut connection = Connection {
+        reader,
+        writer,
+        token: None,
+    };
+    let mut config = match connection.receive()?.ok_or_else(vanished)? {
+        Message::Hello(x) => x,
+        Message::Rejected(reason) => return Err(reason.into()),
+        _ => return Err("The host didn't say hello".into()),
+    };
+    if wanted != SettingsLayer::default() {
+        connection.send(&Message::Settings(wanted.apply(config)))?;
+        match connection.receive()?.ok_or_else(vanished)? {
+            Message::Hello(x) => config = x,
+            Message::Rejected(reason) => println!("Playing with the host's settings: {}", reason),
+            _ => return Err("The host sent something unexpected".into()),
+        }
+    }
+    println!(
+        "Joined! The code has {} pegs, duplicates {}",
+        config.pegs,
+        if config.allow_duplicates {
+            "allowed"
+        } else {
+            "not allowed"
+        }
+    );
+    if let Some(announcement) = config.palette_announcement() {
+        println!("{}", announcement);
+    }
+    if let Some(announcement) = config.colour_announcement() {
+        println!("{}", announcement);
+    }
+    while !guess_until_over(&mut connection, config.pegs, input)? {
+        let token = connection.token.take().ok_or_else(vanished)?;
+        println!("Lost the connection, trying to get back into the game...");
+        connection = reconnect(&connect, token)?;
+    }
+    Ok(())
+}
+
+//
+// Gives back `true` once the game is over (Or there's nothing
+// left to read), or `false` if the connection dropped first.
+//
+fn guess_until_over<R: BufRead, W: Write>(
+    connection: &mut Connection<R, W>,
+    pegs: usize,
+    input: &mut impl BufRead,
+) -> Result<bool, Box<dyn Error>> {
+    let mut line = String::new();
+    loop {
+        print!("Enter your guess > ");
+        io::stdout().flush()?;
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(true);
+        }
+        let guess = match parse_code(line.trim(), pegs) {
+            Ok(x) => x,
+            Err(e) => {
+                println!("Please try again! {}", e);
+                continue;
+            }
+        };
+        if !connection.send(&Message::Guess(guess))? {
+            return Ok(false);
+        }
+        match connection.receive()? {
+            None => return Ok(false),
+            Some(Message::Feedback { exact, colour }) => println!(
+                "{} in the correct position and {} with the right colour",
+                exact, colour
+            ),
+            Some(Message::Rejected(reason)) => println!("Please try again! {}", reason),
+            Some(Message::SlowDown(wait)) => println!(
+                "Slow down! The host takes the next guess in {:.1}s",
+                wait.as_secs_f64()
+            ),
+            Some(Message::GameOver { outcome, secret }) => {
+                println!("You {} the game! The code was {:?}", outcome.name(), secret);
+                return Ok(true);
+            }
+            Some(_) => return Err("The host sent something unexpected".into()),
+        }
+    }
+}
+
+//
+// Connects again and asks for the game `token` was given for,
+// showing the board as the host remembers it. A guess which was
+// on its way when the connection dropped may or may not be on
+// it.
+//
+fn reconnect<R: BufRead, W: Write>(
+    connect: &impl Fn() -> Result<(R, W), Box<dyn Error>>,
+    token: String,
+) -> Result<Connection<R, W>, Box<dyn Error>> {
+    for _ in 0..RECONNECT_ATTEMPTS {
+        thread::sleep(RECONNECT_DELAY);
+        let (reader, writer) = match connect() {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        // The new connection is given a game (And a token) of its
+        // own first, which resuming replaces.
+        let mut connection = Connection {
+            reader,
+            writer,
+            token: None,
+        };
+        match connection.receive()? {
+            Some(Message::Hello(_)) => {}
+            Some(Message::Rejected(reason)) => {
+                println!("{}", reason);
+                continue;
+            }
+            _ => continue,
+        }
+        if !connection.send(&Message::Resume(token.clone()))? {
+            continue;
+        }
+        match connection.receive()? {
+            Some(Message::Resumed { board, .. }) => {
+                println!("Back in the game! Your guesses so far:");
+                for (guess, Feedback { exact, misplaced }) in board {
+                    println!(
+                        "{:?}: {} in the correct position and {} with the right colour",
+                        guess, exact, misplaced
+                    );
+                }
+                connection.token = Some(token);
+                return Ok(connection);
+            }
+            Some(Message::Rejected(reason)) => {
+                return Err(format!("Couldn't get back into the game: {}", reason).into())
+            }
+            _ => continue,
+        }
+    }
+    Err("Couldn't get back into the game, the host can't be reached".into())
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::super::protocol::{self, Message};
+    use super::super::settings::{self, SettingsLayer};
+    use super::super::{Feedback, Guess, Outcome, SilentOutput, State};
+    use super::vanished;
+    use std::error::Error;
+    use std::ffi::CString;
+    use std::io::{self, BufReader};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicPtr, Ordering};
+
+    ///
+    /// The socket file, which is deleted when this is dropped so
+    /// that it doesn't get in the way of the next game.
+    ///
+    struct SocketFile(PathBuf);
+
+    impl Drop for SocketFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    //
+    // The path to delete if the host is interrupted with Ctrl-C,
+    // which skips every `Drop`. The signal handler can only look
+    // at globals, and can't allocate, so it's kept as a C string
+    // made ahead of time.
+    //
+    static INTERRUPT_PATH: AtomicPtr<libc::c_char> = AtomicPtr::new(std::ptr::null_mut());
+
+    extern "C" fn on_interrupt(_: libc::c_int) {
+        let path = INTERRUPT_PATH.load(Ordering::SeqCst);
+        // Both of these are safe to call from a signal handler
+        unsafe {
+            if !path.is_null() {
+                libc::unlink(path);
+            }
+            libc::_exit(130);
+        }
+    }
+
+    fn delete_on_interrupt(path: &Path) {
+        if let Ok(path) = CString::new(path.as_os_str().as_bytes()) {
+            // This is never freed, the handler needs it until exit
+            INTERRUPT_PATH.store(path.into_raw(), Ordering::SeqCst);
+            unsafe {
+                libc::signal(
+                    libc::SIGINT,
+                    on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+                );
+            }
+        }
+    }
+
+    //
+    // Binds the socket, clearing out one left behind by a host
+    // which didn't get to clean up after itself.
+    //
+    fn listen(path: &Path) -> Result<UnixListener, Box<dyn Error>> {
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(format!("A game is already being hosted at {}", path.display()).into());
+            }
+            if std::fs::symlink_metadata(path)?.file_type().is_dir() {
+                return Err(format!("{} is a folder, pick another path", path.display()).into());
+            }
+            println!(
+                "Removing {}, left over from an earlier game",
+                path.display()
+            );
+            std::fs::remove_file(path)?;
+        }
+        Ok(UnixListener::bind(path)?)
+    }
+
+    pub fn host(path: PathBuf) -> Result<(), Box<dyn Error>> {
+        // Like a relay, the game uses the settings from the
+        // config file, and is only one game long.
+        let config = settings::resolve(
+            settings::load_config_file()?,
+            None,
+            SettingsLayer::default(),
+        );
+        let state = State::new(config, |_| {}, |_| {}, SilentOutput)?;
+        let listener = listen(&path)?;
+        let _file = SocketFile(path.clone());
+        delete_on_interrupt(&path);
+        println!(
+            "Waiting for someone to run `mastermind join --socket {}`...",
+            path.display()
+        );
+        play_host(&listener, state)?;
+        Ok(())
+    }
+
+    //
+    // Plays the game in `state` with the first player to connect
+    // to `listener`, giving back how it went for them.
+    //
+    fn play_host(listener: &UnixListener, mut state: State) -> Result<Outcome, Box<dyn Error>> {
+        let config = state.config;
+        // Whoever connects first plays, unless they hang up before
+        // saying anything (Which is also how another host checks
+        // if this socket is in use).
+        let (mut writer, mut reader, mut first) = loop {
+            let (stream, _) = listener.accept()?;
+            let mut writer = stream.try_clone()?;
+            let mut reader = BufReader::new(stream);
+            if protocol::send(&mut writer, &Message::Hello(config)).is_err() {
+                continue;
+            }
+            if let Some(first) = protocol::receive(&mut reader)? {
+                break (writer, reader, Some(first));
+            }
+        };
+        println!("They're here! The code is {:?}", state.reveal_secret());
+        loop {
+            let message = match first.take() {
+                Some(x) => x,
+                None => protocol::receive(&mut reader)?.ok_or_else(vanished)?,
+            };
+            let guess = match message {
+                Message::Guess(x) => x,
+                Message::Settings(_) => {
+                    let reason = "The host picks the settings".into();
+                    protocol::send(&mut writer, &Message::Rejected(reason))?;
+                    continue;
+                }
+                _ => {
+                    protocol::send(&mut writer, &Message::Rejected("Expected a guess".into()))?;
+                    continue;
+                }
+            };
+            let checked = Guess::for_config(guess.clone(), state.config).map_err(From::from);
+            let reply = match checked.and_then(|guess| state.enter_guess(&guess)) {
+                Err(e) => Message::Rejected(e.to_string()),
+                Ok(Some(outcome)) => Message::GameOver {
+                    outcome,
+                    secret: state.previous_games[0].secret.clone(),
+                },
+                Ok(None) => {
+                    let Feedback { exact, misplaced } = state.last_scored().unwrap_or_default();
+                    Message::Feedback {
+                        exact,
+                        colour: misplaced,
+                    }
+                }
+            };
+            match &reply {
+                Message::Feedback { exact, colour } => println!(
+                    "They guessed {:?}: {} in the correct position and {} with the right colour",
+                    guess, exact, colour
+                ),
+                Message::GameOver { outcome, .. } => {
+                    println!("They guessed {:?}, and {} the game", guess, outcome.name())
+                }
+                _ => {}
+            }
+            protocol::send(&mut writer, &reply)?;
+            if let Message::GameOver { outcome, .. } = reply {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    pub fn connect(path: &Path) -> Result<UnixStream, Box<dyn Error>> {
+        Ok(UnixStream::connect(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused => {
+                format!("Nobody is hosting a game at {}", path.display())
+            }
+            _ => e.to_string(),
+        })?)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::super::super::{Colour, StateBuilder};
+        use super::super::play_remote;
+        use super::*;
+        use std::thread;
+
+        // A socket path of its own for each test, which is
+        // deleted again when it's dropped.
+        fn socket(name: &str) -> SocketFile {
+            let name = format!("mastermind-{}-{}.sock", std::process::id(), name);
+            let path = std::env::temp_dir().join(name);
+            let _ = std::fs::remove_file(&path);
+            SocketFile(path)
+        }
+
+        fn seeded() -> State<'static> {
+            StateBuilder::new().seed(11).build().unwrap()
+        }
+
+        fn typed(code: &[Colour]) -> String {
+            let names: Vec<_> = code.iter().map(|x| x.name()).collect();
+            names.join(" ")
+        }
+
+        #[test]
+        fn a_seeded_game_is_played_over_a_socket() {
+            let file = socket("game");
+            let listener = listen(&file.0).unwrap();
+            // `State` can't be sent to another thread, so the host
+            // makes the same seeded game for itself
+            let host =
+                thread::spawn(move || play_host(&listener, seeded()).map_err(|e| e.to_string()));
+            let secret = seeded().reveal_secret().to_vec();
+            let mut wrong = secret.clone();
+            wrong.rotate_left(1);
+            let typing = format!("not a guess\n{}\n{}\n", typed(&wrong), typed(&secret));
+            let path = file.0.clone();
+            play_remote(
+                || {
+                    let stream = connect(&path)?;
+                    Ok((BufReader::new(stream.try_clone()?), stream))
+                },
+                SettingsLayer::default(),
+                &mut typing.as_bytes(),
+            )
+            .unwrap();
+            assert_eq!(host.join().unwrap(), Ok(Outcome::Won));
+        }
+
+        #[test]
+        fn a_socket_in_use_is_not_taken_over() {
+            let file = socket("in-use");
+            let _listener = listen(&file.0).unwrap();
+            let error = listen(&file.0).unwrap_err().to_string();
+            assert!(error.contains("already being hosted"), "{}", error);
+        }
+
+        #[test]
+        fn a_socket_left_behind_is_replaced() {
+            let file = socket("stale");
+            // Dropping the listener leaves the file, but nothing
+            // is listening to it any more
+            drop(UnixListener::bind(&file.0).unwrap());
+            assert!(file.0.exists());
+            let _listener = listen(&file.0).unwrap();
+            assert!(UnixStream::connect(&file.0).is_ok());
+        }
+
+        #[test]
+        fn the_socket_file_is_removed_after_the_game() {
+            let file = socket("removed");
+            let path = file.0.clone();
+            let _listener = listen(&path).unwrap();
+            drop(file);
+            assert!(!path.exists());
+        }
+
+        #[test]
+        fn joining_nobody_says_so() {
+            let file = socket("nobody");
+            let error = connect(&file.0).unwrap_err().to_string();
+            assert!(error.starts_with("Nobody is hosting"), "{}", error);
+        }
+
+        #[test]
+        fn a_host_whose_player_leaves_says_so() {
+            let file = socket("left");
+            let listener = listen(&file.0).unwrap();
+            let host =
+                thread::spawn(move || play_host(&listener, seeded()).map_err(|e| e.to_string()));
+            let stream = connect(&file.0).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            assert!(matches!(
+                protocol::receive(&mut reader).unwrap(),
+                Some(Message::Hello(_))
+            ));
+            protocol::send(&mut writer, &Message::Stats).unwrap();
+            assert!(matches!(
+                protocol::receive(&mut reader).unwrap(),
+                Some(Message::Rejected(_))
+            ));
+            drop((reader, writer));
+            assert_eq!(host.join().unwrap(), Err(vanished().to_string()));
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//!
+//! A menu for picking the settings of a new game with the arrow
+//! keys, used instead of the setup questions when we're talking
+//! to a terminal.
+//!
+//! Every change is checked with `GameConfig::validate` as it's
+//! made, so the menu can never end up with settings which
+//! can't be played.
+//!
+
+use super::terminal::{self, Key, RawMode};
+use super::{GameConfig, MIN_PEGS, PALETTE_SIZE};
+use std::io::{self, Write};
+
+///
+/// The rows of the menu, in order.
+///
+const ROWS: [&str; 16] = [
+    "Pegs",
+    "Duplicates",
+    "Tries",
+    "Colours in code",
+    "Fewest colours",
+    "Most of a colour",
+    "Reveal every",
+    "Hardcore",
+    "Memory",
+    "Block repeats",
+    "Strict input",
+    "Confirm guesses",
+    "Hint points",
+    "Fresh secrets",
+    "Blanks",
+    "Colours in play",
+];
+
+///
+/// Lets the player change `defaults` with the arrow keys. This
+/// gives back `None` if the terminal can't do that, or if the
+/// player pressed escape to answer the questions instead.
+///
+pub fn choose_settings(defaults: GameConfig) -> io::Result<Option<GameConfig>> {
+    if !terminal::echoes_input() {
+        return Ok(None);
+    }
+    let _raw = match RawMode::enable() {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let mut config = defaults;
+    let mut selected = 0;
+    let mut message = String::new();
+    let mut first = true;
+    loop {
+        draw(&config, selected, &message, first)?;
+        first = false;
+        message.clear();
+        let key = match terminal::read_key_event()? {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        match key {
+            Key::Up => selected = (selected + ROWS.len() - 1) % ROWS.len(),
+            Key::Down => selected = (selected + 1) % ROWS.len(),
+            Key::Left | Key::Right => {
+                let up = key == Key::Right;
+                let mut changed = adjust(config, selected, up);
+                // Skip over values which can't be played with the other
+                // settings, like too few colours for the number of pegs.
+                // There are never more than a couple dozen values.
+                for _ in 0..25 {
+                    let next = adjust(changed, selected, up);
+                    if changed.validate().is_ok() || next == changed {
+                        break;
+                    }
+                    changed = next;
+                }
+                // Only keep the change if it's playable
+                match changed.validate() {
+                    Ok(()) => config = changed,
+                    Err(e) => message = e.to_string(),
+                }
+            }
+            Key::Enter => {
+                println!();
+                return Ok(Some(config));
+            }
+            Key::Escape => {
+                println!();
+                return Ok(None);
+            }
+            Key::Char(_) => {}
+        }
+    }
+}
+
+//
+// Draws the menu, on top of the last one unless it's the first.
+//
+fn draw(config: &GameConfig, selected: usize, message: &str, first: bool) -> io::Result<()> {
+    if !first {
+        // Move back up to the first row, since we're in raw mode
+        // nothing the player types has moved the cursor.
+        print!("\x1b[{}A", ROWS.len() + 2);
+    }
+    let on_off = |x: bool| if x { "on" } else { "off" }.to_string();
+    let limit = |x: Option<usize>, none: &str| x.map_or(none.to_string(), |x| x.to_string());
+    let values = [
+        config.pegs.to_string(),
+        if config.allow_duplicates { "yes" } else { "no" }.to_string(),
+        if config.auto_max_tries {
+            format!("{} (fair)", limit(config.max_tries, "unlimited"))
+        } else {
+            limit(config.max_tries, "unlimited")
+        },
+        limit(config.max_distinct_colours, "all"),
+        limit(config.min_distinct_colours, "any"),
+        limit(config.max_per_colour, "no limit"),
+        limit(config.reveal_every, "off"),
+        on_off(config.hardcore),
+        on_off(config.memory),
+        on_off(config.block_repeats),
+        on_off(config.strict_input),
+        on_off(config.confirm_guesses),
+        limit(Some(config.hint_points).filter(|&x| x > 0), "off"),
+        on_off(config.fresh_secrets),
+        on_off(config.allow_blanks),
+        config.palette_size.unwrap_or(PALETTE_SIZE).to_string(),
+    ];
+    for (idx, (name, value)) in ROWS.iter().zip(&values).enumerate() {
+        let marker = if idx == selected { ">" } else { " " };
+        // `\r` goes back to the start of the line, and `2K` clears
+        // it. Raw mode doesn't turn `\n` into `\r\n` for us.
+        print!("\r\x1b[2K{} {:<16} < {} >\r\n", marker, name, value);
+    }
+    print!("\r\x1b[2K{}\r\n", message);
+    print!(
+        "\r\x1b[2KArrows pick and change settings, enter starts, escape asks questions instead\r\n"
+    );
+    io::stdout().flush()
+}
+
+//
+// Moves the setting on row `row` one step up or down.
+//
+fn adjust(mut config: GameConfig, row: usize, up: bool) -> GameConfig {
+    let step = |x: usize, low: usize, high: usize| {
+        if up {
+            (x + 1).min(high)
+        } else {
+            x.saturating_sub(1).max(low)
+        }
+    };
+    // For settings which can be off: `None` sits past the top for
+    // tries (Unlimited is more than any number), and below the
+    // bottom for everything else.
+    let optional = |x: Option<usize>, high: usize, none_is_high: bool| match (x, up) {
+        (None, true) if none_is_high => None,
+        (None, false) if none_is_high => Some(high),
+        (None, true) => Some(1),
+        (None, false) => None,
+        (Some(x), true) if x == high && none_is_high => None,
+        (Some(1), false) if !none_is_high => None,
+        (Some(x), _) => Some(step(x, 1, high)),
+    };
+    match row {
+        0 => config.pegs = step(config.pegs, MIN_PEGS, config.palette().len()),
+        1 => config.allow_duplicates = !config.allow_duplicates,
+        // Picking a limit by hand means it's no longer worked
+        // out for the other settings
+        2 => {
+            config.max_tries = optional(config.max_tries, 20, true);
+            config.auto_max_tries = false;
+        }
+        3 => {
+            config.max_distinct_colours = optional(config.max_distinct_colours, PALETTE_SIZE, false)
+        }
+        4 => {
+            config.min_distinct_colours = optional(config.min_distinct_colours, config.pegs, false)
+        }
+        // No limit sits past the top, a limit of every peg is
+        // the same as none
+        5 => config.max_per_colour = optional(config.max_per_colour, config.pegs - 1, true),
+        6 => config.reveal_every = optional(config.reveal_every, 10, false),
+        7 => config.hardcore = !config.hardcore,
+        8 => config.memory = !config.memory,
+        9 => config.block_repeats = !config.block_repeats,
+        10 => config.strict_input = !config.strict_input,
+        11 => config.confirm_guesses = !config.confirm_guesses,
+        12 => config.hint_points = step(config.hint_points, 0, 20),
+        13 => config.fresh_secrets = !config.fresh_secrets,
+        14 => {
+            config.allow_blanks = !config.allow_blanks;
+            // There's one less peg to go round without blanks
+            config.pegs = config.pegs.min(config.palette().len());
+        }
+        // Every colour in play is the same as no limit
+        _ => {
+            let colours = step(
+                config.palette_size.unwrap_or(PALETTE_SIZE),
+                MIN_PEGS,
+                PALETTE_SIZE,
+            );
+            config.palette_size = Some(colours).filter(|&x| x < PALETTE_SIZE);
+            config.pegs = config.pegs.min(config.palette().len());
+        }
+    }
+    config.with_auto_tries()
+}
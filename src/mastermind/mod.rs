@@ -10,32 +10,285 @@
 /// implement them. In other words, they must be in
 /// scope to be usable.
 ///
-use rand::Rng;
-use std::io::Write;
+use rand::distributions::WeightedIndex;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 ///
-/// An enumeration. This lists the colours we can use
+/// The colours and scoring are the rules themselves, which are
+/// in `rules.rs` at the top of the crate so that they build
+/// without `std`. They're brought in here so that everything
+/// can keep using them as if they were declared in this file.
 ///
-/// In this case, this is similar to a Java enum, in
-/// that there is no attached data to each variant.
+pub use crate::rules::{
+    score_guess, Colour, Feedback, Game, GameError, Outcome, CLASSIC_PALETTE_SIZE, COLOURS,
+    MIN_PEGS, PALETTE_SIZE,
+};
+
 ///
-/// An enum is a fully qualified type, meaning we can
-/// implement traits for it, which in this case include
-/// `Clone` (Cloneable), `Copy` (Can copy bitwise),
-/// `Debug` (Displayable), `PartialEq` (`==` operator).
-/// We implement these using a shorthand for auto code
-/// generation called `derive`. It's pretty common in
-/// rust.
+/// Our hand written JSON support, see `json.rs` in this folder.
 ///
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Colour {
-    Red,
-    Orange,
-    Blue,
-    White,
-    Yellow,
-    Green,
-}
+pub mod json;
+use json::Json;
+
+///
+/// Saving and loading games, see `save.rs`.
+///
+pub mod save;
+pub use save::StateSnapshot;
+
+///
+/// The compact binary format for saves, see `binary.rs`. This is
+/// only built with `--features binary`, which is on by default.
+///
+#[cfg(feature = "binary")]
+pub mod binary;
+
+///
+/// Where files such as autosaves are kept, see `storage.rs`.
+///
+pub mod storage;
+
+///
+/// Player profiles, see `profile.rs`.
+///
+pub mod profile;
+
+///
+/// The record of every finished game, see `history.rs`.
+///
+pub mod history;
+
+///
+/// Where the defaults for a new game come from, see
+/// `settings.rs`.
+///
+pub mod settings;
+use settings::SettingsLayer;
+
+///
+/// How feedback is put into words, see `wording.rs`.
+///
+pub mod wording;
+use wording::FeedbackStyle;
+
+///
+/// Betting points on guesses, see `wager.rs`.
+///
+pub mod wager;
+
+///
+/// Bonus points for winning quickly, see `speed.rs`.
+///
+pub mod speed;
+
+///
+/// Hints which cost points from a budget, see `hints.rs`.
+///
+pub mod hints;
+use hints::{Hint, HintAnswer};
+
+pub mod guess;
+pub use guess::{Guess, GuessError};
+
+///
+/// What pegs can be, other than colours, see `symbol.rs`.
+///
+pub mod symbol;
+pub use symbol::Symbol;
+
+///
+/// Playing with digits instead of colours, see `bulls.rs`.
+///
+pub mod bulls;
+pub use bulls::Digit;
+
+///
+/// What can go wrong entering a guess, see `error.rs`.
+///
+pub mod error;
+pub use error::{ConfigError, MastermindError};
+
+///
+/// Setting up a `State` one setting at a time, see `builder.rs`.
+///
+pub mod builder;
+pub use builder::StateBuilder;
+
+///
+/// Where `State` sends what it has to say, see `output.rs`.
+///
+pub mod output;
+pub use output::{GameOutput, SilentOutput, TerminalOutput};
+
+///
+/// Hearing about every guess and game, see `observer.rs`.
+///
+pub mod observer;
+pub use observer::GameObserver;
+
+///
+/// Playing a game on a thread of its own, see `channel.rs`.
+///
+pub mod channel;
+pub use channel::{ChannelEvent, GameThread};
+
+///
+/// Working out a fair number of tries, see `fair.rs`.
+///
+pub mod fair;
+
+///
+/// A computer player, see `solver.rs`.
+///
+pub mod solver;
+
+///
+/// Going through every code for a palette, see `codes.rs`.
+///
+pub mod codes;
+pub use codes::{all_codes, code_space_size, AllCodes, Code};
+
+///
+/// Codes packed into a `u32` for the solver, see `packed.rs`.
+///
+pub mod packed;
+pub use packed::PackedCode;
+
+///
+/// Plugging in something else to make the guesses, see
+/// `guesser.rs`.
+///
+pub mod guesser;
+pub use guesser::{play_with_guesser, Guesser, RandomGuesser};
+
+///
+/// Whoever answers the guesses, see `codemaker.rs`.
+///
+pub mod codemaker;
+pub use codemaker::{AdversarialCodeMaker, CodeMaker, RandomCodeMaker};
+
+///
+/// Remembering feedback for the solver, see `cache.rs`.
+///
+pub mod cache;
+
+///
+/// Playing as a team against the solver, see `relay.rs`.
+///
+mod relay;
+
+///
+/// The guided first game, see `tutorial.rs`.
+///
+mod tutorial;
+
+///
+/// The game which plays itself, see `demo.rs`.
+///
+mod demo;
+
+///
+/// Picking settings with the arrow keys, see `menu.rs`.
+///
+mod menu;
+
+///
+/// Guesses and their feedback laid out as a table, see
+/// `board.rs`.
+///
+pub mod board;
+pub use board::{format_history, BoardFormatter};
+
+///
+/// What the game needs from whoever is playing it, and the
+/// loop which plays a session through it, see `frontend.rs`.
+///
+pub mod frontend;
+
+///
+/// How a session is shown, see `render.rs`.
+///
+pub mod render;
+
+///
+/// The solver playing sessions by itself, see `simulate.rs`.
+///
+mod simulate;
+
+///
+/// Checking that secrets come out evenly, see `audit.rs`.
+///
+mod audit;
+
+///
+/// Practice at working out feedback, see `quiz.rs`.
+///
+mod quiz;
+
+///
+/// The messages sent between two copies of the game, see
+/// `protocol.rs`.
+///
+pub mod protocol;
+
+///
+/// Playing against someone on the same machine, see `local.rs`.
+///
+mod local;
+
+///
+/// Hosting games for many players at once over TCP, see
+/// `serve.rs`.
+///
+mod serve;
+
+///
+/// Asking a question until the answer makes sense, see
+/// `prompt.rs`.
+///
+mod prompt;
+
+///
+/// The C interface for embedding the game, see `ffi.rs`. This
+/// is only built with `--features ffi`.
+///
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+///
+/// The native methods of `java/Mastermind.java`, see `java.rs`.
+/// This is only built with `--features java`.
+///
+#[cfg(feature = "java")]
+pub mod java;
+
+///
+/// Clearing the screen, see `terminal.rs`.
+///
+pub mod terminal;
+
+///
+/// Commands other than playing, like `mastermind stats`, see
+/// `commands.rs`.
+///
+mod commands;
+
+///
+/// Winding the session down on Ctrl-C, see `interrupt.rs`.
+///
+mod interrupt;
+
+///
+/// What has to hold for every code, see `properties.rs`.
+///
+#[cfg(test)]
+mod properties;
 
 ///
 /// We implement the `str::FromStr` trait to be able
@@ -60,14 +313,13 @@ enum Colour {
 ///     // Foo methods here.
 /// }
 /// ```
-///
 impl std::str::FromStr for Colour {
     ///
     /// This is the error type we return when we get an error.
     /// This is called an associated type, it's named by the
     /// trait.
     ///
-    type Err = String;
+    type Err = MastermindError;
     ///
     /// We take a string (`&str`) and spit out a `Result` which
     /// is either `Ok()` or `Err()`. This is error handling in rust.
@@ -93,436 +345,4578 @@ impl std::str::FromStr for Colour {
         let first: char = text
             .chars()
             .next()
-            .ok_or::<Self::Err>("Input too short!".into())?
+            .ok_or(MastermindError::InputTooShort)?
             // Make it lowercase
             .to_ascii_lowercase();
         //
         // Rust's superpower `switch` statement.
         //
+        // Purple and pink both start with a `p`, so for those the
+        // second letter is needed too.
+        //
         match first {
+            'p' => match text.chars().nth(1).map(|x| x.to_ascii_lowercase()) {
+                Some('u') => Ok(Purple),
+                Some('i') => Ok(Pink),
+                _ => Err(MastermindError::AmbiguousColour {
+                    word: text.to_string(),
+                    could_be: vec![Purple, Pink],
+                }),
+            },
             'r' => Ok(Red),    //
             'b' => Ok(Blue),   //
             'w' => Ok(White),  // All of these branches return a `Result`
             'y' => Ok(Yellow), // If they're okay with it.
             'g' => Ok(Green),  //
             'o' => Ok(Orange), //
+            // A blank is typed as the hole it leaves
+            '.' | '_' => Ok(Blank),
             // In the case we get absolutely anything else, we return an error
             // telling us what we got instead.
-            _ => Err(format!("Invalid initial character: `{}`", first)),
+            _ => Err(MastermindError::InvalidColourChar(first)),
         }
     }
 }
 
-//
-// This just enumerates the colours, for ease of use.
-//
-static COLOURS: &'static [Colour] = &[
-    Colour::Red,
-    Colour::Blue,
-    Colour::White,
-    Colour::Yellow,
-    Colour::Green,
-    Colour::Orange,
-];
-
-///
-/// We make a state struct because we may want to
-/// expose a gui layer, which isn't able to access
-/// variables local to functions.
 ///
-/// This has a single generic parameter `'a` which is
-/// a lifetime. I can't summarize lifetimes in this
-/// short project, but it should suffice to say it's
-/// rust's gimmick to make it so fast and safe (As fast
-/// as C++ and safe as GC languages as Java).
+/// The settings a game is played with. These are grouped
+/// together so that they can be handed around (to a
+/// frontend, into a summary, etc.) as one value instead
+/// of three loose variables.
 ///
-/// There's a nice entry in the rust book should you
-/// want to explore more on this:
-/// https://doc.rust-lang.org/book/ch10-03-lifetime-syntax.html
+/// The settings added since saves began are allowed to be
+/// missing when they're read, and come out as what older
+/// versions played with, see `save.rs`.
 ///
-struct State<'a> {
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GameConfig {
     ///
-    /// The pegs we're looking at right now.
+    /// Number of pegs in the secret code.
     ///
-    /// A `Vec` is the equivalent of a `List` or `ArrayList` in Java.
+    pub pegs: usize,
     ///
-    pegs: Vec<Colour>,
+    /// Whether the same colour may appear more than once.
     ///
-    /// Previously chosen peg combinations, could be
-    /// useful for logging events, etc. Currently unused
-    /// other than to count number of attempts.
+    pub allow_duplicates: bool,
     ///
-    pub previously_chosen: Vec<Vec<Colour>>,
+    /// Optionally describes the maximum number of moves
+    /// in a game.
     ///
-    /// The previous games we've played (IE the previous
-    /// states of `pegs`. This uses a tuple to denote the
-    /// contents. Tuples are structures whose names are
-    /// simply the types they contain. This one contains
-    /// a list of colours, the number of tries it took
-    /// (`usize` if a number type in rust, like `int` or
-    /// `long` in Java), and whether it was won (`bool`)
+    #[serde(deserialize_with = "save::max_tries")]
+    pub max_tries: Option<usize>,
     ///
-    pub previous_games: Vec<(Vec<Colour>, usize, bool)>,
+    /// Whether `max_tries` is a fair limit worked out from the
+    /// other settings (See `fair.rs`) rather than one somebody
+    /// picked, so that it can be worked out again when they
+    /// change, with `with_auto_tries`.
     ///
-    /// Max number of pegs we can play with.
+    // Limits in older saves were all picked by somebody
+    #[serde(default)]
+    pub auto_max_tries: bool,
     ///
-    size_pegs: usize,
-    allow_duplicates: bool,
+    /// Optionally limits the secret code to a handful of
+    /// colours picked out of the palette. The player is told
+    /// how many, but not which ones.
     ///
-    /// We buffer the input, because it could be input
-    /// over multiple lines or through a gui. Once the
-    /// size reaches `size_pegs`, it will flush and try
-    /// to finish a move.
+    #[serde(default)]
+    pub max_distinct_colours: Option<usize>,
     ///
-    buffered_input: Vec<Colour>,
+    /// With duplicates, keeps away codes which use fewer than
+    /// this many different colours, like four reds, which are
+    /// no fun to play against.
     ///
-    /// Optionally describes the maximum number of moves
-    /// in a game.
+    #[serde(default)]
+    pub min_distinct_colours: Option<usize>,
+    ///
+    /// With duplicates, the most times any one colour can be
+    /// in the secret, and in a guess. `None` is no limit.
+    ///
+    #[serde(default)]
+    pub max_per_colour: Option<usize>,
     ///
-    max_tries: Option<usize>,
+    /// After every this many wrong guesses, one position of
+    /// the secret is revealed for the rest of the game.
     ///
-    /// A function pointer (AKA a variable that is a
-    /// function) this is called when the player wins.
+    #[serde(default)]
+    pub reveal_every: Option<usize>,
     ///
-    win: Box<dyn Fn() + 'a>,
+    /// Hides every guess except the last one, so the player has
+    /// to remember what they've tried.
     ///
-    /// Called when the player loses.
+    #[serde(default)]
+    pub hardcore: bool,
     ///
-    lose: Box<dyn Fn() + 'a>,
+    /// Never shows the player's guesses back to them, only the
+    /// feedback for them.
     ///
-    /// Terminal mode. Only outputs terminal prompts
-    /// and messages if this is true.
+    #[serde(default)]
+    pub memory: bool,
     ///
-    terminal: bool,
+    /// Turns away a guess which was already made this game,
+    /// without using up a try.
+    ///
+    #[serde(default)]
+    pub block_repeats: bool,
+    ///
+    /// Turns away a whole line of colours which is longer than
+    /// what's left of the guess being entered, so that a typo
+    /// can't start the next guess. Without it, the colours left
+    /// over carry on into the next guess (See
+    /// `State::push_string_input`).
+    ///
+    #[serde(default)]
+    pub strict_input: bool,
+    ///
+    /// Waits for the player to say a full guess is finished
+    /// (See `State::commit_guess`) rather than scoring it as soon
+    /// as the last colour is in, so that a typo in it can still
+    /// be taken back with `State::undo_input`.
+    ///
+    #[serde(default)]
+    pub confirm_guesses: bool,
+    ///
+    /// How likely each colour is to be picked for the secret
+    /// compared to the others, indexed by `Colour as usize`, so
+    /// a weight of 2 for red makes red twice as likely as a
+    /// colour with a weight of 1. `None` is the same as every
+    /// weight being 1.
+    ///
+    #[serde(default, with = "save::colour_weights")]
+    pub colour_weights: Option<[u32; PALETTE_SIZE]>,
+    ///
+    /// The colours the secret always has at least one of, also
+    /// indexed by `Colour as usize`.
+    ///
+    #[serde(default, with = "save::colour_set")]
+    pub must_include: [bool; PALETTE_SIZE],
+    ///
+    /// How many hint points each game starts with, see
+    /// `hints.rs`. Zero means there are no hints.
+    ///
+    #[serde(default)]
+    pub hint_points: usize,
+    ///
+    /// Picks the secret again when it's one that already came up
+    /// this session, see `State::fresh_pegs_with`.
+    ///
+    // Saves from before this was a setting had it on
+    #[serde(default = "save::yes")]
+    pub fresh_secrets: bool,
+    ///
+    /// Lets the secret have empty holes in it, as
+    /// `Colour::Blank`, see `palette`.
+    ///
+    #[serde(default)]
+    pub allow_blanks: bool,
+    ///
+    /// How many colours are in play, counting from the start of
+    /// `COLOURS`, so 3 is only red, blue and white. A colour
+    /// which isn't in play can't be in the secret or in a guess.
+    /// `None` is all of them.
+    ///
+    #[serde(default = "save::classic_palette")]
+    pub palette_size: Option<usize>,
 }
 
 ///
-/// Implementing the state
+/// The canonical default settings. Everything which needs a
+/// "default" game, such as blank answers at the setup prompts,
+/// goes through this so that there is only a single place
+/// the defaults are written down.
 ///
-impl<'a> State<'a> {
-    ///
-    /// A `new` function, akin to a constructor, will
-    /// create a new `State` given some configuration
-    /// parameters.
+/// Games are played with the classic six colours at the start
+/// of `COLOURS`, without blanks.
+///
+/// The limit on tries is a flat 10, rather than the fair one
+/// from `fair.rs` (Which would be 8 for these settings), so that
+/// a new player has a couple to spare. Answering "auto" at the
+/// setup prompt, or `--max-tries auto`, picks the fair one instead.
+///
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            pegs: 4,
+            allow_duplicates: false,
+            max_tries: Some(10),
+            auto_max_tries: false,
+            max_distinct_colours: None,
+            min_distinct_colours: None,
+            max_per_colour: None,
+            reveal_every: None,
+            hardcore: false,
+            memory: false,
+            block_repeats: false,
+            strict_input: false,
+            confirm_guesses: false,
+            colour_weights: None,
+            must_include: [false; PALETTE_SIZE],
+            hint_points: 0,
+            fresh_secrets: true,
+            allow_blanks: false,
+            palette_size: Some(CLASSIC_PALETTE_SIZE),
+        }
+    }
+}
+
+impl GameConfig {
     ///
-    /// Note that this may or may not return `Self` (
-    /// AKA `State`) if it has invalid parameters.
+    /// These settings with `max_tries` worked out again, if it's
+    /// meant to be a fair limit (See `auto_max_tries`). Anything
+    /// which changes a setting the limit depends on should go
+    /// through this afterwards.
     ///
-    fn new(
-        size: usize,
-        allow_duplicates: bool,
-        //
-        // Equivalent of an `Integer` in Java which allows a
-        // nullable `int`.
-        //
-        max_tries: Option<usize>,
-        //
-        // These functions use fancy talk in rust to say that
-        // this function (new) is generic over some type which
-        // implements the function traits. Kind of like this:
-        // ```java
-        // public static<T> void new(...) where T: MyInterface
-        // ```
-        //
-        win: impl Fn() + 'a,
-        lose: impl Fn() + 'a,
-        terminal: bool,
-    ) -> Option<Self> {
-        //Check if there is a problem with our config
-        if size > COLOURS.len() {
-            if terminal {
-                println!(
-                    "Choose less than or equal to {} pegs to play with!",
-                    COLOURS.len()
-                )
+    pub fn with_auto_tries(self) -> Self {
+        if self.auto_max_tries {
+            Self {
+                max_tries: Some(fair::max_tries(self)),
+                ..self
             }
-            // Return error state (`null` equivalent) if error
-            None
         } else {
-            let pegs = Self::generate_new_pegs(size, allow_duplicates);
-            Some(
-                // This is an inline constructor, we just specify
-                // each field's value on declaration
-                Self {
-                    pegs,
-                    previously_chosen: Vec::with_capacity(max_tries.clone().unwrap_or(0)),
-                    previous_games: Vec::new(),
-                    size_pegs: size,
-                    allow_duplicates,
-                    buffered_input: Vec::with_capacity(size),
-                    max_tries,
-                    win: Box::new(win),
-                    lose: Box::new(lose),
-                    terminal,
-                },
-            )
+            self
         }
     }
 
     ///
-    /// This is an associated function which isn't run on anything. This
-    /// is similar to Java's `static` methods except those have access to
-    /// `static` state, which could be present in a class. These on the
-    /// other hand cannot mutate anything outside of the function.
+    /// The settings most worth knowing about a finished game,
+    /// in a few words, like `4 pegs, no duplicates, 10 tries`.
     ///
-    /// From within this `impl` block, you call this as `Self::generate_new_pegs()`
+    pub fn brief(self) -> String {
+        format!(
+            "{} pegs, {}, {} tries",
+            self.pegs,
+            if self.allow_duplicates {
+                "duplicates"
+            } else {
+                "no duplicates"
+            },
+            self.max_tries
+                .map_or_else(|| "unlimited".to_string(), |x| x.to_string())
+        )
+    }
+
     ///
-    fn generate_new_pegs(size: usize, allow_duplicates: bool) -> Vec<Colour> {
-        let mut rng = rand::thread_rng();
-        // We use `.to_vec` so that we can remove items from it
-        let mut choice_pegs = COLOURS.to_vec();
-        if allow_duplicates {
-            // This is equivalent to looping over `0` to `size` and
-            // collecting these values into a list:
-            //
-            // choice_pegs[rng.gen::<usize>() % choice_pegs.len()]
-            //
-            (0..size)
-                .map(|_| choice_pegs[rng.gen::<usize>() % choice_pegs.len()])
-                .collect()
-        } else {
-            // This is the same idea except that instead of using indexing
-            // we use removal, which will remove the item from the list and
-            // return it.
-            (0..size)
-                .map(move |_| choice_pegs.remove(rng.gen::<usize>() % choice_pegs.len()))
-                .collect()
-        }
-        // Because each branch of the if block ends with an implicit return
-        // expression which isn't `void` (Or in rust, ()), the if statement
-        // itself is now an expression which returns a value.
-    }
-
-    ///
-    /// Returns either the number of correct placements followed by
-    /// present colours or a string describing an error in the case
-    /// it is not happy with its inputs.
-    ///
-    fn matching(&self, idx: Option<usize>) -> Result<(usize, usize), String> {
-        let player = idx
-            .map(|x| &self.previously_chosen[x])
-            .unwrap_or(&self.buffered_input);
-        if !self.allow_duplicates {
-            let mut seen = Vec::new();
-            player
-                .iter()
-                .enumerate()
-                .try_fold((0, 0), |mut state, (idx, val)| {
-                    if seen.contains(val) {
-                        return Err("Cannot have duplicated when using non-duplicate mode!".into());
-                    }
-                    seen.push(*val);
-                    if self.pegs.contains(val) {
-                        if self.pegs[idx] == *val {
-                            state.0 += 1;
-                        } else {
-                            state.1 += 1;
-                        }
-                    }
-                    Ok(state)
-                })
-        } else {
-            Ok(player
-                .iter()
-                .enumerate()
-                .fold((0, 0), |mut state, (idx, val)| {
-                    if self.pegs.contains(val) {
-                        if self.pegs[idx] == *val {
-                            state.0 += 1;
-                        } else {
-                            state.1 += 1;
-                        }
-                    }
-                    state
-                }))
-        }
+    /// Everything a peg of the secret can be: the colours in
+    /// play (See `palette_size`), and then a blank if
+    /// `allow_blanks` is on.
+    ///
+    pub fn palette(self) -> Vec<Colour> {
+        self.symbols()
     }
 
     ///
-    /// Pushes a colour into our buffered input, returning
-    /// if a game change (Not a turn change) occurred.
+    /// `palette`, for a game whose pegs aren't colours (See
+    /// `symbol.rs`).
     ///
-    fn input_buffer(&mut self, value: Colour) -> Result<bool, String> {
-        self.buffered_input.push(value);
-        if self.buffered_input.len() == self.size_pegs {
-            self.finish_try()
-        } else {
-            Ok(false)
+    pub fn symbols<P: Symbol>(self) -> Vec<P> {
+        let mut symbols = P::all().to_vec();
+        if let Some(size) = self.palette_size {
+            symbols.truncate(size);
         }
+        if self.allow_blanks {
+            symbols.extend(P::blank());
+        }
+        symbols
     }
 
     ///
-    /// Parse and push a whole string as an input into the buffer.
-    /// This uses `input_buffer` on every character in the string.
+    /// Whether `symbol` can be in a code with these settings, as
+    /// one of the colours in play or a blank in a game with
+    /// them.
     ///
-    fn push_string_input(&mut self, mut text: &str) -> Result<bool, (String, bool)> {
-        let mut should_reset = false;
-        while text.len() > 0 {
-            //Intentionally ignoring the output because we can accept
-            //strings longer than the max size and just keep processing
-            //them to enter multiple tries at the same time.
-            should_reset |= text
-                .parse()
-                .and_then(|x| self.input_buffer(x))
-                .map_err(|x| (x, should_reset))?;
-            // This line could break should we get a non-ascii character
-            // but it should be fine for now
-            text = &text[1..];
+    pub fn in_play<P: Symbol>(self, symbol: P) -> bool {
+        match symbol.index() {
+            Some(i) => self.palette_size.is_none_or(|size| i < size),
+            None => self.allow_blanks && Some(symbol) == P::blank(),
         }
-        Ok(should_reset)
     }
 
     ///
-    /// Decides to either win the game, or not, or keep going.
+    /// The colours in `must_include`, in palette order.
     ///
-    fn finish_try(&mut self) -> Result<bool, String> {
-        // `true` if we've finished a game, false if we've finished a round
-        let returns;
-        if self.buffered_input == self.pegs {
-            // Call our function we assigned at the start if we win.
-            // This currently just prints a "You win" message
-            (self.win)();
-            self.previous_games
-                .push((self.pegs.clone(), self.previously_chosen.len(), true));
-            self.reset();
-            returns = true;
-        } else {
-            if self.max_tries.unwrap_or(std::usize::MAX) == self.previously_chosen.len() + 1 {
-                (self.lose)();
-                self.previous_games
-                    .push((self.pegs.clone(), self.previously_chosen.len(), false));
-                self.reset();
-                returns = true;
-            } else {
-                if self.terminal {
-                    let matching = self.matching(None)?;
-                    println!(
-                        "Good try, here are your matching pegs: {:?} are in the correct position and {:?} have the right colour",
-                        matching.0,
-                        matching.1,
-                    )
-                }
-                self.previously_chosen
-                    .push(self.buffered_input.drain(..).collect());
-                returns = false;
-            }
-        }
-        Ok(returns)
+    pub fn required_colours(self) -> Vec<Colour> {
+        self.required()
     }
 
-    fn reset(&mut self) {
-        self.previously_chosen = Vec::new();
-        self.buffered_input.clear();
-        self.pegs = Self::generate_new_pegs(self.size_pegs, self.allow_duplicates);
+    ///
+    /// `required_colours`, for a game whose pegs aren't colours.
+    /// `must_include` goes by `Symbol::index` for these.
+    ///
+    pub fn required<P: Symbol>(self) -> Vec<P> {
+        P::all()
+            .iter()
+            .copied()
+            .filter(|x| {
+                x.index()
+                    .is_some_and(|i| self.must_include.get(i) == Some(&true))
+            })
+            .collect()
     }
-}
 
-///
-/// A mock main, meant to be copy-pasteable into other places.
-///
-pub fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // There are three ways to write a string in rust,
-    // "this way", r#"this way"#, and r"this way".
-    // The first one is your standard string with escape
-    // sequences like \n, etc. The second one is to ignore
-    // all characters between the #"s and just take them
-    // as if they were text. r"" text is a byte array literal
-    // instead of a string.
-    println!(
-        r#"
-    ~~~~ Mastermind ~~~~
-Rules: A set of pegs from the
-following colours are selected:
-   ┏━━━━━━┳━━━━━━┳━━━━━━┓
-   ┃Orange┃Yellow┃ Red  ┃
-   ┣━━━━━━╋━━━━━━╋━━━━━━┫
-   ┃ Blue ┃Green ┃White ┃
-   ┗━━━━━━┻━━━━━━┻━━━━━━┛
-The player takes guesses at
-the selected colours, and is
-given the number of pegs in a
-correct position (And colour)
-and the number of correct
-colours chosen in an incorrect
-position.
-"#
-    );
-    let mut input = String::new();
-    print!("Would you like to allow duplicates? (\"true\" or \"false\"): ");
-    std::io::stdout().flush()?;
-    std::io::stdin().read_line(&mut input)?;
-    let mut duplicates = input.trim().parse::<bool>();
-    let duplicates = loop {
-        match duplicates {
-            Ok(x) => break x,
-            Err(_) => {
-                println!("Please try again! Either `true` or `false`.");
-                input.clear();
-                std::io::stdin().read_line(&mut input)?;
-                duplicates = input.trim().parse();
-            }
-        }
+    ///
+    /// Checks that a secret code can actually be made with these
+    /// settings, returning what's wrong if it can't.
+    ///
+    pub fn validate(self) -> Result<(), ConfigError> {
+        self.validate_for::<Colour>()
+    }
+
+    ///
+    /// `validate`, for a game whose pegs aren't colours, since
+    /// there might be more or fewer of them to choose from.
+    ///
+    pub fn validate_for<P: Symbol>(self) -> Result<(), ConfigError> {
+        // A code of no pegs is "guessed" by the first empty
+        // guess, and one peg is just picking a colour.
+        if self.pegs < MIN_PEGS {
+            return Err(ConfigError::TooFewPegs {
+                requested: self.pegs,
+            });
+        }
+        if self
+            .palette_size
+            .is_some_and(|x| x == 0 || x > P::all().len())
+        {
+            return Err(ConfigError::PaletteSize {
+                available: P::all().len(),
+            });
+        }
+        let palette = self.symbols::<P>().len();
+        if self.pegs > palette {
+            return Err(ConfigError::TooManyPegs {
+                requested: self.pegs,
+                available: palette,
+            });
+        }
+        match self.max_tries {
+            Some(0) => return Err(ConfigError::NoTries),
+            Some(x) if x > MAX_TRIES => return Err(ConfigError::TooManyTries { most: MAX_TRIES }),
+            _ => {}
+        }
+        if self.max_per_colour == Some(0) {
+            return Err(ConfigError::NoneOfAnyColour);
+        }
+        if let (Some(colours), Some(most)) = (self.max_distinct_colours, self.max_per_colour) {
+            if self.allow_duplicates && colours * most < self.pegs {
+                return Err(ConfigError::CantFillPegs {
+                    colours,
+                    most,
+                    pegs: self.pegs,
+                });
+            }
+        }
+        if self.reveal_every == Some(0) {
+            return Err(ConfigError::RevealEveryZero);
+        }
+        if self.colour_weights.is_some_and(|x| x.contains(&0)) {
+            return Err(ConfigError::ZeroWeight);
+        }
+        let required = self.required::<P>();
+        if required.iter().any(|&x| !self.in_play(x)) {
+            return Err(ConfigError::RequiredNotInPlay);
+        }
+        let required = required.len();
+        if required > self.pegs {
+            return Err(ConfigError::TooManyRequired {
+                pegs: self.pegs,
+                required,
+            });
+        }
+        if self.max_distinct_colours.is_some_and(|x| x < required) {
+            return Err(ConfigError::RequiredOverLimit { required });
+        }
+        match self.min_distinct_colours {
+            Some(0) => return Err(ConfigError::FewestColoursZero),
+            Some(x) if x > self.pegs => {
+                return Err(ConfigError::FewestOverPegs {
+                    pegs: self.pegs,
+                    fewest: x,
+                })
+            }
+            Some(x) if x > palette => {
+                return Err(ConfigError::NotEnoughColours { available: palette })
+            }
+            Some(x) if self.max_distinct_colours.is_some_and(|y| y < x) => {
+                return Err(ConfigError::FewestOverMost { fewest: x })
+            }
+            _ => {}
+        }
+        match self.max_distinct_colours {
+            Some(0) => Err(ConfigError::MostColoursZero),
+            Some(x) if x > palette => Err(ConfigError::NotEnoughColours { available: palette }),
+            Some(x) if !self.allow_duplicates && x < self.pegs => {
+                Err(ConfigError::TooFewColoursForPegs { pegs: self.pegs })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    ///
+    /// The first colour which `code` has more of than
+    /// `max_per_colour` allows, if there is one.
+    ///
+    pub fn over_limit<P: Symbol>(self, code: &[P]) -> Option<P> {
+        let most = self.max_per_colour?;
+        code.iter()
+            .copied()
+            .find(|x| code.iter().filter(|y| *y == x).count() > most)
+    }
+
+    ///
+    /// Whether `code` has as many different colours as
+    /// `min_distinct_colours` asks for.
+    ///
+    pub fn enough_colours<P: Symbol>(self, code: &[P]) -> bool {
+        self.min_distinct_colours
+            .is_none_or(|x| code.iter().collect::<HashSet<_>>().len() >= x)
+    }
+
+    ///
+    /// What to tell the player about the colours in use, if
+    /// the code is limited to some of them, or has to use a
+    /// few of them. Without duplicates the pegs are all
+    /// different anyway, so there's nothing to say about that.
+    ///
+    pub fn colour_announcement(self) -> Option<String> {
+        let fewest = self.min_distinct_colours.filter(|_| self.allow_duplicates);
+        match (self.max_distinct_colours, fewest) {
+            (Some(colours), _) if colours <= self.pegs => {
+                Some(format!("This code uses only {} different colours", colours))
+            }
+            (Some(colours), None) => Some(format!(
+                "This code only uses colours from a set of {}",
+                colours
+            )),
+            (Some(colours), Some(fewest)) => Some(format!(
+                "This code uses at least {} different colours, from a set of {}",
+                fewest, colours
+            )),
+            (None, Some(fewest)) => Some(format!(
+                "This code uses at least {} different colours",
+                fewest
+            )),
+            (None, None) => None,
+        }
+    }
+
+    ///
+    /// What to tell the player about which colours are in play,
+    /// if it isn't the classic six (See `palette_size`).
+    ///
+    pub fn palette_announcement(self) -> Option<String> {
+        let names = self
+            .palette()
+            .into_iter()
+            .filter(|&x| x != Colour::Blank)
+            .map(|x| format!("{:?}", x))
+            .collect::<Vec<_>>();
+        if names.len() == CLASSIC_PALETTE_SIZE {
+            return None;
+        }
+        Some(format!("The colours in play are {}", names.join(", ")))
+    }
+}
+
+///
+/// Reads `GameConfig::colour_weights` back from JSON like
+/// `{ "red": 2 }`, where colours which aren't mentioned get a
+/// weight of 1, and `null` means there are no weights.
+///
+fn weights_from_json(json: &Json) -> Result<Option<[u32; PALETTE_SIZE]>, String> {
+    let pairs = match json {
+        Json::Null => return Ok(None),
+        Json::Object(pairs) => pairs,
+        _ => return Err("`colour_weights` should be an object of colours to weights".into()),
     };
-    input.clear();
-    print!("How many pegs would you like to play with? (2-6, inclusive): ");
-    std::io::stdout().flush()?;
-    std::io::stdin().read_line(&mut input)?;
-    let mut pegs = input.trim().parse::<usize>();
-    let pegs = loop {
-        match pegs {
-            Ok(x @ 2..=6) => break x,
+    let mut weights = [1; PALETTE_SIZE];
+    for (name, weight) in pairs {
+        let colour = Colour::from_name(name)
+            .ok_or_else(|| format!("`{}` in `colour_weights` isn't a colour", name))?;
+        weights[colour as usize] = match weight.as_usize() {
+            Some(x @ 1..=1_000_000) => x as u32,
             _ => {
-                println!("Please try again! Enter a valid positive integer from 2-6 inclusive.");
-                input.clear();
-                std::io::stdin().read_line(&mut input)?;
-                pegs = input.trim().parse();
+                return Err(format!(
+                    "The weight of `{}` should be a whole number from 1 to 1000000",
+                    name
+                ))
+            }
+        };
+    }
+    Ok(Some(weights))
+}
+
+///
+/// Reads `GameConfig::must_include` back from a JSON list of
+/// colour names.
+///
+fn colour_set_from_json(json: &Json) -> Result<[bool; PALETTE_SIZE], String> {
+    let mut set = [false; PALETTE_SIZE];
+    for name in json
+        .as_array()
+        .ok_or("`must_include` should be a list of colours")?
+    {
+        let colour = name
+            .as_str()
+            .and_then(Colour::from_name)
+            .ok_or_else(|| format!("`{}` in `must_include` isn't a colour", name))?;
+        set[colour as usize] = true;
+    }
+    Ok(set)
+}
+
+///
+/// Where a game stands. A live game is always `InProgress`,
+/// because `State` starts the next game as soon as one is
+/// over; `Won` and `Lost` describe a game which has just
+/// finished.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamePhase {
+    InProgress,
+    Won,
+    Lost,
+}
+
+impl GamePhase {
+    fn name(self) -> &'static str {
+        match self {
+            GamePhase::InProgress => "in_progress",
+            GamePhase::Won => "won",
+            GamePhase::Lost => "lost",
+        }
+    }
+}
+
+///
+/// A snapshot of "where the game stands", which owns all of
+/// its data so that it can be kept around, printed, or sent
+/// somewhere else without holding on to the `State`.
+///
+/// This is what we tell a frontend about the current game.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSummary<P = Colour> {
+    ///
+    /// Number of complete guesses made in this game so far.
+    ///
+    pub attempts_made: usize,
+    pub max_tries: Option<usize>,
+    pub phase: GamePhase,
+    ///
+    /// The feedback for the most recent guess. `None` before the
+    /// first guess.
+    ///
+    pub last_feedback: Option<Feedback>,
+    pub settings: GameConfig,
+    ///
+    /// The positions of the secret revealed so far, and what's
+    /// in them, ordered by position.
+    ///
+    pub revealed: Vec<(usize, P)>,
+    ///
+    /// The hint points which haven't been spent yet this game,
+    /// `None` if there are no hints (See `hints.rs`).
+    ///
+    pub hint_points_left: Option<usize>,
+    ///
+    /// Every guess made so far this game, along with its
+    /// feedback (If it could be scored).
+    ///
+    pub guesses: Vec<(Vec<P>, Option<Feedback>)>,
+    ///
+    /// Which game of the session this is, counting from zero.
+    ///
+    pub game_index: usize,
+    ///
+    /// The code, once the game is over. This is always `None`
+    /// for a game `InProgress`, so that a summary can't give the
+    /// game away.
+    ///
+    pub secret: Option<Vec<P>>,
+}
+
+impl GameSummary {
+    ///
+    /// Converts the summary to JSON, which is how it's
+    /// serialized for anything outside of this program.
+    ///
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("attempts_made", self.attempts_made.into()),
+            ("max_tries", self.max_tries.into()),
+            ("phase", self.phase.name().into()),
+            (
+                "last_feedback",
+                self.last_feedback
+                    .map(|feedback| {
+                        Json::object(vec![
+                            ("correct_position", feedback.exact.into()),
+                            ("correct_colour", feedback.misplaced.into()),
+                        ])
+                    })
+                    .unwrap_or(Json::Null),
+            ),
+            ("settings", Json::from_serde(&self.settings)),
+            (
+                "revealed",
+                Json::Array(
+                    self.revealed
+                        .iter()
+                        .map(|(position, colour)| {
+                            Json::object(vec![
+                                ("position", (*position).into()),
+                                ("colour", colour.name().into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            ("hint_points_left", self.hint_points_left.into()),
+            (
+                "guesses",
+                Json::Array(
+                    self.guesses
+                        .iter()
+                        .map(|(guess, feedback)| {
+                            let colours = guess.iter().map(|x| x.name().into()).collect::<Vec<_>>();
+                            Json::object(vec![
+                                ("colours", Json::Array(colours)),
+                                ("correct_position", feedback.map(|x| x.exact).into()),
+                                ("correct_colour", feedback.map(|x| x.misplaced).into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            ("game_index", self.game_index.into()),
+            (
+                "secret",
+                self.secret
+                    .as_ref()
+                    .map(|x| Json::Array(x.iter().map(|x| x.name().into()).collect()))
+                    .unwrap_or(Json::Null),
+            ),
+        ])
+    }
+}
+
+///
+/// `Display` is rust's version of overriding `toString()`,
+/// this is what we print for the `status` command.
+///
+impl fmt::Display for GameSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, &FeedbackStyle::default())
+    }
+}
+
+impl GameSummary {
+    ///
+    /// The same as `to_string`, but with the feedback written
+    /// in `style`.
+    ///
+    pub fn describe(&self, style: &FeedbackStyle) -> String {
+        let mut text = String::new();
+        // Writing to a `String` can't fail
+        let _ = self.write_to(&mut text, style);
+        text
+    }
+
+    //
+    // `fmt::Write` is anything text can be written to, which
+    // both a `Formatter` and a `String` are.
+    //
+    fn write_to(&self, f: &mut impl fmt::Write, style: &FeedbackStyle) -> fmt::Result {
+        write!(
+            f,
+            "Game #{}: {} attempt",
+            self.game_index + 1,
+            self.attempts_made
+        )?;
+        if self.attempts_made != 1 {
+            write!(f, "s")?;
+        }
+        match self.max_tries {
+            Some(max) => write!(f, " made out of {}", max)?,
+            None => write!(f, " made")?,
+        }
+        write!(
+            f,
+            " with {} pegs, duplicates {}",
+            self.settings.pegs,
+            if self.settings.allow_duplicates {
+                "allowed"
+            } else {
+                "not allowed"
+            }
+        )?;
+        let colours = self.settings.palette_size.unwrap_or(PALETTE_SIZE);
+        if colours != CLASSIC_PALETTE_SIZE {
+            write!(f, ", with {} colours in play", colours)?;
+        }
+        if let Some(colours) = self.settings.max_distinct_colours {
+            write!(f, ", using {} colours", colours)?;
+        }
+        if let Some(colours) = self
+            .settings
+            .min_distinct_colours
+            .filter(|_| self.settings.allow_duplicates)
+        {
+            write!(f, ", using at least {} different colours", colours)?;
+        }
+        match self.last_feedback {
+            Some(feedback) => write!(f, ". Last guess: {}", style.describe(feedback))?,
+            None => write!(f, ". No guesses yet")?,
+        }
+        // Positions are counted from one for the player
+        for (position, colour) in &self.revealed {
+            write!(f, ". Position {} is {:?}", position + 1, colour)?;
+        }
+        if let Some(left) = self.hint_points_left {
+            write!(f, ". {} hint point", left)?;
+            if left != 1 {
+                write!(f, "s")?;
+            }
+            write!(f, " left")?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// What we remember about a game once it's over.
+///
+/// This is laid out the way saves have always stored finished
+/// games, see `save.rs` for where that isn't serde's own.
+///
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(remote = "Self", bound(deserialize = "P: serde::Deserialize<'de>"))]
+pub struct GameRecord<P = Colour> {
+    ///
+    /// The code the player was trying to guess.
+    ///
+    pub secret: Vec<P>,
+    ///
+    /// The number of tries it took.
+    ///
+    pub attempts: usize,
+    pub outcome: Outcome,
+    ///
+    /// How long the game took, this is `None` for games loaded
+    /// from saves which were made before we kept track of time.
+    ///
+    #[serde(rename = "seconds", default, with = "save::optional_seconds")]
+    pub duration: Option<Duration>,
+    ///
+    /// How many positions of the secret were revealed to the
+    /// player, so that wins with some help can be told apart.
+    ///
+    #[serde(default)]
+    pub reveals: usize,
+    ///
+    /// Whether the game was played in hardcore mode.
+    ///
+    #[serde(default)]
+    pub hardcore: bool,
+    ///
+    /// Whether the game was played in memory mode.
+    ///
+    #[serde(default)]
+    pub memory: bool,
+    ///
+    /// Everything the game was played with, since the settings
+    /// can be changed between games. Games finished before these
+    /// were kept have `None`.
+    ///
+    #[serde(default)]
+    pub settings: Option<GameConfig>,
+    ///
+    /// Points for how quickly the game was won, see `speed.rs`.
+    /// This is zero for games which were lost or had help.
+    ///
+    // Games finished before there was a bonus didn't get one
+    #[serde(default)]
+    pub speed_bonus: usize,
+    ///
+    /// How many hint points were spent on the game.
+    ///
+    #[serde(default)]
+    pub hint_points: usize,
+    ///
+    /// A few words the player gave the game once it was over
+    /// (Like "birthday"), cleaned up with `clean_label`.
+    ///
+    #[serde(default)]
+    pub label: Option<String>,
+    ///
+    /// Every guess of the game in order, with its feedback. The
+    /// winning guess is the last one of a game which was won.
+    /// Games finished before these were kept have none.
+    ///
+    #[serde(default, with = "save::guesses")]
+    pub guesses: Vec<Attempt<P>>,
+}
+
+///
+/// What entering a colour did, see `State::input_buffer`. A
+/// frontend can tell a win from a loss with this, without the
+/// callbacks `State` is made with.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum TurnOutcome<P = Colour> {
+    ///
+    /// The guess isn't finished yet.
+    ///
+    Pending,
+    ///
+    /// A guess was finished and scored, and the game goes on.
+    ///
+    RoundComplete { feedback: Feedback },
+    ///
+    /// The guess was the secret, on attempt `attempts`.
+    ///
+    Won { attempts: usize },
+    ///
+    /// The last try was used up without finding `secret`.
+    ///
+    Lost { secret: Vec<P> },
+}
+
+impl<P> TurnOutcome<P> {
+    ///
+    /// Whether this was the end of a game, one way or another.
+    /// The next game has already started by the time this is
+    /// given back.
+    ///
+    pub fn ends_game(&self) -> bool {
+        matches!(self, TurnOutcome::Won { .. } | TurnOutcome::Lost { .. })
+    }
+}
+
+///
+/// One thing which happened as a colour was entered, see
+/// `State::step`. Where a `TurnOutcome` says how entering a
+/// colour ended up, these say everything that happened on the
+/// way, in order: the last colour of a winning guess is
+/// accepted, the guess is scored, the game is won and the next
+/// one starts.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent<P = Colour> {
+    ///
+    /// The colour went into the guess at `position` (Counting
+    /// from zero).
+    ///
+    PegAccepted {
+        position: usize,
+    },
+    ///
+    /// The guess was finished and scored.
+    ///
+    GuessEvaluated {
+        feedback: Feedback,
+    },
+    GameWon,
+    ///
+    /// The last try was used up without finding `secret`.
+    ///
+    GameLost {
+        secret: Vec<P>,
+    },
+    ///
+    /// The next game has started, with a new secret.
+    ///
+    NewGameStarted,
+}
+
+impl<P> GameEvent<P> {
+    ///
+    /// Whether this was the end of a game, one way or another.
+    ///
+    pub fn ends_game(&self) -> bool {
+        matches!(self, GameEvent::GameWon | GameEvent::GameLost { .. })
+    }
+}
+
+///
+/// Why `State::push_string_input` stopped, and how far into the
+/// line it had got, since a long line can have already made a
+/// few guesses before the part which went wrong.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputError<P = Colour> {
+    pub error: MastermindError<P>,
+    ///
+    /// How many characters into the line the colour which went
+    /// wrong was (Counting from zero), or `None` if it wasn't
+    /// down to any one colour.
+    ///
+    pub position: Option<usize>,
+    ///
+    /// How many guesses from the line were finished before it
+    /// went wrong, and how many colours are left in the guess
+    /// being entered.
+    ///
+    pub guesses_finished: usize,
+    pub buffered: usize,
+    ///
+    /// Whether one of those guesses ended a game, in which case
+    /// the rest of the line would have gone into the next one.
+    ///
+    pub game_ended: bool,
+}
+
+impl<P: Symbol> fmt::Display for InputError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(position) = self.position {
+            write!(f, " (At character {}", position + 1)?;
+            match self.guesses_finished {
+                0 => {}
+                1 => write!(f, ", after 1 whole guess")?,
+                x => write!(f, ", after {} whole guesses", x)?,
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+impl<P: Symbol> std::error::Error for InputError<P> {}
+
+///
+/// A guess made during a game, with the feedback it got, see
+/// `rules::Attempt`. Here the guess is a `Guess`, since `State`
+/// only ever finishes guesses its settings allow.
+///
+pub type Attempt<P = Colour> = crate::rules::Attempt<Guess<P>>;
+
+impl<P: Symbol> Attempt<P> {
+    ///
+    /// Scores `guess` against `secret`.
+    ///
+    pub fn new(secret: &[P], guess: Guess<P>) -> Self {
+        Self {
+            feedback: score_guess(secret, &guess),
+            guess,
+        }
+    }
+}
+
+///
+/// The most tries a game can be limited to. Anything more is
+/// as good as unlimited, and a limit of millions would only be
+/// a mistake (Or someone trying to make us run out of memory).
+///
+pub const MAX_TRIES: usize = 1000;
+
+///
+/// The longest label a game can have, in characters.
+///
+pub const MAX_LABEL_LENGTH: usize = 40;
+
+///
+/// How many more times a secret is picked when it came up
+/// earlier in the session, see `State::fresh_pegs_with`.
+///
+pub const SECRET_REROLLS: usize = 20;
+
+///
+/// Tidies up a label typed by the player: control characters
+/// (Which could mess with the terminal when the label is shown
+/// later) are taken out, the ends are trimmed and anything past
+/// `MAX_LABEL_LENGTH` is cut off. A label with nothing left in
+/// it is no label at all, so this gives `None`.
+///
+pub fn clean_label(text: &str) -> Option<String> {
+    let cleaned = text.chars().filter(|x| !x.is_control()).collect::<String>();
+    let cleaned = cleaned
+        .trim()
+        .chars()
+        .take(MAX_LABEL_LENGTH)
+        .collect::<String>();
+    // Cutting it off might leave a space at the end
+    let cleaned = cleaned.trim_end();
+    if cleaned.is_empty() {
+        None
+    } else {
+        Some(cleaned.to_string())
+    }
+}
+
+///
+/// What `State` calls when a game is won or lost, with a summary
+/// of the game which just ended (See `State::ended`).
+///
+pub type OnGameOver<'a, P = Colour> = Box<dyn FnMut(&GameSummary<P>) + 'a>;
+
+///
+/// We make a state struct because we may want to
+/// expose a gui layer, which isn't able to access
+/// variables local to functions.
+///
+/// This has a single generic parameter `'a` which is
+/// a lifetime. I can't summarize lifetimes in this
+/// short project, but it should suffice to say it's
+/// rust's gimmick to make it so fast and safe (As fast
+/// as C++ and safe as GC languages as Java).
+///
+/// There's a nice entry in the rust book should you
+/// want to explore more on this:
+/// https://doc.rust-lang.org/book/ch10-03-lifetime-syntax.html
+///
+pub struct State<'a, P = Colour> {
+    ///
+    /// The game being played right now: its secret, the guess
+    /// being entered and the guesses finished so far, each with
+    /// the feedback it got (See `rules::Game`). Everything else
+    /// here is what goes around it.
+    ///
+    /// This and `previous_games` can only be read from outside
+    /// (See `history` and `games`), since a guess added or taken
+    /// away here wouldn't be counted against the tries left.
+    ///
+    game: Game<P, Guess<P>>,
+    ///
+    /// The previous games we've played (IE the previous
+    /// states of `pegs`), along with how they went.
+    ///
+    previous_games: Vec<GameRecord<P>>,
+    ///
+    /// The settings we're playing with, such as the number
+    /// of pegs.
+    ///
+    config: GameConfig,
+    ///
+    /// When the current game started. An `Instant` is a point
+    /// in time which can only be compared with other instants,
+    /// which is all we need to measure how long a game took.
+    ///
+    started: Instant,
+    ///
+    /// The positions of the secret which have been revealed
+    /// this game, see `GameConfig::reveal_every`.
+    ///
+    revealed: Vec<usize>,
+    ///
+    /// The hint points spent this game, and how many of the
+    /// `revealed` positions were paid for with them (Rather than
+    /// given away by `reveal_every`).
+    ///
+    hint_points_spent: usize,
+    hint_reveals: usize,
+    ///
+    /// The feedback for the most recently finished guess, even
+    /// if it was the one which ended a game.
+    ///
+    last_scored: Option<Feedback>,
+    ///
+    /// The summary of the last game to end, with its secret,
+    /// since by the time anyone can ask the next one has started.
+    ///
+    last_game: Option<GameSummary<P>>,
+    ///
+    /// Whether the secret for this game is one which came up
+    /// before this session, because `fresh_pegs_with` ran out of
+    /// rerolls.
+    ///
+    repeated_secret: bool,
+    ///
+    /// Where every random choice comes from, the secrets and
+    /// the positions revealed. Keeping one for the whole session
+    /// (Rather than asking for a new one each time) means that a
+    /// seeded state, see `new_seeded`, makes the same secrets
+    /// game after game.
+    ///
+    /// `RngCore` is the trait every random number generator
+    /// implements, so this can be any of them, see `with_rng`.
+    /// It's boxed like the observers are, for the same
+    /// reason: `State` would otherwise need a type parameter for
+    /// it, which everything naming a `State` would have to pass
+    /// along.
+    ///
+    rng: Box<dyn RngCore + 'a>,
+    ///
+    /// Everything which is told about each guess and game, in
+    /// the order they were added. The first is always the `win`
+    /// and `lose` callbacks the state was made with (See
+    /// `observer::Callbacks`), so a loss is still reported before
+    /// the next game's secret is picked.
+    ///
+    observers: Vec<Box<dyn GameObserver<P> + 'a>>,
+    ///
+    /// Where the messages the state has for the player go, like
+    /// the feedback for each guess. This is usually a
+    /// `SilentOutput`, since a frontend does the talking.
+    ///
+    output: Box<dyn GameOutput + 'a>,
+    ///
+    /// Whoever answers the guesses, see `codemaker.rs`. `pegs`
+    /// is always what this last said the secret is, so that
+    /// everything else can carry on using it.
+    ///
+    maker: Box<dyn CodeMaker<P> + 'a>,
+}
+
+///
+/// Implementing the state
+///
+impl<'a> State<'a> {
+    ///
+    /// A `new` function, akin to a constructor, will
+    /// create a new `State` given some configuration
+    /// parameters.
+    ///
+    /// Settings which can't make a code give back why instead
+    /// (See `GameConfig::validate`), and nothing is printed
+    /// either way: it's up to the caller to say what went wrong,
+    /// if anything should be said at all.
+    ///
+    /// From outside this module, `StateBuilder` does the same
+    /// with each argument named.
+    ///
+    fn new(
+        config: GameConfig,
+        //
+        // These functions use fancy talk in rust to say that
+        // this function (new) is generic over some type which
+        // implements the function traits. Kind of like this:
+        // ```java
+        // public static<T> void new(...) where T: MyInterface
+        // ```
+        //
+        // `FnMut` is the one of those traits which lets the
+        // function change what it captured, like a closure
+        // which counts up the wins in a variable outside it.
+        //
+        win: impl FnMut(&GameSummary) + 'a,
+        lose: impl FnMut(&GameSummary) + 'a,
+        output: impl GameOutput + 'a,
+    ) -> Result<Self, ConfigError> {
+        Self::build(
+            config,
+            win,
+            lose,
+            Box::new(output),
+            Box::new(rand::thread_rng()),
+            Box::new(RandomCodeMaker::new()),
+        )
+    }
+
+    ///
+    /// Like `default_game`, but with `config`, and with the
+    /// secrets picked from random numbers seeded with `seed`.
+    /// Two states made with the same seed and settings have the
+    /// same secrets, game after game, which makes a game easy
+    /// to play again or to check in a test. This is an error if
+    /// `config` isn't valid.
+    ///
+    pub fn new_seeded(
+        config: GameConfig,
+        seed: u64,
+        win: impl FnMut(&GameSummary) + 'a,
+        lose: impl FnMut(&GameSummary) + 'a,
+    ) -> Result<Self, ConfigError> {
+        Self::with_rng(config, StdRng::seed_from_u64(seed), win, lose)
+    }
+
+    ///
+    /// Like `new_seeded`, but with every random choice made by
+    /// `rng`, whichever generator that is. A server might want
+    /// one which is cryptographically secure (So nobody can work
+    /// out the next secret from the last few), and a test one
+    /// which gives back numbers it was told to.
+    ///
+    pub fn with_rng(
+        config: GameConfig,
+        rng: impl RngCore + 'a,
+        win: impl FnMut(&GameSummary) + 'a,
+        lose: impl FnMut(&GameSummary) + 'a,
+    ) -> Result<Self, ConfigError> {
+        Self::build(
+            config,
+            win,
+            lose,
+            Box::new(SilentOutput),
+            Box::new(rng),
+            Box::new(RandomCodeMaker::new()),
+        )
+    }
+
+    ///
+    /// A playable game with the default settings, which only
+    /// needs to be told what to do when the player wins or loses:
+    /// ```
+    /// let mut state = State::default_game(
+    ///     |game| println!("Won in {} tries", game.attempts_made),
+    ///     |game| println!("Lost, the code was {:?}", game.secret),
+    /// );
+    /// ```
+    /// Terminal output is turned off, use `summary` to find out
+    /// how the game is going.
+    ///
+    pub fn default_game(
+        win: impl FnMut(&GameSummary) + 'a,
+        lose: impl FnMut(&GameSummary) + 'a,
+    ) -> Self {
+        // The defaults are always valid, so this can't fail
+        Self::new(GameConfig::default(), win, lose, SilentOutput)
+            .expect("The default settings should always be valid")
+    }
+
+    ///
+    /// Pays for `hint` out of this game's hint points and gives
+    /// the answer, or says why it can't be had (See `hints.rs`).
+    /// Nothing is spent on a hint which fails.
+    ///
+    pub fn take_hint(&mut self, hint: Hint) -> Result<HintAnswer, String> {
+        hints::check(self.config.hint_points, self.hint_points_spent, hint)?;
+        let answer = match hint {
+            Hint::Reveal => {
+                let position = self
+                    .reveal_hidden()
+                    .ok_or("Every position is already revealed")?;
+                self.hint_reveals += 1;
+                HintAnswer::Revealed {
+                    position,
+                    colour: self.game.secret()[position],
+                }
+            }
+            Hint::Suggest => HintAnswer::Suggestion(
+                solver::next_guess(&self.summary())
+                    .ok_or("No code fits the feedback so far, so there's nothing to suggest")?,
+            ),
+            Hint::Count(colour) => {
+                let count = |code: &[Colour]| code.iter().filter(|&&x| x == colour).count();
+                let answer = count(self.game.secret());
+                self.maker.narrow(&|code| count(code) == answer);
+                HintAnswer::Count {
+                    colour,
+                    count: answer,
+                }
+            }
+        };
+        self.hint_points_spent += hint.cost();
+        Ok(answer)
+    }
+}
+
+///
+/// Everything else, which is the same whatever the pegs are. A
+/// `State` of colours is one of these too, so these are all
+/// there for it as well.
+///
+impl<'a, P: Symbol> State<'a, P> {
+    //
+    // `new`, with the random numbers coming from `rng` and the
+    // guesses answered by `maker`.
+    //
+    fn build(
+        config: GameConfig,
+        win: impl FnMut(&GameSummary<P>) + 'a,
+        lose: impl FnMut(&GameSummary<P>) + 'a,
+        output: Box<dyn GameOutput + 'a>,
+        mut rng: Box<dyn RngCore + 'a>,
+        mut maker: Box<dyn CodeMaker<P> + 'a>,
+    ) -> Result<Self, ConfigError> {
+        //Check if there is a problem with our config, returning
+        //early with it if there is
+        config.validate_for::<P>()?;
+        maker.new_game(&config, Self::generate_new_pegs(config, &mut rng));
+        let game = Game::new(maker.reveal(), config.max_tries);
+        Ok(
+            // This is an inline constructor, we just specify
+            // each field's value on declaration
+            Self {
+                game,
+                previous_games: Vec::new(),
+                config,
+                revealed: Vec::new(),
+                hint_points_spent: 0,
+                hint_reveals: 0,
+                last_scored: None,
+                last_game: None,
+                repeated_secret: false,
+                rng,
+                started: Instant::now(),
+                observers: vec![Box::new(observer::Callbacks::new(win, lose))],
+                output,
+                maker,
+            },
+        )
+    }
+
+    ///
+    /// This is an associated function which isn't run on anything. This
+    /// is similar to Java's `static` methods except those have access to
+    /// `static` state, which could be present in a class. These on the
+    /// other hand cannot mutate anything outside of the function.
+    ///
+    /// From within this `impl` block, you call this as `Self::generate_new_pegs()`
+    ///
+    /// The random numbers come from `rng`, so that a state can
+    /// pass its own in.
+    ///
+    fn generate_new_pegs(config: GameConfig, rng: &mut impl Rng) -> Vec<P> {
+        Self::generate_pegs_with(config, rng)
+    }
+
+    ///
+    /// A secret like `generate_pegs_with` makes, but which isn't
+    /// the secret of any game in `played`, if that's turned on
+    /// with `GameConfig::fresh_secrets`. A repeat is picked again
+    /// up to `SECRET_REROLLS` times, and after that it's kept
+    /// anyway (With `true` next to it, so the player can be told)
+    /// rather than looping for ever. That only really happens
+    /// once nearly every code has come up, like with 2 pegs and
+    /// 3 colours.
+    ///
+    fn fresh_pegs_with(
+        config: GameConfig,
+        played: &[GameRecord<P>],
+        rng: &mut impl Rng,
+    ) -> (Vec<P>, bool) {
+        let seen = |pegs: &[P]| played.iter().any(|x| x.secret == pegs);
+        let mut pegs = Self::generate_pegs_with(config, rng);
+        if !config.fresh_secrets {
+            return (pegs, false);
+        }
+        for _ in 0..SECRET_REROLLS {
+            if !seen(&pegs) {
+                return (pegs, false);
+            }
+            pegs = Self::generate_pegs_with(config, rng);
+        }
+        let repeated = seen(&pegs);
+        (pegs, repeated)
+    }
+
+    ///
+    /// How `generate_new_pegs` makes a secret. Given a seeded
+    /// `rng`, this makes the same secrets every time, which is
+    /// what `mastermind audit --seed` relies on.
+    ///
+    /// A code with too many of one colour (See
+    /// `GameConfig::max_per_colour`), or too few colours, is
+    /// thrown away and picked again. That leaves the codes which
+    /// are allowed exactly as likely as each other, where
+    /// filling the pegs in one at a time without a colour once
+    /// it's used up wouldn't: the last few pegs would be more
+    /// likely to be the colours which hadn't come up yet.
+    ///
+    fn generate_pegs_with(config: GameConfig, rng: &mut impl Rng) -> Vec<P> {
+        if config.colour_weights.is_some() || config.must_include.contains(&true) {
+            return Self::generate_weighted(config, rng);
+        }
+        loop {
+            let pegs = Self::generate_uniform(config, rng);
+            if config.over_limit(&pegs).is_none() && config.enough_colours(&pegs) {
+                return pegs;
+            }
+        }
+    }
+
+    //
+    // `generate_pegs_with` for settings where every colour is as
+    // likely as the others, without the limit on each colour.
+    //
+    fn generate_uniform(config: GameConfig, rng: &mut impl Rng) -> Vec<P> {
+        let size = config.pegs;
+        // `SliceRandom` adds picking and shuffling to slices. Doing
+        // it with `rng.gen::<usize>() % len` would be a little more
+        // likely to pick the first few items than the rest, unless
+        // `len` happens to divide the number of `usize`s evenly.
+        let mut palette = config.symbols::<P>();
+        if let Some(colours) = config.max_distinct_colours {
+            // Pick which colours are in use first, `validate` made
+            // sure there are enough.
+            palette = palette.choose_multiple(rng, colours).copied().collect();
+            if config.allow_duplicates && colours <= size {
+                // We announce exactly how many colours there are, so
+                // each of them gets a spot before the rest are filled
+                // in, and then they're all shuffled around.
+                let mut pegs = palette.clone();
+                while pegs.len() < size {
+                    pegs.push(palette[rng.gen_range(0, palette.len())]);
+                }
+                pegs.shuffle(rng);
+                return pegs;
+            }
+        }
+        // Otherwise every peg is picked the same way, which is the
+        // same way `rules::random_code` picks it for anything else.
+        crate::rules::random_code(&palette, size, config.allow_duplicates, rng)
+    }
+
+    //
+    // `generate_pegs_with` for settings with colour weights or
+    // colours which must be included. The colours which must be
+    // in the code go in first, the rest of the pegs are filled in
+    // by weight, and then everything is shuffled so that the
+    // required colours can be anywhere.
+    //
+    // Colours which are used up (See `max_per_colour`) are left
+    // out of the rest of the pegs here, and enough different
+    // colours are put in first, instead of picking the whole
+    // code again. A very heavy colour would almost always
+    // go over the limit, so picking again could take for ever.
+    //
+    fn generate_weighted(config: GameConfig, rng: &mut impl Rng) -> Vec<P> {
+        let weights = config.colour_weights.unwrap_or([1; PALETTE_SIZE]);
+        // A blank has no weight of its own, so it's as likely
+        // as a colour with a weight of 1.
+        let weight = |x: P| x.index().and_then(|i| weights.get(i)).copied().unwrap_or(1);
+        // Takes a colour out of `palette` at random by weight.
+        // `validate` made sure every weight is more than zero, and
+        // this is never asked to pick from nothing, so the
+        // `WeightedIndex` can always be made.
+        let mut take = |palette: &mut Vec<P>| {
+            let index = WeightedIndex::new(palette.iter().map(|&x| weight(x)))
+                .expect("Colour weights should have been validated");
+            palette.remove(rng.sample(index))
+        };
+        let mut required = config.required::<P>();
+        let mut palette = config.symbols::<P>();
+        if let Some(colours) = config.max_distinct_colours {
+            let mut chosen = required.clone();
+            palette.retain(|x| !chosen.contains(x));
+            while chosen.len() < colours {
+                chosen.push(take(&mut palette));
+            }
+            palette = chosen;
+            // Every announced colour is in the code, like in
+            // `generate_uniform`.
+            if config.allow_duplicates && colours <= config.pegs {
+                required = palette.clone();
+            }
+        }
+        let mut pegs = required;
+        if config.allow_duplicates {
+            // Colours which aren't in yet go in until there are
+            // enough different ones, `validate` made sure there
+            // are that many to pick from
+            while !config.enough_colours(&pegs) {
+                let mut unused = palette.clone();
+                unused.retain(|x| !pegs.contains(x));
+                pegs.push(take(&mut unused));
+            }
+            while pegs.len() < config.pegs {
+                // Taking from a copy puts the colour back again
+                let mut open = palette.clone();
+                if let Some(most) = config.max_per_colour {
+                    open.retain(|x| pegs.iter().filter(|y| *y == x).count() < most);
+                }
+                pegs.push(take(&mut open));
+            }
+        } else {
+            palette.retain(|x| !pegs.contains(x));
+            while pegs.len() < config.pegs {
+                pegs.push(take(&mut palette));
+            }
+        }
+        pegs.shuffle(rng);
+        pegs
+    }
+
+    ///
+    /// Returns the feedback for a guess this game.
+    ///
+    /// `matching(Some(idx))` is the feedback the earlier guess
+    /// `idx` got (Counting from zero), or
+    /// `MastermindError::NoSuchAttempt` if there wasn't one.
+    ///
+    /// `matching(None)` scores the guess being entered, without
+    /// finishing it, or gives `MastermindError::GuessTooShort`
+    /// if it isn't full yet.
+    ///
+    fn matching(&self, idx: Option<usize>) -> Result<Feedback, MastermindError<P>> {
+        match idx {
+            // Worked out once, when the guess was finished
+            Some(idx) => self
+                .game
+                .attempts()
+                .get(idx)
+                .map(|x| x.feedback)
+                .ok_or(MastermindError::NoSuchAttempt(idx)),
+            None => self
+                .game
+                .score_buffer()
+                .ok_or(MastermindError::GuessTooShort {
+                    got: self.game.buffer().len(),
+                    needed: self.config.pegs,
+                }),
+        }
+    }
+
+    ///
+    /// Pushes a colour into our buffered input, returning what
+    /// happened: nothing yet, or the guess it finished and
+    /// whether that ended the game.
+    ///
+    /// Without duplicates, a colour which is already in the guess
+    /// is turned away on its own, leaving the rest of the guess
+    /// so that the player can carry on with another colour. So is
+    /// one which there's already as many of as `max_per_colour`
+    /// allows, and a blank in a game without them.
+    ///
+    /// With `GameConfig::confirm_guesses`, a full guess waits in
+    /// the buffer for `commit_guess`, and no more colours fit in
+    /// until then.
+    ///
+    pub fn input_buffer(&mut self, value: P) -> Result<TurnOutcome<P>, MastermindError<P>> {
+        let so_far = || self.game.buffer().to_vec();
+        if self.game.is_full() {
+            return Err(MastermindError::GuessFull { so_far: so_far() });
+        }
+        if Some(value) == P::blank() && !self.config.allow_blanks {
+            return Err(MastermindError::BlankNotAllowed { so_far: so_far() });
+        }
+        if !self.config.in_play(value) {
+            return Err(MastermindError::NotInPlay(value));
+        }
+        if !self.config.allow_duplicates && self.game.buffer().contains(&value) {
+            return Err(MastermindError::DuplicateNotAllowed {
+                colour: value,
+                so_far: so_far(),
+            });
+        }
+        let already = self.game.buffer().iter().filter(|&&x| x == value).count();
+        if let Some(most) = self.config.max_per_colour.filter(|&x| already >= x) {
+            return Err(MastermindError::TooManyOfColour {
+                colour: value,
+                most,
+                so_far: so_far(),
+            });
+        }
+        self.game
+            .push(value)
+            .expect("The guess isn't full, and a game is never left over");
+        if self.game.is_full() && !self.config.confirm_guesses {
+            self.finish_try()
+        } else {
+            Ok(TurnOutcome::Pending)
+        }
+    }
+
+    ///
+    /// `input_buffer`, but giving back everything that happened
+    /// (See `GameEvent`) instead of only how it ended up. This
+    /// is for a frontend which would rather go through a list
+    /// after each colour than register an observer.
+    ///
+    pub fn step(&mut self, input: P) -> Result<Vec<GameEvent<P>>, MastermindError<P>> {
+        let position = self.game.buffer().len();
+        let outcome = self.input_buffer(input)?;
+        let mut events = vec![GameEvent::PegAccepted { position }];
+        // A guess which ended the game has been scored, but the
+        // outcome only says how the game went.
+        let scored = self
+            .last_scored
+            .map(|feedback| GameEvent::GuessEvaluated { feedback });
+        match outcome {
+            TurnOutcome::Pending => {}
+            TurnOutcome::RoundComplete { feedback } => {
+                events.push(GameEvent::GuessEvaluated { feedback })
+            }
+            TurnOutcome::Won { .. } => {
+                events.extend(scored);
+                events.push(GameEvent::GameWon);
+                events.push(GameEvent::NewGameStarted);
+            }
+            TurnOutcome::Lost { secret } => {
+                events.extend(scored);
+                events.push(GameEvent::GameLost { secret });
+                events.push(GameEvent::NewGameStarted);
+            }
+        }
+        Ok(events)
+    }
+
+    ///
+    /// Scores the guess waiting in the buffer, for games with
+    /// `GameConfig::confirm_guesses`. A guess which isn't full yet
+    /// is left alone, so that the player can finish it.
+    ///
+    pub fn commit_guess(&mut self) -> Result<TurnOutcome<P>, MastermindError<P>> {
+        if !self.game.is_full() {
+            return Err(MastermindError::GuessTooShort {
+                got: self.game.buffer().len(),
+                needed: self.config.pegs,
+            });
+        }
+        self.finish_try()
+    }
+
+    ///
+    /// Takes the last colour back out of the guess being
+    /// entered, giving back what it was, or `None` if there was
+    /// nothing to take back.
+    ///
+    pub fn undo_input(&mut self) -> Option<P> {
+        self.game.pop()
+    }
+
+    ///
+    /// Parse and push a whole string as an input into the buffer.
+    /// This uses `step` on every colour in the string (See
+    /// `parse_colours`), once all of them have been read, and
+    /// gives back the events from all of them, in order.
+    /// An error says where in `text` it went wrong, and how much
+    /// of it was used before then.
+    ///
+    /// With `GameConfig::strict_input`, a line with more colours
+    /// than the guess has room left for is an error instead, and
+    /// nothing from it is buffered.
+    ///
+    /// A line which is exactly one guess, typed with nothing of
+    /// one entered yet, is checked as a whole `Guess` first, so
+    /// a guess which isn't allowed is turned away without any of
+    /// it being left behind in the buffer.
+    ///
+    pub fn push_string_input(&mut self, text: &str) -> Result<Vec<GameEvent<P>>, InputError<P>> {
+        let error = |state: &Self, error, position, events: &[GameEvent<P>]| InputError {
+            error,
+            position,
+            guesses_finished: events
+                .iter()
+                .filter(|x| matches!(x, GameEvent::GuessEvaluated { .. }))
+                .count(),
+            buffered: state.buffered().len(),
+            game_ended: events.iter().any(GameEvent::ends_game),
+        };
+        let colours =
+            locate_symbols(text).map_err(|(position, e)| error(self, e, Some(position), &[]))?;
+        self.check_line(colours.len())
+            .map_err(|e| error(self, e, None, &[]))?;
+        self.check_whole_guess(&colours)
+            .map_err(|(position, e)| error(self, e, position, &[]))?;
+        let mut events = Vec::new();
+        //We can accept strings longer than the max size and just
+        //keep processing them to enter multiple tries at the same
+        //time.
+        for (position, colour) in colours {
+            match self.step(colour) {
+                Ok(x) => events.extend(x),
+                Err(e) => return Err(error(self, e, Some(position), &events)),
+            }
+        }
+        Ok(events)
+    }
+
+    ///
+    /// Whether a line of `count` colours can be entered, which
+    /// is always true unless `GameConfig::strict_input` is on.
+    /// Anything which enters a line a colour at a time, rather
+    /// than with `push_string_input`, checks this first.
+    ///
+    pub fn check_line(&self, count: usize) -> Result<(), MastermindError<P>> {
+        let room = self.config.pegs - self.game.buffer().len();
+        if self.config.strict_input && count > room {
+            return Err(MastermindError::GuessTooLong {
+                got: count,
+                max: room,
+            });
+        }
+        Ok(())
+    }
+
+    //
+    // Checks `colours`, read from a line with where each starts,
+    // as a whole `Guess` if they're all of the next one, see
+    // `push_string_input`. Gives back where in the line the colour
+    // at fault is, if it's down to one.
+    //
+    fn check_whole_guess(
+        &self,
+        colours: &[(usize, P)],
+    ) -> Result<(), (Option<usize>, MastermindError<P>)> {
+        if !self.game.buffer().is_empty() || colours.len() != self.config.pegs {
+            return Ok(());
+        }
+        let pegs = colours.iter().map(|&(_, x)| x).collect::<Vec<_>>();
+        match Guess::for_config(pegs.clone(), self.config) {
+            Ok(_) => Ok(()),
+            Err(e) => Err((e.position(&pegs).map(|at| colours[at].0), e.into())),
+        }
+    }
+
+    ///
+    /// Enters a whole guess at once, replacing anything half
+    /// entered. Unlike `push_string_input`, a guess which isn't
+    /// allowed is turned away before any of it is buffered. This
+    /// gives back how the game ended, if this guess ended it.
+    ///
+    /// `guess` is checked against this game's settings even if it
+    /// was already checked, since it could have been made for
+    /// other ones.
+    ///
+    pub fn enter_guess(&mut self, guess: &Guess<P>) -> Result<Option<Outcome>, MastermindError<P>> {
+        let guess = Guess::for_config(guess.to_vec(), self.config)?;
+        self.game.clear();
+        let mut outcome = TurnOutcome::Pending;
+        for &colour in guess.iter() {
+            outcome = self.input_buffer(colour)?;
+        }
+        // A whole guess entered at once counts as confirmed
+        if self.config.confirm_guesses {
+            outcome = self.commit_guess()?;
+        }
+        Ok(match outcome {
+            TurnOutcome::Won { .. } => Some(Outcome::Won),
+            TurnOutcome::Lost { .. } => Some(Outcome::Lost),
+            _ => None,
+        })
+    }
+
+    ///
+    /// Decides to either win the game, or not, or keep going.
+    ///
+    fn finish_try(&mut self) -> Result<TurnOutcome<P>, MastermindError<P>> {
+        if let Some(idx) = self.repeat_of(self.game.buffer()) {
+            // Emptied so that the next colour starts a new guess
+            // instead of piling up behind this one.
+            self.game.clear();
+            let feedback = self.matching(Some(idx))?;
+            return Err(MastermindError::RepeatedGuess {
+                attempt: idx,
+                feedback,
+            });
+        }
+        // A guess which isn't allowed is emptied out too, or every
+        // colour typed after it would pile up behind it and the
+        // guess would never be full again. Checking it before
+        // anything else also means it can't use up the last try.
+        // `input_buffer` already turns away most of what isn't
+        // allowed, one colour at a time, but anything which filled
+        // the buffer some other way is caught here.
+        let pegs = self
+            .game
+            .take_buffer()
+            .expect("Only a full guess is finished");
+        let guess = Guess::for_config(pegs, self.config)?;
+        let feedback = self.maker.feedback(&guess);
+        // Whoever's answering could have changed their mind about
+        // the secret, as long as it still gives the same answers.
+        self.game.set_secret(self.maker.reveal());
+        let returns;
+        self.last_scored = Some(feedback);
+        if !feedback.is_win(self.config.pegs) {
+            self.output.feedback(&feedback);
+        }
+        let count = self.game.attempts().len() + 1;
+        for observer in &mut self.observers {
+            observer.on_guess(&guess, feedback, count);
+        }
+        // Kept even when it ends the game, so that the game's
+        // record has every guess and counts them all.
+        let outcome = self
+            .game
+            .record(guess, feedback)
+            .expect("A game is never left over once it's ended");
+        if outcome == Some(Outcome::Won) {
+            // Tell everyone watching, including the function we
+            // were given at the start for a win.
+            let attempts = self.game.attempts().len();
+            let summary = self.ended(GamePhase::Won);
+            for observer in &mut self.observers {
+                observer.on_win(attempts);
+                observer.on_game_over(&summary);
+            }
+            self.last_game = Some(summary);
+            self.record_game(Outcome::Won);
+            returns = TurnOutcome::Won {
+                attempts: self.game.attempts().len(),
+            };
+            self.reset();
+        } else {
+            if outcome == Some(Outcome::Lost) {
+                let summary = self.ended(GamePhase::Lost);
+                for observer in &mut self.observers {
+                    observer.on_lose(self.game.secret());
+                    observer.on_game_over(&summary);
+                }
+                self.last_game = Some(summary);
+                self.record_game(Outcome::Lost);
+                returns = TurnOutcome::Lost {
+                    secret: self.game.secret().to_vec(),
+                };
+                self.reset();
+            } else {
+                if let Some(every) = self.config.reveal_every {
+                    if self.game.attempts().len().is_multiple_of(every) {
+                        self.reveal_position();
+                    }
+                }
+                returns = TurnOutcome::RoundComplete { feedback };
+            }
+        }
+        Ok(returns)
+    }
+
+    ///
+    /// Which earlier attempt this game `guess` is the same as, if
+    /// repeats are being turned away (See
+    /// `GameConfig::block_repeats`). Guesses which get this far
+    /// are rejected with a message saying so, this is for
+    /// anything which needs to tell that apart from other
+    /// reasons a guess wasn't allowed.
+    ///
+    pub fn repeat_of(&self, guess: &[P]) -> Option<usize> {
+        if !self.config.block_repeats {
+            return None;
+        }
+        self.game.attempts().iter().position(|x| *x.guess == *guess)
+    }
+
+    ///
+    /// Reveals a random position of the secret which hasn't
+    /// been revealed yet, if there are any left.
+    ///
+    fn reveal_position(&mut self) {
+        let position = match self.reveal_hidden() {
+            Some(x) => x,
+            None => return,
+        };
+        self.output.message(&format!(
+            "Here's some help: position {} is {:?}",
+            position + 1,
+            self.game.secret()[position]
+        ));
+    }
+
+    //
+    // Picks one of the hidden positions and reveals it, giving
+    // back which one it was.
+    //
+    fn reveal_hidden(&mut self) -> Option<usize> {
+        let hidden = (0..self.game.size())
+            .filter(|x| !self.revealed.contains(x))
+            .collect::<Vec<_>>();
+        // `choose` is `None` if there's nothing to choose from
+        let position = *hidden.choose(&mut self.rng)?;
+        let colour = self.game.secret()[position];
+        self.maker.narrow(&|code| code[position] == colour);
+        self.revealed.push(position);
+        self.revealed.sort_unstable();
+        Some(position)
+    }
+
+    fn record_game(&mut self, outcome: Outcome) {
+        let duration = self.started.elapsed();
+        let speed_bonus = match outcome {
+            Outcome::Won => speed::bonus(
+                self.config
+                    .max_tries
+                    .map(|x| x.saturating_sub(self.game.attempts().len())),
+                Some(duration),
+                // Reveals paid for with hint points are charged
+                // for through `hint_points_spent` instead
+                self.revealed.len() - self.hint_reveals,
+                self.hint_points_spent,
+            ),
+            Outcome::Lost => 0,
+        };
+        self.previous_games.push(GameRecord {
+            secret: self.game.secret().to_vec(),
+            attempts: self.game.attempts().len(),
+            outcome,
+            duration: Some(duration),
+            reveals: self.revealed.len(),
+            hardcore: self.config.hardcore,
+            memory: self.config.memory,
+            settings: Some(self.config),
+            speed_bonus,
+            hint_points: self.hint_points_spent,
+            label: None,
+            guesses: self.game.attempts().to_vec(),
+        });
+    }
+
+    fn reset(&mut self) {
+        self.revealed.clear();
+        self.hint_points_spent = 0;
+        self.hint_reveals = 0;
+        self.started = Instant::now();
+        let (pegs, repeated) =
+            Self::fresh_pegs_with(self.config, &self.previous_games, &mut self.rng);
+        self.maker.new_game(&self.config, pegs);
+        self.game.restart(self.maker.reveal());
+        self.repeated_secret = repeated;
+        for observer in &mut self.observers {
+            observer.on_new_game(&self.config);
+        }
+    }
+
+    //
+    // Starts the code maker on a game with `secret` drawn for it,
+    // which is what the game's secret becomes for the usual random
+    // one. This is how the demo and a save set the secret they
+    // want.
+    //
+    fn set_secret(&mut self, secret: Vec<P>) {
+        self.maker.new_game(&self.config, secret);
+        self.game.set_secret(self.maker.reveal());
+    }
+
+    ///
+    /// Adds something to be told about every guess and game from
+    /// now on, see `observer.rs`.
+    ///
+    pub fn add_observer(&mut self, observer: impl GameObserver<P> + 'a) {
+        self.observers.push(Box::new(observer));
+    }
+
+    ///
+    /// Whether this game's secret already came up this session,
+    /// because nearly every secret these settings allow has (See
+    /// `GameConfig::fresh_secrets`).
+    ///
+    pub fn repeated_secret(&self) -> bool {
+        self.repeated_secret
+    }
+
+    ///
+    /// Gives up on the current game, which counts as a loss, and
+    /// starts the next one.
+    ///
+    pub fn forfeit(&mut self) {
+        let summary = self.ended(GamePhase::Lost);
+        for observer in &mut self.observers {
+            observer.on_lose(self.game.secret());
+            observer.on_game_over(&summary);
+        }
+        self.last_game = Some(summary);
+        self.record_game(Outcome::Lost);
+        self.reset();
+    }
+
+    ///
+    /// Leaves `paused` out of the time the current game has
+    /// taken, for time spent away from the game (Like in the
+    /// pause menu).
+    ///
+    pub fn pause_clock(&mut self, paused: Duration) {
+        self.started += paused;
+    }
+
+    ///
+    /// The feedback for the last guess which was finished.
+    /// Unlike `summary`, this still has the guess which ended a
+    /// game after the next one has started.
+    ///
+    pub fn last_scored(&self) -> Option<Feedback> {
+        self.last_scored
+    }
+
+    ///
+    /// How many guesses have been finished this game.
+    ///
+    pub fn attempts(&self) -> usize {
+        self.game.attempts().len()
+    }
+
+    ///
+    /// How many more guesses this game has, or `None` if there's
+    /// no limit.
+    ///
+    pub fn remaining_tries(&self) -> Option<usize> {
+        self.game.tries_left()
+    }
+
+    ///
+    /// The colours entered so far for the guess which isn't
+    /// finished yet.
+    ///
+    pub fn buffered(&self) -> &[P] {
+        self.game.buffer()
+    }
+
+    ///
+    /// The guesses finished this game, oldest first.
+    ///
+    /// Handing out a slice (`&[T]`) rather than the `Vec` is
+    /// like returning `Collections.unmodifiableList` in Java,
+    /// except the compiler is the one stopping any changes.
+    ///
+    pub fn history(&self) -> &[Attempt<P>] {
+        self.game.attempts()
+    }
+
+    ///
+    /// Every game finished this session, oldest first.
+    ///
+    pub fn games(&self) -> &[GameRecord<P>] {
+        &self.previous_games
+    }
+
+    ///
+    /// The summary of the last game to end this session, the
+    /// same one the `win` or `lose` callback was given, or `None`
+    /// before any have.
+    ///
+    pub fn last_game(&self) -> Option<&GameSummary<P>> {
+        self.last_game.as_ref()
+    }
+
+    ///
+    /// Everything this game is being played with.
+    ///
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    ///
+    /// How many pegs the code has.
+    ///
+    pub fn size(&self) -> usize {
+        self.config.pegs
+    }
+
+    ///
+    /// Whether a colour can be in the code more than once.
+    ///
+    pub fn allows_duplicates(&self) -> bool {
+        self.config.allow_duplicates
+    }
+
+    ///
+    /// The secret for this game, which gives the game away.
+    /// This is here for debugging and for tests which need to
+    /// know the answer; a frontend should wait for the game to
+    /// end, when the secret is in its `GameRecord`.
+    ///
+    pub fn reveal_secret(&self) -> &[P] {
+        self.game.secret()
+    }
+
+    ///
+    /// Takes a snapshot of the current game. This copies
+    /// everything out of `self`, so the summary can outlive
+    /// the state it was taken from.
+    ///
+    pub fn summary(&self) -> GameSummary<P> {
+        GameSummary {
+            attempts_made: self.game.attempts().len(),
+            max_tries: self.config.max_tries,
+            phase: GamePhase::InProgress,
+            last_feedback: self.game.attempts().last().map(|x| x.feedback),
+            settings: self.config,
+            revealed: self
+                .revealed
+                .iter()
+                .map(|&x| (x, self.game.secret()[x]))
+                .collect(),
+            hint_points_left: match self.config.hint_points {
+                0 => None,
+                x => Some(x.saturating_sub(self.hint_points_spent)),
+            },
+            guesses: self
+                .game
+                .attempts()
+                .iter()
+                .map(|x| (x.guess.to_vec(), Some(x.feedback)))
+                .collect(),
+            game_index: self.previous_games.len(),
+            secret: None,
+        }
+    }
+
+    //
+    // The summary of the game which is just ending, as `phase`,
+    // with the secret in it. This has to be taken before the
+    // game is recorded and reset, or it'd be the next game's.
+    //
+    fn ended(&self, phase: GamePhase) -> GameSummary<P> {
+        GameSummary {
+            phase,
+            secret: Some(self.game.secret().to_vec()),
+            ..self.summary()
+        }
+    }
+}
+
+///
+/// The banner and rules, printed at the start and from the
+/// pause menu.
+///
+// There are three ways to write a string in rust,
+// "this way", r#"this way"#, and r"this way".
+// The first one is your standard string with escape
+// sequences like \n, etc. The second one is to ignore
+// all characters between the #"s and just take them
+// as if they were text. r"" text is a byte array literal
+// instead of a string.
+const RULES: &str = r#"
+    ~~~~ Mastermind ~~~~
+Rules: A set of pegs from the
+following colours are selected:
+   ┏━━━━━━┳━━━━━━┳━━━━━━┳━━━━━━┓
+   ┃Orange┃Yellow┃ Red  ┃Purple┃
+   ┣━━━━━━╋━━━━━━╋━━━━━━╋━━━━━━┫
+   ┃ Blue ┃Green ┃White ┃ Pink ┃
+   ┗━━━━━━┻━━━━━━┻━━━━━━┻━━━━━━┛
+Purple and pink are only in
+play with more than six colours.
+The player takes guesses at
+the selected colours, and is
+given the number of pegs in a
+correct position (And colour)
+and the number of correct
+colours chosen in an incorrect
+position. Type each colour's
+name, or just its first letter,
+or `pu` and `pi` for purple
+and pink.
+"#;
+
+///
+/// The rules again, for `--accessible`, without the table (Which
+/// a screen reader would read out one line of boxes at a time).
+///
+const PLAIN_RULES: &str = "Mastermind. A secret code of pegs is picked from six \
+colours: orange, yellow, red, blue, green and white, and with more than six \
+colours, purple and pink as well. Take guesses at the code. After each guess \
+you're told how many pegs are the correct colour in the correct position, and how \
+many more are a correct colour in the wrong position. Type each colour's name, or \
+just its first letter, or pu and pi for purple and pink.";
+
+///
+/// Added to the rules when `GameConfig::allow_blanks` is on.
+///
+const BLANK_RULES: &str = "Pegs can also be left blank, which is scored like another \
+colour. Type a blank as `.` or `_`, or as the word blank.";
+
+//
+// The rules, as a table or as sentences, and how blanks work
+// if they're in play.
+//
+fn rules(accessible: bool, blanks: bool) -> String {
+    let rules = if accessible { PLAIN_RULES } else { RULES };
+    if blanks {
+        format!("{}\n{}", rules, BLANK_RULES)
+    } else {
+        rules.to_string()
+    }
+}
+
+///
+/// What the player is asked when it's time for a guess.
+///
+const GUESS_PROMPT: &str = "Enter next colours (or \"status\", \"history\", \"save\", \"menu\") > ";
+
+///
+/// Reads a limit on the number of tries, where "unlimited"
+/// means there isn't one. Zero tries isn't much of a game, so
+/// that gives `None` like anything else we don't understand.
+///
+fn parse_max_tries(text: &str) -> Option<Option<usize>> {
+    match text.trim() {
+        "unlimited" => Some(None),
+        x => match x.parse() {
+            Ok(0) | Err(_) => None,
+            Ok(x) => Some(Some(x)),
+        },
+    }
+}
+
+///
+/// Reads what was given to `--max-tries` into `layer`, which can
+/// be `auto` for a fair limit (See `fair.rs`) as well as anything
+/// `parse_max_tries` knows.
+///
+fn max_tries_flag(layer: &mut SettingsLayer, text: &str) -> Result<(), &'static str> {
+    if text.trim() == "auto" {
+        layer.auto_max_tries = Some(true);
+    } else {
+        layer.max_tries = Some(
+            parse_max_tries(text)
+                .ok_or("`--max-tries` needs a positive number, `unlimited` or `auto`")?,
+        );
+    }
+    Ok(())
+}
+
+///
+/// An answer to "How many tries?", so that it can be asked with
+/// `prompt::ask`, which needs something to implement `FromStr`.
+///
+enum MaxTries {
+    ///
+    /// A fair limit for the settings, see `fair.rs`.
+    ///
+    Auto,
+    Limit(Option<usize>),
+}
+
+impl std::str::FromStr for MaxTries {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.trim() == "auto" {
+            return Ok(MaxTries::Auto);
+        }
+        parse_max_tries(text)
+            .map(MaxTries::Limit)
+            .ok_or("Enter a positive integer, `unlimited` or `auto`.")
+    }
+}
+
+///
+/// An answer to "How many times can one colour be used?", which
+/// is a number or "unlimited", like the limit on tries.
+///
+struct PerColour(Option<usize>);
+
+impl std::str::FromStr for PerColour {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        parse_max_tries(text)
+            .map(PerColour)
+            .ok_or("Enter a positive integer or `unlimited`.")
+    }
+}
+
+///
+/// Reads the colours in a line the player typed. The colours
+/// can be separated by spaces or commas, and each one can be written out
+/// in full (`red`), as the start of its name (`gre`), or as
+/// its first letter. Letters can be run together too, so
+/// `rb green` is three colours. Purple and pink share a first
+/// letter, so they take two (`pu` and `pi`), even in a run of
+/// letters like `rpug`.
+///
+pub fn parse_colours(text: &str) -> Result<Vec<Colour>, MastermindError> {
+    parse_symbols(text)
+}
+
+///
+/// `parse_colours`, for a game whose pegs aren't colours. They're
+/// read the same way, using `Symbol::name` and
+/// `Symbol::from_letter`.
+///
+pub fn parse_symbols<P: Symbol>(text: &str) -> Result<Vec<P>, MastermindError<P>> {
+    locate_symbols(text)
+        .map(|x| x.into_iter().map(|(_, colour)| colour).collect())
+        .map_err(|(_, e)| e)
+}
+
+///
+/// `parse_colours`, but with how many characters into `text`
+/// each colour starts, and where the first one which couldn't
+/// be read is if there is one.
+///
+pub fn locate_colours(text: &str) -> Located {
+    locate_symbols(text)
+}
+
+///
+/// What `locate_colours` gives back: each colour with where it
+/// starts, or where the one which couldn't be read is and why.
+///
+pub type Located<P = Colour> = Result<Vec<(usize, P)>, (usize, MastermindError<P>)>;
+
+///
+/// `locate_colours`, for a game whose pegs aren't colours.
+///
+pub fn locate_symbols<P: Symbol>(text: &str) -> Located<P> {
+    let from_name = |name: &str| {
+        P::all()
+            .iter()
+            .chain(P::blank().as_ref())
+            .copied()
+            .find(|x| x.name() == name)
+    };
+    let mut colours = Vec::new();
+    // `char_indices` counts bytes, but a character can take up
+    // more than one, so characters are counted separately.
+    let separator = |c: char| c.is_whitespace() || c == ',';
+    let mut chars = text.chars().enumerate().peekable();
+    while let Some((start, c)) = chars.next() {
+        if separator(c) {
+            continue;
+        }
+        let mut word = c.to_string();
+        while let Some((_, c)) = chars.next_if(|&(_, c)| !separator(c)) {
+            word.push(c);
+        }
+        let lower = word.to_lowercase();
+        if let Some(colour) = from_name(&lower) {
+            colours.push((start, colour));
+            continue;
+        }
+        let (bad_letter, could_be) = match read_letters(&word, start) {
+            Ok(letters) => {
+                colours.extend(letters);
+                continue;
+            }
+            Err(x) => x,
+        };
+        // The start of a name is only taken if it can't be the
+        // start of any other name, rather than guessing.
+        let starts = P::all()
+            .iter()
+            .copied()
+            .filter(|x| x.name().starts_with(&lower))
+            .collect::<Vec<_>>();
+        match starts.as_slice() {
+            &[colour] => colours.push((start, colour)),
+            // A run of letters which had one that could be more
+            // than one symbol, like the `p` in `rpg`
+            [] if !could_be.is_empty() => {
+                return Err((
+                    bad_letter,
+                    MastermindError::AmbiguousColour {
+                        word: word.chars().skip(bad_letter - start).collect(),
+                        could_be,
+                    },
+                ))
+            }
+            [] => {
+                // Point at the letter, in a long run of them
+                let letter = word
+                    .chars()
+                    .nth(bad_letter - start)
+                    .filter(|_| bad_letter != start);
+                return Err((bad_letter, MastermindError::UnknownColour { word, letter }));
+            }
+            several => {
+                return Err((
+                    start,
+                    MastermindError::AmbiguousColour {
+                        word,
+                        could_be: several.to_vec(),
+                    },
+                ))
+            }
+        }
+    }
+    Ok(colours)
+}
+
+//
+// What `read_letters` gives back, like `Located` but with the
+// symbols a letter could have been when it goes wrong.
+//
+type Letters<P> = Result<Vec<(usize, P)>, (usize, Vec<P>)>;
+
+//
+// Reads `word`, which starts at character `start` of the line,
+// as a run of letters with one symbol for each. A letter which
+// isn't a symbol by itself, like the `p` of purple and pink,
+// is read along with the ones after it until they're the start
+// of only one name, and then as much more of that name as
+// follows, so `rpug` and `rpurpleg` are both red, purple and
+// green. If it goes wrong, this gives back where, and which
+// symbols it could have been if there were several.
+//
+fn read_letters<P: Symbol>(word: &str, start: usize) -> Letters<P> {
+    let letters = word
+        .chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect::<Vec<_>>();
+    let mut symbols = Vec::new();
+    let mut at = 0;
+    while at < letters.len() {
+        if let Some(symbol) = P::from_letter(letters[at]) {
+            symbols.push((start + at, symbol));
+            at += 1;
+            continue;
+        }
+        let mut end = at + 1;
+        let mut could_be = P::all().to_vec();
+        let symbol = loop {
+            let prefix = letters[at..end].iter().collect::<String>();
+            could_be.retain(|x| x.name().starts_with(&prefix));
+            match could_be.as_slice() {
+                [] => return Err((start + at, Vec::new())),
+                &[symbol] => break symbol,
+                _ if end == letters.len() => return Err((start + at, could_be)),
+                _ => end += 1,
+            }
+        };
+        let name = symbol.name().chars().collect::<Vec<_>>();
+        while end < letters.len() && name.get(end - at) == Some(&letters[end]) {
+            end += 1;
+        }
+        symbols.push((start + at, symbol));
+        at = end;
+    }
+    Ok(symbols)
+}
+
+///
+/// Reads a whole code, see `parse_colours`.
+///
+fn parse_code(text: &str, pegs: usize) -> Result<Vec<Colour>, String> {
+    let code = parse_colours(text).map_err(|e| e.to_string())?;
+    if code.len() != pegs {
+        return Err(format!("Enter exactly {} colours", pegs));
+    }
+    Ok(code)
+}
+
+///
+/// A mock main, meant to be copy-pasteable into other places.
+///
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1).peekable();
+    // Commands like `mastermind saves list` do something other
+    // than playing a game.
+    if let Some(command) = args.peek().cloned() {
+        if let Some(result) = commands::run(&command, args.by_ref().skip(1)) {
+            return result;
+        }
+    }
+
+    // `--resume FILE` picks up a saved game instead of asking
+    // how to set up a new one, `--save-format` overrides
+    // the format the `save` command picks from the extension,
+    // and `--save-slot` picks which slot to autosave into.
+    // `--pegs`, `--duplicates`, `--max-tries`,
+    // `--distinct-colours`, `--min-distinct-colours`,
+    // `--max-per-colour`, `--reveal-every`, `--hardcore`,
+    // `--memory`, `--block-repeats`, `--strict-input`,
+    // `--confirm-guesses`, `--hints POINTS`, `--repeat-secrets`,
+    // `--palette-size` and `--blanks` pick settings without
+    // asking, and `--fresh` forgets what was used last.
+    // `--wagers` lets the player bet points on their guesses, and
+    // `--output` picks how the game is shown (See `render.rs`),
+    // `--feedback` how feedback is worded (See `wording.rs`),
+    // `--accessible` plays in words for screen readers, and
+    // `--verbose` ends the session with some numbers about it.
+    let mut resume = None;
+    let mut save_format = None;
+    let mut slot = None;
+    let mut profile_name = None;
+    let mut given = SettingsLayer::default();
+    let mut fresh = false;
+    let mut wagers = false;
+    let mut output = None;
+    let mut accessible = false;
+    let mut verbose = false;
+    let mut feedback = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--resume" => resume = Some(args.next().ok_or("`--resume` needs a file name")?),
+            "--save-format" => {
+                save_format = Some(
+                    args.next()
+                        .ok_or("`--save-format` needs `json` or `binary`")?
+                        .parse::<save::SaveFormat>()?,
+                )
+            }
+            "--save-slot" => {
+                let name = args.next().ok_or("`--save-slot` needs a name")?;
+                storage::validate_slot_name(&name)?;
+                slot = Some(name);
+            }
+            "--profile" => profile_name = Some(args.next().ok_or("`--profile` needs a name")?),
+            "--pegs" => {
+                let pegs = args.next().ok_or("`--pegs` needs a number")?;
+                // One more peg fits with blanks, which `validate`
+                // checks once every setting is known.
+                given.pegs = match pegs.parse() {
+                    Ok(x) if (MIN_PEGS..=PALETTE_SIZE + 1).contains(&x) => Some(x),
+                    _ => {
+                        return Err(format!(
+                            "`--pegs` needs a number from {}-{} inclusive",
+                            MIN_PEGS,
+                            PALETTE_SIZE + 1
+                        )
+                        .into())
+                    }
+                };
+            }
+            "--duplicates" => {
+                let duplicates = args
+                    .next()
+                    .ok_or("`--duplicates` needs `true` or `false`")?;
+                given.allow_duplicates = Some(
+                    duplicates
+                        .parse()
+                        .map_err(|_| "`--duplicates` needs `true` or `false`")?,
+                );
+            }
+            "--max-tries" => {
+                let tries = args.next().ok_or("`--max-tries` needs a number")?;
+                max_tries_flag(&mut given, &tries)?;
+            }
+            "--distinct-colours" => {
+                let colours = args.next().ok_or("`--distinct-colours` needs a number")?;
+                // "all" turns the limit off, if one was remembered
+                given.max_distinct_colours = match colours.as_str() {
+                    "all" => Some(None),
+                    x => {
+                        Some(Some(x.parse().map_err(|_| {
+                            "`--distinct-colours` needs a number or `all`"
+                        })?))
+                    }
+                };
+            }
+            "--min-distinct-colours" => {
+                given.min_distinct_colours = Some(Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--min-distinct-colours` needs a number")?,
+                ));
+            }
+            "--palette-size" => {
+                let colours = args.next().ok_or("`--palette-size` needs a number")?;
+                // "all" puts every colour back in play, if fewer
+                // were remembered
+                given.palette_size = match colours.as_str() {
+                    "all" => Some(None),
+                    x => Some(Some(
+                        x.parse()
+                            .map_err(|_| "`--palette-size` needs a number or `all`")?,
+                    )),
+                };
+            }
+            "--max-per-colour" => {
+                let most = args.next().ok_or("`--max-per-colour` needs a number")?;
+                given.max_per_colour = Some(
+                    parse_max_tries(&most)
+                        .ok_or("`--max-per-colour` needs a positive number or `unlimited`")?,
+                );
+            }
+            "--reveal-every" => {
+                let every = args.next().ok_or("`--reveal-every` needs a number")?;
+                // "off" turns revealing off, if it was remembered
+                given.reveal_every = match every.as_str() {
+                    "off" => Some(None),
+                    x => Some(Some(
+                        x.parse()
+                            .map_err(|_| "`--reveal-every` needs a number or `off`")?,
+                    )),
+                };
+            }
+            "--hardcore" => given.hardcore = Some(true),
+            "--memory" => given.memory = Some(true),
+            "--block-repeats" => given.block_repeats = Some(true),
+            "--strict-input" => given.strict_input = Some(true),
+            "--confirm-guesses" => given.confirm_guesses = Some(true),
+            "--repeat-secrets" => given.fresh_secrets = Some(false),
+            "--blanks" => given.allow_blanks = Some(true),
+            "--hints" => {
+                let points = args.next().ok_or("`--hints` needs a number of points")?;
+                given.hint_points = Some(
+                    points
+                        .parse()
+                        .map_err(|_| "`--hints` needs a number of points")?,
+                );
+            }
+            "--fresh" => fresh = true,
+            "--wagers" => wagers = true,
+            "--output" => output = Some(args.next().ok_or("`--output` needs a name")?),
+            "--accessible" => accessible = true,
+            "--verbose" => verbose = true,
+            "--feedback" => {
+                feedback = Some(
+                    args.next()
+                        .ok_or("`--feedback` needs a style")?
+                        .parse::<FeedbackStyle>()?,
+                )
+            }
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+
+    let accessible = accessible || settings::load_accessible()?;
+    // Accessible mode picks its renderer, unless another one was
+    // asked for.
+    let output = output.unwrap_or_else(|| {
+        if accessible {
+            "accessible".to_string()
+        } else {
+            "text".to_string()
+        }
+    });
+    println!("{}", rules(accessible, given.allow_blanks == Some(true)));
+    // Everything played this session is recorded for this player.
+    let mut player = match profile_name {
+        Some(name) => profile::find_or_create(&name)?,
+        None => choose_profile()?,
+    };
+    profile::set_last_used(&player)?;
+    println!("Playing as {}", player.name);
+    // Nobody who has set up a game before is new here
+    if player.preferred.is_none() && resume.is_none() && offer_tutorial()? {
+        tutorial::run()?;
+    }
+
+    // The frontend tells the player when they win or lose
+    let on_win = |_: &GameSummary| {};
+    let on_lose = |_: &GameSummary| {};
+    let feedback = match feedback {
+        Some(x) => x,
+        None => settings::load_feedback_style()?,
+    };
+    let mut terminal_frontend = frontend::TerminalFrontend::new(
+        given,
+        render::by_name(&output, feedback.clone())
+            .ok_or("`--output` needs `text`, `accessible`, `json` or `silent`")?,
+        feedback,
+    );
+    if accessible {
+        terminal_frontend = terminal_frontend.accessible();
+    }
+    let (mut state, slot) = match resume {
+        Some(path) => {
+            let state = save::load_from_file(Path::new(&path), on_win, on_lose, false)?;
+            println!("Resumed the game saved in {}", path);
+            (
+                state,
+                slot.unwrap_or_else(|| storage::DEFAULT_SLOT.to_string()),
+            )
+        }
+        None => {
+            let mut resumed = None;
+            // Slots with an autosave left behind are ones where the
+            // last session didn't get to finish. If we were given a
+            // slot, that's the only one we look at.
+            let pending = match &slot {
+                Some(name) => vec![name.clone()],
+                None => storage::list_slots()?,
+            }
+            .into_iter()
+            .filter(|name| {
+                storage::autosave_path(name)
+                    .map(|x| save::has_autosave(&x))
+                    .unwrap_or(false)
+            })
+            .collect::<Vec<_>>();
+            if let Some(name) = choose_slot_to_resume(&pending)? {
+                match save::load_autosave(&storage::autosave_path(&name)?, on_win, on_lose, false) {
+                    Ok(state) => resumed = Some((state, name)),
+                    Err(e) => println!("Could not resume the game: {}", e),
+                }
+            }
+            match resumed {
+                Some(x) => x,
+                None => {
+                    // Digit games have a loop of their own, see
+                    // `bulls.rs`, and aren't saved or recorded.
+                    if ask_digits()? {
+                        return bulls::run();
+                    }
+                    let slot = slot.unwrap_or_else(|| storage::DEFAULT_SLOT.to_string());
+                    if pending.contains(&slot) {
+                        println!(
+                            "Note: this replaces the unfinished game in the `{}` save slot, \
+                             use `--save-slot` to play in another slot instead.",
+                            slot
+                        );
+                    }
+                    let last_used = if fresh { None } else { player.preferred };
+                    let defaults =
+                        settings::resolve(settings::load_config_file()?, last_used, given);
+                    let state = frontend::new_game(&mut terminal_frontend, defaults)?;
+                    // Whatever was picked becomes the default next time.
+                    player.preferred = Some(state.summary().settings);
+                    player.store()?;
+                    (state, slot)
+                }
+            }
+        }
+    };
+    let autosave_path = storage::autosave_path(&slot)?;
+
+    let session = frontend::Session {
+        wagers,
+        player: Some(player.id),
+        autosave: Some(&autosave_path),
+        save_format,
+        verbose,
+    };
+    interrupt::catch();
+    // Every game was played, so there's nothing to resume.
+    if frontend::play(&mut terminal_frontend, &mut state, &session)? {
+        save::clear_autosave(&autosave_path);
+    }
+    // 130 is what a shell expects from a program ended by Ctrl-C
+    if interrupt::happened() {
+        std::process::exit(130);
+    }
+    Ok(())
+}
+
+///
+/// Asks who is playing, offering whoever played last as the
+/// default, and making a new profile for a new name.
+///
+fn choose_profile() -> Result<profile::Profile, Box<dyn std::error::Error>> {
+    let profiles = profile::list()?;
+    let last = profile::last_used()
+        .and_then(|id| profiles.iter().find(|x| x.id == id))
+        .map(|x| x.name.clone())
+        .unwrap_or_else(|| "Player".to_string());
+    if !profiles.is_empty() {
+        let names = profiles.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+        println!("Players: {}", names.join(", "));
+    }
+    let name = prompt::ask(
+        &format!("Who is playing? (blank for {}): ", last),
+        Some(last.clone()),
+        |x: &String| profile::validate_name(x),
+    )?;
+    Ok(profile::find_or_create(&name)?)
+}
+
+///
+/// Asks a new player if they'd like to play the tutorial.
+///
+fn offer_tutorial() -> std::io::Result<bool> {
+    let prompt::YesNo(answer) = prompt::ask(
+        "New here? Would you like a quick tutorial first? (\"yes\" or \"no\", blank for no): ",
+        Some(prompt::YesNo(false)),
+        |_| Ok(()),
+    )?;
+    Ok(answer)
+}
+
+///
+/// Asks which of the `pending` save slots to resume, if any.
+///
+fn choose_slot_to_resume(pending: &[String]) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    match pending {
+        [] => Ok(None),
+        [name] => {
+            let prompt::YesNo(answer) = prompt::ask(
+                &format!(
+                    "There is an unfinished game in the `{}` save slot, resume it? (\"yes\" or \"no\", blank for no): ",
+                    name
+                ),
+                Some(prompt::YesNo(false)),
+                |_| Ok(()),
+            )?;
+            Ok(if answer { Some(name.clone()) } else { None })
+        }
+        _ => {
+            println!("There are unfinished games in these save slots:");
+            for (idx, name) in pending.iter().enumerate() {
+                println!("  {}: {}", idx + 1, name);
+            }
+            // The blank answer is 0, which can't be typed, since
+            // the slots are numbered from 1.
+            let answer = prompt::ask(
+                "Enter the number of one to resume it, or leave it blank for a new game: ",
+                Some(0),
+                |&x: &usize| {
+                    if (1..=pending.len()).contains(&x) {
+                        Ok(())
+                    } else {
+                        Err(format!(
+                            "Enter a number from 1-{} inclusive.",
+                            pending.len()
+                        ))
+                    }
+                },
+            )?;
+            Ok(answer.checked_sub(1).map(|x| pending[x].clone()))
+        }
+    }
+}
+
+///
+/// Asks whether to play with colours, or with digits as Bulls
+/// and Cows, giving back whether it was digits.
+///
+fn ask_digits() -> std::io::Result<bool> {
+    let answer = prompt::ask(
+        "Colours or digits? (\"colours\" or \"digits\", blank for colours): ",
+        Some("colours".to_string()),
+        |x: &String| match x.as_str() {
+            "colours" | "c" | "digits" | "d" => Ok(()),
+            _ => Err("Enter \"colours\" or \"digits\".".to_string()),
+        },
+    )?;
+    Ok(answer.starts_with('d'))
+}
+
+fn ask_duplicates(default: bool) -> std::io::Result<bool> {
+    prompt::ask(
+        &format!(
+            "Would you like to allow duplicates? (\"true\" or \"false\", blank for {}): ",
+            default
+        ),
+        Some(default),
+        |_| Ok(()),
+    )
+}
+
+//
+// Asks for the number of pegs, which can't be more than `most`,
+// the number of colours in play.
+//
+fn ask_pegs(default: usize, most: usize) -> std::io::Result<usize> {
+    let default = default.min(most);
+    prompt::ask(
+        &format!(
+            "How many pegs would you like to play with? ({}-{}, inclusive, blank for {}): ",
+            MIN_PEGS, most, default
+        ),
+        Some(default),
+        |&x| {
+            if (MIN_PEGS..=most).contains(&x) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Enter a valid positive integer from {}-{} inclusive.",
+                    MIN_PEGS, most
+                ))
+            }
+        },
+    )
+}
+
+//
+// Asks how many colours to play with, where all of them is
+// `None`, like in `GameConfig::palette_size`.
+//
+fn ask_palette_size(default: Option<usize>) -> std::io::Result<Option<usize>> {
+    let default = default.unwrap_or(COLOURS.len());
+    let answer = prompt::ask(
+        &format!(
+            "How many colours would you like to play with? ({}-{}, inclusive, blank for {}): ",
+            MIN_PEGS,
+            COLOURS.len(),
+            default
+        ),
+        Some(default),
+        |&x| {
+            if (MIN_PEGS..=COLOURS.len()).contains(&x) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Enter a valid positive integer from {}-{} inclusive.",
+                    MIN_PEGS,
+                    COLOURS.len()
+                ))
+            }
+        },
+    )?;
+    Ok(Some(answer).filter(|&x| x < COLOURS.len()))
+}
+
+fn ask_max_per_colour(default: Option<usize>, pegs: usize) -> std::io::Result<Option<usize>> {
+    let answer = prompt::ask(
+        &format!(
+            "How many times can one colour be in the code? (1-{}, \"unlimited\", blank for {}): ",
+            pegs,
+            default.map_or("unlimited".to_string(), |x| x.to_string())
+        ),
+        Some(PerColour(default)),
+        |_| Ok(()),
+    )?;
+    Ok(answer.0)
+}
+
+//
+// Asks for the limit on tries for games with `config`, where
+// the pegs and duplicates have already been picked so that a
+// fair limit (See `fair.rs`) can be offered. If the limit in
+// `config` is already a fair one, that's the default.
+//
+fn ask_max_tries(config: GameConfig) -> std::io::Result<GameConfig> {
+    let fair = fair::max_tries(config);
+    let (question, default) = match config.max_tries {
+        _ if config.auto_max_tries => (
+            format!(
+                "(A number or \"unlimited\", blank for {}, a fair limit for these settings)",
+                fair
+            ),
+            MaxTries::Auto,
+        ),
+        x => (
+            format!(
+                "(A number, \"unlimited\" or \"auto\" for a fair limit of {}, blank for {})",
+                fair,
+                x.map_or("unlimited".to_string(), |x| x.to_string())
+            ),
+            MaxTries::Limit(x),
+        ),
+    };
+    let answer = prompt::ask(
+        &format!("How many tries would you like per game? {}: ", question),
+        Some(default),
+        |x| match x {
+            MaxTries::Limit(Some(x)) if *x > MAX_TRIES => {
+                Err(ConfigError::TooManyTries { most: MAX_TRIES }.to_string())
+            }
+            _ => Ok(()),
+        },
+    )?;
+    Ok(match answer {
+        MaxTries::Auto => GameConfig {
+            auto_max_tries: true,
+            ..config
+        }
+        .with_auto_tries(),
+        MaxTries::Limit(max_tries) => GameConfig {
+            max_tries,
+            auto_max_tries: false,
+            ..config
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_huge_try_limit_is_a_config_error() {
+        let config = GameConfig {
+            max_tries: Some(1_000_000_000_000),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let too_many = ConfigError::TooManyTries { most: MAX_TRIES };
+        assert_eq!(config.validate(), Err(too_many));
+        assert_eq!(
+            StateBuilder::new().config(config).build().err(),
+            Some(too_many)
+        );
+        let most = GameConfig {
+            max_tries: Some(MAX_TRIES),
+            ..config
+        };
+        assert_eq!(most.validate(), Ok(()));
+    }
+
+    #[test]
+    fn the_defaults_are_pinned() {
+        let config = GameConfig::default();
+        assert_eq!(config.pegs, 4);
+        assert!(!config.allow_duplicates);
+        assert_eq!(config.max_tries, Some(10));
+        assert!(!config.auto_max_tries);
+        assert_eq!(config.palette(), COLOURS[..CLASSIC_PALETTE_SIZE].to_vec());
+        assert!(!config.allow_blanks);
+        assert_eq!(config.hint_points, 0);
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn everything_starts_from_the_same_defaults() {
+        let state = State::default_game(|_| {}, |_| {});
+        assert_eq!(state.config, GameConfig::default());
+        assert_eq!(state.remaining_tries(), Some(10));
+        let resolved = settings::resolve(Default::default(), None, Default::default());
+        assert_eq!(resolved, GameConfig::default());
+    }
+
+    #[test]
+    fn only_winning_unaided_earns_a_speed_bonus() {
+        let mut state = StateBuilder::new()
+            .config(GameConfig::default())
+            .seed(4)
+            .build()
+            .unwrap();
+        let secret = state.reveal_secret().to_vec();
+        let guess = Guess::for_config(secret, state.config).unwrap();
+        assert_eq!(state.enter_guess(&guess).unwrap(), Some(Outcome::Won));
+        // Won on the first of 10 tries, so 9 are left over
+        let won = state.games()[0].speed_bonus;
+        assert!(won >= 9 * speed::POINTS_PER_TRY, "{}", won);
+
+        state.forfeit();
+        assert_eq!(state.games()[1].speed_bonus, 0);
+
+        let mut helped = StateBuilder::new()
+            .config(GameConfig {
+                reveal_every: Some(1),
+                ..GameConfig::default()
+            })
+            .seed(4)
+            .build()
+            .unwrap();
+        let mut codes = all_codes(&COLOURS[..CLASSIC_PALETTE_SIZE], 4, false);
+        while helped.games().is_empty() {
+            let guess = Guess::for_config(codes.next().unwrap(), helped.config).unwrap();
+            helped.enter_guess(&guess).unwrap();
+        }
+        assert_eq!(helped.games()[0].speed_bonus, 0);
+    }
+
+    // Secrets from a fixed seed for some settings, so that a
+    // change to how they're made has to change these on purpose.
+    fn seeded_secrets(config: GameConfig) -> Vec<Vec<Colour>> {
+        let mut rng = StdRng::seed_from_u64(240);
+        (0..3)
+            .map(|_| State::<Colour>::generate_pegs_with(config, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn seeded_secrets_are_pinned() {
+        use Colour::*;
+        let duplicates = GameConfig {
+            allow_duplicates: true,
+            ..GameConfig::default()
+        };
+        let announced = GameConfig {
+            pegs: 5,
+            max_distinct_colours: Some(3),
+            ..duplicates
+        };
+        assert_eq!(
+            seeded_secrets(GameConfig::default()),
+            vec![
+                vec![Blue, White, Red, Yellow],
+                vec![Orange, Red, Green, White],
+                vec![White, Orange, Yellow, Blue],
+            ]
+        );
+        assert_eq!(
+            seeded_secrets(duplicates),
+            vec![
+                vec![Red, Green, Blue, Yellow],
+                vec![Orange, Green, White, Orange],
+                vec![Orange, Red, Yellow, Yellow],
+            ]
+        );
+        // Exactly three colours, as announced
+        assert_eq!(
+            seeded_secrets(announced),
+            vec![
+                vec![White, Orange, White, Red, Red],
+                vec![Blue, Red, Red, Yellow, Yellow],
+                vec![White, Red, Yellow, Yellow, Yellow],
+            ]
+        );
+    }
+
+    #[test]
+    fn secrets_keep_to_their_settings() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let config = GameConfig {
+            pegs: 6,
+            palette_size: Some(8),
+            ..GameConfig::default()
+        };
+        let announced = GameConfig {
+            allow_duplicates: true,
+            max_distinct_colours: Some(2),
+            ..config
+        };
+        for _ in 0..1000 {
+            let mut secret = State::<Colour>::generate_pegs_with(config, &mut rng);
+            secret.sort_unstable();
+            secret.dedup();
+            assert_eq!(secret.len(), 6);
+            let mut secret = State::<Colour>::generate_pegs_with(announced, &mut rng);
+            secret.sort_unstable();
+            secret.dedup();
+            assert_eq!(secret.len(), 2);
+        }
+    }
+
+    #[test]
+    fn labels_are_cleaned_up() {
+        assert_eq!(clean_label("  birthday \n"), Some("birthday".to_string()));
+        assert_eq!(
+            clean_label("bi\x1b[2Jrth\u{7}day"),
+            Some("bi[2Jrthday".to_string())
+        );
+        assert_eq!(clean_label(""), None);
+        assert_eq!(clean_label(" \t\r\n"), None);
+        let long = "a".repeat(MAX_LABEL_LENGTH - 1) + " bcd";
+        assert_eq!(clean_label(&long), Some("a".repeat(MAX_LABEL_LENGTH - 1)));
+    }
+
+    // The games of a session which has had `secrets`, one after
+    // the other.
+    fn played(secrets: &[Vec<Colour>]) -> Vec<GameRecord> {
+        let mut state = StateBuilder::new().build().unwrap();
+        for secret in secrets {
+            state.set_secret(secret.clone());
+            state.forfeit();
+        }
+        state.previous_games
+    }
+
+    #[test]
+    fn a_repeated_secret_is_picked_again() {
+        let config = GameConfig::default();
+        let mut rng = StdRng::seed_from_u64(248);
+        let first = State::<Colour>::generate_pegs_with(config, &mut rng);
+        let second = State::<Colour>::generate_pegs_with(config, &mut rng);
+        assert_ne!(first, second);
+        // Starting from the same seed, the first secret has come
+        // up already, so the second is used instead
+        let played = played(&[first]);
+        let mut rng = StdRng::seed_from_u64(248);
+        let (pegs, repeated) = State::fresh_pegs_with(config, &played, &mut rng);
+        assert_eq!(pegs, second);
+        assert!(!repeated);
+    }
+
+    #[test]
+    fn a_generator_stuck_on_one_secret_gives_up_on_rerolling() {
+        // Always the same number, so always the same secret
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+        let config = GameConfig::default();
+        let stuck = State::<Colour>::generate_pegs_with(config, &mut rng);
+        let played = played(std::slice::from_ref(&stuck));
+        let (pegs, repeated) = State::fresh_pegs_with(config, &played, &mut rng);
+        assert_eq!(pegs, stuck);
+        assert!(repeated);
+
+        let anything_goes = GameConfig {
+            fresh_secrets: false,
+            ..config
+        };
+        let (pegs, repeated) = State::fresh_pegs_with(anything_goes, &played, &mut rng);
+        assert_eq!(pegs, stuck);
+        assert!(!repeated);
+    }
+
+    #[test]
+    fn a_tiny_code_space_runs_out_without_looping_for_ever() {
+        // Only 6 codes, 2 pegs from 3 colours
+        let config = GameConfig {
+            pegs: 2,
+            palette_size: Some(3),
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).seed(3).build().unwrap();
+        let mut secrets = Vec::new();
+        let mut repeats = Vec::new();
+        for _ in 0..10 {
+            repeats.push(state.repeated_secret());
+            state.forfeit();
+            secrets.push(state.games().last().unwrap().secret.clone());
+        }
+        let mut first_six = secrets[..6].to_vec();
+        first_six.sort_unstable();
+        first_six.dedup();
+        assert_eq!(first_six.len(), 6, "{:?}", secrets);
+        assert_eq!(repeats[..6], [false; 6]);
+        assert_eq!(repeats[6..], [true; 4]);
+    }
+
+    #[test]
+    fn fresh_secrets_are_the_same_for_the_same_seed() {
+        let secrets = || {
+            let mut state = StateBuilder::new().seed(8).build().unwrap();
+            (0..20)
+                .map(|_| {
+                    state.forfeit();
+                    state.games().last().unwrap().secret.clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(secrets(), secrets());
+    }
+
+    #[test]
+    fn a_game_scores_guesses_like_score_guess() {
+        use Colour::*;
+        for &allow_duplicates in &[true, false] {
+            let config = GameConfig {
+                allow_duplicates,
+                ..GameConfig::default()
+            };
+            let mut state = StateBuilder::new().config(config).build().unwrap();
+            state.set_secret(vec![Red, Blue, Green, White]);
+            let guesses = if allow_duplicates {
+                vec![
+                    (vec![Red, Red, Red, Red], (1, 0)),
+                    (vec![Blue, Blue, Red, Red], (1, 1)),
+                ]
+            } else {
+                vec![
+                    (vec![Red, Green, Blue, Yellow], (1, 2)),
+                    (vec![White, Red, Blue, Green], (0, 4)),
+                ]
+            };
+            for (guess, (exact, misplaced)) in &guesses {
+                let guess = Guess::for_config(guess.clone(), config).unwrap();
+                assert_eq!(state.enter_guess(&guess).unwrap(), None);
+                let scored = state.history().last().unwrap().feedback;
+                assert_eq!((scored.exact, scored.misplaced), (*exact, *misplaced));
+                assert_eq!(scored, score_guess(state.reveal_secret(), &guess));
+                assert!(scored.exact + scored.misplaced <= config.pegs);
+            }
+        }
+    }
+
+    // A game without duplicates, with a known secret
+    fn no_duplicates() -> State<'static, Colour> {
+        let config = GameConfig {
+            allow_duplicates: false,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![
+            Colour::Red,
+            Colour::Blue,
+            Colour::Green,
+            Colour::White,
+        ]);
+        state
+    }
+
+    #[test]
+    fn play_goes_on_after_a_duplicate_colour() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        assert!(matches!(state.input_buffer(Red), Ok(TurnOutcome::Pending)));
+        match state.input_buffer(Red) {
+            Err(MastermindError::DuplicateNotAllowed { colour, so_far }) => {
+                assert_eq!(colour, Red);
+                assert_eq!(so_far, vec![Red]);
             }
+            other => panic!("{:?}", other),
         }
-    };
+        for &colour in &[Blue, Green] {
+            assert!(matches!(
+                state.input_buffer(colour),
+                Ok(TurnOutcome::Pending)
+            ));
+        }
+        assert!(matches!(
+            state.input_buffer(White),
+            Ok(TurnOutcome::Won { attempts: 1 })
+        ));
+    }
 
-    // Here we use our new function above.
-    let mut state = State::new(
-        pegs,
-        duplicates,
-        Some(10),
-        || println!("You won!"),
-        || println!("Uh-oh, you lost"),
-        true,
-    )
-    .unwrap();
-
-    for i in 0..2 {
-        println!("Generated new state! Game #{}", i + 1);
-        'a: loop {
-            print!("Enter next colours > ");
-            std::io::stdout().flush()?;
-            input.clear();
-            std::io::stdin().read_line(&mut input)?;
-            match state.push_string_input(input.trim()) {
-                Ok(f) => {
-                    if f {
-                        break 'a;
-                    }
+    #[test]
+    fn play_goes_on_after_a_duplicate_guess() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        // Filled in behind `input_buffer`'s back, the way a whole
+        // guess from somewhere else could be
+        for &colour in &[Red, Red, Blue, Green] {
+            state.game.push(colour).unwrap();
+        }
+        assert!(state.finish_try().is_err());
+        assert!(state.buffered().is_empty());
+        assert!(state.history().is_empty());
+
+        let duplicates = GameConfig {
+            allow_duplicates: true,
+            ..state.config
+        };
+        let twice = Guess::for_config(vec![Red, Blue, Blue, Green], duplicates).unwrap();
+        assert!(state.enter_guess(&twice).is_err());
+
+        for &colour in &[Red, White, Blue, Green] {
+            let _ = state.input_buffer(colour).unwrap();
+        }
+        assert_eq!(state.attempts(), 1);
+        let guess = Guess::for_config(vec![Red, Blue, Green, White], state.config).unwrap();
+        assert_eq!(state.enter_guess(&guess).unwrap(), Some(Outcome::Won));
+        assert_eq!(state.games().last().unwrap().attempts, 2);
+    }
+
+    // Writes down every message and scored guess it's told about
+    struct Notes<'b>(&'b mut Vec<String>);
+
+    impl GameOutput for Notes<'_> {
+        fn message(&mut self, text: &str) {
+            self.0.push(text.to_string());
+        }
+    }
+
+    struct Scores<'b>(&'b mut Vec<(usize, Feedback)>);
+
+    impl GameObserver for Scores<'_> {
+        fn on_guess(&mut self, _: &[Colour], feedback: Feedback, attempt: usize) {
+            self.0.push((attempt, feedback));
+        }
+    }
+
+    #[test]
+    fn the_losing_guess_gets_feedback_and_is_recorded() {
+        use Colour::*;
+        let mut said = Vec::new();
+        let mut scored = Vec::new();
+        let config = GameConfig {
+            max_tries: Some(3),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let games = {
+            let mut state = StateBuilder::new()
+                .config(config)
+                .output(Notes(&mut said))
+                .build()
+                .unwrap();
+            state.add_observer(Scores(&mut scored));
+            state.set_secret(vec![Red, Blue, Green, White]);
+            let wrong = [
+                vec![Yellow, Orange, White, Red],
+                vec![Blue, Red, Yellow, Orange],
+                vec![Red, Blue, White, Green],
+            ];
+            let outcomes = wrong
+                .iter()
+                .map(|guess| {
+                    let guess = Guess::for_config(guess.clone(), config).unwrap();
+                    state.enter_guess(&guess).unwrap()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(outcomes, [None, None, Some(Outcome::Lost)]);
+            state.games().to_vec()
+        };
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].attempts, 3);
+        assert_eq!(games[0].guesses.len(), 3);
+        assert_eq!(
+            scored.last(),
+            Some(&(
+                3,
+                Feedback {
+                    exact: 2,
+                    misplaced: 2
                 }
-                Err((text, f)) => {
-                    println!("Error encountered: {}", text);
-                    if f {
-                        break 'a;
-                    }
+            ))
+        );
+        let feedback = said.iter().filter(|x| x.starts_with("Good try")).count();
+        assert_eq!(feedback, 3, "{:?}", said);
+    }
+
+    #[test]
+    fn matching_a_missing_guess_is_an_error() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        assert!(matches!(
+            state.matching(Some(0)),
+            Err(MastermindError::NoSuchAttempt(0))
+        ));
+        let guesses = [
+            (vec![Yellow, Orange, White, Red], (0, 2)),
+            (vec![Red, Green, Blue, Yellow], (1, 2)),
+            (vec![Red, Blue, Yellow, Orange], (2, 0)),
+        ];
+        for (guess, _) in &guesses {
+            let guess = Guess::for_config(guess.clone(), state.config).unwrap();
+            state.enter_guess(&guess).unwrap();
+        }
+        let feedback = state.matching(Some(1)).unwrap();
+        assert_eq!((feedback.exact, feedback.misplaced), guesses[1].1);
+        match state.matching(Some(3)) {
+            Err(error @ MastermindError::NoSuchAttempt(3)) => {
+                assert_eq!(error.to_string(), "There is no guess 4 this game");
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn matching_none_scores_the_guess_being_entered() {
+        let mut state = no_duplicates();
+        assert!(matches!(
+            state.matching(None),
+            Err(MastermindError::GuessTooShort { got: 0, needed: 4 })
+        ));
+        let secret = state.reveal_secret().to_vec();
+        let mut guess = secret.clone();
+        guess.swap(0, 1);
+        for &colour in &guess[..3] {
+            state.input_buffer(colour).unwrap();
+        }
+        assert!(matches!(
+            state.matching(None),
+            Err(MastermindError::GuessTooShort { got: 3, needed: 4 })
+        ));
+        state.config.confirm_guesses = true;
+        state.input_buffer(guess[3]).unwrap();
+        assert_eq!(
+            state.matching(None),
+            Ok(Feedback {
+                exact: 2,
+                misplaced: 2
+            })
+        );
+        // Scoring it doesn't use up a try
+        assert!(state.history().is_empty());
+        assert_eq!(state.buffered(), &guess[..]);
+    }
+
+    // How many times each colour came up in each position, over
+    // `games` secrets picked with `config`
+    fn tally(config: GameConfig, games: usize, seed: u64) -> Vec<Vec<usize>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut counts = vec![vec![0; COLOURS.len()]; config.pegs];
+        for _ in 0..games {
+            let pegs = State::<Colour>::generate_pegs_with(config, &mut rng);
+            for (position, colour) in pegs.iter().enumerate() {
+                counts[position][*colour as usize] += 1;
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn every_colour_comes_up_about_as_often() {
+        for &allow_duplicates in &[false, true] {
+            let config = GameConfig {
+                allow_duplicates,
+                ..GameConfig::default()
+            };
+            let counts = tally(config, 10_000, 257);
+            let colours = config.palette().len();
+            // Each colour should be at each position in about a
+            // sixth of the games. Allowing 10% either way, a fair
+            // generator is far more likely to be struck by
+            // lightning than to fail this.
+            let expected = 10_000 / colours;
+            for position in &counts {
+                for &count in &position[..colours] {
+                    assert!(
+                        count > expected * 9 / 10 && count < expected * 11 / 10,
+                        "{:?}",
+                        counts
+                    );
+                }
+                assert!(position[colours..].iter().all(|&x| x == 0));
+            }
+        }
+    }
+
+    #[test]
+    fn without_duplicates_no_colour_is_ever_repeated() {
+        let mut rng = StdRng::seed_from_u64(257);
+        for &(pegs, palette_size) in &[(4, None), (6, Some(6)), (5, Some(8))] {
+            let config = GameConfig {
+                pegs,
+                palette_size,
+                ..GameConfig::default()
+            };
+            for _ in 0..10_000 {
+                let mut secret = State::<Colour>::generate_pegs_with(config, &mut rng);
+                secret.sort_unstable();
+                secret.dedup();
+                assert_eq!(secret.len(), pegs);
+            }
+        }
+    }
+
+    #[test]
+    fn too_few_pegs_is_a_config_error() {
+        for &pegs in &[0, 1] {
+            let config = GameConfig {
+                pegs,
+                ..GameConfig::default()
+            };
+            let error = ConfigError::TooFewPegs { requested: pegs };
+            assert_eq!(config.validate(), Err(error));
+            assert_eq!(
+                StateBuilder::new().config(config).build().err(),
+                Some(error)
+            );
+        }
+        let two = GameConfig {
+            pegs: MIN_PEGS,
+            ..GameConfig::default()
+        };
+        assert!(StateBuilder::new().config(two).build().is_ok());
+    }
+
+    #[test]
+    fn no_tries_is_a_config_error() {
+        let config = GameConfig {
+            max_tries: Some(0),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        assert_eq!(config.validate(), Err(ConfigError::NoTries));
+        assert_eq!(
+            StateBuilder::new().config(config).build().err(),
+            Some(ConfigError::NoTries)
+        );
+    }
+
+    #[test]
+    fn one_try_is_one_guess_and_then_a_loss() {
+        use Colour::*;
+        let config = GameConfig {
+            max_tries: Some(1),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![Red, Blue, Green, White]);
+        let wrong = Guess::for_config(vec![Red, Blue, White, Green], config).unwrap();
+        assert_eq!(state.enter_guess(&wrong).unwrap(), Some(Outcome::Lost));
+        let record = state.games().last().unwrap();
+        assert_eq!(record.attempts, 1);
+        assert_eq!(record.secret, vec![Red, Blue, Green, White]);
+        // And the next game has its one try, which can win
+        let secret = state.reveal_secret().to_vec();
+        let right = Guess::for_config(secret, config).unwrap();
+        assert_eq!(state.enter_guess(&right).unwrap(), Some(Outcome::Won));
+    }
+
+    #[test]
+    fn the_history_keeps_each_guess_with_its_feedback_until_the_game_ends() {
+        let mut state = StateBuilder::new()
+            .config(GameConfig::default())
+            .seed(260)
+            .build()
+            .unwrap();
+        let secret = state.reveal_secret().to_vec();
+        let mut guesses = Vec::new();
+        for turn in 1..4 {
+            let mut guess = secret.clone();
+            guess.rotate_left(turn);
+            guesses.push(guess.clone());
+            let guess = Guess::for_config(guess, state.config).unwrap();
+            state.enter_guess(&guess).unwrap();
+        }
+        let history = state.history().to_vec();
+        assert_eq!(history.len(), 3);
+        for (attempt, guess) in history.iter().zip(&guesses) {
+            assert_eq!(&*attempt.guess, &guess[..]);
+            assert_eq!(attempt.feedback, score_guess(&secret, guess));
+        }
+        // Still there after more is typed, but not a finished guess
+        state.input_buffer(secret[0]).unwrap();
+        assert_eq!(state.history(), &history[..]);
+
+        state.forfeit();
+        assert!(state.history().is_empty());
+        assert_eq!(state.games().last().unwrap().guesses, history);
+    }
+
+    #[test]
+    fn a_session_keeps_every_game_s_guesses() {
+        let mut state = StateBuilder::new()
+            .config(GameConfig::default())
+            .seed(261)
+            .build()
+            .unwrap();
+        let mut played = Vec::new();
+        for game in 0..2 {
+            let secret = state.reveal_secret().to_vec();
+            let mut guesses = Vec::new();
+            for turn in 1..3 {
+                let mut guess = secret.clone();
+                guess.rotate_left(turn);
+                guesses.push(guess);
+            }
+            // The first game is won, the second given up on
+            if game == 0 {
+                guesses.push(secret.clone());
+            }
+            for guess in &guesses {
+                let guess = Guess::for_config(guess.clone(), state.config).unwrap();
+                state.enter_guess(&guess).unwrap();
+            }
+            if game == 1 {
+                state.forfeit();
+            }
+            played.push((secret, guesses));
+        }
+        let games = state.games();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].outcome, Outcome::Won);
+        assert_eq!(games[1].outcome, Outcome::Lost);
+        for (record, (secret, guesses)) in games.iter().zip(&played) {
+            assert_eq!(&record.secret, secret);
+            assert_eq!(record.attempts, guesses.len());
+            let kept = record
+                .guesses
+                .iter()
+                .map(|x| x.guess.to_vec())
+                .collect::<Vec<_>>();
+            assert_eq!(&kept, guesses);
+            for attempt in &record.guesses {
+                assert_eq!(attempt.feedback, score_guess(secret, &attempt.guess));
+            }
+        }
+    }
+
+    #[test]
+    fn a_game_colour_by_colour() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        let mut outcomes = Vec::new();
+        for &colour in &[Red, Green, Blue, Yellow, Red, Blue, Green, White] {
+            outcomes.push(state.input_buffer(colour).unwrap());
+        }
+        let feedback = Feedback {
+            exact: 1,
+            misplaced: 2,
+        };
+        assert_eq!(
+            outcomes,
+            [
+                TurnOutcome::Pending,
+                TurnOutcome::Pending,
+                TurnOutcome::Pending,
+                TurnOutcome::RoundComplete { feedback },
+                TurnOutcome::Pending,
+                TurnOutcome::Pending,
+                TurnOutcome::Pending,
+                TurnOutcome::Won { attempts: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_lost_game_colour_by_colour() {
+        use Colour::*;
+        let config = GameConfig {
+            max_tries: Some(1),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![Red, Blue, Green, White]);
+        let outcomes = [White, Green, Blue, Red]
+            .iter()
+            .map(|&x| state.input_buffer(x).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            [
+                TurnOutcome::Pending,
+                TurnOutcome::Pending,
+                TurnOutcome::Pending,
+                TurnOutcome::Lost {
+                    secret: vec![Red, Blue, Green, White]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_bad_line_leaves_nothing_behind() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        let error = state.push_string_input("rgbz").unwrap_err();
+        assert!(matches!(
+            error.error,
+            MastermindError::UnknownColour {
+                letter: Some('z'),
+                ..
+            }
+        ));
+        assert_eq!(error.position, Some(3));
+        assert_eq!(error.buffered, 0);
+        assert_eq!(error.guesses_finished, 0);
+        assert!(state.buffered().is_empty());
+
+        let events = state.push_string_input("wbgr").unwrap();
+        let guess = state.history().last().unwrap();
+        assert_eq!(&*guess.guess, &[White, Blue, Green, Red][..]);
+        assert_eq!(
+            guess.feedback,
+            Feedback {
+                exact: 2,
+                misplaced: 2
+            }
+        );
+        assert_eq!(state.history().len(), 1);
+        assert!(!events.iter().any(GameEvent::ends_game));
+    }
+
+    #[test]
+    fn a_line_going_wrong_part_way_says_what_it_left_behind() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        state.push_string_input("r").unwrap();
+        // Green twice only goes wrong once the first one is in
+        let error = state.push_string_input("gbg").unwrap_err();
+        assert!(matches!(
+            error.error,
+            MastermindError::DuplicateNotAllowed { colour: Green, .. }
+        ));
+        assert_eq!(error.position, Some(2));
+        assert_eq!(error.buffered, 3);
+        assert_eq!(state.buffered(), vec![Red, Green, Blue]);
+        state.push_string_input("w").unwrap();
+        assert_eq!(state.history().len(), 1);
+        assert_eq!(&*state.history()[0].guess, &[Red, Green, Blue, White][..]);
+    }
+
+    #[test]
+    fn a_repeated_colour_is_turned_away_as_it_is_typed() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        let error = state.push_string_input("r r").unwrap_err();
+        assert!(matches!(
+            error.error,
+            MastermindError::DuplicateNotAllowed { colour: Red, .. }
+        ));
+        assert_eq!(error.buffered, 1);
+        assert_eq!(state.buffered(), vec![Red]);
+        // The first red is still there, so this finishes the guess
+        state.push_string_input("g b w").unwrap();
+        assert_eq!(&*state.history()[0].guess, &[Red, Green, Blue, White][..]);
+        assert!(state.buffered().is_empty());
+
+        // With duplicates, the same line is fine
+        let mut state = StateBuilder::new()
+            .config(GameConfig {
+                allow_duplicates: true,
+                ..GameConfig::default()
+            })
+            .build()
+            .unwrap();
+        state.push_string_input("r r").unwrap();
+        assert_eq!(state.buffered(), vec![Red, Red]);
+    }
+
+    #[test]
+    fn blanks_are_typed_as_holes() {
+        assert_eq!(".".parse::<Colour>().unwrap(), Colour::Blank);
+        assert_eq!("_".parse::<Colour>().unwrap(), Colour::Blank);
+        let mut state = no_duplicates();
+        assert!(matches!(
+            state.input_buffer(Colour::Blank),
+            Err(MastermindError::BlankNotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn blanks_are_scored_like_any_other_peg() {
+        use Colour::*;
+        let config = GameConfig {
+            allow_blanks: true,
+            allow_duplicates: true,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        // A secret with holes in it
+        state.set_secret(vec![Red, Blank, Blank, Blue]);
+        state.push_string_input("..rg").unwrap();
+        let scored = state.history()[0].feedback;
+        assert_eq!((scored.exact, scored.misplaced), (1, 2));
+        state.push_string_input("r..b").unwrap();
+        assert_eq!(state.games()[0].outcome, Outcome::Won);
+
+        // And holes in a guess at one without any, which only
+        // score for the colours around them
+        state.set_secret(vec![Red, Blue, Green, White]);
+        state.push_string_input(".b.r").unwrap();
+        let scored = state.history()[0].feedback;
+        assert_eq!((scored.exact, scored.misplaced), (1, 1));
+    }
+
+    #[test]
+    fn blanks_are_only_in_secrets_when_allowed() {
+        let mut rng = StdRng::seed_from_u64(265);
+        for &allow_blanks in &[false, true] {
+            let config = GameConfig {
+                allow_blanks,
+                allow_duplicates: true,
+                ..GameConfig::default()
+            };
+            let with_blanks = (0..1000)
+                .filter(|_| {
+                    State::<Colour>::generate_pegs_with(config, &mut rng).contains(&Colour::Blank)
+                })
+                .count();
+            // With seven to pick from, about 1 - (6/7)^4 of the
+            // secrets have at least one blank
+            assert_eq!(with_blanks > 300, allow_blanks, "{}", with_blanks);
+            assert_eq!(with_blanks == 0, !allow_blanks);
+        }
+        let palette = GameConfig {
+            allow_blanks: true,
+            ..GameConfig::default()
+        }
+        .palette();
+        assert_eq!(palette.last(), Some(&Colour::Blank));
+    }
+
+    #[test]
+    fn no_colour_is_used_more_than_max_per_colour_allows() {
+        let mut rng = StdRng::seed_from_u64(266);
+        for &(pegs, palette_size) in &[(4, None), (5, Some(5)), (6, Some(8))] {
+            let config = GameConfig {
+                pegs,
+                palette_size,
+                allow_duplicates: true,
+                max_per_colour: Some(2),
+                ..GameConfig::default()
+            };
+            assert_eq!(config.validate(), Ok(()));
+            let mut pairs = 0;
+            for _ in 0..5000 {
+                let secret = State::<Colour>::generate_pegs_with(config, &mut rng);
+                assert_eq!(config.over_limit(&secret), None, "{:?}", secret);
+                for colour in &secret {
+                    assert!(secret.iter().filter(|&x| x == colour).count() <= 2);
                 }
+                if secret
+                    .iter()
+                    .any(|x| secret.iter().filter(|&y| y == x).count() == 2)
+                {
+                    pairs += 1;
+                }
+            }
+            // The limit still lets a colour come up twice
+            assert!(pairs > 0);
+        }
+    }
+
+    #[test]
+    fn a_guess_over_max_per_colour_is_turned_away() {
+        use Colour::*;
+        let config = GameConfig {
+            allow_duplicates: true,
+            max_per_colour: Some(2),
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![Red, Red, Blue, Blue]);
+        let error = state.push_string_input("rrr").unwrap_err();
+        assert!(matches!(
+            error.error,
+            MastermindError::TooManyOfColour {
+                colour: Red,
+                most: 2,
+                ..
+            }
+        ));
+        assert_eq!(state.buffered(), vec![Red, Red]);
+        state.push_string_input("bb").unwrap();
+        assert_eq!(state.games()[0].outcome, Outcome::Won);
+    }
+
+    #[test]
+    fn max_per_colour_has_to_be_able_to_fill_the_code() {
+        let config = GameConfig {
+            allow_duplicates: true,
+            max_per_colour: Some(0),
+            ..GameConfig::default()
+        };
+        assert_eq!(config.validate(), Err(ConfigError::NoneOfAnyColour));
+        let config = GameConfig {
+            max_per_colour: Some(1),
+            max_distinct_colours: Some(3),
+            ..config
+        };
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::CantFillPegs {
+                colours: 3,
+                most: 1,
+                pegs: 4
+            })
+        );
+    }
+
+    #[test]
+    fn secrets_have_as_many_colours_as_asked_for() {
+        let mut rng = StdRng::seed_from_u64(267);
+        for &fewest in &[2, 3, 4] {
+            let config = GameConfig {
+                allow_duplicates: true,
+                min_distinct_colours: Some(fewest),
+                ..GameConfig::default()
+            };
+            assert_eq!(config.validate(), Ok(()));
+            for _ in 0..5000 {
+                let secret = State::<Colour>::generate_pegs_with(config, &mut rng);
+                assert!(config.enough_colours(&secret), "{:?}", secret);
+                let colours = secret.iter().collect::<HashSet<_>>().len();
+                assert!(colours >= fewest, "{:?}", secret);
             }
         }
     }
 
-    println!("Previous games:");
-    for (idx, (pegs, attempts, won)) in state.previous_games.iter().enumerate() {
-        println!(
-            "Game #{} with pegs {:?} was {} with {} attempts",
-            idx + 1,
+    #[test]
+    fn impossible_colour_counts_are_config_errors() {
+        let config = |pegs, fewest, most| GameConfig {
             pegs,
-            if *won { "won" } else { "lost" },
-            attempts
+            allow_duplicates: true,
+            min_distinct_colours: fewest,
+            max_distinct_colours: most,
+            ..GameConfig::default()
+        };
+        assert_eq!(
+            config(2, Some(3), None).validate(),
+            Err(ConfigError::FewestOverPegs { pegs: 2, fewest: 3 })
+        );
+        assert_eq!(
+            config(4, Some(0), None).validate(),
+            Err(ConfigError::FewestColoursZero)
+        );
+        assert_eq!(
+            config(4, Some(3), Some(2)).validate(),
+            Err(ConfigError::FewestOverMost { fewest: 3 })
         );
+        assert_eq!(config(2, Some(2), None).validate(), Ok(()));
+    }
+
+    // The secrets of `games` games in a row, starting from `state`
+    fn secrets_of(mut state: State<'_>, games: usize) -> Vec<Vec<Colour>> {
+        (0..games)
+            .map(|_| {
+                let secret = state.reveal_secret().to_vec();
+                state.forfeit();
+                secret
+            })
+            .collect()
+    }
+
+    #[test]
+    fn seeded_states_play_the_same_secrets() {
+        let config = GameConfig::default();
+        let seeded = |seed| State::new_seeded(config, seed, |_| {}, |_| {}).unwrap();
+        let first = secrets_of(seeded(268), 10);
+        assert_eq!(first, secrets_of(seeded(268), 10));
+        let built = StateBuilder::new()
+            .config(config)
+            .seed(268)
+            .build()
+            .unwrap();
+        assert_eq!(first, secrets_of(built, 10));
+        assert_ne!(first, secrets_of(seeded(269), 10));
+    }
+
+    //
+    // A random number generator which isn't: it hands out the
+    // numbers it was given, round and round, and counts how many
+    // it's been asked for.
+    //
+    struct MockRng {
+        script: Vec<u64>,
+        drawn: usize,
+    }
+
+    impl MockRng {
+        //
+        // Numbers which make the `gen_range(0, n)` for each `(k, n)`
+        // of `picks` come out as `k`, whether the range is drawn from
+        // 64 bits (Like a `usize` one) or from 32 (Like picking from
+        // a slice). `gen_range` scales the number it draws down into
+        // the range, so `k` out of `n` is a `k / n` of the way up,
+        // plus a bit so that rounding down doesn't give `k - 1`.
+        //
+        fn picking(picks: &[(usize, usize)]) -> Self {
+            let script = picks
+                .iter()
+                .map(|&(k, n)| (((k as u128) << 64) / n as u128) as u64 + (1 << 32))
+                .collect();
+            Self { script, drawn: 0 }
+        }
+    }
+
+    impl RngCore for MockRng {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_u64() >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let next = self.script[self.drawn % self.script.len()];
+            self.drawn += 1;
+            next
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let bytes = self.next_u64().to_le_bytes();
+                chunk.copy_from_slice(&bytes[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn with_duplicates_each_peg_is_one_pick() {
+        use Colour::*;
+        let config = GameConfig {
+            allow_duplicates: true,
+            ..GameConfig::default()
+        };
+        let mut rng = MockRng::picking(&[(0, 6), (5, 6), (2, 6), (2, 6)]);
+        let pegs = State::<Colour>::generate_new_pegs(config, &mut rng);
+        assert_eq!(pegs, vec![Red, Orange, White, White]);
+        assert_eq!(rng.drawn, 4);
+        // The same number every time is the same colour every time
+        let mut rng = MockRng::picking(&[(3, 6)]);
+        let pegs = State::<Colour>::generate_new_pegs(config, &mut rng);
+        assert_eq!(pegs, vec![Yellow; 4]);
+    }
+
+    #[test]
+    fn without_duplicates_the_palette_is_shuffled() {
+        use Colour::*;
+        let config = GameConfig::default();
+        // The shuffle works from the back of the palette, swapping
+        // each place with one from the front of it, and keeps the
+        // last four. Picking each place itself leaves it alone.
+        let mut rng = MockRng::picking(&[(5, 6), (4, 5), (3, 4), (2, 3)]);
+        let pegs = State::<Colour>::generate_new_pegs(config, &mut rng);
+        assert_eq!(pegs, vec![White, Yellow, Green, Orange]);
+        assert_eq!(rng.drawn, 4);
+        // Always swapping with the first place moves Red to the end
+        // and everything along one
+        let mut rng = MockRng::picking(&[(0, 6), (0, 5), (0, 4), (0, 3)]);
+        let pegs = State::<Colour>::generate_new_pegs(config, &mut rng);
+        assert_eq!(pegs, vec![Yellow, Green, Orange, Red]);
+    }
+
+    #[test]
+    fn a_state_picks_its_secrets_with_the_rng_it_was_given() {
+        use Colour::*;
+        let rng = MockRng::picking(&[(5, 6), (4, 5), (3, 4), (2, 3)]);
+        let mut state = State::with_rng(GameConfig::default(), rng, |_| {}, |_| {}).unwrap();
+        assert_eq!(state.reveal_secret(), vec![White, Yellow, Green, Orange]);
+        // The next secret would be the same one, so it's picked a
+        // few times before giving up (See `fresh_pegs_with`)
+        state.forfeit();
+        assert_eq!(state.reveal_secret(), vec![White, Yellow, Green, Orange]);
+        assert!(state.repeated_secret());
+    }
+
+    #[test]
+    fn strict_input_turns_away_a_line_too_long_for_the_guess() {
+        use Colour::*;
+        let mut strict = StateBuilder::new()
+            .config(GameConfig {
+                strict_input: true,
+                ..GameConfig::default()
+            })
+            .build()
+            .unwrap();
+        strict.set_secret(vec![Red, Blue, Green, White]);
+        let error = strict.push_string_input("rgbyw").unwrap_err();
+        assert!(matches!(
+            error.error,
+            MastermindError::GuessTooLong { got: 5, .. }
+        ));
+        assert_eq!(error.buffered, 0);
+        assert!(strict.buffered().is_empty());
+        assert!(strict.history().is_empty());
+        // A line with room left is fine, and then there's less room
+        strict.push_string_input("rg").unwrap();
+        assert!(strict.push_string_input("byw").is_err());
+        assert_eq!(strict.buffered(), vec![Red, Green]);
+
+        let mut lenient = no_duplicates();
+        let events = lenient.push_string_input("rgbyw").unwrap();
+        let finished = events
+            .iter()
+            .filter(|x| matches!(x, GameEvent::GuessEvaluated { .. }))
+            .count();
+        assert_eq!(finished, 1);
+        assert_eq!(
+            &*lenient.history()[0].guess,
+            &[Red, Green, Blue, Yellow][..]
+        );
+        assert_eq!(lenient.buffered(), vec![White]);
+    }
+
+    #[test]
+    fn a_confirmed_guess_waits_to_be_committed() {
+        use Colour::*;
+        let config = GameConfig {
+            confirm_guesses: true,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![Red, Blue, Green, White]);
+        assert!(matches!(
+            state.commit_guess(),
+            Err(MastermindError::GuessTooShort { got: 0, needed: 4 })
+        ));
+        for &colour in &[Red, Blue, Green, Yellow] {
+            assert_eq!(state.input_buffer(colour).unwrap(), TurnOutcome::Pending);
+        }
+        // Full, but not scored yet, and nothing else fits
+        assert!(state.history().is_empty());
+        assert!(matches!(
+            state.input_buffer(Orange),
+            Err(MastermindError::GuessFull { .. })
+        ));
+        // The typo can be taken back and fixed
+        assert_eq!(state.undo_input(), Some(Yellow));
+        assert_eq!(state.input_buffer(White).unwrap(), TurnOutcome::Pending);
+        assert_eq!(
+            state.commit_guess().unwrap(),
+            TurnOutcome::Won { attempts: 1 }
+        );
+        assert_eq!(state.undo_input(), None);
+    }
+
+    #[test]
+    fn without_confirmation_a_full_guess_is_scored_straight_away() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        for &colour in &[Red, Blue, Green] {
+            state.input_buffer(colour).unwrap();
+        }
+        assert_eq!(
+            state.input_buffer(White).unwrap(),
+            TurnOutcome::Won { attempts: 1 }
+        );
+        // There's nothing waiting to commit
+        assert!(state.commit_guess().is_err());
+    }
+
+    #[test]
+    fn a_pasted_line_says_where_it_went_wrong() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        // Two whole guesses, and then blue twice in the third
+        let error = state.push_string_input("rgbywrogbb").unwrap_err();
+        assert!(matches!(
+            error.error,
+            MastermindError::DuplicateNotAllowed { colour: Blue, .. }
+        ));
+        assert_eq!(error.position, Some(9));
+        assert_eq!(error.guesses_finished, 2);
+        assert_eq!(error.buffered, 1);
+        assert!(!error.game_ended);
+        assert_eq!(state.history().len(), 2);
+        assert!(error
+            .to_string()
+            .ends_with("(At character 10, after 2 whole guesses)"));
+
+        // A word which isn't a colour is found before anything is
+        // entered, so nothing of the line is used
+        let mut state = no_duplicates();
+        let error = state.push_string_input("rgbywrogbx").unwrap_err();
+        assert_eq!(error.position, Some(9));
+        assert_eq!(error.guesses_finished, 0);
+        assert_eq!(error.buffered, 0);
+        assert!(state.history().is_empty());
+        assert!(error.to_string().ends_with("(At character 10)"));
+    }
+
+    #[test]
+    fn a_pasted_line_can_end_the_game_before_it_goes_wrong() {
+        let mut state = no_duplicates();
+        let error = state.push_string_input("rbgwrr").unwrap_err();
+        assert_eq!(error.guesses_finished, 1);
+        assert!(error.game_ended);
+        assert_eq!(error.position, Some(5));
+        assert_eq!(state.games()[0].outcome, Outcome::Won);
+    }
+
+    #[test]
+    fn bad_colours_are_told_apart() {
+        use Colour::*;
+        assert_eq!("".parse::<Colour>(), Err(MastermindError::InputTooShort));
+        assert_eq!(
+            "z".parse::<Colour>(),
+            Err(MastermindError::InvalidColourChar('z'))
+        );
+        assert_eq!(
+            "p".parse::<Colour>(),
+            Err(MastermindError::AmbiguousColour {
+                word: "p".into(),
+                could_be: vec![Purple, Pink]
+            })
+        );
+        let mut state = no_duplicates();
+        let error = state.push_string_input("zebra").unwrap_err().error;
+        assert!(
+            matches!(error, MastermindError::UnknownColour { .. }),
+            "{:?}",
+            error
+        );
+        assert_eq!(
+            state.input_buffer(Purple),
+            Err(MastermindError::NotInPlay(Purple))
+        );
+    }
+
+    #[test]
+    fn bad_guesses_are_told_apart() {
+        use Colour::*;
+        let config = GameConfig {
+            block_repeats: true,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![Red, Blue, Green, White]);
+        let guess = Guess::for_config(vec![Red, Green, Blue, Yellow], config).unwrap();
+        state.enter_guess(&guess).unwrap();
+        assert_eq!(
+            state.enter_guess(&guess),
+            Err(MastermindError::RepeatedGuess {
+                attempt: 0,
+                feedback: Feedback {
+                    exact: 1,
+                    misplaced: 2
+                }
+            })
+        );
+        let short = Guess::new(vec![Red, Blue], 2, false).unwrap();
+        assert_eq!(
+            state.enter_guess(&short),
+            Err(MastermindError::InvalidGuess(GuessError::WrongLength {
+                expected: 4,
+                found: 2
+            }))
+        );
+        state.push_string_input("rg").unwrap();
+        assert_eq!(
+            state.commit_guess(),
+            Err(MastermindError::GuessTooShort { got: 2, needed: 4 })
+        );
+    }
+
+    #[test]
+    fn every_error_has_a_message() {
+        use std::error::Error;
+        let errors: Vec<MastermindError> = vec![
+            MastermindError::InputTooShort,
+            MastermindError::InvalidColourChar('z'),
+            MastermindError::NotInPlay(Colour::Pink),
+            MastermindError::GuessTooLong { got: 5, max: 4 },
+            MastermindError::NoSuchAttempt(2),
+        ];
+        for error in &errors {
+            assert!(!error.to_string().is_empty(), "{:?}", error);
+            assert!(error.source().is_none());
+        }
+        assert_eq!(
+            MastermindError::<Colour>::NoSuchAttempt(2).to_string(),
+            "There is no guess 3 this game"
+        );
+    }
+
+    #[test]
+    fn seven_pegs_without_duplicates_is_too_many() {
+        let config = GameConfig {
+            pegs: 7,
+            ..GameConfig::default()
+        };
+        let error = ConfigError::TooManyPegs {
+            requested: 7,
+            available: 6,
+        };
+        let made = State::new(config, |_| {}, |_| {}, SilentOutput);
+        assert_eq!(made.err(), Some(error));
+        assert_eq!(
+            StateBuilder::new().config(config).build().err(),
+            Some(error)
+        );
+        assert_eq!(
+            error.to_string(),
+            "Choose less than or equal to 6 pegs to play with!"
+        );
+        // Duplicates don't make more colours to fill the pegs with
+        let duplicates = GameConfig {
+            allow_duplicates: true,
+            ..config
+        };
+        assert_eq!(duplicates.validate(), Err(error));
+        let more_colours = GameConfig {
+            palette_size: Some(8),
+            ..config
+        };
+        assert_eq!(more_colours.validate(), Ok(()));
+    }
+
+    #[test]
+    fn the_accessors_follow_a_game_along() {
+        use Colour::*;
+        let config = GameConfig {
+            max_tries: Some(5),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![Red, Blue, Green, White]);
+        assert_eq!(state.size(), 4);
+        assert!(!state.allows_duplicates());
+        assert_eq!(state.config().max_tries, Some(5));
+        assert_eq!(state.reveal_secret(), &[Red, Blue, Green, White]);
+        assert_eq!(state.attempts(), 0);
+        assert_eq!(state.remaining_tries(), Some(5));
+        assert_eq!(state.last_scored(), None);
+
+        state.push_string_input("rgby").unwrap();
+        state.push_string_input("wb").unwrap();
+        assert_eq!(state.attempts(), 1);
+        assert_eq!(state.remaining_tries(), Some(4));
+        assert_eq!(state.buffered(), &[White, Blue]);
+        assert_eq!(state.history().len(), 1);
+        assert_eq!(&*state.history()[0].guess, &[Red, Green, Blue, Yellow][..]);
+        let scored = state.last_scored().unwrap();
+        assert_eq!((scored.exact, scored.misplaced), (1, 2));
+        assert!(state.games().is_empty());
+        assert!(state.last_game().is_none());
+
+        state.forfeit();
+        assert_eq!(state.attempts(), 0);
+        assert_eq!(state.remaining_tries(), Some(5));
+        assert!(state.buffered().is_empty());
+        assert_eq!(state.games().len(), 1);
+        assert_eq!(state.games()[0].secret, vec![Red, Blue, Green, White]);
+        assert!(state.last_game().is_some());
+    }
+
+    #[test]
+    fn the_settings_are_kept_with_every_game() {
+        let config = GameConfig {
+            pegs: 5,
+            allow_duplicates: true,
+            max_tries: Some(9),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new()
+            .config(config)
+            .seed(284)
+            .build()
+            .unwrap();
+        assert_eq!(*state.config(), config);
+        assert_eq!(state.summary().settings, config);
+        for _ in 0..3 {
+            state.forfeit();
+        }
+        assert_eq!(state.games().len(), 3);
+        assert!(state.games().iter().all(|x| x.settings == Some(config)));
+        // And they're written down in a way that reads back the same
+        let json = Json::from_serde(&config);
+        assert_eq!(json.to_serde::<GameConfig>().unwrap(), config);
+    }
+
+    #[test]
+    fn everything_a_game_says_goes_through_its_output() {
+        use Colour::*;
+        let mut said = Vec::new();
+        {
+            let config = GameConfig {
+                reveal_every: Some(2),
+                ..GameConfig::default()
+            };
+            let mut state = StateBuilder::new()
+                .config(config)
+                .output(Notes(&mut said))
+                .seed(285)
+                .build()
+                .unwrap();
+            state.set_secret(vec![Red, Blue, Green, White]);
+            state.push_string_input("rgby").unwrap();
+            state.push_string_input("yogr").unwrap();
+            state.push_string_input("rbgw").unwrap();
+        }
+        assert_eq!(said.len(), 3, "{:?}", said);
+        assert_eq!(
+            said[0],
+            "Good try, here are your matching pegs: ●○○ (1 in the correct position and 2 with the right colour)"
+        );
+        assert_eq!(
+            said[1],
+            "Good try, here are your matching pegs: ●○ (1 in the correct position and 1 with the right colour)"
+        );
+        // The help after every second guess, and nothing for the
+        // winning one, which is left to the win callback
+        assert!(
+            said[2].starts_with("Here's some help: position "),
+            "{}",
+            said[2]
+        );
+    }
+
+    #[test]
+    fn the_callbacks_can_change_what_they_capture() {
+        use Colour::*;
+        let mut wins = 0;
+        let mut losses = Vec::new();
+        {
+            let config = GameConfig {
+                max_tries: Some(1),
+                auto_max_tries: false,
+                ..GameConfig::default()
+            };
+            let mut state = State::new(
+                config,
+                |_| wins += 1,
+                |summary| losses.push(summary.game_index),
+                SilentOutput,
+            )
+            .unwrap();
+            for game in 0..4 {
+                state.set_secret(vec![Red, Blue, Green, White]);
+                let line = if game % 2 == 0 { "rbgw" } else { "wgbr" };
+                state.push_string_input(line).unwrap();
+            }
+        }
+        assert_eq!(wins, 2);
+        assert_eq!(losses, vec![1, 3]);
+    }
+
+    #[test]
+    fn the_callbacks_get_a_summary_of_the_game() {
+        use Colour::*;
+        let (mut won, mut lost) = (None, None);
+        {
+            let mut state = State::new(
+                GameConfig::default(),
+                |summary: &GameSummary| won = Some(summary.clone()),
+                |summary: &GameSummary| lost = Some(summary.clone()),
+                SilentOutput,
+            )
+            .unwrap();
+            state.set_secret(vec![Red, Blue, Green, White]);
+            state.push_string_input("rgbyrbgw").unwrap();
+            state.set_secret(vec![Yellow, Orange, Red, Green]);
+            state.push_string_input("rgby").unwrap();
+            state.forfeit();
+        }
+        let won = won.unwrap();
+        assert_eq!(won.phase, GamePhase::Won);
+        assert_eq!(won.game_index, 0);
+        assert_eq!(won.attempts_made, 2);
+        assert_eq!(won.secret, Some(vec![Red, Blue, Green, White]));
+        let guesses: Vec<_> = won.guesses.iter().map(|(x, _)| x.clone()).collect();
+        assert_eq!(
+            guesses,
+            vec![
+                vec![Red, Green, Blue, Yellow],
+                vec![Red, Blue, Green, White]
+            ]
+        );
+        let lost = lost.unwrap();
+        assert_eq!(lost.phase, GamePhase::Lost);
+        assert_eq!(lost.game_index, 1);
+        assert_eq!(lost.attempts_made, 1);
+        assert_eq!(lost.secret, Some(vec![Yellow, Orange, Red, Green]));
+    }
+
+    #[test]
+    fn the_last_peg_of_a_win_is_every_event_at_once() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        for &colour in &[Red, Blue, Green] {
+            assert_eq!(
+                state.step(colour).unwrap(),
+                [GameEvent::PegAccepted {
+                    position: state.buffered().len() - 1
+                }]
+            );
+        }
+        assert_eq!(
+            state.step(White).unwrap(),
+            [
+                GameEvent::PegAccepted { position: 3 },
+                GameEvent::GuessEvaluated {
+                    feedback: Feedback {
+                        exact: 4,
+                        misplaced: 0
+                    }
+                },
+                GameEvent::GameWon,
+                GameEvent::NewGameStarted,
+            ]
+        );
+    }
+
+    #[test]
+    fn a_line_is_the_events_of_each_of_its_pegs() {
+        use Colour::*;
+        let config = GameConfig {
+            max_tries: Some(2),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![Red, Blue, Green, White]);
+        let feedback = |exact, misplaced| GameEvent::GuessEvaluated {
+            feedback: Feedback { exact, misplaced },
+        };
+        let peg = |position| GameEvent::PegAccepted { position };
+        assert_eq!(
+            state.push_string_input("rgbyyo").unwrap(),
+            [
+                peg(0),
+                peg(1),
+                peg(2),
+                peg(3),
+                feedback(1, 2),
+                peg(0),
+                peg(1)
+            ]
+        );
+        assert_eq!(
+            state.push_string_input("rg").unwrap(),
+            [
+                peg(2),
+                peg(3),
+                feedback(0, 2),
+                GameEvent::GameLost {
+                    secret: vec![Red, Blue, Green, White]
+                },
+                GameEvent::NewGameStarted,
+            ]
+        );
+    }
+
+    #[test]
+    fn a_three_colour_game_only_uses_those_colours() {
+        use Colour::*;
+        let config = GameConfig {
+            pegs: 3,
+            palette_size: Some(3),
+            ..GameConfig::default()
+        };
+        assert_eq!(config.palette(), [Red, Blue, White]);
+        for seed in 0..50 {
+            let state = StateBuilder::new()
+                .config(config)
+                .seed(seed)
+                .build()
+                .unwrap();
+            let mut secret = state.reveal_secret().to_vec();
+            secret.sort_unstable();
+            assert_eq!(secret, [Red, Blue, White]);
+        }
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        state.set_secret(vec![White, Red, Blue]);
+        state.push_string_input("rbw").unwrap();
+        state.push_string_input("wbr").unwrap();
+        let events = state.push_string_input("wrb").unwrap();
+        assert!(events.contains(&GameEvent::GameWon));
+        assert_eq!(state.games()[0].attempts, 3);
+    }
+
+    #[test]
+    fn colours_out_of_the_palette_are_rejected() {
+        use Colour::*;
+        let config = GameConfig {
+            pegs: 3,
+            palette_size: Some(3),
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        let error = state.push_string_input("r g b").unwrap_err();
+        assert_eq!(
+            error.error,
+            MastermindError::InvalidGuess(GuessError::NotInPlay(Green))
+        );
+        assert_eq!(error.position, Some(2));
+        assert_eq!(error.error.to_string(), "Green is not in play this game");
+        assert!(state.buffered().is_empty());
+        assert!(state.history().is_empty());
+        assert_eq!(
+            state.input_buffer(Orange),
+            Err(MastermindError::NotInPlay(Orange))
+        );
+        assert!(state.input_buffer(Red).is_ok());
+    }
+
+    #[test]
+    fn single_pegs_and_lines_can_be_mixed() {
+        use Colour::*;
+        let mut state = no_duplicates();
+        state.input_buffer(Red).unwrap();
+        state.push_string_input("b").unwrap();
+        assert_eq!(state.buffered(), [Red, Blue]);
+        state.push_string_input("w").unwrap();
+        state.push_string_input("yellow g").unwrap();
+        assert_eq!(
+            state.history()[0].guess.to_vec(),
+            [Red, Blue, White, Yellow]
+        );
+        assert_eq!(state.buffered(), [Green]);
     }
-    Ok(())
 }
@@ -0,0 +1,185 @@
+//!
+//! `GameObserver`, for anything which wants to hear about each
+//! thing that happens in a game as it happens.
+//!
+//! The `win` and `lose` callbacks a `State` is made with only
+//! say how a game ended. An observer is told about every guess
+//! too, with its feedback, and about each new game starting,
+//! which is enough to keep a log or a live display without
+//! asking the state over and over.
+//!
+//! A state can have any number of observers, added with
+//! `State::add_observer`. The callbacks it was made with are one
+//! of them, wrapped up in `Callbacks`.
+//!
+
+use super::{Colour, Feedback, GameConfig, GamePhase, GameSummary, OnGameOver, Symbol};
+
+///
+/// Gets told what happens in a game. Every method does nothing
+/// unless it's overridden, like an adapter class in Java, so an
+/// observer only needs the ones it cares about.
+///
+/// `P` is what the game's pegs are, see `symbol.rs`.
+///
+pub trait GameObserver<P = Colour> {
+    ///
+    /// A guess was finished and scored. `attempt` is which one it
+    /// was this game, counting from one. This comes before
+    /// `on_win` or `on_lose` for a guess which ended the game.
+    ///
+    fn on_guess(&mut self, _guess: &[P], _feedback: Feedback, _attempt: usize) {}
+
+    ///
+    /// The game was won, after `attempts` guesses.
+    ///
+    fn on_win(&mut self, _attempts: usize) {}
+
+    ///
+    /// The game was lost (Or given up), and this was the code.
+    ///
+    fn on_lose(&mut self, _secret: &[P]) {}
+
+    ///
+    /// The game just ended, with everything about it: its
+    /// `phase` says how, and it has the secret. This comes after
+    /// `on_win` or `on_lose`.
+    ///
+    fn on_game_over(&mut self, _summary: &GameSummary<P>) {}
+
+    ///
+    /// The next game has started, with these settings.
+    ///
+    fn on_new_game(&mut self, _config: &GameConfig) {}
+}
+
+///
+/// The `win` and `lose` callbacks a `State` is made with, as an
+/// observer, so that they're called in the same place as any
+/// other.
+///
+pub struct Callbacks<'a, P = Colour> {
+    win: OnGameOver<'a, P>,
+    lose: OnGameOver<'a, P>,
+}
+
+impl<'a, P> Callbacks<'a, P> {
+    pub fn new(
+        win: impl FnMut(&GameSummary<P>) + 'a,
+        lose: impl FnMut(&GameSummary<P>) + 'a,
+    ) -> Self {
+        Self {
+            win: Box::new(win),
+            lose: Box::new(lose),
+        }
+    }
+}
+
+impl<P: Symbol> GameObserver<P> for Callbacks<'_, P> {
+    fn on_game_over(&mut self, summary: &GameSummary<P>) {
+        match summary.phase {
+            GamePhase::Won => (self.win)(summary),
+            _ => (self.lose)(summary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{State, StateBuilder};
+    use super::*;
+    use Colour::*;
+
+    // Writes down everything it's told, in order
+    struct Recording<'b>(&'b mut Vec<String>);
+
+    impl GameObserver for Recording<'_> {
+        fn on_guess(&mut self, guess: &[Colour], feedback: Feedback, attempt: usize) {
+            self.0
+                .push(format!("guess {} {:?} {}", attempt, guess, feedback));
+        }
+
+        fn on_win(&mut self, attempts: usize) {
+            self.0.push(format!("win {}", attempts));
+        }
+
+        fn on_lose(&mut self, secret: &[Colour]) {
+            self.0.push(format!("lose {:?}", secret));
+        }
+
+        fn on_game_over(&mut self, summary: &GameSummary) {
+            self.0.push(format!("over {:?}", summary.phase));
+        }
+
+        fn on_new_game(&mut self, config: &GameConfig) {
+            self.0.push(format!("new {}", config.pegs));
+        }
+    }
+
+    // Plays `lines` with a recording observer, two tries a game
+    fn record(lines: &[&str]) -> Vec<String> {
+        let mut events = Vec::new();
+        {
+            let config = GameConfig {
+                max_tries: Some(2),
+                auto_max_tries: false,
+                ..GameConfig::default()
+            };
+            let mut state: State = StateBuilder::new().config(config).build().unwrap();
+            state.add_observer(Recording(&mut events));
+            state.set_secret(vec![Red, Blue, Green, White]);
+            for line in lines {
+                state.push_string_input(line).unwrap();
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn a_win_is_every_guess_then_the_win() {
+        assert_eq!(
+            record(&["rgby", "rbgw"]),
+            [
+                "guess 1 [Red, Green, Blue, Yellow] ●○○",
+                "guess 2 [Red, Blue, Green, White] ●●●●",
+                "win 2",
+                "over Won",
+                "new 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn a_loss_is_every_guess_then_the_secret() {
+        assert_eq!(
+            record(&["rgby", "yorg"]),
+            [
+                "guess 1 [Red, Green, Blue, Yellow] ●○○",
+                "guess 2 [Yellow, Orange, Red, Green] ○○",
+                "lose [Red, Blue, Green, White]",
+                "over Lost",
+                "new 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn the_callbacks_hear_the_same_endings() {
+        let (mut won, mut lost) = (0, 0);
+        let mut events = Vec::new();
+        {
+            let mut state: State = StateBuilder::new()
+                .on_win(|_| won += 1)
+                .on_lose(|_| lost += 1)
+                .build()
+                .unwrap();
+            state.add_observer(Recording(&mut events));
+            state.set_secret(vec![Red, Blue, Green, White]);
+            state.push_string_input("rbgw").unwrap();
+            state.forfeit();
+        }
+        assert_eq!((won, lost), (1, 1));
+        let endings: Vec<_> = events.iter().filter(|x| x.starts_with("over")).collect();
+        assert_eq!(endings, ["over Won", "over Lost"]);
+    }
+}
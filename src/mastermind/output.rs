@@ -0,0 +1,65 @@
+//!
+//! `GameOutput`, where `State` sends the few things it has to
+//! say itself while a game is played.
+//!
+//! Most of the talking is done by a frontend, which looks at
+//! what each guess did and decides how to show it. A `State`
+//! playing on its own (Like in the demo) still needs to say how
+//! a guess went, and instead of printing that straight away it
+//! hands it to one of these. That way the same game can print to
+//! a terminal, stay quiet, or have its messages collected up by
+//! whatever is embedding it.
+//!
+
+use super::wording::FeedbackStyle;
+use super::Feedback;
+
+///
+/// Something which can be told what's happening in a game.
+/// This is an interface in Java terms, and `State` keeps one as
+/// a `Box<dyn GameOutput>` so that it doesn't need to know which.
+///
+/// Only `message` needs writing: everything else is turned into
+/// a message unless an implementation wants to do better.
+///
+pub trait GameOutput {
+    ///
+    /// Something to tell the player, as a line of text.
+    ///
+    fn message(&mut self, text: &str);
+
+    ///
+    /// The feedback for a guess which didn't win. A winning one
+    /// is left to the `win` callback.
+    ///
+    fn feedback(&mut self, feedback: &Feedback) {
+        self.message(&format!(
+            "Good try, here are your matching pegs: {} ({})",
+            feedback,
+            FeedbackStyle::default().describe(*feedback)
+        ));
+    }
+}
+
+///
+/// Prints every message on a line of its own.
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TerminalOutput;
+
+impl GameOutput for TerminalOutput {
+    fn message(&mut self, text: &str) {
+        println!("{}", text);
+    }
+}
+
+///
+/// Says nothing, for a game where something else (Like a
+/// frontend) does the talking.
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentOutput;
+
+impl GameOutput for SilentOutput {
+    fn message(&mut self, _: &str) {}
+}
@@ -0,0 +1,230 @@
+//!
+//! Codes packed into a single `u32`, for solver code which goes
+//! through so many of them that a `Vec<Colour>` each would be
+//! most of the work.
+//!
+//! Each peg takes three bits, which is enough for the eight
+//! colours of `COLOURS`, and the first peg is in the lowest
+//! three, so ten pegs fit in the thirty bits at the bottom. A
+//! `PackedCode` doesn't know how many pegs it has, so that's
+//! always given alongside it:
+//! ```
+//! let packed = pack(&[Colour::Red, Colour::Blue]);
+//! assert_eq!(unpack(packed, 2), vec![Colour::Red, Colour::Blue]);
+//! ```
+//! Blanks aren't one of the eight, so a code with a blank in it
+//! can't be packed. Everything else, like `State`, keeps using
+//! lists of colours.
+//!
+
+use super::{Colour, Feedback, COLOURS, PALETTE_SIZE};
+
+///
+/// How many bits each peg takes.
+///
+pub const BITS: u32 = 3;
+
+///
+/// The most pegs a `PackedCode` can have.
+///
+pub const MAX_PACKED_PEGS: usize = 10;
+
+///
+/// A code of up to `MAX_PACKED_PEGS` colours, see the top of
+/// this file. Two codes of the same length are the same code if
+/// and only if they're equal.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackedCode(u32);
+
+impl PackedCode {
+    ///
+    /// The bits of the code, three for each peg.
+    ///
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    //
+    // Where in `COLOURS` the peg at `position` is.
+    //
+    fn peg(self, position: usize) -> usize {
+        ((self.0 >> (position as u32 * BITS)) & ((1 << BITS) - 1)) as usize
+    }
+}
+
+///
+/// Packs `code`. This panics if it has a blank in it, or more
+/// than `MAX_PACKED_PEGS` pegs, since neither will fit.
+///
+pub fn pack(code: &[Colour]) -> PackedCode {
+    assert!(
+        code.len() <= MAX_PACKED_PEGS,
+        "Only {} pegs fit in a packed code",
+        MAX_PACKED_PEGS
+    );
+    let bits = code
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (position, &colour)| {
+            assert!(colour != Colour::Blank, "A blank can't be packed");
+            bits | ((colour as u32) << (position as u32 * BITS))
+        });
+    PackedCode(bits)
+}
+
+///
+/// `pack`, or `None` for a code which won't fit, rather than
+/// panicking.
+///
+pub fn try_pack(code: &[Colour]) -> Option<PackedCode> {
+    if code.len() > MAX_PACKED_PEGS || code.contains(&Colour::Blank) {
+        return None;
+    }
+    Some(pack(code))
+}
+
+///
+/// The `size` colours of `code`, which is the opposite of `pack`.
+///
+pub fn unpack(code: PackedCode, size: usize) -> Vec<Colour> {
+    (0..size).map(|x| COLOURS[code.peg(x)]).collect()
+}
+
+///
+/// The feedback for guessing `b` when the secret is `a`, which
+/// is the same as `score_guess` on the unpacked codes, but
+/// without making any lists.
+///
+pub fn feedback_packed(a: PackedCode, b: PackedCode, size: usize) -> Feedback {
+    // How many of each colour the two codes have, and then how
+    // many of them they have in common, in or out of place.
+    let mut in_a = [0; PALETTE_SIZE];
+    let mut in_b = [0; PALETTE_SIZE];
+    let mut exact = 0;
+    for position in 0..size {
+        let (x, y) = (a.peg(position), b.peg(position));
+        if x == y {
+            exact += 1;
+        }
+        in_a[x] += 1;
+        in_b[y] += 1;
+    }
+    let shared = in_a
+        .iter()
+        .zip(&in_b)
+        .map(|(&x, &y)| usize::min(x, y))
+        .sum::<usize>();
+    Feedback {
+        exact,
+        misplaced: shared - exact,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::{all_codes, score_guess, GameConfig, StateBuilder};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_code(rng: &mut StdRng, size: usize) -> Vec<Colour> {
+        (0..size)
+            .map(|_| COLOURS[rng.gen_range(0, COLOURS.len())])
+            .collect()
+    }
+
+    #[test]
+    fn every_four_peg_code_round_trips() {
+        for code in all_codes(COLOURS, 4, true) {
+            assert_eq!(unpack(pack(&code), 4), code);
+        }
+    }
+
+    #[test]
+    fn codes_of_any_length_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for size in 0..=MAX_PACKED_PEGS {
+            for _ in 0..100 {
+                let code = random_code(&mut rng, size);
+                let packed = pack(&code);
+                assert_eq!(unpack(packed, size), code);
+                assert_eq!(try_pack(&code), Some(packed));
+                assert!(packed.bits() < 1 << (size as u32 * BITS));
+            }
+        }
+    }
+
+    #[test]
+    fn first_peg_is_lowest() {
+        assert_eq!(pack(&[]).bits(), 0);
+        assert_eq!(pack(&[Colour::Blue]).bits(), 1);
+        assert_eq!(pack(&[Colour::Red, Colour::Blue]).bits(), 1 << BITS);
+        assert_eq!(pack(&[Colour::Pink; 10]).bits(), (1 << 30) - 1);
+    }
+
+    #[test]
+    fn codes_which_dont_fit() {
+        assert_eq!(try_pack(&[Colour::Red, Colour::Blank]), None);
+        assert_eq!(try_pack(&[Colour::Red; MAX_PACKED_PEGS + 1]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "A blank can't be packed")]
+    fn packing_a_blank_panics() {
+        pack(&[Colour::Blank]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only 10 pegs fit")]
+    fn packing_too_many_panics() {
+        pack(&[Colour::Red; MAX_PACKED_PEGS + 1]);
+    }
+
+    #[test]
+    fn packed_feedback_agrees_with_score_guess() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..5000 {
+            let size = rng.gen_range(1, MAX_PACKED_PEGS + 1);
+            let a = random_code(&mut rng, size);
+            let b = random_code(&mut rng, size);
+            assert_eq!(
+                feedback_packed(pack(&a), pack(&b), size),
+                score_guess(&a, &b),
+                "{:?} {:?}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn packed_feedback_agrees_with_matching() {
+        let config = GameConfig {
+            allow_duplicates: true,
+            palette_size: None,
+            max_tries: None,
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut state = StateBuilder::new().config(config).build().unwrap();
+        for _ in 0..1000 {
+            let secret = random_code(&mut rng, 4);
+            let guess = random_code(&mut rng, 4);
+            state.set_secret(secret.clone());
+            for &colour in &guess {
+                state.input_buffer(colour).unwrap();
+            }
+            let expected = feedback_packed(pack(&secret), pack(&guess), 4);
+            // Guessing the secret starts a new game, which has
+            // nothing to match against any more.
+            if secret == guess {
+                assert_eq!(expected.exact, 4);
+            } else {
+                let last = state.history().len() - 1;
+                assert_eq!(state.matching(Some(last)), Ok(expected));
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//!
+//! Player profiles. A profile is a name, the settings that
+//! player likes to use, and an `id` which never changes, not
+//! even when the profile is renamed. Anything which needs to
+//! remember who played what, like the game history, refers to
+//! the player by that `id`.
+//!
+//! Each profile is stored as `profiles/<id>.json` in the data
+//! directory, and `profiles/last` holds the `id` of whoever
+//! played last.
+//!
+
+use super::json::Json;
+use super::storage;
+use super::GameConfig;
+use std::io;
+use std::path::PathBuf;
+
+///
+/// One player's profile.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub id: u64,
+    pub name: String,
+    ///
+    /// The settings this player used last, which are used as
+    /// the defaults when they set up a new game. `None` until
+    /// they've set up a game.
+    ///
+    pub preferred: Option<GameConfig>,
+}
+
+impl Profile {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("id", self.id.into()),
+            ("name", self.name.as_str().into()),
+            ("preferred", Json::from_serde(&self.preferred)),
+        ])
+    }
+
+    fn from_json(json: &Json) -> Option<Self> {
+        Some(Self {
+            id: json.get("id")?.as_usize()? as u64,
+            name: json.get("name")?.as_str()?.to_string(),
+            preferred: json.get("preferred")?.to_serde().ok()?,
+        })
+    }
+
+    ///
+    /// Writes any changes to the profile back to its file.
+    ///
+    pub fn store(&self) -> io::Result<()> {
+        std::fs::create_dir_all(profiles_dir())?;
+        std::fs::write(profile_path(self.id), self.to_json().to_string())
+    }
+}
+
+fn profiles_dir() -> PathBuf {
+    storage::data_dir().join("profiles")
+}
+
+fn profile_path(id: u64) -> PathBuf {
+    profiles_dir().join(format!("{}.json", id))
+}
+
+//
+// `io::Error` can carry our own messages too, which keeps every
+// function in here returning the same kind of error.
+//
+fn error(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+///
+/// Checks that `name` is reasonable to show on screen.
+///
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name.chars().count() > 32 {
+        return Err("Player names must be between 1 and 32 characters long".into());
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Player names can't contain control characters".into());
+    }
+    Ok(())
+}
+
+///
+/// Every profile, sorted by name.
+///
+pub fn list() -> io::Result<Vec<Profile>> {
+    let entries = match std::fs::read_dir(profiles_dir()) {
+        Ok(x) => x,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut profiles = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|x| x == "json") {
+            // A damaged profile is skipped instead of stopping
+            // everyone else from playing.
+            if let Some(profile) = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|x| Json::parse(&x).ok())
+                .and_then(|x| Profile::from_json(&x))
+            {
+                profiles.push(profile);
+            }
+        }
+    }
+    profiles.sort_by_key(|x| x.name.to_lowercase());
+    Ok(profiles)
+}
+
+///
+/// Finds a profile by name, ignoring upper and lower case.
+///
+pub fn find(name: &str) -> io::Result<Option<Profile>> {
+    let name = name.trim().to_lowercase();
+    Ok(list()?.into_iter().find(|x| x.name.to_lowercase() == name))
+}
+
+///
+/// Finds a profile by its `id`.
+///
+pub fn find_by_id(id: u64) -> io::Result<Option<Profile>> {
+    Ok(list()?.into_iter().find(|x| x.id == id))
+}
+
+///
+/// Makes a new profile called `name`.
+///
+pub fn create(name: &str) -> io::Result<Profile> {
+    let name = name.trim();
+    validate_name(name).map_err(error)?;
+    let existing = list()?;
+    if existing
+        .iter()
+        .any(|x| x.name.to_lowercase() == name.to_lowercase())
+    {
+        return Err(error(format!(
+            "There is already a player called `{}`",
+            name
+        )));
+    }
+    let profile = Profile {
+        // Ids are never reused, even if a profile is deleted, as
+        // long as it wasn't the newest one.
+        id: existing.iter().map(|x| x.id).max().unwrap_or(0) + 1,
+        name: name.to_string(),
+        preferred: None,
+    };
+    profile.store()?;
+    Ok(profile)
+}
+
+///
+/// Finds the profile called `name`, or makes it if it's new.
+///
+pub fn find_or_create(name: &str) -> io::Result<Profile> {
+    match find(name)? {
+        Some(profile) => Ok(profile),
+        None => create(name),
+    }
+}
+
+///
+/// Renames a profile. The `id` stays the same, so everything
+/// recorded for the old name carries over.
+///
+pub fn rename(old: &str, new: &str) -> io::Result<Profile> {
+    let new = new.trim();
+    validate_name(new).map_err(error)?;
+    let mut profile =
+        find(old)?.ok_or_else(|| error(format!("There is no player called `{}`", old)))?;
+    if let Some(other) = find(new)? {
+        if other.id != profile.id {
+            return Err(error(format!("There is already a player called `{}`", new)));
+        }
+    }
+    profile.name = new.to_string();
+    profile.store()?;
+    Ok(profile)
+}
+
+///
+/// Deletes a profile. The games they played stay in the
+/// history, but no longer count towards anyone's statistics.
+///
+pub fn delete(name: &str) -> io::Result<()> {
+    let profile =
+        find(name)?.ok_or_else(|| error(format!("There is no player called `{}`", name)))?;
+    std::fs::remove_file(profile_path(profile.id))
+}
+
+///
+/// The `id` of whoever played last, if anyone has.
+///
+pub fn last_used() -> Option<u64> {
+    std::fs::read_to_string(profiles_dir().join("last"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+///
+/// Remembers `profile` as the one who played last.
+///
+pub fn set_last_used(profile: &Profile) -> io::Result<()> {
+    std::fs::create_dir_all(profiles_dir())?;
+    std::fs::write(profiles_dir().join("last"), profile.id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_preferred_settings_are_kept_with_the_profile() {
+        let mut profile = Profile {
+            id: 7,
+            name: "Sam".to_string(),
+            preferred: None,
+        };
+        assert_eq!(
+            Profile::from_json(&profile.to_json()),
+            Some(profile.clone())
+        );
+        profile.preferred = Some(GameConfig {
+            pegs: 5,
+            hardcore: true,
+            ..GameConfig::default()
+        });
+        let text = profile.to_json().to_string();
+        let json = Json::parse(&text).unwrap();
+        assert_eq!(Profile::from_json(&json), Some(profile));
+    }
+}
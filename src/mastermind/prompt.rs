@@ -0,0 +1,250 @@
+//!
+//! Asking the player a question, and asking again until the
+//! answer is one we can use.
+//!
+
+use std::fmt::Display;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::str::FromStr;
+
+///
+/// How many answers we take before giving up on a question.
+///
+pub const MAX_ATTEMPTS: usize = 5;
+
+///
+/// Writes `question` to `out` and reads an answer from `inp`,
+/// parsed as a `T` (Like `bool` or `usize`) and checked with
+/// `validate`. A blank answer means `default`, if there is one,
+/// which isn't checked.
+///
+/// A wrong answer is met with the reason it's wrong, and the
+/// question again, up to `MAX_ATTEMPTS` times. Running out of
+/// attempts is an error rather than asking forever, and so is
+/// running out of input, unless `eof_takes_default` is set and
+/// there is a default (For when the answers are being piped in,
+/// and nobody is there to give any more).
+///
+/// `T::Err: Display` means that the error from parsing can be
+/// shown to the player, which is true of everything in the
+/// standard library.
+///
+pub fn prompt<T: FromStr>(
+    out: &mut impl Write,
+    inp: &mut impl BufRead,
+    question: &str,
+    mut default: Option<T>,
+    validate: impl Fn(&T) -> Result<(), String>,
+    eof_takes_default: bool,
+) -> io::Result<T>
+where
+    T::Err: Display,
+{
+    let mut answer = String::new();
+    for _ in 0..MAX_ATTEMPTS {
+        write!(out, "{}", question)?;
+        out.flush()?;
+        answer.clear();
+        if inp.read_line(&mut answer)? == 0 {
+            // The question is still waiting for its line to end
+            writeln!(out)?;
+            if let (true, Some(x)) = (eof_takes_default, default.take()) {
+                return Ok(x);
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Ran out of input while waiting for an answer",
+            ));
+        }
+        let reason = match answer.trim() {
+            "" => match default.take() {
+                Some(x) => return Ok(x),
+                None => "Please enter an answer".to_string(),
+            },
+            x => match x.parse::<T>() {
+                Ok(x) => match validate(&x) {
+                    Ok(()) => return Ok(x),
+                    Err(e) => e,
+                },
+                Err(e) => e.to_string(),
+            },
+        };
+        writeln!(out, "Please try again! {}", reason)?;
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("No usable answer after {} tries", MAX_ATTEMPTS),
+    ))
+}
+
+///
+/// An answer to a yes or no question, which is "yes" or "no",
+/// or just their first letters, in any case. The `bool` is
+/// whether it was yes.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct YesNo(pub bool);
+
+impl FromStr for YesNo {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "yes" | "y" => Ok(YesNo(true)),
+            "no" | "n" => Ok(YesNo(false)),
+            _ => Err("Enter \"yes\" or \"no\"."),
+        }
+    }
+}
+
+///
+/// `prompt` on the terminal. If the answers aren't being typed
+/// (Standard input isn't a terminal), running out of them takes
+/// the default.
+///
+pub fn ask<T: FromStr>(
+    question: &str,
+    default: Option<T>,
+    validate: impl Fn(&T) -> Result<(), String>,
+) -> io::Result<T>
+where
+    T::Err: Display,
+{
+    prompt(
+        &mut io::stdout(),
+        &mut io::stdin().lock(),
+        question,
+        default,
+        validate,
+        !io::stdin().is_terminal(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //
+    // Asks for a number from 1 to 6 with the answers in `input`,
+    // giving back the result and everything written out.
+    //
+    fn ask_die(
+        input: &str,
+        default: Option<u8>,
+        eof_takes_default: bool,
+    ) -> (io::Result<u8>, String) {
+        let mut out = Vec::new();
+        let result = prompt(
+            &mut out,
+            &mut input.as_bytes(),
+            "Roll? ",
+            default,
+            |&x| {
+                if (1..=6).contains(&x) {
+                    Ok(())
+                } else {
+                    Err("Enter 1-6.".to_string())
+                }
+            },
+            eof_takes_default,
+        );
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn a_good_answer_is_taken_straight_away() {
+        let (result, out) = ask_die(" 4 \n", None, false);
+        assert_eq!(result.unwrap(), 4);
+        assert_eq!(out, "Roll? ");
+    }
+
+    #[test]
+    fn a_blank_answer_is_the_default_which_isnt_checked() {
+        assert_eq!(ask_die("\n", Some(3), false).0.unwrap(), 3);
+        assert_eq!(ask_die("   \n", Some(9), false).0.unwrap(), 9);
+    }
+
+    #[test]
+    fn a_blank_answer_without_a_default_is_asked_again() {
+        let (result, out) = ask_die("\n2\n", None, false);
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(
+            out,
+            "Roll? Please try again! Please enter an answer\nRoll? "
+        );
+    }
+
+    #[test]
+    fn wrong_answers_are_asked_again_with_the_reason() {
+        let (result, out) = ask_die("seven\n7\n6\n", Some(1), false);
+        assert_eq!(result.unwrap(), 6);
+        let lines: Vec<_> = out.split("Roll? ").filter(|x| !x.is_empty()).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Please try again! invalid digit"));
+        assert_eq!(lines[1], "Please try again! Enter 1-6.\n");
+    }
+
+    #[test]
+    fn too_many_wrong_answers_are_an_error() {
+        let input = "0\n".repeat(MAX_ATTEMPTS);
+        let (result, out) = ask_die(&input, Some(1), false);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(out.matches("Roll? ").count(), MAX_ATTEMPTS);
+        // One fewer wrong answer leaves a try for a good one
+        let input = format!("{}5\n", "0\n".repeat(MAX_ATTEMPTS - 1));
+        assert_eq!(ask_die(&input, None, false).0.unwrap(), 5);
+    }
+
+    #[test]
+    fn running_out_of_input_is_an_error() {
+        let (result, out) = ask_die("", Some(1), false);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(out, "Roll? \n");
+    }
+
+    #[test]
+    fn running_out_of_input_mid_question_is_an_error() {
+        // A wrong answer, then a half-typed one which never ends
+        let (result, out) = ask_die("8\n", Some(1), false);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert!(out.ends_with("Roll? \n"));
+        let (result, _) = ask_die("8\n", None, true);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn a_partial_last_line_is_still_an_answer() {
+        assert_eq!(ask_die("5", None, false).0.unwrap(), 5);
+    }
+
+    #[test]
+    fn piped_input_takes_the_default_when_it_runs_out() {
+        assert_eq!(ask_die("", Some(2), true).0.unwrap(), 2);
+        assert_eq!(ask_die("9\n", Some(2), true).0.unwrap(), 2);
+    }
+
+    #[test]
+    fn yes_and_no_take_their_first_letters() {
+        for &(text, answer) in &[("yes", true), ("Y", true), (" no ", false), ("N", false)] {
+            assert_eq!(text.parse::<YesNo>(), Ok(YesNo(answer)));
+        }
+        assert!("maybe".parse::<YesNo>().is_err());
+        assert!("yeah".parse::<YesNo>().is_err());
+    }
+
+    #[test]
+    fn yes_or_no_is_asked_again_until_it_is_one() {
+        let mut out = Vec::new();
+        let answer = prompt(
+            &mut out,
+            &mut "sure\ny\n".as_bytes(),
+            "Tutorial? ",
+            Some(YesNo(false)),
+            |_| Ok(()),
+            false,
+        );
+        assert_eq!(answer.unwrap(), YesNo(true));
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Please try again! Enter \"yes\" or \"no\"."));
+    }
+}
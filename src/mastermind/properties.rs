@@ -0,0 +1,133 @@
+//!
+//! Checks of what has to hold for every code, rather than for
+//! a few picked by hand: a few thousand settings and codes are
+//! drawn from a seeded generator, so a failure happens again
+//! the next time the tests are run and can be looked into.
+//!
+//! Each test goes round a loop of `CASES` cases, making up
+//! settings with `settings` and codes for them with
+//! `State::generate_pegs_with`, and checking one thing about
+//! every one of them.
+//!
+
+use super::{score_guess, Colour, GameConfig, Guess, State, StateBuilder, Symbol};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+const CASES: usize = 2000;
+
+// Settings for a game of 2 to 6 pegs, from as few colours as
+// they can have (One for each peg) up to all of them, with or
+// without duplicates and blanks.
+fn settings(rng: &mut impl Rng) -> GameConfig {
+    let pegs = rng.gen_range(2, 7);
+    GameConfig {
+        pegs,
+        allow_duplicates: rng.gen(),
+        allow_blanks: rng.gen(),
+        palette_size: Some(rng.gen_range(pegs, Colour::all().len() + 1)),
+        ..GameConfig::default()
+    }
+}
+
+// Some settings, and two codes for them
+fn case(rng: &mut impl Rng) -> (GameConfig, Vec<Colour>, Vec<Colour>) {
+    let config = settings(rng);
+    let secret = State::<Colour>::generate_pegs_with(config, rng);
+    let guess = State::<Colour>::generate_pegs_with(config, rng);
+    (config, secret, guess)
+}
+
+#[test]
+fn codes_have_as_many_pegs_as_the_settings() {
+    let mut rng = StdRng::seed_from_u64(270);
+    for _ in 0..CASES {
+        let (config, secret, _) = case(&mut rng);
+        assert_eq!(config.validate(), Ok(()), "{:?}", config);
+        assert_eq!(secret.len(), config.pegs, "{:?}", config);
+        let palette = config.palette();
+        assert!(secret.iter().all(|x| palette.contains(x)), "{:?}", secret);
+    }
+}
+
+#[test]
+fn codes_without_duplicates_are_all_different() {
+    let mut rng = StdRng::seed_from_u64(271);
+    for _ in 0..CASES {
+        let (config, secret, _) = case(&mut rng);
+        if !config.allow_duplicates {
+            let colours = secret.iter().collect::<HashSet<_>>();
+            assert_eq!(colours.len(), secret.len(), "{:?}", secret);
+        }
+    }
+}
+
+#[test]
+fn a_code_against_itself_is_all_exact() {
+    let mut rng = StdRng::seed_from_u64(272);
+    for _ in 0..CASES {
+        let (config, secret, _) = case(&mut rng);
+        let feedback = score_guess(&secret, &secret);
+        assert_eq!((feedback.exact, feedback.misplaced), (config.pegs, 0));
+        assert!(feedback.is_win(config.pegs));
+    }
+}
+
+#[test]
+fn the_total_is_the_same_both_ways_round() {
+    let mut rng = StdRng::seed_from_u64(273);
+    for _ in 0..CASES {
+        let (_, secret, guess) = case(&mut rng);
+        let there = score_guess(&secret, &guess);
+        let back = score_guess(&guess, &secret);
+        assert_eq!(there.exact, back.exact, "{:?} {:?}", secret, guess);
+        assert_eq!(
+            there.exact + there.misplaced,
+            back.exact + back.misplaced,
+            "{:?} {:?}",
+            secret,
+            guess
+        );
+    }
+}
+
+#[test]
+fn the_total_never_tops_the_pegs() {
+    let mut rng = StdRng::seed_from_u64(274);
+    for _ in 0..CASES {
+        let (config, secret, guess) = case(&mut rng);
+        let feedback = score_guess(&secret, &guess);
+        assert!(feedback.exact + feedback.misplaced <= config.pegs);
+        assert_eq!(feedback.is_win(config.pegs), secret == guess);
+    }
+}
+
+#[test]
+fn a_game_scores_like_score_guess() {
+    let mut rng = StdRng::seed_from_u64(275);
+    for _ in 0..CASES / 10 {
+        let config = GameConfig {
+            max_tries: None,
+            auto_max_tries: false,
+            ..settings(&mut rng)
+        };
+        let seed = rng.gen();
+        let mut state = StateBuilder::new()
+            .config(config)
+            .seed(seed)
+            .build()
+            .unwrap();
+        let secret = state.reveal_secret().to_vec();
+        for _ in 0..5 {
+            let guess = State::<Colour>::generate_pegs_with(config, &mut rng);
+            if guess == secret {
+                continue;
+            }
+            let entered = Guess::for_config(guess.clone(), config).unwrap();
+            state.enter_guess(&entered).unwrap();
+            let scored = state.history().last().unwrap().feedback;
+            assert_eq!(scored, score_guess(&secret, &guess));
+        }
+    }
+}
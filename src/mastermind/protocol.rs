@@ -0,0 +1,342 @@
+//!
+//! The messages two copies of the game send each other when
+//! playing over a connection (See `local.rs`). Each message is
+//! one line of JSON, so any stream which carries text will do.
+//!
+
+use super::json::Json;
+use super::{Colour, Feedback, GameConfig, Outcome};
+use std::io::{self, BufRead, Read, Write};
+use std::time::Duration;
+
+///
+/// Everything that can be said over the connection. The host
+/// says `Hello` first, then the guesser sends `Guess`es, each of
+/// which is answered with `Feedback`, `Rejected` or `GameOver`.
+///
+/// The guesser can ask for other `Settings` before guessing,
+/// which the host answers with another `Hello` if it agrees. A
+/// server also answers `Stats` with `ServerStats`.
+///
+/// A server follows its `Hello` with a `Token`, which a guesser
+/// whose connection dropped can send back as `Resume` from a new
+/// connection to carry on with the same game. That's answered
+/// with `Resumed`, which has the guesses made so far.
+///
+/// A server which is getting guesses faster than it allows
+/// answers them with `SlowDown` instead, saying how long until
+/// it will take the next one.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Hello(GameConfig),
+    Settings(GameConfig),
+    Token(String),
+    Resume(String),
+    Resumed {
+        config: GameConfig,
+        board: Vec<(Vec<Colour>, Feedback)>,
+    },
+    Stats,
+    ServerStats {
+        active: usize,
+        completed: usize,
+    },
+    Guess(Vec<Colour>),
+    Feedback {
+        exact: usize,
+        colour: usize,
+    },
+    Rejected(String),
+    SlowDown(Duration),
+    GameOver {
+        outcome: Outcome,
+        secret: Vec<Colour>,
+    },
+}
+
+impl Message {
+    pub fn to_json(&self) -> Json {
+        let colours = |code: &[Colour]| Json::Array(code.iter().map(|x| x.name().into()).collect());
+        match self {
+            Message::Hello(config) => Json::object(vec![
+                ("type", "hello".into()),
+                ("settings", Json::from_serde(config)),
+            ]),
+            Message::Settings(config) => Json::object(vec![
+                ("type", "settings".into()),
+                ("settings", Json::from_serde(config)),
+            ]),
+            Message::Token(token) => Json::object(vec![
+                ("type", "token".into()),
+                ("token", token.as_str().into()),
+            ]),
+            Message::Resume(token) => Json::object(vec![
+                ("type", "resume".into()),
+                ("token", token.as_str().into()),
+            ]),
+            Message::Resumed { config, board } => Json::object(vec![
+                ("type", "resumed".into()),
+                ("settings", Json::from_serde(config)),
+                (
+                    "board",
+                    Json::Array(
+                        board
+                            .iter()
+                            .map(|(code, feedback)| {
+                                Json::object(vec![
+                                    ("code", colours(code)),
+                                    ("correct_position", feedback.exact.into()),
+                                    ("correct_colour", feedback.misplaced.into()),
+                                ])
+                            })
+                            .collect(),
+                    ),
+                ),
+            ]),
+            Message::Stats => Json::object(vec![("type", "stats".into())]),
+            Message::ServerStats { active, completed } => Json::object(vec![
+                ("type", "server_stats".into()),
+                ("active_sessions", (*active).into()),
+                ("games_completed", (*completed).into()),
+            ]),
+            Message::Guess(code) => {
+                Json::object(vec![("type", "guess".into()), ("code", colours(code))])
+            }
+            Message::Feedback { exact, colour } => Json::object(vec![
+                ("type", "feedback".into()),
+                ("correct_position", (*exact).into()),
+                ("correct_colour", (*colour).into()),
+            ]),
+            Message::Rejected(reason) => Json::object(vec![
+                ("type", "rejected".into()),
+                ("reason", reason.as_str().into()),
+            ]),
+            Message::SlowDown(wait) => Json::object(vec![
+                ("type", "slow_down".into()),
+                ("retry_after_ms", (wait.as_millis() as usize).into()),
+            ]),
+            Message::GameOver { outcome, secret } => Json::object(vec![
+                ("type", "game_over".into()),
+                ("outcome", outcome.name().into()),
+                ("secret", colours(secret)),
+            ]),
+        }
+    }
+
+    pub fn from_json(json: &Json) -> Result<Self, String> {
+        let number_in = |json: &Json, key: &str| {
+            json.get(key)
+                .and_then(Json::as_usize)
+                .ok_or(format!("`{}` should be a number", key))
+        };
+        let number = |key: &str| number_in(json, key);
+        let colours_in = |json: &Json, key: &str| {
+            json.get(key)
+                .and_then(Json::as_array)
+                .ok_or(format!("`{}` should be a list", key))?
+                .iter()
+                .map(|x| {
+                    x.as_str()
+                        .and_then(Colour::from_name)
+                        .ok_or(format!("`{}` is not a colour", x))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let colours = |key: &str| colours_in(json, key);
+        let token = || {
+            json.get("token")
+                .and_then(Json::as_str)
+                .map(str::to_string)
+                .ok_or("`token` should be a string")
+        };
+        let settings = || {
+            json.get("settings")
+                .ok_or("missing `settings`")?
+                .to_serde::<GameConfig>()
+        };
+        match json.get("type").and_then(Json::as_str) {
+            Some("hello") => Ok(Message::Hello(settings()?)),
+            Some("settings") => Ok(Message::Settings(settings()?)),
+            Some("token") => Ok(Message::Token(token()?)),
+            Some("resume") => Ok(Message::Resume(token()?)),
+            Some("resumed") => Ok(Message::Resumed {
+                config: settings()?,
+                board: json
+                    .get("board")
+                    .and_then(Json::as_array)
+                    .ok_or("`board` should be a list")?
+                    .iter()
+                    .map(|x| {
+                        Ok((
+                            colours_in(x, "code")?,
+                            Feedback {
+                                exact: number_in(x, "correct_position")?,
+                                misplaced: number_in(x, "correct_colour")?,
+                            },
+                        ))
+                    })
+                    .collect::<Result<_, String>>()?,
+            }),
+            Some("stats") => Ok(Message::Stats),
+            Some("server_stats") => Ok(Message::ServerStats {
+                active: number("active_sessions")?,
+                completed: number("games_completed")?,
+            }),
+            Some("guess") => Ok(Message::Guess(colours("code")?)),
+            Some("feedback") => Ok(Message::Feedback {
+                exact: number("correct_position")?,
+                colour: number("correct_colour")?,
+            }),
+            Some("rejected") => Ok(Message::Rejected(
+                json.get("reason")
+                    .and_then(Json::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            )),
+            Some("slow_down") => Ok(Message::SlowDown(Duration::from_millis(number(
+                "retry_after_ms",
+            )?
+                as u64))),
+            Some("game_over") => Ok(Message::GameOver {
+                outcome: json
+                    .get("outcome")
+                    .and_then(Json::as_str)
+                    .and_then(Outcome::from_name)
+                    .ok_or("`outcome` should be `won` or `lost`")?,
+                secret: colours("secret")?,
+            }),
+            _ => Err("Unknown message".into()),
+        }
+    }
+}
+
+///
+/// Writes one message, as one line.
+///
+pub fn send(writer: &mut impl Write, message: &Message) -> io::Result<()> {
+    writeln!(writer, "{}", message.to_json())?;
+    writer.flush()
+}
+
+///
+/// Reads the next message, or `None` if the other side has
+/// hung up.
+///
+pub fn receive(reader: &mut impl BufRead) -> io::Result<Option<Message>> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) => return Ok(None),
+        Ok(_) => {}
+        // Hanging up in the middle of something looks like this
+        Err(e) if e.kind() == io::ErrorKind::ConnectionReset => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    parse(&line).map(Some)
+}
+
+///
+/// The same as `receive`, but for messages from someone who
+/// can't be trusted not to send a line without an end. A line
+/// longer than `limit` bytes is skipped, without ever holding
+/// more than `limit` bytes of it, and is an `InvalidData` error.
+///
+pub fn receive_bounded(reader: &mut impl BufRead, limit: usize) -> io::Result<Option<Message>> {
+    let mut line = Vec::new();
+    // `take` stops reading after that many bytes, even if the
+    // line hasn't ended yet.
+    match (&mut *reader)
+        .take(limit as u64 + 1)
+        .read_until(b'\n', &mut line)
+    {
+        Ok(0) => return Ok(None),
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::ConnectionReset => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    if line.last() != Some(&b'\n') && line.len() > limit {
+        skip_line(reader)?;
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Messages can't be longer than {} bytes", limit),
+        ));
+    }
+    let line = String::from_utf8(line)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Messages must be UTF-8"))?;
+    parse(&line).map(Some)
+}
+
+//
+// Throws away the rest of the current line.
+//
+fn skip_line(reader: &mut impl BufRead) -> io::Result<()> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        match buffer.iter().position(|&x| x == b'\n') {
+            Some(end) => {
+                reader.consume(end + 1);
+                return Ok(());
+            }
+            None => {
+                let length = buffer.len();
+                reader.consume(length);
+            }
+        }
+    }
+}
+
+fn parse(line: &str) -> io::Result<Message> {
+    Json::parse(line)
+        .and_then(|x| Message::from_json(&x))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_oversized_line_is_skipped_without_being_read() {
+        let stats = Message::Stats.to_json().to_string();
+        let input = format!("{}\n{}\n", "x".repeat(10_000), stats);
+        let mut reader = io::BufReader::new(input.as_bytes());
+        let error = receive_bounded(&mut reader, 64).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            receive_bounded(&mut reader, 64).unwrap(),
+            Some(Message::Stats)
+        );
+        assert_eq!(receive_bounded(&mut reader, 64).unwrap(), None);
+    }
+
+    #[test]
+    fn deep_nesting_is_invalid_data() {
+        let input = format!("{}\n", "[".repeat(4000));
+        let mut reader = io::BufReader::new(input.as_bytes());
+        let error = receive_bounded(&mut reader, 1 << 20).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let messages = vec![
+            Message::Hello(GameConfig::default()),
+            Message::Guess(vec![Colour::Red, Colour::Pink]),
+            Message::Feedback {
+                exact: 1,
+                colour: 2,
+            },
+            Message::SlowDown(Duration::from_millis(250)),
+            Message::GameOver {
+                outcome: Outcome::Lost,
+                secret: vec![Colour::Blue, Colour::Green],
+            },
+        ];
+        for message in messages {
+            assert_eq!(Message::from_json(&message.to_json()), Ok(message));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+//!
+//! `mastermind quiz`, practice at working out feedback by hand,
+//! which is the hardest part of playing on a real board (Or of
+//! teaching somebody to play). Each question is a secret and a
+//! guess, and the answer is the feedback as "exact misplaced",
+//! like `2 1`. A wrong answer is explained peg by peg.
+//!
+//! Most questions have a colour more than once in the secret or
+//! the guess, since that's where people go wrong.
+//!
+//! `--difficulty` picks how long the codes are, from `easy` (3
+//! pegs) through `normal` and `hard` to `expert` (6 pegs),
+//! `--questions N` how many to ask, and `--seed N` asks the
+//! same ones every time. Results are kept (See `history.rs`)
+//! for `--profile NAME`, or whoever played last.
+//!
+
+use super::history::{self, QuizEntry};
+use super::profile;
+use super::{score_guess, Colour, Feedback, CLASSIC_PALETTE_SIZE, COLOURS};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::io::{self, Write};
+
+///
+/// How many questions a quiz has, unless `--questions` says.
+///
+pub const DEFAULT_QUESTIONS: usize = 10;
+
+///
+/// The chance of a question being made to repeat a colour, on
+/// top of any repeats it gets by chance.
+///
+const REPEAT_CHANCE: f64 = 0.75;
+
+///
+/// How many pegs the codes have at each difficulty.
+///
+fn pegs_for(difficulty: &str) -> Option<usize> {
+    match difficulty {
+        "easy" => Some(3),
+        "normal" => Some(4),
+        "hard" => Some(5),
+        "expert" => Some(6),
+        _ => None,
+    }
+}
+
+///
+/// A secret and a guess with `pegs` pegs each, to work out the
+/// feedback for. About half of the guess is made up of colours
+/// from the secret, so that there's usually something to find.
+///
+pub fn question(pegs: usize, rng: &mut impl Rng) -> (Vec<Colour>, Vec<Colour>) {
+    // The classic colours, like a game which doesn't pick others
+    let colours = &COLOURS[..CLASSIC_PALETTE_SIZE];
+    let mut secret = (0..pegs)
+        .map(|_| *colours.choose(rng).unwrap())
+        .collect::<Vec<_>>();
+    let mut guess = (0..pegs)
+        .map(|_| {
+            if rng.gen_bool(0.5) {
+                *secret.choose(rng).unwrap()
+            } else {
+                *colours.choose(rng).unwrap()
+            }
+        })
+        .collect::<Vec<_>>();
+    if rng.gen_bool(REPEAT_CHANCE) {
+        match rng.gen_range(0, 3) {
+            0 => repeat_colour(&mut secret, rng),
+            1 => repeat_colour(&mut guess, rng),
+            _ => {
+                repeat_colour(&mut secret, rng);
+                repeat_colour(&mut guess, rng);
+            }
+        }
+    }
+    (secret, guess)
+}
+
+//
+// Copies one peg of `code` over another, so that its colour is
+// in there at least twice.
+//
+fn repeat_colour(code: &mut [Colour], rng: &mut impl Rng) {
+    let from = rng.gen_range(0, code.len());
+    let to = (from + rng.gen_range(1, code.len())) % code.len();
+    code[to] = code[from];
+}
+
+///
+/// How each peg of `guess` counts towards its feedback against
+/// `secret`, one line per peg, the same way `score_guess` works
+/// it out: exact matches use up their secret pegs first, and
+/// then each other guess peg, from left to right, is misplaced
+/// if there's a secret peg of its colour still unused.
+///
+pub fn explain(secret: &[Colour], guess: &[Colour]) -> Vec<String> {
+    let mut unused = guess
+        .iter()
+        .zip(secret)
+        .filter(|(x, y)| x != y)
+        .map(|(_, &y)| y)
+        .collect::<Vec<_>>();
+    guess
+        .iter()
+        .zip(secret)
+        .enumerate()
+        .map(|(idx, (&colour, &actual))| {
+            let reason = if colour == actual {
+                "is in the same place in the secret, so it's exact".to_string()
+            } else if let Some(found) = unused.iter().position(|&x| x == colour) {
+                unused.swap_remove(found);
+                "is in the secret, but not here, so it's misplaced".to_string()
+            } else if secret.contains(&colour) {
+                format!(
+                    "doesn't count, every {:?} in the secret is already matched",
+                    colour
+                )
+            } else {
+                "isn't in the secret, so it doesn't count".to_string()
+            };
+            format!("Position {}: {:?} {}", idx + 1, colour, reason)
+        })
+        .collect()
+}
+
+//
+// Reads an answer like `2 1` (Or `2,1`) for a code of `pegs`
+// pegs.
+//
+fn parse_answer(text: &str, pegs: usize) -> Result<Feedback, String> {
+    let numbers = text
+        .split(|x: char| x.is_whitespace() || x == ',')
+        .filter(|x| !x.is_empty())
+        .map(|x| {
+            x.parse::<usize>()
+                .map_err(|_| format!("`{}` isn't a number", x))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    match numbers[..] {
+        [exact, misplaced] if exact + misplaced <= pegs => Ok(Feedback { exact, misplaced }),
+        [_, _] => Err(format!(
+            "There are only {} pegs, so the two numbers can't add up to more than that",
+            pegs
+        )),
+        _ => Err("Answer with two numbers, exact and then misplaced, like `2 1`".into()),
+    }
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut pegs = 4;
+    let mut questions = DEFAULT_QUESTIONS;
+    let mut seed = None;
+    let mut name = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--difficulty" => {
+                pegs = args
+                    .next()
+                    .as_deref()
+                    .and_then(pegs_for)
+                    .ok_or("`--difficulty` needs `easy`, `normal`, `hard` or `expert`")?
+            }
+            "--questions" => {
+                questions = args
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .filter(|&x| x > 0)
+                    .ok_or("`--questions` needs a positive number")?
+            }
+            "--seed" => {
+                seed = Some(
+                    args.next()
+                        .and_then(|x| x.parse().ok())
+                        .ok_or("`--seed` needs a number")?,
+                )
+            }
+            "--profile" => name = Some(args.next().ok_or("`--profile` needs a name")?),
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    let player = match name {
+        Some(name) => Some(profile::find_or_create(&name)?),
+        None => match profile::last_used() {
+            Some(id) => profile::find_by_id(id)?,
+            None => None,
+        },
+    };
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    println!(
+        "Work out the feedback for each guess: how many pegs are exact (The right \
+         colour in the right place), and how many more are misplaced (A colour in \
+         the secret, but not in that place). Answer like `2 1`, or `quit` to stop."
+    );
+    let mut input = String::new();
+    let mut asked = 0;
+    let mut right = 0;
+    'questions: while asked < questions {
+        let (secret, guess) = question(pegs, &mut rng);
+        let feedback = score_guess(&secret, &guess);
+        println!();
+        println!("Question {} of {}", asked + 1, questions);
+        println!("  Secret: {:?}", secret);
+        println!("  Guess:  {:?}", guess);
+        let answer = loop {
+            print!("Feedback > ");
+            io::stdout().flush()?;
+            input.clear();
+            if io::stdin().read_line(&mut input)? == 0 {
+                println!();
+                break 'questions;
+            }
+            if input.trim() == "quit" {
+                break 'questions;
+            }
+            match parse_answer(&input, pegs) {
+                Ok(x) => break x,
+                Err(e) => println!("Please try again! {}", e),
+            }
+        };
+        asked += 1;
+        if answer == feedback {
+            right += 1;
+            println!("Correct!");
+        } else {
+            println!(
+                "Not quite, it's {} exact and {} misplaced:",
+                feedback.exact, feedback.misplaced
+            );
+            for line in explain(&secret, &guess) {
+                println!("  {}", line);
+            }
+        }
+        println!("{} of {} right so far", right, asked);
+    }
+
+    if asked == 0 {
+        return Ok(());
+    }
+    let entry = QuizEntry { pegs, asked, right };
+    println!();
+    println!(
+        "You got {} of {} right ({:.0}%)",
+        right,
+        asked,
+        entry.accuracy() * 100.0
+    );
+    match player {
+        Some(player) => history::append_quiz(player.id, entry)?,
+        None => {
+            println!("There's no profile to keep this in, use `--profile NAME` next time")
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+//!
+//! `mastermind relay NAME NAME...`, where a team of players
+//! takes turns guessing on one board, trying to find the secret
+//! in no more guesses than the solver needs for the same code.
+//!
+
+use super::settings::{self, SettingsLayer};
+use super::{parse_code, solver, Colour, Feedback, SilentOutput, State};
+use std::error::Error;
+use std::io::Write;
+
+///
+/// One guess made by the team, and who made it.
+///
+struct Turn {
+    guesser: usize,
+    guess: Vec<Colour>,
+    feedback: Option<Feedback>,
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let team = args.collect::<Vec<_>>();
+    if team.len() < 2 {
+        return Err("`relay` needs the names of at least two players".into());
+    }
+    // There's no setup here, so the game uses the settings from
+    // the config file. A relay is only one game long, and it's
+    // over if it's won or the tries run out, whichever is first.
+    let config = settings::resolve(
+        settings::load_config_file()?,
+        None,
+        SettingsLayer::default(),
+    );
+    let feedback_style = settings::load_feedback_style()?;
+    let mut state = State::new(config, |_| {}, |_| {}, SilentOutput)?;
+    // The solver plays first, quietly, so that the team has
+    // something to aim for.
+    let benchmark =
+        solver::solve(state.reveal_secret(), config).ok_or("The solver couldn't play this game")?;
+    println!(
+        "The solver found this code in {} guesses, can {} do as well?",
+        benchmark,
+        team.join(" and ")
+    );
+    if let Some(announcement) = config.palette_announcement() {
+        println!("{}", announcement);
+    }
+    if let Some(announcement) = config.colour_announcement() {
+        println!("{}", announcement);
+    }
+
+    let mut turns = Vec::<Turn>::new();
+    let mut input = String::new();
+    while state.previous_games.is_empty() {
+        let guesser = turns.len() % team.len();
+        print!("{}'s guess > ", team[guesser]);
+        std::io::stdout().flush()?;
+        input.clear();
+        if std::io::stdin().read_line(&mut input)? == 0 {
+            println!();
+            return Err("Ran out of input before the game was over".into());
+        }
+        // Each player only gets one guess per turn, so a line has
+        // to be exactly one code.
+        let guess = match parse_code(input.trim(), config.pegs) {
+            Ok(x) => x,
+            Err(e) => {
+                println!("Please try again! {}", e);
+                continue;
+            }
+        };
+        let guesses_before = state.attempts();
+        if let Err(e) = state.push_string_input(input.trim()) {
+            println!("Please try again! {}", e);
+            continue;
+        }
+        if state.attempts() == guesses_before && state.previous_games.is_empty() {
+            continue;
+        }
+        let feedback = state.last_scored();
+        if let (Some(feedback), true) = (feedback, state.previous_games.is_empty()) {
+            println!("{}", feedback_style.describe(feedback));
+        }
+        turns.push(Turn {
+            guesser,
+            guess,
+            feedback,
+        });
+    }
+
+    let record = &state.previous_games[0];
+    println!("The code was {:?}", record.secret);
+    for (idx, turn) in turns.iter().enumerate() {
+        print!(
+            "{}. {} guessed {:?}",
+            idx + 1,
+            team[turn.guesser],
+            turn.guess
+        );
+        if let Some(feedback) = turn.feedback {
+            print!(", {}", feedback_style.describe(feedback));
+        }
+        println!();
+    }
+    let names = team.join(", ");
+    let used = turns.len();
+    match record.outcome {
+        super::Outcome::Won if used <= benchmark => println!(
+            "{} beat the solver, finding the code in {} guesses to its {} ({} to spare)",
+            names,
+            used,
+            benchmark,
+            benchmark - used
+        ),
+        super::Outcome::Won => println!(
+            "{} found the code in {} guesses, but the solver only needed {} ({} more)",
+            names,
+            used,
+            benchmark,
+            used - benchmark
+        ),
+        super::Outcome::Lost => println!(
+            "{} ran out of tries after {} guesses, the solver only needed {}",
+            names, used, benchmark
+        ),
+    }
+    Ok(())
+}
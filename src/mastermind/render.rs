@@ -0,0 +1,760 @@
+//!
+//! Renderers turn what happens in a session (The `Output`s
+//! from `frontend::play`) into something for the player to
+//! look at. Which one is used is picked with `--output`.
+//!
+
+use super::frontend::Output;
+use super::hints::HintAnswer;
+use super::wording::FeedbackStyle;
+use super::{terminal, BoardFormatter, Colour, Feedback, Outcome};
+use std::io::{self, Write};
+
+///
+/// Shows each `Output` somehow. All of the formatting for a
+/// session lives in the renderers, so swapping one out changes
+/// how everything looks without touching the game.
+///
+pub trait Renderer {
+    ///
+    /// Writes `output` to `out`.
+    ///
+    fn render_to(&mut self, output: &Output, out: &mut dyn Write) -> io::Result<()>;
+
+    ///
+    /// Writes `output` to stdout, which is what the game does.
+    /// Tests use `render_to` instead, to read back what was
+    /// written.
+    ///
+    fn render(&mut self, output: &Output) -> io::Result<()> {
+        self.render_to(output, &mut io::stdout())
+    }
+}
+
+///
+/// Picks a renderer by the name given to `--output`. Renderers
+/// which write sentences write feedback in `feedback`.
+///
+pub fn by_name(name: &str, feedback: FeedbackStyle) -> Option<Box<dyn Renderer>> {
+    match name {
+        "text" => Some(Box::new(TextRenderer {
+            colours: terminal::colour_support(),
+            feedback,
+            ..TextRenderer::default()
+        })),
+        "accessible" => Some(Box::new(AccessibleRenderer::default())),
+        "json" => Some(Box::new(JsonRenderer)),
+        "silent" => Some(Box::new(SilentRenderer)),
+        _ => None,
+    }
+}
+
+///
+/// Sentences for a person reading along in a terminal.
+///
+#[derive(Default)]
+pub struct TextRenderer {
+    ///
+    /// Whether the game being played is in hardcore mode, so
+    /// that the screen is only cleared for hardcore games.
+    ///
+    hardcore: bool,
+    ///
+    /// Whether it's in memory mode, so that guesses aren't
+    /// written back to the player.
+    ///
+    memory: bool,
+    ///
+    /// What colours pegs can be shown in. The default is none.
+    ///
+    colours: terminal::ColourSupport,
+    feedback: FeedbackStyle,
+}
+
+impl TextRenderer {
+    //
+    // A code like `Red, Blue`, each colour shown in itself.
+    //
+    fn code(&self, code: &[Colour]) -> String {
+        let pegs = code.iter().map(|&x| self.colour(x)).collect::<Vec<_>>();
+        pegs.join(", ")
+    }
+
+    fn colour(&self, colour: Colour) -> String {
+        self.paint(colour, &colour.to_string())
+    }
+
+    //
+    // `text` shown in `colour`, if the terminal can.
+    //
+    fn paint(&self, colour: Colour, text: &str) -> String {
+        terminal::paint(text, colour.rgb(), self.colours)
+    }
+}
+
+impl Renderer for TextRenderer {
+    fn render_to(&mut self, output: &Output, out: &mut dyn Write) -> io::Result<()> {
+        match output {
+            Output::NewGame { number, config } => {
+                self.hardcore = config.hardcore;
+                self.memory = config.memory;
+                writeln!(out, "Generated new state! Game #{}", number)?;
+                if let Some(announcement) = config.palette_announcement() {
+                    writeln!(out, "{}", announcement)?;
+                }
+                if let Some(announcement) = config.colour_announcement() {
+                    writeln!(out, "{}", announcement)?;
+                }
+                if config.allow_blanks {
+                    writeln!(out, "Some pegs may be blank, type a blank as `.` or `_`")?;
+                }
+                if let Some(most) = config.max_per_colour.filter(|_| config.allow_duplicates) {
+                    writeln!(out, "No colour is in the code more than {} times", most)?;
+                }
+                if config.memory {
+                    writeln!(
+                        out,
+                        "Memory mode: your guesses won't be shown, so keep track of them yourself"
+                    )?;
+                }
+                if config.hint_points > 0 {
+                    writeln!(
+                        out,
+                        "You have {} hint points: \"hint reveal\" costs 3, \"hint suggest\" 2 \
+                         and \"hint count COLOUR\" 1",
+                        config.hint_points
+                    )?;
+                }
+                if config.hardcore {
+                    if terminal::supports_clearing() {
+                        writeln!(
+                            out,
+                            "Hardcore mode: only your last guess's feedback will stay on screen"
+                        )?;
+                    } else {
+                        writeln!(
+                            out,
+                            "Hardcore mode: the screen can't be cleared here, so please don't scroll back!"
+                        )?;
+                    }
+                }
+            }
+            Output::Points { points, score } => writeln!(
+                out,
+                "You have {} points to wager, and your score so far is {}",
+                points, score
+            )?,
+            Output::WagerSettled { won: true, points } => {
+                writeln!(out, "Your wager paid off, you now have {} points", points)?
+            }
+            Output::WagerSettled { won: false, points } => {
+                writeln!(out, "You lost your wager, you now have {} points", points)?
+            }
+            Output::Feedback {
+                guess,
+                exact,
+                colour,
+            } => {
+                let feedback = self.feedback.describe(Feedback {
+                    exact: *exact,
+                    misplaced: *colour,
+                });
+                if self.memory {
+                    writeln!(out, "Good try, here are your matching pegs: {}", feedback)?
+                } else {
+                    writeln!(
+                        out,
+                        "Good try, here are your matching pegs for {}: {}",
+                        self.code(guess),
+                        feedback
+                    )?
+                }
+            }
+            Output::Reveal { position, colour } => writeln!(
+                out,
+                "Here's some help: position {} is {}",
+                position + 1,
+                self.colour(*colour)
+            )?,
+            Output::Hint {
+                answer,
+                points_left,
+            } => {
+                match answer {
+                    HintAnswer::Revealed { position, colour } => writeln!(
+                        out,
+                        "Here's your hint: position {} is {}",
+                        position + 1,
+                        self.colour(*colour)
+                    )?,
+                    HintAnswer::Suggestion(guess) => {
+                        writeln!(out, "Here's your hint: try {}", self.code(guess))?
+                    }
+                    HintAnswer::Count { colour, count } => writeln!(
+                        out,
+                        "Here's your hint: the code has {} {}",
+                        count,
+                        self.colour(*colour)
+                    )?,
+                }
+                writeln!(out, "You have {} hint points left", points_left)?;
+            }
+            // In hardcore mode, wipe the screen after every guess
+            // and only put back where the game stands.
+            Output::Progress(summary) => {
+                if self.hardcore {
+                    if terminal::supports_clearing() {
+                        terminal::clear_screen()?;
+                    }
+                    writeln!(out, "{}", summary.describe(&self.feedback))?;
+                }
+            }
+            Output::GameOver(record) => match record.outcome {
+                Outcome::Won if record.speed_bonus > 0 => writeln!(
+                    out,
+                    "You won! That's a speed bonus of {} points",
+                    record.speed_bonus
+                )?,
+                Outcome::Won => writeln!(out, "You won!")?,
+                Outcome::Lost => writeln!(
+                    out,
+                    "Uh-oh, you lost. The code was {}",
+                    self.code(&record.secret)
+                )?,
+            },
+            Output::Score(score) => writeln!(out, "Your score this session is now {}", score)?,
+            Output::Error(text) => writeln!(out, "Error encountered: {}", text)?,
+            Output::Message(text) => writeln!(out, "{}", text)?,
+            Output::SessionOver { games, abandoned } => {
+                writeln!(out, "Previous games:")?;
+                for (idx, record) in games.iter().enumerate() {
+                    write!(
+                        out,
+                        "Game #{} with pegs {} was {} with {} attempts",
+                        idx + 1,
+                        self.code(&record.secret),
+                        record.outcome.name(),
+                        record.attempts
+                    )?;
+                    if let Some(settings) = record.settings {
+                        write!(out, ", playing {}", settings.brief())?;
+                    }
+                    match &record.label {
+                        Some(label) => writeln!(out, " ({:?})", label)?,
+                        None => writeln!(out)?,
+                    }
+                    // The game is over, so even hardcore and memory
+                    // games can be looked back over now.
+                    if !record.guesses.is_empty() {
+                        let paint = |colour, text: &str| self.paint(colour, text);
+                        let board = BoardFormatter::new()
+                            .paint(&paint)
+                            .feedback(&self.feedback)
+                            .format_attempts(&record.guesses);
+                        for line in board.lines() {
+                            writeln!(out, "  {}", line)?;
+                        }
+                    }
+                }
+                if let Some(attempts) = abandoned {
+                    writeln!(
+                        out,
+                        "Game #{} was abandoned with {} attempts",
+                        games.len() + 1,
+                        attempts
+                    )?;
+                }
+                let bonus = games.iter().map(|x| x.speed_bonus).sum::<usize>();
+                if bonus > 0 {
+                    writeln!(out, "Speed bonus this session: {} points", bonus)?;
+                }
+            }
+            Output::Metrics(metrics) => {
+                writeln!(out, "This session:")?;
+                writeln!(out, "  Took {:.1}s", metrics.duration.as_secs_f64())?;
+                writeln!(out, "  Games played: {}", metrics.games_played)?;
+                writeln!(out, "  Guesses entered: {}", metrics.guesses_entered)?;
+                writeln!(out, "  Parse errors: {}", metrics.parse_errors)?;
+                writeln!(out, "  Commands used: {}", metrics.commands_used)?;
+                writeln!(out, "  Hints taken: {}", metrics.hints_taken)?;
+                if let Some(each) = metrics.time_per_guess() {
+                    writeln!(out, "  Time per guess: {:.1}s", each.as_secs_f64())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Plain sentences for screen readers, picked with `--output
+/// accessible` or `--accessible`. Nothing here is drawn with
+/// symbols or redrawn in place: every line is printed once, and
+/// says in words what changed, like
+///
+/// ```text
+/// Attempt 3: Red, Blue, Green, Yellow. Two correct positions, one correct colour.
+/// It is attempt 4 of 10.
+/// ```
+///
+/// Feedback styles (See `wording.rs`) aren't used here, since
+/// some of them are symbols, which is what this is avoiding.
+///
+#[derive(Default)]
+pub struct AccessibleRenderer {
+    ///
+    /// Whether the game being played is in memory mode, so that
+    /// guesses aren't read back.
+    ///
+    memory: bool,
+}
+
+impl Renderer for AccessibleRenderer {
+    fn render_to(&mut self, output: &Output, out: &mut dyn Write) -> io::Result<()> {
+        match output {
+            Output::NewGame { number, config } => {
+                self.memory = config.memory;
+                writeln!(
+                    out,
+                    "Game {} has started. The secret code has {} pegs, {}.",
+                    number,
+                    spell(config.pegs),
+                    if config.allow_duplicates {
+                        "and colours can repeat"
+                    } else {
+                        "and no colour repeats"
+                    }
+                )?;
+                if let Some(announcement) = config.palette_announcement() {
+                    writeln!(out, "{}.", announcement)?;
+                }
+                if let Some(announcement) = config.colour_announcement() {
+                    writeln!(out, "{}.", announcement)?;
+                }
+                if config.allow_blanks {
+                    writeln!(
+                        out,
+                        "Some pegs may be blank. Type a blank as a full stop, or the word blank."
+                    )?;
+                }
+                if let Some(most) = config.max_per_colour.filter(|_| config.allow_duplicates) {
+                    writeln!(out, "No colour is in the code more than {} times.", most)?;
+                }
+                if config.memory {
+                    writeln!(
+                        out,
+                        "Memory mode is on, so your guesses won't be read back."
+                    )?;
+                }
+                if config.hint_points > 0 {
+                    writeln!(
+                        out,
+                        "You have {} hint points. Hint reveal costs three, hint suggest costs \
+                         two, and hint count followed by a colour costs one.",
+                        config.hint_points
+                    )?;
+                }
+                if config.hardcore {
+                    writeln!(
+                        out,
+                        "Hardcore mode is on, but nothing can be hidden from a screen reader, \
+                         so please only listen to the latest feedback."
+                    )?;
+                }
+                writeln!(out, "{}", attempt_of(1, config.max_tries))?;
+            }
+            Output::Points { points, score } => writeln!(
+                out,
+                "You have {} points to wager. Your score so far is {}.",
+                points, score
+            )?,
+            Output::WagerSettled { won, points } => writeln!(
+                out,
+                "Your wager {}. You now have {} points.",
+                if *won { "paid off" } else { "was lost" },
+                points
+            )?,
+            // Everything in here is in the progress that follows,
+            // which also knows which attempt it was.
+            Output::Feedback { .. } => {}
+            Output::Reveal { position, colour } => writeln!(
+                out,
+                "Here's some help: position {} is {}.",
+                position + 1,
+                colour
+            )?,
+            Output::Hint {
+                answer,
+                points_left,
+            } => {
+                match answer {
+                    HintAnswer::Revealed { position, colour } => {
+                        writeln!(out, "Hint: position {} is {}.", position + 1, colour)?
+                    }
+                    HintAnswer::Suggestion(guess) => writeln!(out, "Hint: try {}.", names(guess))?,
+                    HintAnswer::Count { colour, count } => writeln!(
+                        out,
+                        "Hint: the code has {} {} peg{}.",
+                        spell(*count),
+                        colour,
+                        if *count == 1 { "" } else { "s" }
+                    )?,
+                }
+                writeln!(out, "You have {} hint points left.", spell(*points_left))?;
+            }
+            Output::Progress(summary) => {
+                if let Some((guess, feedback)) = summary.guesses.last() {
+                    let guess = if self.memory { None } else { Some(&guess[..]) };
+                    writeln!(
+                        out,
+                        "{}",
+                        spoken_attempt(summary.guesses.len(), guess, *feedback)
+                    )?;
+                }
+                writeln!(
+                    out,
+                    "{}",
+                    attempt_of(summary.attempts_made + 1, summary.max_tries)
+                )?;
+            }
+            Output::GameOver(record) => {
+                match record.outcome {
+                    Outcome::Won => {
+                        writeln!(out, "You won! The code was {}.", names(&record.secret))?
+                    }
+                    Outcome::Lost => {
+                        writeln!(out, "You lost. The code was {}.", names(&record.secret))?
+                    }
+                }
+                if record.speed_bonus > 0 {
+                    writeln!(
+                        out,
+                        "That's a speed bonus of {} points.",
+                        record.speed_bonus
+                    )?;
+                }
+            }
+            Output::Score(score) => writeln!(out, "Your score this session is now {}.", score)?,
+            Output::Error(text) => writeln!(out, "Error: {}", text)?,
+            Output::Message(text) => writeln!(out, "{}", text)?,
+            Output::SessionOver { games, abandoned } => {
+                writeln!(
+                    out,
+                    "The session is over. You played {} game{}.",
+                    games.len(),
+                    if games.len() == 1 { "" } else { "s" }
+                )?;
+                for (idx, record) in games.iter().enumerate() {
+                    write!(
+                        out,
+                        "Game {} was {}, with the code {}, after {} attempts.",
+                        idx + 1,
+                        record.outcome.name(),
+                        names(&record.secret),
+                        record.attempts
+                    )?;
+                    if let Some(settings) = record.settings {
+                        write!(out, " It was played with {}.", settings.brief())?;
+                    }
+                    match &record.label {
+                        Some(label) => writeln!(out, " You labelled it {:?}.", label)?,
+                        None => writeln!(out)?,
+                    }
+                    for (idx, attempt) in record.guesses.iter().enumerate() {
+                        writeln!(
+                            out,
+                            "{}",
+                            spoken_attempt(idx + 1, Some(&attempt.guess), Some(attempt.feedback))
+                        )?;
+                    }
+                }
+                if let Some(attempts) = abandoned {
+                    writeln!(
+                        out,
+                        "Game {} was abandoned, after {} attempts.",
+                        games.len() + 1,
+                        attempts
+                    )?;
+                }
+            }
+            Output::Metrics(metrics) => {
+                writeln!(
+                    out,
+                    "This session took {:.1} seconds. You played {} games, entered {} guesses, \
+                     made {} parse errors, used {} commands and took {} hints.",
+                    metrics.duration.as_secs_f64(),
+                    metrics.games_played,
+                    metrics.guesses_entered,
+                    metrics.parse_errors,
+                    metrics.commands_used,
+                    metrics.hints_taken
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// One attempt in words, like "Attempt 3: Red, Blue, Green,
+/// Yellow. Two correct positions, one correct colour.", for
+/// the accessible renderer and anything else which wants to
+/// read out a board. The guess is left out if it's `None`, for
+/// memory mode.
+///
+pub fn spoken_attempt(
+    number: usize,
+    guess: Option<&[Colour]>,
+    feedback: Option<Feedback>,
+) -> String {
+    let feedback = match feedback {
+        Some(Feedback {
+            exact,
+            misplaced: colour,
+        }) => format!(
+            "{} correct position{}, {} correct colour{}.",
+            capitalise(&spell(exact)),
+            if exact == 1 { "" } else { "s" },
+            spell(colour),
+            if colour == 1 { "" } else { "s" }
+        ),
+        None => "This guess couldn't be scored.".to_string(),
+    };
+    match guess {
+        Some(guess) => format!("Attempt {}: {}. {}", number, names(guess), feedback),
+        None => format!("Attempt {}. {}", number, feedback),
+    }
+}
+
+//
+// A code read out as a list, like `Red, Blue, Green`.
+//
+fn names(code: &[Colour]) -> String {
+    code.iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+//
+// Which attempt is next, like "It is attempt 4 of 10.".
+//
+fn attempt_of(attempt: usize, max_tries: Option<usize>) -> String {
+    match max_tries {
+        Some(max) => format!("It is attempt {} of {}.", attempt, max),
+        None => format!("It is attempt {}.", attempt),
+    }
+}
+
+//
+// Small numbers as words, which a screen reader says the same
+// way a person would. Zero is "no", as in "no correct colours".
+//
+fn spell(number: usize) -> String {
+    const WORDS: [&str; 11] = [
+        "no", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    ];
+    WORDS
+        .get(number)
+        .map_or_else(|| number.to_string(), |x| x.to_string())
+}
+
+fn capitalise(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+///
+/// One JSON object per line, for other programs to read.
+///
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn render_to(&mut self, output: &Output, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{}", output.to_json())?;
+        // Whoever is reading probably wants each line as soon as
+        // it happens, not when the buffer fills up.
+        out.flush()
+    }
+}
+
+///
+/// Shows nothing at all, for when only the finished games
+/// matter (Like when simulating lots of them).
+///
+pub struct SilentRenderer;
+
+impl Renderer for SilentRenderer {
+    fn render_to(&mut self, _: &Output, _: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::frontend::{self, Frontend, PlayerInput, Session};
+    use super::super::json::Json;
+    use super::super::{GameConfig, GameSummary, StateBuilder};
+    use super::*;
+
+    // Plays the same game every time, showing it with `renderer`,
+    // and gives back everything that was written.
+    fn play_with(renderer: impl Renderer) -> String {
+        struct Rendering<R> {
+            inputs: Vec<PlayerInput>,
+            renderer: R,
+            written: Vec<u8>,
+        }
+
+        impl<R: Renderer> Frontend for Rendering<R> {
+            fn configure(&mut self, defaults: GameConfig) -> io::Result<GameConfig> {
+                Ok(defaults)
+            }
+
+            fn next_input(&mut self, _: &GameSummary) -> io::Result<PlayerInput> {
+                Ok(if self.inputs.is_empty() {
+                    PlayerInput::Abandon
+                } else {
+                    self.inputs.remove(0)
+                })
+            }
+
+            fn present(&mut self, output: &Output) -> io::Result<()> {
+                self.renderer.render_to(output, &mut self.written)
+            }
+        }
+
+        let mut state = StateBuilder::new().seed(3).build().unwrap();
+        let secret: Vec<_> = state.reveal_secret().iter().map(|x| x.name()).collect();
+        let mut wrong = secret.clone();
+        wrong.reverse();
+        let mut frontend = Rendering {
+            inputs: vec![
+                PlayerInput::Guess(wrong.join(" ")),
+                PlayerInput::Guess("nope".into()),
+                PlayerInput::Guess(secret.join(" ")),
+                PlayerInput::Forfeit,
+            ],
+            renderer,
+            written: Vec::new(),
+        };
+        frontend::play(&mut frontend, &mut state, &Session::default()).unwrap();
+        String::from_utf8(frontend.written).unwrap()
+    }
+
+    // What happened, going by the lines the text renderer wrote.
+    // `Progress` isn't written outside of hardcore games.
+    fn text_events(text: &str) -> Vec<&'static str> {
+        let kinds = [
+            ("Generated new state!", "new_game"),
+            ("Good try", "feedback"),
+            ("Error encountered", "error"),
+            ("You won", "game_over"),
+            ("Uh-oh, you lost", "game_over"),
+            ("Previous games:", "session_over"),
+        ];
+        text.lines()
+            .filter_map(|line| {
+                let kind = kinds.iter().find(|(start, _)| line.starts_with(start));
+                kind.map(|(_, kind)| *kind)
+            })
+            .collect()
+    }
+
+    fn json_events(text: &str) -> Vec<String> {
+        text.lines()
+            .map(|line| Json::parse(line).unwrap())
+            .map(|json| json.get("kind").unwrap().as_str().unwrap().to_string())
+            .filter(|kind| kind != "progress")
+            .collect()
+    }
+
+    #[test]
+    fn text_and_json_describe_the_same_game() {
+        let text = play_with(TextRenderer::default());
+        let json = play_with(JsonRenderer);
+        let expected = vec![
+            "new_game",
+            "feedback",
+            "error",
+            "game_over",
+            "new_game",
+            "game_over",
+            "session_over",
+        ];
+        assert_eq!(text_events(&text), expected);
+        assert_eq!(json_events(&json), expected);
+    }
+
+    #[test]
+    fn json_is_one_object_per_line() {
+        let json = play_with(JsonRenderer);
+        assert!(json.lines().all(|x| x.starts_with('{') && x.ends_with('}')));
+        assert!(json.ends_with('\n'));
+    }
+
+    #[test]
+    fn the_silent_renderer_writes_nothing() {
+        assert_eq!(play_with(SilentRenderer), "");
+    }
+
+    #[test]
+    fn an_accessible_transcript() {
+        let transcript = play_with(AccessibleRenderer::default());
+        // The speed bonus depends on how long the test took
+        let lines = transcript
+            .lines()
+            .map(|line| match line.starts_with("That's a speed bonus of ") {
+                true => "That's a speed bonus of N points.",
+                false => line,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "Game 1 has started. The secret code has four pegs, and no colour repeats.",
+                "It is attempt 1.",
+                "Attempt 1: Yellow, Orange, Green, Red. No correct positions, four correct colours.",
+                "It is attempt 2.",
+                "Error: `nope` isn't a colour, the colours are red, blue, white, yellow, green, \
+                 orange, purple, pink (At character 1)",
+                "You won! The code was Red, Green, Orange, Yellow.",
+                "That's a speed bonus of N points.",
+                "Game 2 has started. The secret code has four pegs, and no colour repeats.",
+                "It is attempt 1.",
+                "You lost. The code was Orange, Green, Red, White.",
+                "The session is over. You played 2 games.",
+                "Game 1 was won, with the code Red, Green, Orange, Yellow, after 2 attempts. \
+                 It was played with 4 pegs, no duplicates, unlimited tries.",
+                "Attempt 1: Yellow, Orange, Green, Red. No correct positions, four correct colours.",
+                "Attempt 2: Red, Green, Orange, Yellow. Four correct positions, no correct colours.",
+                "Game 2 was lost, with the code Orange, Green, Red, White, after 0 attempts. \
+                 It was played with 4 pegs, no duplicates, unlimited tries.",
+            ]
+        );
+        // Nothing is drawn in place, or with escapes
+        assert!(!transcript.contains('\x1b') && !transcript.contains('\r'));
+    }
+
+    #[test]
+    fn the_summary_lists_every_guess() {
+        let text = play_with(TextRenderer::default());
+        let summary = text.split("Previous games:\n").nth(1).unwrap();
+        let lines = summary.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("Game #1 with pegs Red, Green, Orange, Yellow was won"));
+        assert_eq!(
+            &lines[1..4],
+            [
+                "  #  Guess                        Feedback",
+                "  1  Yellow Orange Green  Red     0 in the correct position and 4 with the right colour",
+                "  2  Red    Green  Orange Yellow  4 in the correct position and 0 with the right colour",
+            ]
+        );
+        // The game given up on straight away had no guesses
+        assert!(lines[4].starts_with("Game #2 "));
+        assert!(lines[5].starts_with("Speed bonus this session"));
+    }
+}
@@ -0,0 +1,1161 @@
+//!
+//! Saving a game in progress to a file, and loading it back.
+//!
+//! Save files are JSON, and always start with a
+//! `format_version` number. Whenever the layout of a save
+//! changes we bump that number, and teach `load` how to read
+//! the older layout, so that old saves keep working.
+//!
+//! The versions so far are:
+//! - `1`: Finished games are stored as their secret, their
+//!   number of attempts, and a `won` boolean.
+//! - `2`: Finished games store an `outcome` name instead of
+//!   `won`, and how many seconds they took. The time spent on
+//!   the current game is also saved.
+//! - `3`: The game itself (In the version 2 layout) moves
+//!   into a `game` field, next to a `checksum` of it. This
+//!   lets us tell a damaged file apart from a good one. The
+//!   time the file was saved at is stored too, as seconds
+//!   since 1970 in `saved_at`.
+//!
+//! Some fields have been added since version 3 without a new
+//! version, such as the settings for newer game modes. Those
+//! are always optional, and being missing means the mode
+//! wasn't in use.
+//!
+//! In between the game and the file, a save is a
+//! `StateSnapshot`, which is plain data that can be read and
+//! changed from anywhere. The game inside a save is its serde
+//! layout, so anything which changes the layout of a
+//! `StateSnapshot`, `GameConfig` or `GameRecord` changes saves
+//! too. The few places serde's layout isn't the one saves have
+//! always had are at the end of this file.
+//!
+//! With the `binary` feature, a save can also be written in the
+//! more compact encoding from `binary.rs`, which holds exactly
+//! the same data. Loading works out which one a file is by
+//! itself.
+//!
+
+#[cfg(feature = "binary")]
+use super::binary;
+use super::json::Json;
+use super::{
+    Attempt, Colour, ConfigError, Feedback, GameConfig, GameRecord, GameSummary, Guess, Outcome,
+    State, StateBuilder, Symbol, CLASSIC_PALETTE_SIZE, COLOURS, MAX_TRIES, PALETTE_SIZE,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+///
+/// The version of the layout which `save` writes.
+///
+pub const FORMAT_VERSION: usize = 3;
+
+///
+/// Where the `save` command writes to if it isn't given a path.
+///
+pub const DEFAULT_PATH: &str = "mastermind-save.json";
+
+///
+/// The ways a save can be written to a file.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SaveFormat {
+    Json,
+    #[cfg(feature = "binary")]
+    Binary,
+}
+
+impl SaveFormat {
+    ///
+    /// Picks a format by the file's extension: `.mmsave` files
+    /// are binary, and anything else is JSON. Without the
+    /// `binary` feature everything is JSON.
+    ///
+    pub fn from_path(path: &str) -> Self {
+        match path {
+            #[cfg(feature = "binary")]
+            x if x.ends_with(".mmsave") => SaveFormat::Binary,
+            _ => SaveFormat::Json,
+        }
+    }
+}
+
+impl std::str::FromStr for SaveFormat {
+    type Err = String;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "json" => Ok(SaveFormat::Json),
+            #[cfg(feature = "binary")]
+            "binary" => Ok(SaveFormat::Binary),
+            #[cfg(not(feature = "binary"))]
+            "binary" => Err(
+                "This build can only write JSON saves, it was built without \
+                             the `binary` feature"
+                    .into(),
+            ),
+            _ => Err(format!(
+                "Unknown save format `{}`, expected `json` or `binary`",
+                text
+            )),
+        }
+    }
+}
+
+///
+/// Everything which could go wrong while loading a save.
+///
+#[derive(Debug)]
+pub enum SaveError {
+    ///
+    /// Reading or writing the file itself failed.
+    ///
+    Io(std::io::Error),
+    ///
+    /// The file isn't valid JSON (Or valid binary data, for a
+    /// binary save).
+    ///
+    Syntax(String),
+    ///
+    /// The file is JSON, but doesn't look like a save.
+    ///
+    Invalid(String),
+    ///
+    /// The checksum in the file doesn't match the game stored
+    /// in it, so it was cut short or edited by hand.
+    ///
+    Corrupted,
+    ///
+    /// The save was made by a newer version of this program,
+    /// which used a layout we don't know about.
+    ///
+    NewerVersion(usize),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{}", e),
+            SaveError::Syntax(e) => write!(f, "the save file could not be read: {}", e),
+            SaveError::Invalid(e) => write!(f, "the save file is invalid: {}", e),
+            SaveError::Corrupted => write!(f, "save file appears corrupted: checksum mismatch"),
+            SaveError::NewerVersion(version) => write!(
+                f,
+                "this save was created by a newer version of the game (format version {}, \
+                 we understand up to {})",
+                version, FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+///
+/// Implementing `Error` lets a `SaveError` be returned with
+/// `?` from functions returning `Box<dyn Error>`, like `main`.
+///
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+///
+/// Everything a save keeps about a session: the game being
+/// played, and the games finished before it. What it doesn't
+/// keep is the random number generator and the callbacks, so
+/// a game restored from one picks its next secrets afresh.
+///
+/// `State::snapshot` takes one and `State::from_snapshot` turns
+/// it back into a game. Since it's all plain data, it can also
+/// be looked at or changed in between, or written out as JSON
+/// with `to_json`, which is what `save` does.
+///
+/// It can be written with any serde format too, as the same
+/// layout as the game in a save. Only `to_json` adds the
+/// version and checksum around it which `load` needs, though.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub config: GameConfig,
+    pub secret: Vec<Colour>,
+    ///
+    /// The guesses made so far this game, oldest first. Their
+    /// feedback isn't kept, since it comes from the secret.
+    ///
+    pub history: Vec<Vec<Colour>>,
+    ///
+    /// The colours entered of the next guess.
+    ///
+    pub buffered: Vec<Colour>,
+    ///
+    /// The positions of the secret revealed so far.
+    ///
+    // Revealing positions, and hints, came along later than
+    // version 2, so these are allowed to be missing.
+    #[serde(default)]
+    pub revealed: Vec<usize>,
+    #[serde(default)]
+    pub hint_points_spent: usize,
+    ///
+    /// How many of `revealed` were hints, rather than revealed
+    /// by `GameConfig::reveal_every`.
+    ///
+    #[serde(default)]
+    pub hint_reveals: usize,
+    ///
+    /// How long the game had been going for.
+    ///
+    #[serde(rename = "elapsed_seconds", with = "seconds")]
+    pub elapsed: Duration,
+    pub games: Vec<GameRecord>,
+}
+
+impl StateSnapshot {
+    ///
+    /// Writes the snapshot out as a save, in the newest layout.
+    ///
+    pub fn to_json(&self) -> Json {
+        let game = Json::from_serde(self);
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_secs())
+            .unwrap_or(0);
+        Json::object(vec![
+            ("format_version", FORMAT_VERSION.into()),
+            ("saved_at", saved_at.into()),
+            ("checksum", format!("{:08x}", checksum(&game)).into()),
+            ("game", game),
+        ])
+    }
+
+    ///
+    /// Reads a save of any version back in. Nothing is checked
+    /// against the settings until it's made into a game.
+    ///
+    pub fn from_json(json: &Json) -> Result<Self, SaveError> {
+        read_save(json)
+    }
+}
+
+impl<'a> State<'a> {
+    ///
+    /// Everything about this session which can be saved, see
+    /// `StateSnapshot`.
+    ///
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            config: self.config,
+            secret: self.game.secret().to_vec(),
+            history: self
+                .game
+                .attempts()
+                .iter()
+                .map(|x| x.guess.to_vec())
+                .collect(),
+            buffered: self.game.buffer().to_vec(),
+            revealed: self.revealed.clone(),
+            hint_points_spent: self.hint_points_spent,
+            hint_reveals: self.hint_reveals,
+            elapsed: self.started.elapsed(),
+            games: self.previous_games.clone(),
+        }
+    }
+
+    ///
+    /// The session `snapshot` was taken of, carrying on where it
+    /// left off. Like `StateBuilder::new`, it prints nothing and
+    /// does nothing when a game ends, see `load` for a game which
+    /// does. A snapshot which couldn't have come from a game with
+    /// its settings is an error.
+    ///
+    pub fn from_snapshot(snapshot: StateSnapshot) -> Result<Self, SaveError> {
+        restore(snapshot, |_| {}, |_| {}, false)
+    }
+}
+
+///
+/// Writes the whole state of `state` out as JSON, using the
+/// newest layout.
+///
+pub fn save(state: &State) -> Json {
+    state.snapshot().to_json()
+}
+
+///
+/// Reads a save back in, given the callbacks for the restored
+/// state (Functions can't be saved to a file).
+///
+pub fn load<'a>(
+    json: &Json,
+    win: impl FnMut(&GameSummary) + 'a,
+    lose: impl FnMut(&GameSummary) + 'a,
+    terminal: bool,
+) -> Result<State<'a>, SaveError> {
+    restore(read_save(json)?, win, lose, terminal)
+}
+
+//
+// Brings a save of any version up to date.
+//
+fn read_save(json: &Json) -> Result<StateSnapshot, SaveError> {
+    let version = json
+        .get("format_version")
+        .and_then(Json::as_usize)
+        .ok_or_else(|| invalid("missing `format_version`"))?;
+    // Version 1 has a function of its own, which takes care
+    // of filling in whatever it didn't store.
+    let data = match version {
+        1 => load_v1(json)?,
+        2 => json.to_serde().map_err(SaveError::Invalid)?,
+        3 => {
+            let game = json.get("game").ok_or_else(|| invalid("missing `game`"))?;
+            let expected = json
+                .get("checksum")
+                .ok_or_else(|| invalid("missing `checksum`"))?
+                .as_str()
+                .and_then(|x| u32::from_str_radix(x, 16).ok())
+                .ok_or(SaveError::Corrupted)?;
+            if checksum(game) != expected {
+                return Err(SaveError::Corrupted);
+            }
+            // Other than being wrapped up, the game is laid out
+            // the same way as it was in version 2.
+            game.to_serde().map_err(SaveError::Invalid)?
+        }
+        x if x > FORMAT_VERSION => return Err(SaveError::NewerVersion(x)),
+        x => return Err(invalid(&format!("unknown format version {}", x))),
+    };
+    Ok(data)
+}
+
+fn restore<'a>(
+    data: StateSnapshot,
+    win: impl FnMut(&GameSummary) + 'a,
+    lose: impl FnMut(&GameSummary) + 'a,
+    terminal: bool,
+) -> Result<State<'a>, SaveError> {
+    let mut state = StateBuilder::new()
+        .config(data.config)
+        .on_win(win)
+        .on_lose(lose)
+        .terminal(terminal)
+        .build()
+        .map_err(|e| invalid(&format!("the saved settings are not valid: {}", e)))?;
+    if data.secret.len() != data.config.pegs
+        || data.buffered.len() > data.config.pegs
+        || (data.buffered.len() == data.config.pegs && !data.config.confirm_guesses)
+        || data.revealed.iter().any(|&x| x >= data.config.pegs)
+        || data.hint_reveals > data.revealed.len()
+    {
+        return Err(invalid("the saved codes don't match the number of pegs"));
+    }
+    // `State` never lets a colour into the guess being entered
+    // twice when duplicates are off, or more often than
+    // `max_per_colour` allows
+    let mut seen = HashSet::new();
+    if !data.config.allow_duplicates && !data.buffered.iter().all(|x| seen.insert(*x)) {
+        return Err(invalid(
+            "the saved guess has a colour twice, but duplicates are off",
+        ));
+    }
+    if data.config.over_limit(&data.buffered).is_some() {
+        return Err(invalid(
+            "the saved guess has more of a colour than the settings allow",
+        ));
+    }
+    // The secret has to be one these settings could have picked,
+    // which are the same rules a guess is held to
+    Guess::for_config(data.secret.clone(), data.config)
+        .map_err(|e| invalid(&format!("the saved secret isn't allowed: {}", e)))?;
+    // A game which had been won, or had run out of tries, would
+    // have been over before it could be saved. Loading one would
+    // leave a game which never ends.
+    if data.history.contains(&data.secret) {
+        return Err(invalid("the saved game has already been won"));
+    }
+    if data
+        .config
+        .max_tries
+        .is_some_and(|x| data.history.len() >= x)
+    {
+        return Err(invalid("the saved game has no tries left"));
+    }
+    state.set_secret(data.secret);
+    // Only the guesses are saved, their feedback can be worked
+    // out again from the secret. Each one is checked the same way
+    // as if it had just been entered.
+    for guess in data.history {
+        let guess = Guess::for_config(guess, data.config)
+            .map_err(|e| invalid(&format!("a saved guess isn't allowed: {}", e)))?;
+        state
+            .game
+            .guess(guess)
+            .expect("A saved game has already been checked to still be going");
+    }
+    for colour in data.buffered {
+        state
+            .game
+            .push(colour)
+            .expect("The saved guess has already been checked to fit");
+    }
+    state.revealed = data.revealed;
+    state.hint_points_spent = data.hint_points_spent;
+    state.hint_reveals = data.hint_reveals;
+    state.previous_games = data.games;
+    // Pretend the game started as long ago as it had been
+    // going for when it was saved.
+    state.started = Instant::now()
+        .checked_sub(data.elapsed)
+        .unwrap_or_else(Instant::now);
+    Ok(state)
+}
+
+///
+/// Writes a save to `path`, in `format` or in whichever format
+/// the file extension suggests if that's `None`.
+///
+pub fn save_to_file(
+    state: &State,
+    path: &Path,
+    format: Option<SaveFormat>,
+) -> Result<(), SaveError> {
+    let json = save(state);
+    let format = format.unwrap_or_else(|| SaveFormat::from_path(&path.to_string_lossy()));
+    let bytes = match format {
+        SaveFormat::Json => json.to_string().into_bytes(),
+        #[cfg(feature = "binary")]
+        SaveFormat::Binary => binary::encode(&json),
+    };
+    write_atomically(path, &bytes, false)
+}
+
+//
+// Writes to a temporary file first and then renames it over
+// `path`. A rename either happens completely or not at all,
+// so if we crash part way through writing, `path` still has
+// the old contents rather than half of the new ones.
+//
+// If `keep_backup` is set, the old contents are moved to
+// `backup_path(path)` rather than being replaced.
+//
+fn write_atomically(path: &Path, bytes: &[u8], keep_backup: bool) -> Result<(), SaveError> {
+    let temporary = with_suffix(path, ".tmp");
+    {
+        // The file is closed at the end of this block.
+        let mut file = std::fs::File::create(&temporary)?;
+        std::io::Write::write_all(&mut file, bytes)?;
+        // Make sure it's really on the disk before the rename.
+        file.sync_all()?;
+    }
+    if keep_backup && path.exists() {
+        std::fs::rename(path, backup_path(path))?;
+    }
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    name.into()
+}
+
+///
+/// Where the previous autosave is kept.
+///
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+///
+/// Saves `state` to `path`, moving the previous autosave (If
+/// any) to `backup_path(path)` so that there is always one
+/// good save around even if this one gets damaged.
+///
+pub fn autosave(state: &State, path: &Path) -> Result<(), SaveError> {
+    write_atomically(path, save(state).to_string().as_bytes(), true)
+}
+
+///
+/// Whether there's an autosave (Or its backup) at `path` to
+/// resume.
+///
+pub fn has_autosave(path: &Path) -> bool {
+    path.exists() || backup_path(path).exists()
+}
+
+///
+/// Loads an autosave, falling back to the previous one if the
+/// newest can't be read. The error is from the newest save if
+/// neither of them work.
+///
+pub fn load_autosave<'a>(
+    path: &Path,
+    win: impl FnMut(&GameSummary) + 'a,
+    lose: impl FnMut(&GameSummary) + 'a,
+    terminal: bool,
+) -> Result<State<'a>, SaveError> {
+    let data = match read_file(path).and_then(|x| read_save(&x)) {
+        Ok(data) => data,
+        Err(e) => {
+            let data = read_file(&backup_path(path))
+                .and_then(|x| read_save(&x))
+                .map_err(|_| e)?;
+            if terminal {
+                println!("The latest autosave could not be read, so the one before it was loaded");
+            }
+            data
+        }
+    };
+    restore(data, win, lose, terminal)
+}
+
+///
+/// Removes the autosave and its backup, once a session has
+/// been played to the end and there's nothing left to resume.
+///
+pub fn clear_autosave(path: &Path) {
+    // It's fine if either of these didn't exist.
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(backup_path(path));
+}
+
+///
+/// A short description of a save, to show in a list of saves
+/// without loading the whole thing.
+///
+#[derive(Clone, Debug)]
+pub struct SaveInfo {
+    pub config: GameConfig,
+    ///
+    /// Guesses made in the game which was in progress.
+    ///
+    pub attempts: usize,
+    ///
+    /// Games finished before this one.
+    ///
+    pub games_played: usize,
+    ///
+    /// When the save was written, if it says.
+    ///
+    pub saved_at: Option<SystemTime>,
+}
+
+///
+/// Reads the description of the save at `path` (Or its
+/// backup, like `load_autosave`).
+///
+pub fn describe(path: &Path) -> Result<SaveInfo, SaveError> {
+    let json = read_file(path).or_else(|e| read_file(&backup_path(path)).map_err(|_| e))?;
+    let data = read_save(&json)?;
+    Ok(SaveInfo {
+        config: data.config,
+        attempts: data.history.len(),
+        games_played: data.games.len(),
+        saved_at: json
+            .get("saved_at")
+            .and_then(Json::as_usize)
+            .map(|x| UNIX_EPOCH + Duration::from_secs(x as u64)),
+    })
+}
+
+//
+// A CRC-32 (The same one used by zip files) of the game as
+// text. This is good at catching accidental damage, such as a
+// file which was cut off or had a number changed.
+//
+fn checksum(game: &Json) -> u32 {
+    let mut crc = !0u32;
+    for byte in game.to_string().bytes() {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            // `wrapping_neg` of 0 or 1 gives us a mask of all
+            // zeros or all ones, to xor the polynomial in with.
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+///
+/// Reads a save from `path`, see `load`. This doesn't care
+/// about the file extension, instead it looks at the start of
+/// the file to decide if it's binary or JSON.
+///
+pub fn load_from_file<'a>(
+    path: &Path,
+    win: impl FnMut(&GameSummary) + 'a,
+    lose: impl FnMut(&GameSummary) + 'a,
+    terminal: bool,
+) -> Result<State<'a>, SaveError> {
+    load(&read_file(path)?, win, lose, terminal)
+}
+
+fn read_file(path: &Path) -> Result<Json, SaveError> {
+    let bytes = std::fs::read(path)?;
+    #[cfg(feature = "binary")]
+    {
+        if bytes.starts_with(binary::MAGIC) {
+            return binary::decode(&bytes).map_err(SaveError::Syntax);
+        }
+    }
+    let text = String::from_utf8(bytes)
+        .map_err(|_| SaveError::Syntax("the file is neither JSON nor a binary save".into()))?;
+    Json::parse(&text).map_err(SaveError::Syntax)
+}
+
+//
+// Version 1 didn't know how long games took, or about
+// outcomes other than winning and losing, so it has a layout
+// of its own which is read and then filled in.
+//
+#[derive(Deserialize)]
+struct Version1 {
+    config: GameConfig,
+    secret: Vec<Colour>,
+    history: Vec<Vec<Colour>>,
+    buffered: Vec<Colour>,
+    games: Vec<Version1Game>,
+}
+
+#[derive(Deserialize)]
+struct Version1Game {
+    secret: Vec<Colour>,
+    attempts: usize,
+    won: bool,
+}
+
+fn load_v1(json: &Json) -> Result<StateSnapshot, SaveError> {
+    let old: Version1 = json.to_serde().map_err(SaveError::Invalid)?;
+    let games = old
+        .games
+        .into_iter()
+        .map(|game| GameRecord {
+            secret: game.secret,
+            attempts: game.attempts,
+            outcome: if game.won {
+                Outcome::Won
+            } else {
+                Outcome::Lost
+            },
+            duration: None,
+            reveals: 0,
+            hardcore: false,
+            memory: false,
+            settings: None,
+            speed_bonus: 0,
+            hint_points: 0,
+            label: None,
+            guesses: Vec::new(),
+        })
+        .collect();
+    Ok(StateSnapshot {
+        config: old.config,
+        secret: old.secret,
+        history: old.history,
+        buffered: old.buffered,
+        revealed: Vec::new(),
+        hint_points_spent: 0,
+        hint_reveals: 0,
+        elapsed: Duration::from_secs(0),
+        games,
+    })
+}
+
+fn invalid(message: &str) -> SaveError {
+    SaveError::Invalid(message.to_string())
+}
+
+//
+// Everything below is how `GameConfig`, `GameRecord` and
+// `StateSnapshot` are laid out where serde wouldn't do it the
+// same way as version 3 by itself. Their `#[serde(...)]`
+// attributes point here. Most of it is for fields added since
+// version 3, which have to be optional.
+//
+
+pub(crate) fn yes() -> bool {
+    true
+}
+
+//
+// Older saves were all played with the classic colours.
+//
+pub(crate) fn classic_palette() -> Option<usize> {
+    Some(CLASSIC_PALETTE_SIZE)
+}
+
+//
+// Checked here as well as by `validate`, since a limit this big
+// isn't something a save should ever have.
+//
+pub(crate) fn max_tries<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<usize>, D::Error> {
+    match Option::<usize>::deserialize(deserializer)? {
+        Some(x) if x > MAX_TRIES => Err(de::Error::custom(ConfigError::TooManyTries {
+            most: MAX_TRIES,
+        })),
+        x => Ok(x),
+    }
+}
+
+//
+// `Duration`s are whole seconds. Serde would write the
+// nanoseconds too, as `{ "secs": 1, "nanos": 0 }`.
+//
+pub(crate) mod seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_secs)
+    }
+}
+
+pub(crate) mod optional_seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        duration.map(|x| x.as_secs()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_secs))
+    }
+}
+
+//
+// `GameConfig::colour_weights` as an object of colour names to
+// weights, like `{ "red": 2 }`, rather than a list nobody could
+// make sense of by reading it.
+//
+pub(crate) mod colour_weights {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        weights: &Option<[u32; PALETTE_SIZE]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match weights {
+            Some(weights) => {
+                serializer.collect_map(COLOURS.iter().map(|&x| (x.name(), weights[x as usize])))
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<[u32; PALETTE_SIZE]>, D::Error> {
+        super::super::weights_from_json(&Json::deserialize(deserializer)?)
+            .map_err(de::Error::custom)
+    }
+}
+
+//
+// `GameConfig::must_include` as a list of the colours' names.
+//
+pub(crate) mod colour_set {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        set: &[bool; PALETTE_SIZE],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            COLOURS
+                .iter()
+                .filter(|&&x| set[x as usize])
+                .map(|x| x.name()),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[bool; PALETTE_SIZE], D::Error> {
+        super::super::colour_set_from_json(&Json::deserialize(deserializer)?)
+            .map_err(de::Error::custom)
+    }
+}
+
+//
+// Only the guesses of a finished game are written down, like in
+// a save, and `GameRecord::deserialize` below works out their
+// feedback again.
+//
+pub(crate) mod guesses {
+    use super::*;
+
+    pub fn serialize<P: Serialize, S: Serializer>(
+        guesses: &[Attempt<P>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(guesses.iter().map(|x| &x.guess))
+    }
+
+    pub fn deserialize<'de, P: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Attempt<P>>, D::Error> {
+        Ok(Vec::<Guess<P>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|guess| Attempt {
+                guess,
+                feedback: Feedback::default(),
+            })
+            .collect())
+    }
+}
+
+//
+// `GameRecord` has `#[serde(remote = "Self")]`, which makes its
+// derives write `GameRecord::serialize` and `deserialize` as
+// plain functions. These are the traits, which call them and
+// then tidy up what was read.
+//
+impl<P: Serialize> Serialize for GameRecord<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameRecord::serialize(self, serializer)
+    }
+}
+
+impl<'de, P: Symbol + Deserialize<'de>> Deserialize<'de> for GameRecord<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut record = GameRecord::deserialize(deserializer)?;
+        // The rest of the settings aren't in a record, so only
+        // the length can be checked
+        for attempt in &mut record.guesses {
+            let guess = Guess::new(attempt.guess.to_vec(), record.secret.len(), true)
+                .map_err(|e| de::Error::custom(format!("a recorded guess: {}", e)))?;
+            *attempt = Attempt::new(&record.secret, guess);
+        }
+        // Labels are cleaned again in case the file was edited by
+        // hand
+        record.label = record.label.as_deref().and_then(super::clean_label);
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::all_codes;
+    use crate::mastermind::hints::Hint;
+
+    //
+    // A snapshot of a new game, with three tries, seeded so it
+    // always has the same secret.
+    //
+    fn snapshot() -> StateSnapshot {
+        let config = GameConfig {
+            max_tries: Some(3),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        StateBuilder::new()
+            .config(config)
+            .seed(7)
+            .build()
+            .unwrap()
+            .snapshot()
+    }
+
+    //
+    // `count` guesses which aren't the secret.
+    //
+    fn wrong_guesses(snapshot: &StateSnapshot, count: usize) -> Vec<Vec<Colour>> {
+        all_codes(&snapshot.config.palette(), snapshot.config.pegs, false)
+            .filter(|x| *x != snapshot.secret)
+            .take(count)
+            .collect()
+    }
+
+    fn rejected(snapshot: StateSnapshot) -> String {
+        match State::from_snapshot(snapshot) {
+            Err(SaveError::Invalid(reason)) => reason,
+            Err(e) => panic!("expected an invalid save, got {}", e),
+            Ok(_) => panic!("expected an invalid save, but it loaded"),
+        }
+    }
+
+    #[test]
+    fn a_game_with_tries_left_loads() {
+        let mut snapshot = snapshot();
+        snapshot.history = wrong_guesses(&snapshot, 2);
+        let state = State::from_snapshot(snapshot.clone()).unwrap();
+        assert_eq!(state.attempts(), 2);
+        assert_eq!(state.remaining_tries(), Some(1));
+        assert_eq!(state.snapshot().secret, snapshot.secret);
+    }
+
+    #[test]
+    fn a_game_with_no_tries_left_is_invalid() {
+        let mut snapshot = snapshot();
+        snapshot.history = wrong_guesses(&snapshot, 3);
+        assert!(rejected(snapshot.clone()).contains("no tries left"));
+        snapshot.history = wrong_guesses(&snapshot, 5);
+        assert!(rejected(snapshot).contains("no tries left"));
+    }
+
+    #[test]
+    fn a_game_which_was_won_is_invalid() {
+        let mut snapshot = snapshot();
+        snapshot.history = vec![snapshot.secret.clone()];
+        assert!(rejected(snapshot).contains("already been won"));
+    }
+
+    #[test]
+    fn a_secret_the_settings_couldnt_pick_is_invalid() {
+        let mut twice = snapshot();
+        twice.secret[1] = twice.secret[0];
+        assert!(rejected(twice).contains("secret"));
+        // Pink isn't one of the classic six
+        let mut pink = snapshot();
+        pink.secret[0] = Colour::Pink;
+        assert!(rejected(pink).contains("secret"));
+        let mut blank = snapshot();
+        blank.secret[0] = Colour::Blank;
+        assert!(rejected(blank).contains("secret"));
+    }
+
+    #[test]
+    fn a_huge_try_limit_in_a_save_is_invalid() {
+        let mut config = Json::from_serde(&GameConfig::default());
+        if let Json::Object(fields) = &mut config {
+            for (key, value) in fields.iter_mut() {
+                if key == "max_tries" {
+                    *value = Json::Number(1_000_000_000_000);
+                }
+            }
+        }
+        let error = config.to_serde::<GameConfig>().unwrap_err();
+        assert!(error.contains(&MAX_TRIES.to_string()), "{}", error);
+    }
+
+    //
+    // The wins, losses and hints of a session are all kept in
+    // its records: a game won straight away, one won with a hint,
+    // one lost by running out of tries, one given up on, and the
+    // game being played when it was saved.
+    //
+    fn every_kind_of_game() -> State<'static> {
+        let config = GameConfig {
+            max_tries: Some(3),
+            auto_max_tries: false,
+            hint_points: 5,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).seed(7).build().unwrap();
+        let guess = |x: Vec<Colour>| Guess::for_config(x, config).unwrap();
+        let wrong = |state: &State| wrong_guesses(&state.snapshot(), 3);
+        state
+            .enter_guess(&guess(state.reveal_secret().to_vec()))
+            .unwrap();
+        state.take_hint(Hint::Reveal).unwrap();
+        state
+            .enter_guess(&guess(state.reveal_secret().to_vec()))
+            .unwrap();
+        for x in wrong(&state) {
+            state.enter_guess(&guess(x)).unwrap();
+        }
+        state.enter_guess(&guess(wrong(&state)[0].clone())).unwrap();
+        state.forfeit();
+        state.enter_guess(&guess(wrong(&state)[1].clone())).unwrap();
+        state.input_buffer(state.reveal_secret()[2]).unwrap();
+        state
+    }
+
+    //
+    // Saves only keep whole seconds, so this is what a snapshot
+    // is expected to look like after one.
+    //
+    fn to_the_second(mut snapshot: StateSnapshot) -> StateSnapshot {
+        fn whole(x: Duration) -> Duration {
+            Duration::from_secs(x.as_secs())
+        }
+        snapshot.elapsed = whole(snapshot.elapsed);
+        for game in &mut snapshot.games {
+            game.duration = game.duration.map(whole);
+        }
+        snapshot
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn every_kind_of_game_round_trips_in_both_formats() {
+        let state = every_kind_of_game();
+        let outcomes: Vec<_> = state.games().iter().map(|x| x.outcome).collect();
+        use Outcome::*;
+        assert_eq!(outcomes, vec![Won, Won, Lost, Lost]);
+        assert_eq!(state.games()[1].hint_points, 3);
+        assert_eq!(state.games()[1].reveals, 1);
+        assert_eq!(state.games()[3].attempts, 1);
+
+        let expected = to_the_second(state.snapshot());
+        let json = save(&state);
+        let text = Json::parse(&json.to_string()).unwrap();
+        assert_eq!(to_the_second(read_save(&text).unwrap()), expected);
+        let bytes = binary::encode(&json);
+        assert!(bytes.starts_with(binary::MAGIC));
+        let decoded = binary::decode(&bytes).unwrap();
+        assert_eq!(to_the_second(read_save(&decoded).unwrap()), expected);
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn files_in_either_format_are_told_apart_by_their_contents() {
+        let state = every_kind_of_game();
+        let expected = to_the_second(state.snapshot());
+        let dir = std::env::temp_dir().join(format!("mastermind-formats-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // The wrong extension for each, so only the contents can
+        // say which it is
+        for &(name, format) in &[
+            ("save.mmsave", SaveFormat::Json),
+            ("save.json", SaveFormat::Binary),
+        ] {
+            let path = dir.join(name);
+            save_to_file(&state, &path, Some(format)).unwrap();
+            let loaded = load_from_file(&path, |_| {}, |_| {}, false).unwrap();
+            assert_eq!(to_the_second(loaded.snapshot()), expected);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn round_trip<T>(value: &T) -> T
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let text = serde_json::to_string(value).unwrap();
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn settings_round_trip_through_serde() {
+        let mut weights = [1; crate::rules::PALETTE_SIZE];
+        weights[Colour::Red as usize] = 3;
+        let config = GameConfig {
+            pegs: 5,
+            allow_duplicates: true,
+            max_tries: None,
+            auto_max_tries: false,
+            colour_weights: Some(weights),
+            palette_size: Some(8),
+            hint_points: 4,
+            ..GameConfig::default()
+        };
+        assert_eq!(round_trip(&config), config);
+        assert_eq!(round_trip(&GameConfig::default()), GameConfig::default());
+    }
+
+    #[test]
+    fn snapshots_and_records_round_trip_through_serde() {
+        let config = snapshot().config;
+        let mut state = StateBuilder::new().config(config).seed(7).build().unwrap();
+        let first = state.snapshot();
+        for guess in wrong_guesses(&first, 2) {
+            let guess = Guess::for_config(guess, config).unwrap();
+            assert_eq!(state.enter_guess(&guess).unwrap(), None);
+        }
+        let secret = Guess::for_config(first.secret.clone(), config).unwrap();
+        assert_eq!(state.enter_guess(&secret).unwrap(), Some(Outcome::Won));
+        // Half of the next guess
+        let next = state.snapshot().secret;
+        state.input_buffer(next[0]).unwrap();
+
+        // Serde keeps whole seconds too, the same as a save
+        let snapshot = to_the_second(state.snapshot());
+        assert_eq!(snapshot.buffered, vec![next[0]]);
+        let record = &snapshot.games[0];
+        assert_eq!(record.guesses.len(), 3);
+        assert_eq!(round_trip(record), *record);
+        assert_eq!(round_trip(&record.guesses[2]), record.guesses[2]);
+        assert_eq!(round_trip(&snapshot), snapshot);
+    }
+
+    #[test]
+    fn serde_writes_colours_and_outcomes_by_name() {
+        let snapshot = snapshot();
+        let value = serde_json::to_value(&snapshot).unwrap();
+        let names: Vec<_> = snapshot.secret.iter().map(|x| x.name()).collect();
+        assert_eq!(value["secret"], serde_json::json!(names));
+        assert_eq!(serde_json::to_string(&Outcome::Lost).unwrap(), "\"lost\"");
+    }
+
+    #[test]
+    fn the_game_in_a_save_is_the_serde_layout_of_its_snapshot() {
+        let state = every_kind_of_game();
+        let snapshot = to_the_second(state.snapshot());
+        let json = snapshot.to_json();
+        assert_eq!(json.get("game"), Some(&Json::from_serde(&snapshot)));
+        let game = json.get("game").unwrap();
+        assert_eq!(
+            game.get("elapsed_seconds").and_then(Json::as_usize),
+            Some(0)
+        );
+        let record = &game.get("games").unwrap().as_array().unwrap()[0];
+        assert!(record.get("seconds").is_some());
+        assert_eq!(record.get("outcome").and_then(Json::as_str), Some("won"));
+    }
+
+    #[test]
+    fn a_game_saved_after_two_guesses_can_be_won_after_loading() {
+        let config = GameConfig {
+            max_tries: Some(5),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        };
+        let mut state = StateBuilder::new().config(config).seed(7).build().unwrap();
+        let secret = state.reveal_secret().to_vec();
+        for guess in wrong_guesses(&state.snapshot(), 2) {
+            let guess = Guess::for_config(guess, config).unwrap();
+            assert_eq!(state.enter_guess(&guess).unwrap(), None);
+        }
+        assert_eq!(state.remaining_tries(), Some(3));
+        let text = save(&state).to_string();
+        drop(state);
+
+        let won = std::cell::Cell::new(None);
+        let lost = std::cell::Cell::new(false);
+        let json = Json::parse(&text).unwrap();
+        let mut state = load(
+            &json,
+            |x| won.set(Some(x.attempts_made)),
+            |_| lost.set(true),
+            false,
+        )
+        .unwrap();
+        assert_eq!(state.reveal_secret(), &secret[..]);
+        assert_eq!(state.attempts(), 2);
+        assert_eq!(state.remaining_tries(), Some(3));
+        assert_eq!(state.history().len(), 2);
+        // A wrong guess after loading still counts against the
+        // tries left
+        let wrong = wrong_guesses(&state.snapshot(), 3).pop().unwrap();
+        let wrong = Guess::for_config(wrong, config).unwrap();
+        assert_eq!(state.enter_guess(&wrong).unwrap(), None);
+        assert_eq!(state.remaining_tries(), Some(2));
+        let guess = Guess::for_config(secret.clone(), config).unwrap();
+        assert_eq!(state.enter_guess(&guess).unwrap(), Some(Outcome::Won));
+        assert_eq!(won.get(), Some(4));
+        assert!(!lost.get());
+        let record = state.games().last().unwrap();
+        assert_eq!(record.secret, secret);
+        assert_eq!(record.attempts, 4);
+        assert_eq!(record.outcome, Outcome::Won);
+    }
+}
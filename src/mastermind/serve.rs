@@ -0,0 +1,843 @@
+//!
+//! `mastermind serve`, which hosts games for anyone who connects
+//! over TCP with `mastermind join --address HOST:PORT`. Unlike
+//! `mastermind host`, everybody who connects gets a game of their
+//! own, all at the same time, and the server keeps going after
+//! each of them leaves.
+//!
+//! Each connection is handled on a thread of its own, much like
+//! giving every client a `Thread` in Java, rather than all of
+//! them sharing an async runtime (Which would mean a crate like
+//! `tokio`). A thread each is plenty for a classroom, and at
+//! most `--max-sessions` of them (64 unless it's changed) are
+//! running at once. What the threads share is kept in an
+//! `Arc<Mutex<...>>`: `Arc` is a reference count which can be
+//! shared between threads, and `Mutex` is a lock which owns
+//! what it guards, so it can't be touched without locking it.
+//!
+//! Every session is given a token when it connects. When a
+//! connection drops, its game is put aside for `--grace`
+//! seconds, and whoever sends that token back from a new
+//! connection in that time carries on with it. The clock stops
+//! while a game is put aside, so the outage doesn't count
+//! towards how long the game took.
+//!
+//! Nobody connecting is trusted: a message longer than
+//! `--max-message-bytes` is refused without being read into
+//! memory, and each connection can only send `--message-rate`
+//! messages a second (With bursts of up to `--message-burst`),
+//! whatever they are. Sending faster than that is answered with
+//! `SlowDown`.
+//!
+//! What happens to each session is written to stderr, one line
+//! of `key=value` pairs per event.
+//!
+
+use super::json::Json;
+use super::protocol::{self, Message};
+use super::settings::{self, SettingsLayer};
+use super::{save, GameConfig, Guess, SilentOutput, State};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+///
+/// The limits every session is held to.
+///
+#[derive(Clone, Copy)]
+struct Options {
+    idle_timeout: Duration,
+    grace: Duration,
+    max_message_bytes: usize,
+    max_sessions: usize,
+    message_rate: f64,
+    message_burst: f64,
+}
+
+///
+/// Rate limiting, as a bucket which holds up to `capacity`
+/// tokens and is topped up with `per_second` of them every
+/// second. Each message takes a token out, and a message which
+/// finds the bucket empty has to wait.
+///
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    topped_up: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_second: f64, capacity: f64) -> Self {
+        Self {
+            capacity,
+            per_second,
+            tokens: capacity,
+            topped_up: Instant::now(),
+        }
+    }
+
+    //
+    // Takes a token, or gives back how long until there is one.
+    //
+    fn take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.topped_up).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.topped_up = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+///
+/// A game whose connection dropped, waiting to be resumed.
+///
+/// `State` can't be sent to another thread (Its callbacks
+/// could be anything), so it's kept the way it would be saved
+/// to a file instead, and loaded back by whichever thread
+/// resumes it.
+///
+struct Parked {
+    session: usize,
+    save: Json,
+    since: Instant,
+}
+
+///
+/// What every session can see of the others.
+///
+#[derive(Default)]
+struct Server {
+    active: usize,
+    completed: usize,
+    ///
+    /// The tokens of the sessions which are connected right now,
+    /// which can't be resumed from a second connection.
+    ///
+    connected: HashSet<String>,
+    parked: HashMap<String, Parked>,
+}
+
+impl Server {
+    //
+    // Forgets the games which have been put aside for longer
+    // than `grace`.
+    //
+    fn expire(&mut self, grace: Duration) {
+        self.parked.retain(|_, parked| {
+            let keep = parked.since.elapsed() <= grace;
+            if !keep {
+                eprintln!("event=session_expired session={}", parked.session);
+            }
+            keep
+        });
+    }
+}
+
+///
+/// One of `Server::active`, for as long as it's kept. The count
+/// goes back down when this is dropped, which happens however
+/// the session's thread ends, even if it panics.
+///
+struct Active(Arc<Mutex<Server>>);
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        lock(&self.0).active -= 1;
+    }
+}
+
+//
+// A thread which panicked while holding the lock leaves it
+// "poisoned", but what it guards is still fine to use.
+//
+fn lock(server: &Mutex<Server>) -> MutexGuard<'_, Server> {
+    server.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+//
+// Hard enough to guess that nobody can take over someone else's
+// game by trying tokens.
+//
+fn new_token() -> String {
+    let mut rng = rand::thread_rng();
+    format!("{:016x}{:016x}", rng.gen::<u64>(), rng.gen::<u64>())
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(300),
+            grace: Duration::from_secs(60),
+            max_message_bytes: 4096,
+            max_sessions: 64,
+            message_rate: 5.0,
+            message_burst: 10.0,
+        }
+    }
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut address = "127.0.0.1:7878".to_string();
+    let mut options = Options::default();
+    let seconds = |args: &mut dyn Iterator<Item = String>, flag: &str| {
+        args.next()
+            .and_then(|x| x.parse().ok())
+            .filter(|&x| x > 0)
+            .map(Duration::from_secs)
+            .ok_or(format!("`{}` needs a positive number of seconds", flag))
+    };
+    let positive = |args: &mut dyn Iterator<Item = String>, flag: &str| {
+        args.next()
+            .and_then(|x| x.parse::<f64>().ok())
+            .filter(|&x| x > 0.0 && x.is_finite())
+            .ok_or(format!("`{}` needs a positive number", flag))
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--address" => address = args.next().ok_or("`--address` needs HOST:PORT")?,
+            "--max-sessions" => {
+                options.max_sessions = args
+                    .next()
+                    .and_then(|x| x.parse::<usize>().ok())
+                    .filter(|&x| x > 0)
+                    .ok_or("`--max-sessions` needs a positive number")?
+            }
+            "--idle-timeout" => options.idle_timeout = seconds(&mut args, "--idle-timeout")?,
+            "--grace" => options.grace = seconds(&mut args, "--grace")?,
+            "--max-message-bytes" => {
+                options.max_message_bytes = positive(&mut args, "--max-message-bytes")? as usize
+            }
+            "--message-rate" => options.message_rate = positive(&mut args, "--message-rate")?,
+            "--message-burst" => {
+                options.message_burst = positive(&mut args, "--message-burst")?.max(1.0)
+            }
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    // Everyone starts with the settings from the config file, and
+    // can ask for others once they've connected.
+    let defaults = settings::resolve(
+        settings::load_config_file()?,
+        None,
+        SettingsLayer::default(),
+    );
+    defaults.validate()?;
+    let listener = TcpListener::bind(&address)
+        .map_err(|e| format!("Could not listen on {}: {}", address, e))?;
+    eprintln!("event=listening address={}", listener.local_addr()?);
+    serve(listener, defaults, options);
+    Ok(())
+}
+
+//
+// Takes connections from `listener` for as long as it works,
+// giving each a game with `defaults` until it asks for others.
+//
+fn serve(listener: TcpListener, defaults: GameConfig, options: Options) {
+    let server = Arc::new(Mutex::new(Server::default()));
+    for (id, stream) in listener.incoming().enumerate() {
+        let mut stream = match stream {
+            Ok(x) => x,
+            Err(e) => {
+                eprintln!("event=accept_failed error={:?}", e.to_string());
+                continue;
+            }
+        };
+        let peer = stream
+            .peer_addr()
+            .map_or_else(|_| "unknown".to_string(), |x| x.to_string());
+        let (token, active) = {
+            let mut guard = lock(&server);
+            guard.expire(options.grace);
+            if guard.active >= options.max_sessions {
+                drop(guard);
+                let reason = "The server is full, try again later".to_string();
+                let _ = protocol::send(&mut stream, &Message::Rejected(reason));
+                eprintln!("event=session_refused peer={} reason=full", peer);
+                continue;
+            }
+            guard.active += 1;
+            let token = new_token();
+            guard.connected.insert(token.clone());
+            (token, Active(Arc::clone(&server)))
+        };
+        eprintln!("event=session_started session={} peer={}", id, peer);
+        let server = Arc::clone(&server);
+        thread::spawn(move || {
+            let result = session(stream, defaults, token, options, id, &server);
+            drop(active);
+            match result {
+                Ok(reason) => eprintln!("event=session_ended session={} reason={}", id, reason),
+                Err(e) => eprintln!(
+                    "event=session_ended session={} reason=error error={:?}",
+                    id,
+                    e.to_string()
+                ),
+            }
+        });
+    }
+}
+
+///
+/// The game a connection is playing, and the token to resume
+/// it with.
+///
+struct Game {
+    id: usize,
+    token: String,
+    state: State<'static>,
+}
+
+//
+// One connection, from the first `Hello` until it hangs up or
+// goes quiet for `idle_timeout`. Gives back why it ended.
+//
+// Whichever way it ends, the game is put aside afterwards in
+// case the player comes back for it.
+//
+fn session(
+    stream: TcpStream,
+    config: GameConfig,
+    token: String,
+    options: Options,
+    id: usize,
+    server: &Mutex<Server>,
+) -> io::Result<&'static str> {
+    let state = match State::new(config, |_| {}, |_| {}, SilentOutput) {
+        Ok(x) => x,
+        Err(e) => {
+            lock(server).connected.remove(&token);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, e));
+        }
+    };
+    let mut game = Game { id, token, state };
+    let result = converse(stream, &mut game, options, server);
+    let mut server = lock(server);
+    server.connected.remove(&game.token);
+    server.parked.insert(
+        game.token,
+        Parked {
+            session: game.id,
+            save: save::save(&game.state),
+            since: Instant::now(),
+        },
+    );
+    result
+}
+
+fn converse(
+    stream: TcpStream,
+    game: &mut Game,
+    options: Options,
+    server: &Mutex<Server>,
+) -> io::Result<&'static str> {
+    // Reads give up with an error once nothing has arrived for
+    // this long, which is how idle sessions are noticed.
+    stream.set_read_timeout(Some(options.idle_timeout))?;
+    let mut bucket = TokenBucket::new(options.message_rate, options.message_burst);
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    protocol::send(&mut writer, &Message::Hello(game.state.config))?;
+    protocol::send(&mut writer, &Message::Token(game.token.clone()))?;
+    loop {
+        let message = match receive(&mut reader, &mut writer, options.max_message_bytes)? {
+            Ok(x) => x,
+            Err(reason) => return Ok(reason),
+        };
+        // A message which comes too soon isn't looked at at all.
+        if let Err(wait) = bucket.take() {
+            protocol::send(&mut writer, &Message::SlowDown(wait))?;
+            continue;
+        }
+        let reply = match message {
+            // Asking for new settings starts a new game with them,
+            // even in the middle of one.
+            Message::Settings(wanted) => match State::new(wanted, |_| {}, |_| {}, SilentOutput) {
+                Ok(state) => {
+                    game.state = state;
+                    eprintln!(
+                        "event=settings_changed session={} pegs={} duplicates={} max_tries={}",
+                        game.id,
+                        wanted.pegs,
+                        wanted.allow_duplicates,
+                        wanted
+                            .max_tries
+                            .map_or_else(|| "unlimited".to_string(), |x| x.to_string())
+                    );
+                    Message::Hello(wanted)
+                }
+                Err(e) => Message::Rejected(e.to_string()),
+            },
+            Message::Resume(token) => resume(game, token, options.grace, server),
+            Message::Stats => {
+                let server = lock(server);
+                Message::ServerStats {
+                    active: server.active,
+                    completed: server.completed,
+                }
+            }
+            Message::Guess(guess) => match Guess::for_config(guess, game.state.config)
+                .map_err(From::from)
+                .and_then(|guess| game.state.enter_guess(&guess))
+            {
+                Err(e) => Message::Rejected(e.to_string()),
+                Ok(Some(outcome)) => {
+                    lock(server).completed += 1;
+                    eprintln!(
+                        "event=game_over session={} outcome={}",
+                        game.id,
+                        outcome.name()
+                    );
+                    Message::GameOver {
+                        outcome,
+                        secret: game
+                            .state
+                            .previous_games
+                            .last()
+                            .map(|x| x.secret.clone())
+                            .unwrap_or_default(),
+                    }
+                }
+                Ok(None) => {
+                    let feedback = game.state.last_scored().unwrap_or_default();
+                    Message::Feedback {
+                        exact: feedback.exact,
+                        colour: feedback.misplaced,
+                    }
+                }
+            },
+            _ => Message::Rejected("Expected a guess, settings or stats".into()),
+        };
+        protocol::send(&mut writer, &reply)?;
+    }
+}
+
+//
+// The next message, or why the session is over. Messages which
+// aren't understood (Or are too long) are answered and skipped.
+//
+fn receive(
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    limit: usize,
+) -> io::Result<Result<Message, &'static str>> {
+    loop {
+        match protocol::receive_bounded(reader, limit) {
+            Ok(Some(x)) => return Ok(Ok(x)),
+            Ok(None) => return Ok(Err("hung_up")),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                let reason = "Closing the session, it has been idle for too long".to_string();
+                let _ = protocol::send(writer, &Message::Rejected(reason));
+                return Ok(Err("idle"));
+            }
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                protocol::send(writer, &Message::Rejected(e.to_string()))?;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+//
+// Swaps the game on this connection for the one put aside under
+// `token`. The game this connection had been given is dropped.
+//
+fn resume(game: &mut Game, token: String, grace: Duration, server: &Mutex<Server>) -> Message {
+    let mut server = lock(server);
+    server.expire(grace);
+    if server.connected.contains(&token) {
+        return Message::Rejected("That game is being played on another connection".into());
+    }
+    let parked = match server.parked.remove(&token) {
+        Some(x) => x,
+        None => {
+            return Message::Rejected(
+                "There is no game to resume with that token, it may have expired".into(),
+            )
+        }
+    };
+    // The clock picks up from how long the game had been going
+    // when it was put aside, see `save::load`.
+    game.state = match save::load(&parked.save, |_| {}, |_| {}, false) {
+        Ok(x) => x,
+        Err(e) => return Message::Rejected(e.to_string()),
+    };
+    server.connected.remove(&game.token);
+    server.connected.insert(token.clone());
+    game.token = token;
+    eprintln!(
+        "event=session_resumed session={} from_session={} attempts={}",
+        game.id,
+        parked.session,
+        game.state.attempts()
+    );
+    let board = game
+        .state
+        .history()
+        .iter()
+        .map(|x| (x.guess.to_vec(), x.feedback))
+        .collect();
+    Message::Resumed {
+        config: game.state.config,
+        board,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::{
+        Colour, ConfigError, Feedback, Outcome, StateBuilder, COLOURS, MAX_TRIES,
+    };
+    use std::net::SocketAddr;
+
+    //
+    // A server on a port of its own, for as long as the tests run.
+    //
+    fn start(options: Options) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, GameConfig::default(), options));
+        address
+    }
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+        token: String,
+    }
+
+    impl Client {
+        //
+        // Connects, and reads the `Hello` and `Token` which every
+        // session starts with.
+        //
+        fn connect(address: SocketAddr) -> Self {
+            let writer = TcpStream::connect(address).unwrap();
+            writer
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+            let mut client = Self {
+                reader: BufReader::new(writer.try_clone().unwrap()),
+                writer,
+                token: String::new(),
+            };
+            assert!(matches!(client.receive(), Message::Hello(_)));
+            match client.receive() {
+                Message::Token(token) => client.token = token,
+                x => panic!("expected a token, got {:?}", x),
+            }
+            client
+        }
+
+        //
+        // Asks for the game put aside under `token`. The old
+        // connection's thread has to notice it hung up before
+        // the game is put aside, so this waits for that.
+        //
+        fn resume(&mut self, token: &str) -> Message {
+            for _ in 0..100 {
+                self.send(&Message::Resume(token.to_string()));
+                match self.receive() {
+                    Message::Rejected(reason) if reason.contains("no game") => {
+                        thread::sleep(Duration::from_millis(20))
+                    }
+                    x => return x,
+                }
+            }
+            panic!("the game was never put aside");
+        }
+
+        fn send(&mut self, message: &Message) {
+            protocol::send(&mut self.writer, message).unwrap();
+        }
+
+        fn receive(&mut self) -> Message {
+            protocol::receive(&mut self.reader).unwrap().unwrap()
+        }
+    }
+
+    #[test]
+    fn settings_with_too_many_tries_are_rejected() {
+        let mut client = Client::connect(start(Options::default()));
+        client.send(&Message::Settings(GameConfig {
+            max_tries: Some(1_000_000_000_000),
+            auto_max_tries: false,
+            ..GameConfig::default()
+        }));
+        match client.receive() {
+            Message::Rejected(reason) => {
+                let error = ConfigError::TooManyTries { most: MAX_TRIES };
+                assert!(reason.contains(&error.to_string()), "{}", reason);
+            }
+            x => panic!("expected a rejection, got {:?}", x),
+        }
+        // The session, and the server, carry on
+        client.send(&Message::Stats);
+        assert!(matches!(client.receive(), Message::ServerStats { .. }));
+    }
+
+    #[test]
+    fn oversized_and_deeply_nested_messages_are_rejected() {
+        let options = Options {
+            max_message_bytes: 1 << 16,
+            ..Options::default()
+        };
+        let mut client = Client::connect(start(options));
+        writeln!(client.writer, "{}", "[".repeat(20_000)).unwrap();
+        assert!(matches!(client.receive(), Message::Rejected(_)));
+        writeln!(client.writer, "{}", "x".repeat(100_000)).unwrap();
+        match client.receive() {
+            Message::Rejected(reason) => assert!(reason.contains("longer than")),
+            x => panic!("expected a rejection, got {:?}", x),
+        }
+        client.send(&Message::Stats);
+        assert!(matches!(client.receive(), Message::ServerStats { .. }));
+    }
+
+    #[test]
+    fn a_flood_of_guesses_is_slowed_down() {
+        let options = Options {
+            message_rate: 1.0,
+            message_burst: 3.0,
+            ..Options::default()
+        };
+        let mut client = Client::connect(start(options));
+        let guess = Message::Guess(vec![
+            Colour::Red,
+            Colour::Blue,
+            Colour::White,
+            Colour::Yellow,
+        ]);
+        for _ in 0..20 {
+            client.send(&guess);
+        }
+        let replies = (0..20).map(|_| client.receive()).collect::<Vec<_>>();
+        let slowed = replies
+            .iter()
+            .filter(|x| matches!(x, Message::SlowDown(_)))
+            .count();
+        // Only the burst gets through, give or take one topped
+        // up while the guesses were arriving
+        assert!(slowed >= 16, "{:?}", replies);
+        assert!(!matches!(replies[0], Message::SlowDown(_)));
+    }
+
+    #[test]
+    fn every_kind_of_message_is_rate_limited() {
+        let options = Options {
+            message_rate: 1.0,
+            message_burst: 3.0,
+            ..Options::default()
+        };
+        let mut client = Client::connect(start(options));
+        for _ in 0..10 {
+            client.send(&Message::Settings(GameConfig::default()));
+        }
+        let slowed = (0..10)
+            .filter(|_| matches!(client.receive(), Message::SlowDown(_)))
+            .count();
+        assert!(slowed >= 6, "only {} were slowed down", slowed);
+    }
+
+    #[test]
+    fn a_full_server_turns_clients_away() {
+        let options = Options {
+            max_sessions: 2,
+            ..Options::default()
+        };
+        let address = start(options);
+        let _first = Client::connect(address);
+        let _second = Client::connect(address);
+        let third = TcpStream::connect(address).unwrap();
+        let reply = protocol::receive(&mut BufReader::new(third)).unwrap();
+        assert!(matches!(reply, Some(Message::Rejected(_))), "{:?}", reply);
+    }
+
+    #[test]
+    fn a_session_which_panics_still_stops_being_active() {
+        let server = Arc::new(Mutex::new(Server::default()));
+        lock(&server).active += 1;
+        let active = Active(Arc::clone(&server));
+        let session = thread::spawn(move || {
+            let _active = active;
+            panic!("a session going wrong");
+        });
+        assert!(session.join().is_err());
+        assert_eq!(lock(&server).active, 0);
+    }
+
+    #[test]
+    fn dozens_of_clients_play_at_once() {
+        const CLIENTS: usize = 30;
+        let address = start(Options::default());
+        let clients = (0..CLIENTS)
+            .map(|_| {
+                thread::spawn(move || {
+                    let mut client = Client::connect(address);
+                    // Every guess is different, so the game ends
+                    // by the time they run out
+                    let mut guesses = crate::mastermind::all_codes(&COLOURS[..6], 4, false);
+                    loop {
+                        client.send(&Message::Guess(guesses.next().unwrap()));
+                        match client.receive() {
+                            Message::Feedback { .. } => {}
+                            Message::GameOver { .. } => return,
+                            x => panic!("unexpected reply {:?}", x),
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for client in clients {
+            client.join().unwrap();
+        }
+        // The clients have hung up, but it can take the server a
+        // moment to notice
+        let mut client = Client::connect(address);
+        for _ in 0..100 {
+            client.send(&Message::Stats);
+            match client.receive() {
+                Message::ServerStats {
+                    active: 1,
+                    completed,
+                } => {
+                    assert_eq!(completed, CLIENTS);
+                    return;
+                }
+                Message::ServerStats { .. } | Message::SlowDown(_) => {
+                    thread::sleep(Duration::from_millis(50))
+                }
+                x => panic!("unexpected reply {:?}", x),
+            }
+        }
+        panic!("the sessions never ended");
+    }
+
+    #[test]
+    fn a_dropped_game_is_resumed_with_its_board() {
+        let address = start(Options::default());
+        let mut client = Client::connect(address);
+        let guesses = [
+            vec![Colour::Red, Colour::Blue, Colour::Green, Colour::Yellow],
+            vec![Colour::Orange, Colour::White, Colour::Red, Colour::Blue],
+        ];
+        let mut feedback = Vec::new();
+        for guess in &guesses {
+            client.send(&Message::Guess(guess.clone()));
+            match client.receive() {
+                Message::Feedback { exact, colour } => feedback.push(Feedback {
+                    exact,
+                    misplaced: colour,
+                }),
+                // The secret was guessed, so there's nothing left
+                // to resume
+                Message::GameOver { .. } => return,
+                x => panic!("unexpected reply {:?}", x),
+            }
+        }
+        let token = client.token.clone();
+        drop(client);
+
+        let mut client = Client::connect(address);
+        match client.resume(&token) {
+            Message::Resumed { config, board } => {
+                assert_eq!(config, GameConfig::default());
+                let expected = guesses.iter().cloned().zip(feedback).collect::<Vec<_>>();
+                assert_eq!(board, expected);
+            }
+            x => panic!("expected the game back, got {:?}", x),
+        }
+        // The game carries on from its third attempt, which shows
+        // in when it's lost
+        let max_tries = GameConfig::default().max_tries.unwrap();
+        let mut codes =
+            crate::mastermind::all_codes(&COLOURS[..6], 4, false).filter(|x| !guesses.contains(x));
+        for attempt in 3.. {
+            client.send(&Message::Guess(codes.next().unwrap()));
+            match client.receive() {
+                Message::Feedback { .. } => assert!(attempt < max_tries),
+                Message::GameOver {
+                    outcome: Outcome::Lost,
+                    ..
+                } => {
+                    assert_eq!(attempt, max_tries);
+                    break;
+                }
+                Message::GameOver { .. } => break,
+                x => panic!("unexpected reply {:?}", x),
+            }
+        }
+    }
+
+    #[test]
+    fn a_token_in_use_cannot_be_resumed() {
+        let address = start(Options::default());
+        let first = Client::connect(address);
+        let mut second = Client::connect(address);
+        second.send(&Message::Resume(first.token.clone()));
+        match second.receive() {
+            Message::Rejected(reason) => assert!(reason.contains("another connection")),
+            x => panic!("expected a rejection, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn a_resumed_token_cannot_be_used_twice() {
+        let address = start(Options::default());
+        let client = Client::connect(address);
+        let token = client.token.clone();
+        drop(client);
+        let mut second = Client::connect(address);
+        assert!(matches!(second.resume(&token), Message::Resumed { .. }));
+        let mut third = Client::connect(address);
+        third.send(&Message::Resume(token));
+        assert!(matches!(third.receive(), Message::Rejected(_)));
+    }
+
+    #[test]
+    fn tokens_expire_after_the_grace_period() {
+        let options = Options {
+            grace: Duration::from_millis(100),
+            ..Options::default()
+        };
+        let server = Mutex::new(Server::default());
+        lock(&server).parked.insert(
+            "old".to_string(),
+            Parked {
+                session: 0,
+                save: save::save(&StateBuilder::new().seed(1).build().unwrap()),
+                since: Instant::now() - Duration::from_secs(1),
+            },
+        );
+        let mut game = Game {
+            id: 1,
+            token: "new".to_string(),
+            state: StateBuilder::new().seed(2).build().unwrap(),
+        };
+        match resume(&mut game, "old".to_string(), options.grace, &server) {
+            Message::Rejected(reason) => assert!(reason.contains("expired")),
+            x => panic!("expected a rejection, got {:?}", x),
+        }
+        assert!(lock(&server).parked.is_empty());
+        assert_eq!(game.token, "new");
+    }
+}
@@ -0,0 +1,366 @@
+//!
+//! Working out which settings a new game should default to.
+//!
+//! Settings can come from a few places, and each one overrides
+//! the ones before it:
+//!
+//! 1. The built in defaults, `GameConfig::default()`.
+//! 2. The config file, `config.json` in the data directory.
+//! 3. Whatever the player used last time (Unless `--fresh` is
+//!    given), remembered in their profile.
+//! 4. Flags on the command line, like `--pegs 5`.
+//!
+//! The result is what the setup questions offer as their
+//! defaults. Settings given on the command line aren't asked
+//! about at all.
+//!
+//! A config file might look like this, where every setting is
+//! optional:
+//! ```text
+//! { "pegs": 5, "allow_duplicates": true, "max_tries": null }
+//! ```
+//!
+//! Where `"max_tries"` can also be `"auto"`, for a limit worked
+//! out from the other settings (See `fair.rs`).
+//!
+//! A few settings can only be picked here, and not in the setup
+//! questions or the menu. `"colour_weights"` makes some colours
+//! more likely to be in the secret than others, and
+//! `"must_include"` lists colours which are always in it:
+//! ```text
+//! { "colour_weights": { "red": 2 }, "must_include": ["green"] }
+//! ```
+//!
+//! The config file can also pick how feedback is worded, with
+//! `"feedback"` (See `wording.rs`). That isn't a setting of the
+//! game, so it's read on its own with `load_feedback_style`.
+//! The same goes for `"accessible": true`, which plays as if
+//! `--accessible` was given (See `load_accessible`).
+//!
+
+use super::json::Json;
+use super::storage;
+use super::wording::FeedbackStyle;
+use super::{GameConfig, PALETTE_SIZE};
+use std::io;
+
+///
+/// Some (Or none, or all) of the settings, as given by one of
+/// the places settings can come from. `None` means "this
+/// place doesn't say", so the setting is left as it was.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SettingsLayer {
+    pub pegs: Option<usize>,
+    pub allow_duplicates: Option<bool>,
+    ///
+    /// This is an `Option<Option<_>>` because "unlimited tries"
+    /// (`Some(None)`) is different from not saying (`None`).
+    ///
+    pub max_tries: Option<Option<usize>>,
+    ///
+    /// Whether the limit on tries should be a fair one (See
+    /// `fair.rs`). A layer which gives `max_tries` without
+    /// saying anything about this turns it off.
+    ///
+    pub auto_max_tries: Option<bool>,
+    pub max_distinct_colours: Option<Option<usize>>,
+    pub min_distinct_colours: Option<Option<usize>>,
+    pub max_per_colour: Option<Option<usize>>,
+    pub reveal_every: Option<Option<usize>>,
+    pub hardcore: Option<bool>,
+    pub memory: Option<bool>,
+    pub block_repeats: Option<bool>,
+    pub strict_input: Option<bool>,
+    pub confirm_guesses: Option<bool>,
+    pub colour_weights: Option<Option<[u32; PALETTE_SIZE]>>,
+    pub must_include: Option<[bool; PALETTE_SIZE]>,
+    pub hint_points: Option<usize>,
+    pub fresh_secrets: Option<bool>,
+    pub allow_blanks: Option<bool>,
+    pub palette_size: Option<Option<usize>>,
+}
+
+impl SettingsLayer {
+    ///
+    /// Overrides the settings in `config` which this layer
+    /// says something about.
+    ///
+    pub fn apply(&self, config: GameConfig) -> GameConfig {
+        GameConfig {
+            pegs: self.pegs.unwrap_or(config.pegs),
+            allow_duplicates: self.allow_duplicates.unwrap_or(config.allow_duplicates),
+            max_tries: self.max_tries.unwrap_or(config.max_tries),
+            auto_max_tries: match (self.auto_max_tries, self.max_tries) {
+                (Some(x), _) => x,
+                (None, Some(_)) => false,
+                (None, None) => config.auto_max_tries,
+            },
+            max_distinct_colours: self
+                .max_distinct_colours
+                .unwrap_or(config.max_distinct_colours),
+            min_distinct_colours: self
+                .min_distinct_colours
+                .unwrap_or(config.min_distinct_colours),
+            max_per_colour: self.max_per_colour.unwrap_or(config.max_per_colour),
+            reveal_every: self.reveal_every.unwrap_or(config.reveal_every),
+            hardcore: self.hardcore.unwrap_or(config.hardcore),
+            memory: self.memory.unwrap_or(config.memory),
+            block_repeats: self.block_repeats.unwrap_or(config.block_repeats),
+            strict_input: self.strict_input.unwrap_or(config.strict_input),
+            confirm_guesses: self.confirm_guesses.unwrap_or(config.confirm_guesses),
+            colour_weights: self.colour_weights.unwrap_or(config.colour_weights),
+            must_include: self.must_include.unwrap_or(config.must_include),
+            hint_points: self.hint_points.unwrap_or(config.hint_points),
+            fresh_secrets: self.fresh_secrets.unwrap_or(config.fresh_secrets),
+            allow_blanks: self.allow_blanks.unwrap_or(config.allow_blanks),
+            palette_size: self.palette_size.unwrap_or(config.palette_size),
+        }
+        .with_auto_tries()
+    }
+
+    fn from_json(json: &Json) -> Result<Self, String> {
+        // `"max_tries": "auto"` asks for a fair limit
+        let auto = json.get("max_tries").and_then(Json::as_str) == Some("auto");
+        Ok(Self {
+            pegs: match json.get("pegs") {
+                None => None,
+                Some(x) => Some(x.as_usize().ok_or_else(|| wrong("pegs"))?),
+            },
+            allow_duplicates: optional_bool(json, "allow_duplicates")?,
+            max_tries: if auto {
+                None
+            } else {
+                optional_limit(json, "max_tries")?
+            },
+            auto_max_tries: if auto { Some(true) } else { None },
+            max_distinct_colours: optional_limit(json, "max_distinct_colours")?,
+            min_distinct_colours: optional_limit(json, "min_distinct_colours")?,
+            max_per_colour: optional_limit(json, "max_per_colour")?,
+            reveal_every: optional_limit(json, "reveal_every")?,
+            hardcore: optional_bool(json, "hardcore")?,
+            memory: optional_bool(json, "memory")?,
+            block_repeats: optional_bool(json, "block_repeats")?,
+            strict_input: optional_bool(json, "strict_input")?,
+            confirm_guesses: optional_bool(json, "confirm_guesses")?,
+            colour_weights: json
+                .get("colour_weights")
+                .map(super::weights_from_json)
+                .transpose()?,
+            must_include: json
+                .get("must_include")
+                .map(super::colour_set_from_json)
+                .transpose()?,
+            hint_points: match json.get("hint_points") {
+                None => None,
+                Some(x) => Some(x.as_usize().ok_or_else(|| wrong("hint_points"))?),
+            },
+            fresh_secrets: optional_bool(json, "fresh_secrets")?,
+            allow_blanks: optional_bool(json, "allow_blanks")?,
+            palette_size: optional_limit(json, "palette_size")?,
+        })
+    }
+}
+
+///
+/// A layer which sets every setting, to whatever `config` has.
+///
+impl From<GameConfig> for SettingsLayer {
+    fn from(config: GameConfig) -> Self {
+        Self {
+            pegs: Some(config.pegs),
+            allow_duplicates: Some(config.allow_duplicates),
+            max_tries: Some(config.max_tries),
+            auto_max_tries: Some(config.auto_max_tries),
+            max_distinct_colours: Some(config.max_distinct_colours),
+            min_distinct_colours: Some(config.min_distinct_colours),
+            max_per_colour: Some(config.max_per_colour),
+            reveal_every: Some(config.reveal_every),
+            hardcore: Some(config.hardcore),
+            memory: Some(config.memory),
+            block_repeats: Some(config.block_repeats),
+            strict_input: Some(config.strict_input),
+            confirm_guesses: Some(config.confirm_guesses),
+            colour_weights: Some(config.colour_weights),
+            must_include: Some(config.must_include),
+            hint_points: Some(config.hint_points),
+            fresh_secrets: Some(config.fresh_secrets),
+            allow_blanks: Some(config.allow_blanks),
+            palette_size: Some(config.palette_size),
+        }
+    }
+}
+
+fn wrong(name: &str) -> String {
+    format!("`{}` in the config file has the wrong type", name)
+}
+
+fn optional_bool(json: &Json, key: &str) -> Result<Option<bool>, String> {
+    match json.get(key) {
+        None => Ok(None),
+        Some(x) => Ok(Some(x.as_bool().ok_or_else(|| wrong(key))?)),
+    }
+}
+
+//
+// Reads a number setting where `null` means "no limit".
+//
+fn optional_limit(json: &Json, key: &str) -> Result<Option<Option<usize>>, String> {
+    match json.get(key) {
+        None => Ok(None),
+        Some(Json::Null) => Ok(Some(None)),
+        Some(x) => Ok(Some(Some(x.as_usize().ok_or_else(|| wrong(key))?))),
+    }
+}
+
+///
+/// Reads the config file, which is allowed to not exist.
+///
+pub fn load_config_file() -> io::Result<SettingsLayer> {
+    read_config_file(|json| match json {
+        Some(json) => SettingsLayer::from_json(json),
+        None => Ok(SettingsLayer::default()),
+    })
+}
+
+///
+/// The feedback style picked in the config file, or the
+/// default one. A template which doesn't make sense is an
+/// error here, before any game is played with it.
+///
+pub fn load_feedback_style() -> io::Result<FeedbackStyle> {
+    read_config_file(|json| match json.and_then(|x| x.get("feedback")) {
+        None => Ok(FeedbackStyle::default()),
+        Some(x) => x.as_str().ok_or_else(|| wrong("feedback"))?.parse(),
+    })
+}
+
+///
+/// Whether the config file turns on accessible mode, for
+/// players who always want it.
+///
+pub fn load_accessible() -> io::Result<bool> {
+    read_config_file(|json| match json.and_then(|x| x.get("accessible")) {
+        None => Ok(false),
+        Some(x) => x.as_bool().ok_or_else(|| wrong("accessible")),
+    })
+}
+
+//
+// Reads the config file and hands it to `read`, which is given
+// `None` if there isn't one.
+//
+fn read_config_file<T>(read: impl FnOnce(Option<&Json>) -> Result<T, String>) -> io::Result<T> {
+    let path = storage::data_dir().join("config.json");
+    let text = match std::fs::read_to_string(&path) {
+        Ok(x) => x,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return read(None).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+        Err(e) => return Err(e),
+    };
+    Json::parse(&text)
+        .and_then(|x| read(Some(&x)))
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Could not read {}: {}", path.display(), e),
+            )
+        })
+}
+
+///
+/// Combines the layers in the order described at the top of
+/// this file.
+///
+pub fn resolve(
+    file: SettingsLayer,
+    last_used: Option<GameConfig>,
+    command_line: SettingsLayer,
+) -> GameConfig {
+    let config = file.apply(GameConfig::default());
+    let config = last_used
+        .map(SettingsLayer::from)
+        .unwrap_or_default()
+        .apply(config);
+    command_line.apply(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(text: &str) -> SettingsLayer {
+        SettingsLayer::from_json(&Json::parse(text).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn each_place_overrides_the_ones_before_it() {
+        let from_file = file(r#"{ "pegs": 5, "hardcore": true }"#);
+        let last_used = GameConfig {
+            pegs: 6,
+            allow_duplicates: true,
+            ..GameConfig::default()
+        };
+        let command_line = SettingsLayer {
+            pegs: Some(3),
+            ..SettingsLayer::default()
+        };
+
+        let config = resolve(from_file, None, SettingsLayer::default());
+        assert_eq!((config.pegs, config.hardcore), (5, true));
+        // The last game's settings are all of them, so nothing
+        // from the file is left
+        let config = resolve(from_file, Some(last_used), SettingsLayer::default());
+        assert_eq!((config.pegs, config.hardcore), (6, false));
+        assert!(config.allow_duplicates);
+        let config = resolve(from_file, Some(last_used), command_line);
+        assert_eq!(config.pegs, 3);
+        assert!(config.allow_duplicates);
+    }
+
+    #[test]
+    fn fresh_leaves_out_the_last_settings() {
+        // `--fresh` is resolving without them
+        let from_file = file(r#"{ "allow_duplicates": true }"#);
+        let config = resolve(from_file, None, SettingsLayer::default());
+        assert_eq!(
+            config,
+            GameConfig {
+                allow_duplicates: true,
+                ..GameConfig::default()
+            }
+            .with_auto_tries()
+        );
+    }
+
+    #[test]
+    fn a_limit_turns_off_the_fair_limit_unless_it_is_auto() {
+        let config = file(r#"{ "max_tries": 4 }"#).apply(GameConfig::default());
+        assert_eq!(config.max_tries, Some(4));
+        assert!(!config.auto_max_tries);
+        let config = file(r#"{ "max_tries": null }"#).apply(GameConfig::default());
+        assert_eq!(config.max_tries, None);
+        let six = GameConfig {
+            pegs: 6,
+            allow_duplicates: true,
+            ..config
+        };
+        let config = file(r#"{ "max_tries": "auto" }"#).apply(six);
+        assert!(config.auto_max_tries);
+        assert_eq!(
+            config.max_tries,
+            Some(crate::mastermind::fair::max_tries(six))
+        );
+    }
+
+    #[test]
+    fn a_setting_of_the_wrong_type_is_an_error() {
+        let json = Json::parse(r#"{ "pegs": "five" }"#).unwrap();
+        assert!(SettingsLayer::from_json(&json)
+            .unwrap_err()
+            .contains("pegs"));
+        let json = Json::parse(r#"{ "hardcore": 1 }"#).unwrap();
+        assert!(SettingsLayer::from_json(&json).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//!
+//! `mastermind simulate`, where the solver plays whole sessions
+//! by itself through the same loop a person plays through.
+//!
+//! `--sessions N` plays more than one, and `--output` picks how
+//! the games are shown, which is not at all by default.
+//!
+//! `--cache` has the solver remember the feedback it has worked
+//! out before (See `cache.rs`), and `--verbose` adds how long the
+//! run took and how well the cache did to the report, which is
+//! how to tell whether the cache is worth it for some settings.
+//!
+
+use super::cache::FeedbackCache;
+use super::frontend::{self, Frontend, Output, PlayerInput, Session};
+use super::render::{self, Renderer};
+use super::settings::{self, SettingsLayer};
+use super::wording::FeedbackStyle;
+use super::{score_guess, solver, GameConfig, GameSummary, Outcome};
+use std::error::Error;
+use std::io;
+use std::time::Instant;
+
+//
+// How many pairs `--cache` remembers, about 16 bytes each.
+//
+const CACHE_CAPACITY: usize = 1 << 20;
+
+///
+/// Plays as the solver, and keeps what happened for the report
+/// at the end.
+///
+struct SolverFrontend {
+    renderer: Box<dyn Renderer>,
+    cache: Option<FeedbackCache>,
+    won: usize,
+    lost: usize,
+    attempts: usize,
+}
+
+impl Frontend for SolverFrontend {
+    fn configure(&mut self, defaults: GameConfig) -> io::Result<GameConfig> {
+        Ok(defaults)
+    }
+
+    fn next_input(&mut self, summary: &GameSummary) -> io::Result<PlayerInput> {
+        // A code is typed in as the full name of each colour, a
+        // blank's first letter would be read as blue.
+        let guess = match &self.cache {
+            Some(cache) => solver::next_guess_with(summary, |a, b| cache.score(a, b)),
+            None => solver::next_guess_with(summary, score_guess),
+        };
+        Ok(match guess {
+            Some(code) => {
+                PlayerInput::Guess(code.iter().map(|x| x.name()).collect::<Vec<_>>().join(" "))
+            }
+            None => PlayerInput::Forfeit,
+        })
+    }
+
+    fn present(&mut self, output: &Output) -> io::Result<()> {
+        if let Output::GameOver(record) = output {
+            match record.outcome {
+                Outcome::Won => self.won += 1,
+                Outcome::Lost => self.lost += 1,
+            }
+            self.attempts += record.attempts;
+        }
+        self.renderer.render(output)
+    }
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut sessions = 1;
+    let mut renderer: Box<dyn Renderer> = Box::new(render::SilentRenderer);
+    let mut cache = None;
+    let mut verbose = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--sessions" => {
+                sessions = args
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .ok_or("`--sessions` needs a number")?
+            }
+            "--output" => {
+                renderer = args
+                    .next()
+                    .and_then(|x| render::by_name(&x, FeedbackStyle::default()))
+                    .ok_or("`--output` needs `text`, `accessible`, `json` or `silent`")?
+            }
+            "--cache" => cache = Some(FeedbackCache::new(CACHE_CAPACITY)),
+            "--verbose" => verbose = true,
+            _ => return Err(format!("Unknown argument `{}`", arg).into()),
+        }
+    }
+    let mut player = SolverFrontend {
+        renderer,
+        cache,
+        won: 0,
+        lost: 0,
+        attempts: 0,
+    };
+    // Like a relay, this uses the settings from the config file.
+    let defaults = settings::resolve(
+        settings::load_config_file()?,
+        None,
+        SettingsLayer::default(),
+    );
+    // Nothing is recorded or saved, these aren't a player's games.
+    let session = Session::default();
+    let started = Instant::now();
+    for _ in 0..sessions {
+        let mut state = frontend::new_game(&mut player, defaults)?;
+        frontend::play(&mut player, &mut state, &session)?;
+    }
+    let played = player.won + player.lost;
+    println!(
+        "The solver won {} of {} game(s), making {:.1} guesses per game on average",
+        player.won,
+        played,
+        player.attempts as f64 / played.max(1) as f64
+    );
+    if verbose {
+        println!("Took {:.2}s", started.elapsed().as_secs_f64());
+        match &player.cache {
+            Some(cache) => {
+                let looked_up = cache.hits() + cache.misses();
+                println!(
+                    "Feedback cache: {} hit(s) and {} miss(es), {:.1}% of {} lookups",
+                    cache.hits(),
+                    cache.misses(),
+                    100.0 * cache.hits() as f64 / looked_up.max(1) as f64,
+                    looked_up
+                );
+            }
+            None => println!("Feedback cache: off"),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//!
+//! A computer player, which guesses codes until it finds the
+//! secret.
+//!
+//! It uses the simplest strategy which still plays well: keep
+//! a set of every code the secret could still be, and always
+//! guess the first one. After each guess, throw out every code
+//! which wouldn't have given the same feedback if it were the
+//! secret. This usually finds a four peg code in five or six
+//! guesses.
+//!
+
+use super::{all_codes, score_guess, Code, Colour, Feedback, GameConfig, GameSummary, Symbol};
+use std::collections::{BTreeSet, HashSet};
+
+///
+/// Every code which could be the secret for a game with
+/// `config`, in a fixed order. The pegs can be anything (See
+/// `symbol.rs`), which is usually worked out from where the
+/// codes are used, but can also be given like
+/// `candidates::<Digit>(config)`.
+///
+pub fn candidates<P: Symbol>(config: GameConfig) -> Vec<Vec<P>> {
+    let palette = config.symbols::<P>();
+    let mut codes = all_codes(&palette, config.pegs, config.allow_duplicates)
+        .filter(|code| config.over_limit(code).is_none() && config.enough_colours(code))
+        .collect::<Vec<_>>();
+    // The player knows how many colours are in use, so the
+    // solver does too.
+    if let Some(colours) = config.max_distinct_colours {
+        codes.retain(|code| {
+            let distinct = code.iter().collect::<HashSet<_>>().len();
+            if config.allow_duplicates && colours <= config.pegs {
+                distinct == colours
+            } else {
+                distinct <= colours
+            }
+        });
+    }
+    codes
+}
+
+///
+/// Plays against `secret` without any output, and returns the
+/// number of guesses it took, including the winning one. This
+/// is `None` if `secret` couldn't have come from `config`.
+///
+pub fn solve(secret: &[Colour], config: GameConfig) -> Option<usize> {
+    // A set, so that each code is only kept once. Codes are
+    // ordered the way `candidates` gives them (See `Code`), so
+    // the first in the set is the first in the list too.
+    let mut remaining: BTreeSet<Code> = candidates(config).into_iter().map(Code::from).collect();
+    let mut guesses = 0;
+    loop {
+        // The secret itself always survives, so we only run out
+        // if it was never one of the candidates.
+        let guess = remaining.iter().next()?.clone();
+        guesses += 1;
+        let feedback = score_guess(secret, &guess);
+        if feedback.is_win(secret.len()) {
+            return Some(guesses);
+        }
+        remaining.retain(|code| score_guess(code, &guess) == feedback);
+    }
+}
+
+///
+/// The guess the solver would make next in the game described
+/// by `summary`, which is the first code agreeing with all of
+/// the feedback (And revealed positions) so far.
+///
+pub fn next_guess(summary: &GameSummary) -> Option<Vec<Colour>> {
+    next_guess_with(summary, score_guess)
+}
+
+///
+/// The same as `next_guess`, but scoring with `score` (Like
+/// `FeedbackCache::score`) instead of `score_guess`.
+///
+pub fn next_guess_with(
+    summary: &GameSummary,
+    score: impl Fn(&[Colour], &[Colour]) -> Feedback,
+) -> Option<Vec<Colour>> {
+    candidates(summary.settings).into_iter().find(|code| {
+        let scored = summary
+            .guesses
+            .iter()
+            .all(|(guess, feedback)| feedback.is_none_or(|x| score(code, guess) == x));
+        scored
+            && summary
+                .revealed
+                .iter()
+                .all(|&(position, colour)| code[position] == colour)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::COLOURS;
+
+    #[test]
+    fn the_set_of_codes_left_is_gone_through_in_order() {
+        let config = GameConfig {
+            pegs: 3,
+            allow_duplicates: true,
+            palette_size: Some(4),
+            ..GameConfig::default()
+        };
+        for secret in all_codes(&COLOURS[..4], 3, true) {
+            // The same game, keeping the codes in a list
+            let mut remaining = candidates::<Colour>(config);
+            let mut guesses = 0;
+            loop {
+                let guess = remaining[0].clone();
+                guesses += 1;
+                let feedback = score_guess(&secret, &guess);
+                if feedback.is_win(3) {
+                    break;
+                }
+                remaining.retain(|code| score_guess(code, &guess) == feedback);
+            }
+            assert_eq!(solve(&secret, config), Some(guesses), "{:?}", secret);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//!
+//! Points for winning quickly, both in tries and in time. A
+//! win is worth
+//!
+//! ```text
+//! 100 × (tries left over) + 500 × ½^(seconds / 30)
+//! ```
+//!
+//! rounded down. So each try that wasn't needed is worth 100,
+//! and the time part starts at 500 and halves every 30 seconds
+//! the game took. A game with unlimited tries has no tries to
+//! leave over, so only the time part counts.
+//!
+//! Losing is worth nothing, and so is winning with help (Any
+//! positions revealed by `reveal_every`): a bonus which came
+//! for free with the settings would say more about them than
+//! about the player. Hints are different, since the player
+//! chose to pay for them (See `hints.rs`), so each hint point
+//! spent just takes `HINT_PENALTY` off the bonus.
+//!
+
+use std::time::Duration;
+
+///
+/// Points for each try left over at the end of a win.
+///
+pub const POINTS_PER_TRY: usize = 100;
+
+///
+/// The time part of the bonus for a win in no time at all.
+///
+pub const TIME_POINTS: f64 = 500.0;
+
+///
+/// How long the time part of the bonus takes to halve.
+///
+pub const HALF_LIFE: Duration = Duration::from_secs(30);
+
+///
+/// What each hint point spent takes off the bonus.
+///
+pub const HINT_PENALTY: usize = 50;
+
+///
+/// The bonus for a game which was won with `tries_left` tries
+/// to spare, in `duration`, with `reveals` positions of the
+/// secret revealed along the way and `hint_points` spent on
+/// hints.
+///
+/// The time part is left out for games which weren't timed
+/// (`duration` is `None`), like those from older saves.
+///
+pub fn bonus(
+    tries_left: Option<usize>,
+    duration: Option<Duration>,
+    reveals: usize,
+    hint_points: usize,
+) -> usize {
+    if reveals > 0 {
+        return 0;
+    }
+    let tries = tries_left.unwrap_or(0) * POINTS_PER_TRY;
+    let time = duration.map_or(0.0, |x| {
+        TIME_POINTS * 0.5f64.powf(x.as_secs_f64() / HALF_LIFE.as_secs_f64())
+    });
+    (tries + time as usize).saturating_sub(hint_points * HINT_PENALTY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_try_left_is_worth_the_same() {
+        assert_eq!(bonus(Some(0), None, 0, 0), 0);
+        assert_eq!(bonus(Some(1), None, 0, 0), POINTS_PER_TRY);
+        assert_eq!(bonus(Some(7), None, 0, 0), 7 * POINTS_PER_TRY);
+        // Unlimited tries have none left over
+        assert_eq!(bonus(None, None, 0, 0), 0);
+    }
+
+    #[test]
+    fn the_time_part_halves_every_half_life() {
+        let after = |x| bonus(None, Some(x), 0, 0);
+        assert_eq!(after(Duration::from_secs(0)), 500);
+        assert_eq!(after(HALF_LIFE), 250);
+        assert_eq!(after(HALF_LIFE * 2), 125);
+        assert_eq!(after(HALF_LIFE * 20), 0);
+        assert_eq!(bonus(Some(3), Some(HALF_LIFE), 0, 0), 300 + 250);
+    }
+
+    #[test]
+    fn any_reveal_means_no_bonus() {
+        assert_eq!(bonus(Some(7), Some(Duration::from_secs(1)), 1, 0), 0);
+    }
+
+    #[test]
+    fn hints_are_taken_off_the_bonus() {
+        assert_eq!(bonus(Some(3), None, 0, 2), 300 - 2 * HINT_PENALTY);
+        // It never goes below nothing
+        assert_eq!(bonus(Some(1), None, 0, 10), 0);
+    }
+}
@@ -0,0 +1,149 @@
+//!
+//! Where the game keeps its files between runs.
+//!
+//! Everything lives under one data directory (See `data_dir`),
+//! laid out like this:
+//!
+//! ```text
+//! <data dir>/
+//!     slots/
+//!         default/
+//!             autosave.json
+//!             autosave.json.bak
+//!         <another slot>/
+//!             ...
+//! ```
+//!
+//! Each save slot is its own directory, so playing in one slot
+//! never touches the games saved in another.
+//!
+
+use std::io;
+use std::path::PathBuf;
+
+///
+/// The slot used when no `--save-slot` is given.
+///
+pub const DEFAULT_SLOT: &str = "default";
+
+///
+/// The directory the game stores its files in.
+///
+/// Setting the `MASTERMIND_DATA_DIR` environment variable
+/// overrides this, which is handy for trying things out
+/// without touching your real saves. Otherwise this is the
+/// usual place for the platform:
+/// - Windows: `%APPDATA%\mastermind`
+/// - macOS: `~/Library/Application Support/mastermind`
+/// - Elsewhere: `$XDG_DATA_HOME/mastermind`, or
+///   `~/.local/share/mastermind`
+///
+pub fn data_dir() -> PathBuf {
+    // `var_os` gives back `None` if the variable isn't set.
+    let var = |name| std::env::var_os(name).map(PathBuf::from);
+    if let Some(dir) = var("MASTERMIND_DATA_DIR") {
+        return dir;
+    }
+    let base = if cfg!(windows) {
+        var("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|x| x.join("Library").join("Application Support"))
+    } else {
+        var("XDG_DATA_HOME").or_else(|| var("HOME").map(|x| x.join(".local").join("share")))
+    };
+    // With nowhere better to go, use the current directory.
+    base.unwrap_or_else(|| PathBuf::from("."))
+        .join("mastermind")
+}
+
+///
+/// Checks that `name` is usable as a slot name. Slot names
+/// become directory names, so we only allow characters which
+/// are safe in a file name on every platform.
+///
+pub fn validate_slot_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > 32 {
+        return Err("Save slot names must be between 1 and 32 characters long".into());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid save slot name `{}`, only letters, numbers, `-` and `_` are allowed",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn slots_dir() -> PathBuf {
+    data_dir().join("slots")
+}
+
+///
+/// The autosave file for the slot `name`, creating the slot's
+/// directory if it doesn't exist yet.
+///
+pub fn autosave_path(name: &str) -> io::Result<PathBuf> {
+    let dir = slots_dir().join(name);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join("autosave.json"))
+}
+
+///
+/// The names of every slot, in alphabetical order.
+///
+pub fn list_slots() -> io::Result<Vec<String>> {
+    let entries = match std::fs::read_dir(slots_dir()) {
+        Ok(x) => x,
+        // No slots directory just means nothing was saved yet
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            if let Some(name) = entry.file_name().to_str() {
+                if validate_slot_name(name).is_ok() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+///
+/// Deletes the slot `name` and everything saved in it.
+///
+pub fn delete_slot(name: &str) -> io::Result<()> {
+    std::fs::remove_dir_all(slots_dir().join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_names_are_safe_file_names() {
+        for name in &["default", "kid-1", "Saturday_game", &"x".repeat(32)] {
+            assert_eq!(validate_slot_name(name), Ok(()), "{}", name);
+        }
+        let long = "x".repeat(33);
+        for name in &[
+            "",
+            "../up",
+            "a/b",
+            "a\\b",
+            "with space",
+            "dot.ted",
+            "ünï",
+            &long,
+        ] {
+            assert!(validate_slot_name(name).is_err(), "{}", name);
+        }
+    }
+}
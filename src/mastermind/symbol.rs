@@ -0,0 +1,227 @@
+//!
+//! `Symbol`, for whatever the pegs of a code are made of.
+//!
+//! Scoring a guess, picking a secret and reading a line of input
+//! only need to compare pegs, copy them, and know which there are
+//! to choose from. So `State` (And everything it hands around,
+//! like a `Guess`) has a type parameter for its pegs, which is
+//! `Colour` unless it's given:
+//! ```
+//! let colours: State = StateBuilder::new().build()?;
+//! let digits: State<Digit> = StateBuilder::for_symbols().build()?;
+//! ```
+//! This is like a generic class in Java, except that the
+//! parameter has a default, so everything which was written for
+//! colours still means colours without having to say so.
+//!
+//! A few things are still only for colours, since they need to
+//! know what each colour is: hints, saves, and the settings
+//! which are written as colour names in the config file.
+//!
+
+use super::{Colour, COLOURS};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+///
+/// Something a peg can be. `Copy`, `Eq` and `Hash` are what let
+/// pegs be handed around by value, compared and put in sets, and
+/// `Debug` is how they're written in messages.
+///
+/// Only `all` has to be written. The rest work anything out they
+/// need from it unless they're overridden.
+///
+pub trait Symbol: Copy + Eq + Hash + Debug + 'static {
+    ///
+    /// Every symbol the secret can be made from, in order, like
+    /// `COLOURS`. Settings which say something about each symbol
+    /// (Like `GameConfig::colour_weights`) go by where it is in
+    /// here, see `index`.
+    ///
+    fn all() -> &'static [Self];
+
+    ///
+    /// The symbol for an empty hole, which isn't in `all`, if
+    /// these pegs can be left blank (See
+    /// `GameConfig::allow_blanks`).
+    ///
+    fn blank() -> Option<Self> {
+        None
+    }
+
+    ///
+    /// What one of these is called in messages, like "colour".
+    ///
+    fn noun() -> &'static str {
+        "symbol"
+    }
+
+    ///
+    /// The lowercase name of the symbol, which the player can
+    /// type to enter it.
+    ///
+    fn name(self) -> String {
+        format!("{:?}", self).to_lowercase()
+    }
+
+    ///
+    /// The symbol typed as this one character, if there is one.
+    /// Unless it's overridden this is the symbol whose name
+    /// starts with it, as long as only one does.
+    ///
+    fn from_letter(letter: char) -> Option<Self> {
+        let letter = letter.to_lowercase().next()?;
+        let blank = Self::blank();
+        let mut matches = Self::all()
+            .iter()
+            .chain(blank.as_ref())
+            .filter(|x| x.name().starts_with(letter));
+        match (matches.next(), matches.next()) {
+            (Some(&x), None) => Some(x),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Where this is in `all`, or `None` for a blank.
+    ///
+    fn index(self) -> Option<usize> {
+        Self::all().iter().position(|&x| x == self)
+    }
+}
+
+impl Symbol for Colour {
+    fn all() -> &'static [Self] {
+        COLOURS
+    }
+
+    fn blank() -> Option<Self> {
+        Some(Colour::Blank)
+    }
+
+    fn noun() -> &'static str {
+        "colour"
+    }
+
+    fn name(self) -> String {
+        Colour::name(self).to_string()
+    }
+
+    fn from_letter(letter: char) -> Option<Self> {
+        letter.to_string().parse().ok()
+    }
+
+    fn index(self) -> Option<usize> {
+        Some(self as usize).filter(|_| self != Colour::Blank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::{parse_symbols, score_guess, solver, GameConfig, State, StateBuilder};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    enum Suit {
+        Hearts,
+        Diamonds,
+        Clubs,
+        Spades,
+    }
+
+    impl Symbol for Suit {
+        fn all() -> &'static [Self] {
+            &[Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades]
+        }
+    }
+
+    impl Symbol for u8 {
+        fn all() -> &'static [Self] {
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+        }
+    }
+
+    fn config(palette: usize) -> GameConfig {
+        GameConfig {
+            pegs: 3,
+            palette_size: Some(palette),
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn defaults_come_from_all() {
+        assert_eq!(Suit::Clubs.name(), "clubs");
+        assert_eq!(Suit::from_letter('s'), Some(Suit::Spades));
+        assert_eq!(Suit::from_letter('D'), Some(Suit::Diamonds));
+        assert_eq!(Suit::from_letter('x'), None);
+        assert_eq!(Suit::Clubs.index(), Some(2));
+        assert_eq!(Suit::blank(), None);
+        assert_eq!(Suit::noun(), "symbol");
+        assert_eq!(7u8.name(), "7");
+        assert_eq!(u8::from_letter('7'), Some(7));
+        assert_eq!(42u8.index(), None);
+    }
+
+    #[test]
+    fn colours_keep_their_own_names() {
+        assert_eq!(Colour::from_letter('g'), Some(Colour::Green));
+        assert_eq!(Colour::from_letter('p'), None);
+        assert_eq!(Colour::blank(), Some(Colour::Blank));
+        assert_eq!(Colour::Blank.index(), None);
+        assert_eq!(Colour::White.index(), Some(2));
+        assert_eq!(Colour::noun(), "colour");
+    }
+
+    #[test]
+    fn parses_other_symbols() {
+        assert_eq!(
+            parse_symbols::<Suit>("hearts c sp"),
+            Ok(vec![Suit::Hearts, Suit::Clubs, Suit::Spades])
+        );
+        assert_eq!(parse_symbols::<u8>("3 0 9"), Ok(vec![3, 0, 9]));
+        assert!(parse_symbols::<Suit>("hearts x").is_err());
+    }
+
+    #[test]
+    fn secrets_come_from_the_palette() {
+        for seed in 0..50 {
+            let suits: State<Suit> = StateBuilder::for_symbols()
+                .config(config(4))
+                .seed(seed)
+                .build()
+                .unwrap();
+            let secret = suits.reveal_secret();
+            assert_eq!(secret.len(), 3);
+            assert!(secret.iter().all(|x| Suit::all().contains(x)));
+            let digits: State<u8> = StateBuilder::for_symbols()
+                .config(config(10))
+                .seed(seed)
+                .build()
+                .unwrap();
+            assert!(digits.reveal_secret().iter().all(|&x| x < 10));
+        }
+    }
+
+    #[test]
+    fn plays_a_game_of_suits() {
+        let mut state: State<Suit> = StateBuilder::for_symbols()
+            .config(config(4))
+            .build()
+            .unwrap();
+        state.set_secret(vec![Suit::Hearts, Suit::Clubs, Suit::Spades]);
+        state.push_string_input("c h s").unwrap();
+        assert_eq!(
+            state.history()[0].feedback,
+            score_guess(
+                &[Suit::Hearts, Suit::Clubs, Suit::Spades],
+                &[Suit::Clubs, Suit::Hearts, Suit::Spades]
+            )
+        );
+        assert_eq!(state.history()[0].feedback.exact, 1);
+        assert_eq!(state.history()[0].feedback.misplaced, 2);
+        state.push_string_input("h c s").unwrap();
+        assert_eq!(state.games().len(), 1);
+        assert_eq!(solver::candidates::<Suit>(config(4)).len(), 4 * 3 * 2);
+    }
+}
@@ -0,0 +1,380 @@
+//!
+//! Small helpers for doing more with the terminal than printing
+//! lines, like clearing the screen.
+//!
+//! These use ANSI escape codes, which are sequences starting
+//! with the "escape" character (`\x1b`) which the terminal
+//! treats as instructions rather than text. Nearly every
+//! terminal understands them, but if our output is going to a
+//! file or a pipe they'd just end up in there as garbage.
+//!
+
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+
+///
+/// Whether we can clear the screen, meaning our output is going
+/// straight to a terminal which isn't marked as "dumb".
+///
+pub fn supports_clearing() -> bool {
+    io::stdout().is_terminal() && std::env::var("TERM").map_or(true, |x| x != "dumb")
+}
+
+///
+/// Whether what's typed at the keyboard shows up in our
+/// output, so that it's on screen for `erase_previous_line`.
+///
+pub fn echoes_input() -> bool {
+    io::stdin().is_terminal() && supports_clearing()
+}
+
+///
+/// How many colours the terminal can show text in.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColourSupport {
+    ///
+    /// No colours, because our output isn't going to a terminal
+    /// (Or the player asked for none with `NO_COLOR`).
+    ///
+    #[default]
+    None,
+    ///
+    /// The eight colours every terminal has.
+    ///
+    Basic,
+    ///
+    /// A fixed palette of 256 colours.
+    ///
+    Extended,
+    ///
+    /// Any colour at all, given as red, green and blue.
+    ///
+    TrueColour,
+}
+
+///
+/// Works out how many colours the terminal can show. There's
+/// no way to ask the terminal itself, so this goes by what it
+/// says about itself in the environment: `COLORTERM` is set to
+/// `truecolor` by terminals which can show any colour, and a
+/// `TERM` like `xterm-256color` has the 256 colour palette.
+///
+pub fn colour_support() -> ColourSupport {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    if !supports_clearing() || std::env::var_os("NO_COLOR").is_some() {
+        ColourSupport::None
+    } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+        ColourSupport::TrueColour
+    } else if var("TERM").contains("256color") {
+        ColourSupport::Extended
+    } else {
+        ColourSupport::Basic
+    }
+}
+
+///
+/// `text` in the colour closest to `rgb` which `support` can
+/// show.
+///
+pub fn paint(text: &str, rgb: (u8, u8, u8), support: ColourSupport) -> String {
+    let (r, g, b) = rgb;
+    // `38;...m` picks the colour of the text after it, and `0m`
+    // puts everything back to normal.
+    match support {
+        ColourSupport::None => text.to_string(),
+        ColourSupport::Basic => format!("\x1b[{}m{}\x1b[0m", 30 + nearest_basic(rgb), text),
+        ColourSupport::Extended => format!("\x1b[38;5;{}m{}\x1b[0m", nearest_extended(rgb), text),
+        ColourSupport::TrueColour => format!("\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, text),
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+//
+// The eight basic colours, numbered the way the terminal numbers
+// them, are black, red, green, yellow, blue, magenta, cyan and
+// white. Terminals show them however they like, these are what
+// xterm shows by default.
+//
+const BASIC: [(u8, u8, u8); 8] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+];
+
+///
+/// The basic colour (From 0 to 7) closest to `rgb`.
+///
+pub fn nearest_basic(rgb: (u8, u8, u8)) -> u8 {
+    (0..BASIC.len())
+        .min_by_key(|&i| distance(BASIC[i], rgb))
+        .unwrap_or(7) as u8
+}
+
+///
+/// The colour from the 256 colour palette closest to `rgb`.
+///
+/// The first 16 are the basic colours (Twice, once brighter),
+/// which are left out since terminals change them. Then come
+/// 216 colours with every mix of six levels of red, green and
+/// blue, numbered 16 + 36r + 6g + b, and last are 24 greys.
+///
+pub fn nearest_extended(rgb: (u8, u8, u8)) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |x: u8| {
+        (0..LEVELS.len())
+            .min_by_key(|&i| (LEVELS[i] as i32 - x as i32).abs())
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (16 + 36 * r + 6 * g + b) as u8;
+    let cube_rgb = (LEVELS[r], LEVELS[g], LEVELS[b]);
+    // The greys go from 8 up to 238 in steps of 10
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let grey = 8 + 10 * step;
+    if distance((grey, grey, grey), rgb) < distance(cube_rgb, rgb) {
+        232 + step
+    } else {
+        cube
+    }
+}
+
+///
+/// Clears the line above the cursor, and moves the cursor up
+/// to it. After reading a line, this is the line that was typed.
+///
+pub fn erase_previous_line() -> io::Result<()> {
+    // `1A` moves the cursor up a line, and `2K` clears the line
+    // it's on.
+    print!("\x1b[1A\x1b[2K");
+    io::stdout().flush()
+}
+
+///
+/// Clears the screen and the scrollback above it, leaving the
+/// cursor at the top left.
+///
+pub fn clear_screen() -> io::Result<()> {
+    // `2J` clears the screen, `3J` clears the scrollback and `H`
+    // moves the cursor to the top left.
+    print!("\x1b[2J\x1b[3J\x1b[H");
+    io::stdout().flush()
+}
+
+///
+/// While one of these is alive, the terminal is in "raw mode":
+/// each key is handed to us as soon as it's pressed, instead of
+/// once enter is pressed, and isn't printed to the screen. When
+/// it's dropped (Goes out of scope), the terminal goes back to
+/// how it was before.
+///
+/// This is what Java would do with a `try`/`finally` block, but
+/// here the `Drop` implementation plays the part of `finally`.
+///
+pub struct RawMode {
+    #[cfg(unix)]
+    original: libc::termios,
+}
+
+impl RawMode {
+    ///
+    /// Switches the terminal to raw mode, if stdin is a terminal
+    /// we know how to do that for.
+    ///
+    #[cfg(unix)]
+    pub fn enable() -> Option<Self> {
+        if !io::stdin().is_terminal() {
+            return None;
+        }
+        // `unsafe` is needed to call C functions, since rust can't
+        // check what they do. `zeroed` makes a `termios` with every
+        // field zero, which `tcgetattr` then fills in.
+        unsafe {
+            let mut original = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return None;
+            }
+            let mut raw = original;
+            // `ICANON` waits for enter, `ECHO` prints what's typed.
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return None;
+            }
+            Some(Self { original })
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn enable() -> Option<Self> {
+        None
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+///
+/// Reads a single key press (Or rather, a single byte of it) in
+/// raw mode. `None` means stdin has been closed.
+///
+pub fn read_key() -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    // `io::stdin()` keeps its own buffer, which would hide the
+    // rest of an escape sequence from `input_waiting`. Reading
+    // straight from the file descriptor avoids that.
+    #[cfg(unix)]
+    let read = match unsafe { libc::read(libc::STDIN_FILENO, byte.as_mut_ptr().cast(), 1) } {
+        x if x < 0 => return Err(io::Error::last_os_error()),
+        x => x as usize,
+    };
+    #[cfg(not(unix))]
+    let read = io::Read::read(&mut io::stdin(), &mut byte)?;
+    match read {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+///
+/// The keys we care about in raw mode.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Escape,
+    Char(char),
+}
+
+///
+/// Reads a key press in raw mode, working out which key it was.
+/// `None` means stdin has been closed.
+///
+/// Most keys are a single byte, but the arrow keys send an
+/// escape sequence like `\x1b[A`. The escape key on its own
+/// sends just `\x1b`, so if nothing follows quickly, that's
+/// what it was.
+///
+pub fn read_key_event() -> io::Result<Option<Key>> {
+    let key = match read_key()? {
+        None => return Ok(None),
+        Some(b'\r') | Some(b'\n') => Key::Enter,
+        Some(0x1b) => {
+            if !input_waiting(Duration::from_millis(50)) {
+                return Ok(Some(Key::Escape));
+            }
+            match (read_key()?, read_key()?) {
+                (Some(b'['), Some(b'A')) => Key::Up,
+                (Some(b'['), Some(b'B')) => Key::Down,
+                (Some(b'['), Some(b'C')) => Key::Right,
+                (Some(b'['), Some(b'D')) => Key::Left,
+                _ => Key::Escape,
+            }
+        }
+        Some(x) => Key::Char(x as char),
+    };
+    Ok(Some(key))
+}
+
+//
+// Waits up to `timeout` for there to be something to read.
+//
+#[cfg(unix)]
+fn input_waiting(timeout: Duration) -> bool {
+    let mut poll = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    unsafe { libc::poll(&mut poll, 1, timeout.as_millis() as libc::c_int) > 0 }
+}
+
+#[cfg(not(unix))]
+fn input_waiting(_timeout: Duration) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mastermind::Colour;
+
+    #[test]
+    fn the_basic_colours_are_their_own_nearest() {
+        for (idx, &rgb) in BASIC.iter().enumerate() {
+            assert_eq!(nearest_basic(rgb), idx as u8);
+        }
+    }
+
+    #[test]
+    fn pegs_fall_back_to_a_sensible_basic_colour() {
+        let basic = |colour: Colour| nearest_basic(colour.rgb());
+        assert_eq!(basic(Colour::Red), 1);
+        assert_eq!(basic(Colour::Green), 2);
+        assert_eq!(basic(Colour::Yellow), 3);
+        assert_eq!(basic(Colour::Blue), 4);
+        assert_eq!(basic(Colour::Purple), 5);
+        assert_eq!(basic(Colour::White), 7);
+    }
+
+    #[test]
+    fn the_corners_of_the_cube_are_found() {
+        assert_eq!(nearest_extended((0, 0, 0)), 16);
+        assert_eq!(nearest_extended((255, 0, 0)), 196);
+        assert_eq!(nearest_extended((0, 255, 0)), 46);
+        assert_eq!(nearest_extended((0, 0, 255)), 21);
+        assert_eq!(nearest_extended((255, 255, 255)), 231);
+    }
+
+    #[test]
+    fn greys_use_the_grey_ramp() {
+        assert_eq!(nearest_extended((128, 128, 128)), 244);
+        assert_eq!(nearest_extended((8, 8, 8)), 232);
+        assert_eq!(nearest_extended((238, 238, 238)), 255);
+        // Close to grey, but not quite
+        assert_eq!(nearest_extended((130, 128, 126)), 244);
+    }
+
+    #[test]
+    fn pegs_fall_back_to_the_nearest_extended_colour() {
+        assert_eq!(nearest_extended(Colour::Red.rgb()), 160);
+        assert_eq!(nearest_extended(Colour::Orange.rgb()), 208);
+        assert_eq!(nearest_extended(Colour::Yellow.rgb()), 220);
+    }
+
+    #[test]
+    fn paint_matches_what_the_terminal_supports() {
+        let rgb = (245, 140, 20);
+        assert_eq!(paint("x", rgb, ColourSupport::None), "x");
+        assert_eq!(paint("x", rgb, ColourSupport::Basic), "\x1b[33mx\x1b[0m");
+        assert_eq!(
+            paint("x", rgb, ColourSupport::Extended),
+            "\x1b[38;5;208mx\x1b[0m"
+        );
+        assert_eq!(
+            paint("x", rgb, ColourSupport::TrueColour),
+            "\x1b[38;2;245;140;20mx\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//!
+//! A guided first game, started with `mastermind tutorial` or
+//! offered to new players.
+//!
+//! It's a normal `State` underneath, just with a small secret
+//! which is always the same, so that the commentary can talk
+//! about what's happening. Tutorial games aren't recorded
+//! anywhere, and typing `quit` leaves at any point.
+//!
+
+use super::{Colour, Feedback, GameConfig, GameEvent, SilentOutput, State};
+use std::error::Error;
+use std::io::Write;
+
+///
+/// The code the tutorial is always played against.
+///
+const SECRET: [Colour; 3] = [Colour::Green, Colour::Red, Colour::Blue];
+
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let config = GameConfig {
+        pegs: SECRET.len(),
+        allow_duplicates: false,
+        max_tries: None,
+        auto_max_tries: false,
+        ..GameConfig::default()
+    };
+    // Nothing is printed by the state itself, so that all of
+    // the talking is done here.
+    let mut state = State::new(config, |_| {}, |_| {}, SilentOutput)?;
+    state.set_secret(SECRET.to_vec());
+
+    println!(
+        r#"
+Welcome to Mastermind! I've picked a secret code of 3 pegs,
+with no colour used twice, and your job is to work out what
+it is. The colours are Red, Blue, White, Yellow, Green and
+Orange, and you type a guess as the first letter of each
+colour, for example "rwy" for Red, White, Yellow.
+
+Type "quit" at any time to leave the tutorial.
+"#
+    );
+    let mut input = String::new();
+    let mut last = None;
+    loop {
+        let guesses = state.attempts();
+        match (guesses, last) {
+            (0, _) => println!("For a first guess, anything goes. Try three different colours."),
+            (
+                _,
+                Some(Feedback {
+                    exact: 0,
+                    misplaced: 0,
+                }),
+            ) => println!(
+                "None of those colours are in the code at all, so you can \
+                 leave all three of them out from now on."
+            ),
+            (1, Some(_)) => println!(
+                "A good second guess keeps some of what you learned. Try \
+                 keeping one or two colours, and swapping the others for \
+                 colours you haven't tried yet."
+            ),
+            (_, Some(x)) if x.colours_found() == SECRET.len() => println!(
+                "You've found all three colours! Now it's only a matter of \
+                 putting them in the right order."
+            ),
+            _ => println!(
+                "Compare this guess with the earlier ones: what changed, \
+                 and how did the feedback change with it?"
+            ),
+        }
+        print!("Guess #{} > ", guesses + 1);
+        std::io::stdout().flush()?;
+        input.clear();
+        if std::io::stdin().read_line(&mut input)? == 0 || input.trim() == "quit" {
+            println!("Leaving the tutorial, come back any time with `mastermind tutorial`");
+            return Ok(());
+        }
+        let events = match state.push_string_input(input.trim()) {
+            Ok(x) => x,
+            Err(e) => {
+                println!("That didn't work: {}. Try three letters, like \"rwy\".", e);
+                continue;
+            }
+        };
+        if events.iter().any(GameEvent::ends_game) {
+            break;
+        }
+        if !events
+            .iter()
+            .any(|x| matches!(x, GameEvent::GuessEvaluated { .. }))
+        {
+            println!("That's not a whole guess yet, it needs three colours.");
+            continue;
+        }
+        last = state.last_scored();
+        if let Some(Feedback { exact, misplaced }) = last {
+            println!(
+                "Feedback: {} in the correct position and {} with the right colour",
+                exact, misplaced
+            );
+            if guesses == 0 {
+                explain(exact, misplaced);
+            }
+        }
+    }
+
+    println!(
+        r#"
+You got it, the code was Green, Red, Blue!
+
+In a normal game the code is picked at random, and you only get
+a limited number of tries. While playing, you can also type:
+  "status"  to see how the game is going,
+  "history" to list your guesses so far, and
+  "save"    to save the game to a file, to carry on later.
+Run `mastermind` again to play for real. Good luck!
+"#
+    );
+    Ok(())
+}
+
+//
+// Walks through what the feedback for the first guess means.
+//
+fn explain(exact: usize, colour: usize) {
+    match exact {
+        0 => println!("None of your pegs are the right colour in the right place."),
+        1 => println!(
+            "One peg is the right colour in the right place, but you \
+             don't know which one!"
+        ),
+        x => println!(
+            "{} pegs are the right colour in the right place, but you \
+             don't know which ones!",
+            x
+        ),
+    }
+    match colour {
+        0 => {}
+        1 => println!("One more peg is a colour in the code, but in the wrong place."),
+        x => println!(
+            "{} more pegs are colours in the code, but in the wrong places.",
+            x
+        ),
+    }
+}
@@ -0,0 +1,179 @@
+//!
+//! The rules for wagering points on guesses, used with
+//! `--wagers`.
+//!
+//! Each game starts with `STARTING_POINTS`. Before a guess, the
+//! player can bet some of them on how the guess will do:
+//! - `exact`: At least one peg will be in the correct position.
+//!   This pays out as much as was bet.
+//! - `win`: The guess will be the secret. This is a lot harder,
+//!   so it pays out `WIN_PAYOUT` times as much.
+//!
+//! A wrong bet loses what was bet. Whatever is left at the end
+//! of a game is added to the score for the session.
+//!
+//! Nothing in here touches a `State`, wagering is only ever
+//! about the feedback a guess got, so it can't change how a
+//! game goes.
+//!
+
+use std::fmt;
+use std::str::FromStr;
+
+///
+/// How many points each game starts with.
+///
+pub const STARTING_POINTS: usize = 100;
+
+///
+/// How many times what was bet a correct `win` bet pays.
+///
+pub const WIN_PAYOUT: usize = 5;
+
+///
+/// What the player is betting will happen.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bet {
+    ExactMatch,
+    Win,
+}
+
+///
+/// A bet, and how many points are on it.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Wager {
+    pub amount: usize,
+    pub bet: Bet,
+}
+
+///
+/// Reads a wager written like `20 exact` or `10 win`.
+///
+impl FromStr for Wager {
+    type Err = String;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut words = text.split_whitespace();
+        let amount = words
+            .next()
+            .and_then(|x| x.parse().ok())
+            .filter(|x| *x > 0)
+            .ok_or("A wager starts with a positive number of points")?;
+        let bet = match words.next() {
+            Some("exact") => Bet::ExactMatch,
+            Some("win") => Bet::Win,
+            _ => return Err("A wager is on either `exact` or `win`".into()),
+        };
+        if words.next().is_some() {
+            return Err("A wager is just a number of points and `exact` or `win`".into());
+        }
+        Ok(Self { amount, bet })
+    }
+}
+
+impl fmt::Display for Wager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bet {
+            Bet::ExactMatch => write!(f, "{} on at least one exact match", self.amount),
+            Bet::Win => write!(f, "{} on this being the winning guess", self.amount),
+        }
+    }
+}
+
+impl Wager {
+    ///
+    /// Whether the bet came true for a guess with `exact` pegs
+    /// in the correct position, out of `pegs`.
+    ///
+    pub fn won(&self, exact: usize, pegs: usize) -> bool {
+        match self.bet {
+            Bet::ExactMatch => exact > 0,
+            Bet::Win => exact == pegs,
+        }
+    }
+
+    ///
+    /// How many points the player has after this wager, if they
+    /// had `points` before it. The amount can't be more than
+    /// `points`, which `place` checks.
+    ///
+    pub fn settle(&self, points: usize, exact: usize, pegs: usize) -> usize {
+        if !self.won(exact, pegs) {
+            points - self.amount
+        } else {
+            match self.bet {
+                Bet::ExactMatch => points + self.amount,
+                Bet::Win => points + self.amount * WIN_PAYOUT,
+            }
+        }
+    }
+}
+
+///
+/// Reads a wager from `text`, making sure the player has
+/// enough `points` to cover it.
+///
+pub fn place(text: &str, points: usize) -> Result<Wager, String> {
+    let wager = text.parse::<Wager>()?;
+    if wager.amount > points {
+        Err(format!("You only have {} points to wager", points))
+    } else {
+        Ok(wager)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wager(amount: usize, bet: Bet) -> Wager {
+        Wager { amount, bet }
+    }
+
+    #[test]
+    fn wagers_are_read_as_points_then_a_bet() {
+        assert_eq!("20 exact".parse(), Ok(wager(20, Bet::ExactMatch)));
+        assert_eq!("  5   win ".parse(), Ok(wager(5, Bet::Win)));
+        assert!("0 exact".parse::<Wager>().is_err());
+        assert!("-3 win".parse::<Wager>().is_err());
+        assert!("exact 20".parse::<Wager>().is_err());
+        assert!("20".parse::<Wager>().is_err());
+        assert!("20 place".parse::<Wager>().is_err());
+        assert!("20 win now".parse::<Wager>().is_err());
+    }
+
+    #[test]
+    fn a_wager_cannot_be_more_than_the_points_left() {
+        assert_eq!(place("100 win", STARTING_POINTS), Ok(wager(100, Bet::Win)));
+        assert!(place("101 win", STARTING_POINTS)
+            .unwrap_err()
+            .contains("100"));
+        assert!(place("1 exact", 0).is_err());
+    }
+
+    #[test]
+    fn an_exact_match_bet_pays_evens() {
+        let bet = wager(30, Bet::ExactMatch);
+        assert!(bet.won(1, 4));
+        assert!(bet.won(4, 4));
+        assert!(!bet.won(0, 4));
+        assert_eq!(bet.settle(100, 2, 4), 130);
+        assert_eq!(bet.settle(100, 0, 4), 70);
+    }
+
+    #[test]
+    fn a_win_bet_only_pays_on_the_winning_guess() {
+        let bet = wager(10, Bet::Win);
+        assert!(!bet.won(3, 4));
+        assert!(bet.won(4, 4));
+        assert_eq!(bet.settle(100, 4, 4), 100 + 10 * WIN_PAYOUT);
+        assert_eq!(bet.settle(100, 3, 4), 90);
+    }
+
+    #[test]
+    fn betting_everything_and_losing_leaves_nothing() {
+        let bet = place("100 exact", STARTING_POINTS).unwrap();
+        assert_eq!(bet.settle(STARTING_POINTS, 0, 4), 0);
+    }
+}
@@ -0,0 +1,215 @@
+//!
+//! How feedback is put into words. People who grew up with
+//! other versions of the game have their own names for it, so
+//! there are a few styles to pick from, with `"feedback"` in
+//! the config file or with `--feedback`:
+//!
+//! - `pegs`: "2 in the correct position and 1 with the right
+//!   colour", which is the default.
+//! - `bulls-cows`: "2 bulls and 1 cow".
+//! - `plain-counts`: "2 exact, 1 misplaced".
+//! - `symbols`: "●●○", a filled circle for each peg in the
+//!   correct position and a hollow one for each peg with only
+//!   the right colour.
+//!
+//! Anything else with braces in it is a template, where
+//! `{exact}` and `{misplaced}` are replaced with the numbers,
+//! so `"{exact} hits, {misplaced} blows"` gives "2 hits, 1
+//! blows".
+//!
+
+use super::Feedback;
+use std::str::FromStr;
+
+///
+/// One way of writing feedback, see the top of this file.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum FeedbackStyle {
+    #[default]
+    Pegs,
+    BullsCows,
+    PlainCounts,
+    Symbols,
+    ///
+    /// A template which has been checked to only use the
+    /// placeholders we know about.
+    ///
+    Template(String),
+}
+
+impl FeedbackStyle {
+    ///
+    /// Writes `feedback` in this style.
+    ///
+    pub fn describe(&self, feedback: Feedback) -> String {
+        let Feedback { exact, misplaced } = feedback;
+        // "1 cow" but "2 cows"
+        let counted = |count: usize, name: &str| {
+            format!("{} {}{}", count, name, if count == 1 { "" } else { "s" })
+        };
+        match self {
+            FeedbackStyle::Pegs => format!(
+                "{} in the correct position and {} with the right colour",
+                exact, misplaced
+            ),
+            FeedbackStyle::BullsCows => {
+                format!(
+                    "{} and {}",
+                    counted(exact, "bull"),
+                    counted(misplaced, "cow")
+                )
+            }
+            FeedbackStyle::PlainCounts => format!("{} exact, {} misplaced", exact, misplaced),
+            FeedbackStyle::Symbols => feedback.to_string(),
+            FeedbackStyle::Template(template) => template
+                .replace("{exact}", &exact.to_string())
+                .replace("{misplaced}", &misplaced.to_string()),
+        }
+    }
+}
+
+impl FromStr for FeedbackStyle {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "pegs" => Ok(FeedbackStyle::Pegs),
+            "bulls-cows" => Ok(FeedbackStyle::BullsCows),
+            "plain-counts" => Ok(FeedbackStyle::PlainCounts),
+            "symbols" => Ok(FeedbackStyle::Symbols),
+            x if x.contains('{') => {
+                validate_template(x).map(|()| FeedbackStyle::Template(x.into()))
+            }
+            x => Err(format!(
+                "`{}` isn't a feedback style, use `pegs`, `bulls-cows`, `plain-counts`, \
+                 `symbols` or a template with `{{exact}}` and `{{misplaced}}` in it",
+                x
+            )),
+        }
+    }
+}
+
+//
+// Makes sure every pair of braces in `template` is one of our
+// placeholders, so that a typo like `{exakt}` is caught when
+// the template is read rather than shown to the player.
+//
+fn validate_template(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if rest[..start].contains('}') {
+            return Err(format!("`{}` has a `}}` without a `{{`", template));
+        }
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("`{}` has a `{{` without a `}}`", template))?;
+        match &rest[start..start + end + 1] {
+            "{exact}" | "{misplaced}" => {}
+            x => {
+                return Err(format!(
+                    "`{}` isn't a placeholder, use `{{exact}}` or `{{misplaced}}`",
+                    x
+                ))
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(format!("`{}` has a `}}` without a `{{`", template));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every style, with how it writes no pegs, one of each, and
+    // some of both.
+    fn snapshot(style: &str) -> Vec<String> {
+        let style = style.parse::<FeedbackStyle>().unwrap();
+        [(0, 0), (1, 1), (2, 1), (0, 3), (4, 0)]
+            .iter()
+            .map(|&(exact, misplaced)| style.describe(Feedback { exact, misplaced }))
+            .collect()
+    }
+
+    #[test]
+    fn pegs_style() {
+        assert_eq!(
+            snapshot("pegs"),
+            vec![
+                "0 in the correct position and 0 with the right colour",
+                "1 in the correct position and 1 with the right colour",
+                "2 in the correct position and 1 with the right colour",
+                "0 in the correct position and 3 with the right colour",
+                "4 in the correct position and 0 with the right colour",
+            ]
+        );
+    }
+
+    #[test]
+    fn bulls_cows_style() {
+        assert_eq!(
+            snapshot("bulls-cows"),
+            vec![
+                "0 bulls and 0 cows",
+                "1 bull and 1 cow",
+                "2 bulls and 1 cow",
+                "0 bulls and 3 cows",
+                "4 bulls and 0 cows",
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_counts_style() {
+        assert_eq!(
+            snapshot("plain-counts"),
+            vec![
+                "0 exact, 0 misplaced",
+                "1 exact, 1 misplaced",
+                "2 exact, 1 misplaced",
+                "0 exact, 3 misplaced",
+                "4 exact, 0 misplaced",
+            ]
+        );
+    }
+
+    #[test]
+    fn symbols_style() {
+        // A dash, so that no pegs at all still shows something
+        assert_eq!(snapshot("symbols"), vec!["-", "●○", "●●○", "○○○", "●●●●"]);
+    }
+
+    #[test]
+    fn template_style() {
+        assert_eq!(
+            snapshot("{exact} hits, {misplaced} blows"),
+            vec![
+                "0 hits, 0 blows",
+                "1 hits, 1 blows",
+                "2 hits, 1 blows",
+                "0 hits, 3 blows",
+                "4 hits, 0 blows",
+            ]
+        );
+        assert_eq!(snapshot("{exact}{exact}")[2], "22");
+    }
+
+    #[test]
+    fn the_default_is_pegs() {
+        assert_eq!(FeedbackStyle::default(), FeedbackStyle::Pegs);
+    }
+
+    #[test]
+    fn bad_styles_and_templates_are_caught() {
+        assert!("beads".parse::<FeedbackStyle>().is_err());
+        let error = "{exakt} right".parse::<FeedbackStyle>().unwrap_err();
+        assert!(error.contains("{exakt}"), "{}", error);
+        assert!("{exact".parse::<FeedbackStyle>().is_err());
+        assert!("{exact}}".parse::<FeedbackStyle>().is_err());
+        assert!("} {exact}".parse::<FeedbackStyle>().is_err());
+    }
+}
@@ -0,0 +1,838 @@
+//!
+//! The rules of the game on their own: the colours, scoring a
+//! guess, and picking a secret.
+//!
+//! Nothing in here needs an operating system. There's no
+//! terminal, no files and no clock, and random numbers come
+//! from whatever `RngCore` is handed in, rather than from the
+//! system. So this still builds without `std`, with
+//! `--no-default-features`, for something like a badge with a
+//! few buttons and LEDs on it. It does need to allocate, since
+//! codes are `Vec`s (From the `alloc` crate, which is the part
+//! of `std` which only needs memory).
+//!
+//! A game itself is a `Game`: the secret, the guess being
+//! entered, the guesses so far and whether it's been won or
+//! lost. That's all a program without `std` needs to play.
+//!
+//! Everything else is in `mastermind`, which needs `std`. Its
+//! `State` is a whole session, with hints, saves, settings and
+//! so on, but each of its games is a `Game` from here, and it
+//! uses the colours, scoring and secrets from here too, which
+//! it also re-exports.
+//!
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+///
+/// An enumeration. This lists the colours we can use
+///
+/// In this case, this is similar to a Java enum, in
+/// that there is no attached data to each variant.
+///
+/// An enum is a fully qualified type, meaning we can
+/// implement traits for it, which in this case include
+/// `Clone` (Cloneable), `Copy` (Can copy bitwise),
+/// `Debug` (Displayable), `PartialEq` (`==` operator).
+/// We implement these using a shorthand for auto code
+/// generation called `derive`. It's pretty common in
+/// rust.
+///
+/// `Eq` and `Hash` let colours (and lists of them) be used
+/// in a `HashSet` or as the keys of a `HashMap`, and `Ord`
+/// (Comparable) orders them in the order they are declared
+/// here, which is the same order as `COLOURS`. A list of
+/// colours, like a code, is then ordered lexicographically
+/// (Like words in a dictionary) using that order.
+///
+/// With the `serde` feature, a colour is written as its `name`
+/// (Like `"red"`) rather than as a number, so files stay
+/// readable.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Colour {
+    Red,
+    Blue,
+    White,
+    Yellow,
+    Green,
+    Orange,
+    ///
+    /// Purple and pink are the two extra colours of the deluxe
+    /// game, which are only in play when more than six colours
+    /// are (See `mastermind::GameConfig::palette_size`).
+    ///
+    Purple,
+    Pink,
+    ///
+    /// An empty hole, which is only part of the game when
+    /// `mastermind::GameConfig::allow_blanks` is on. It's scored just like
+    /// another colour, but it isn't in `COLOURS`, so everything
+    /// which goes through the usual colours leaves it out.
+    ///
+    Blank,
+}
+
+///
+/// `Display` is how a colour is written for the player to read,
+/// which is its name with a capital letter, like `Red`. It's
+/// what `{}` uses in `format!`, where `{:?}` uses `Debug`, which
+/// is meant for the programmer instead.
+///
+impl fmt::Display for Colour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.name();
+        // `pad` rather than `write!`, so that `{:<8}` lines it up
+        f.pad(&format!("{}{}", name[..1].to_uppercase(), &name[1..]))
+    }
+}
+
+impl Colour {
+    ///
+    /// How much red, green and blue (Each from 0 to 255) to show
+    /// this colour with, on a terminal which can show any colour.
+    ///
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Colour::Red => (220, 40, 40),
+            Colour::Blue => (40, 90, 220),
+            Colour::White => (240, 240, 240),
+            Colour::Yellow => (240, 210, 40),
+            Colour::Green => (40, 170, 70),
+            Colour::Orange => (245, 140, 20),
+            Colour::Purple => (140, 60, 200),
+            Colour::Pink => (245, 130, 190),
+            Colour::Blank => (90, 90, 90),
+        }
+    }
+
+    ///
+    /// The lowercase name of the colour, used when we write
+    /// colours out to files so that they stay readable.
+    ///
+    pub fn name(self) -> &'static str {
+        match self {
+            Colour::Red => "red",
+            Colour::Blue => "blue",
+            Colour::White => "white",
+            Colour::Yellow => "yellow",
+            Colour::Green => "green",
+            Colour::Orange => "orange",
+            Colour::Purple => "purple",
+            Colour::Pink => "pink",
+            Colour::Blank => "blank",
+        }
+    }
+
+    ///
+    /// The opposite of `name`, this only accepts full names.
+    ///
+    pub fn from_name(name: &str) -> Option<Self> {
+        COLOURS
+            .iter()
+            .chain(&[Colour::Blank])
+            .copied()
+            .find(|x| x.name() == name)
+    }
+}
+
+///
+/// How many colours there are. Settings which say something
+/// about each colour (Like `GameConfig::colour_weights`) are
+/// arrays this long, indexed by `Colour as usize`.
+///
+/// `Colour::Blank` isn't counted, it's declared after all of
+/// the colours so that its index is the one just past the end.
+///
+pub const PALETTE_SIZE: usize = 8;
+
+///
+/// How many colours are in play unless a game picks otherwise,
+/// which is the six of the original game, leaving out purple
+/// and pink (See `GameConfig::palette_size`).
+///
+pub const CLASSIC_PALETTE_SIZE: usize = 6;
+
+///
+/// The fewest pegs a code can have, see `GameConfig::validate`.
+///
+pub const MIN_PEGS: usize = 2;
+
+///
+/// This just enumerates the colours, for ease of use. This
+/// must list them in the same order they're declared in so
+/// that the order of this list agrees with `Ord`. A blank
+/// isn't one of them, see `GameConfig::palette`.
+///
+pub static COLOURS: &[Colour; PALETTE_SIZE] = &[
+    Colour::Red,
+    Colour::Blue,
+    Colour::White,
+    Colour::Yellow,
+    Colour::Green,
+    Colour::Orange,
+    Colour::Purple,
+    Colour::Pink,
+];
+
+///
+/// What a guess got: the number of pegs in the correct
+/// position, and the number with the right colour in the wrong
+/// position.
+///
+/// Naming the two numbers means they can't be swapped by
+/// accident, which was easy to do when this was a pair. Being
+/// `Copy` makes a `Feedback` behave like a Java `int` rather
+/// than an object, so it can be handed around freely.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Feedback {
+    pub exact: usize,
+    pub misplaced: usize,
+}
+
+impl Feedback {
+    ///
+    /// Whether this is the feedback for the secret itself, in a
+    /// game with `size` pegs.
+    ///
+    pub fn is_win(&self, size: usize) -> bool {
+        self.exact == size
+    }
+
+    ///
+    /// How many pegs of the guess are a colour in the secret,
+    /// whether or not they're in the right place.
+    ///
+    pub fn colours_found(&self) -> usize {
+        self.exact + self.misplaced
+    }
+}
+
+///
+/// Writes the key pegs of the classic board game, a filled
+/// circle for each exact peg and a hollow one for each
+/// misplaced peg, like `●●○`. Feedback of nothing at all is a
+/// `-`, so that it doesn't print as an empty string.
+///
+impl fmt::Display for Feedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.colours_found() == 0 {
+            return write!(f, "-");
+        }
+        for _ in 0..self.exact {
+            f.write_str("●")?;
+        }
+        for _ in 0..self.misplaced {
+            f.write_str("○")?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Scores `guess` against `secret`, giving the number of pegs
+/// in the correct position and the number of pegs which are the
+/// right colour but in the wrong position.
+///
+/// Each peg of the secret can only be matched once, so with a
+/// secret of `[Red, Blue, Green, White]` the guess `[Red, Red,
+/// Red, Red]` gets one in the correct position and none with
+/// the right colour: the one red in the secret is used up by
+/// the exact match. This means the two numbers never add up to
+/// more than the number of pegs.
+///
+/// This doesn't need a `State`, so anything which wants to
+/// know what feedback a guess would get (Like the solver) can
+/// use it without playing a game.
+///
+pub fn score_guess<P: Eq>(secret: &[P], guess: &[P]) -> Feedback {
+    // First the exact matches, keeping the pegs left over on each
+    // side. This only needs pegs to be compared, so it works for
+    // any of them, not just colours (See `symbol.rs`).
+    let mut exact = 0;
+    let mut secret_left = Vec::with_capacity(secret.len());
+    let mut guess_left = Vec::with_capacity(guess.len());
+    for (val, actual) in guess.iter().zip(secret) {
+        if val == actual {
+            exact += 1;
+        } else {
+            secret_left.push(actual);
+            guess_left.push(val);
+        }
+    }
+    // Then a leftover guess peg is misplaced if there's a
+    // leftover secret peg of the same colour for it, which is
+    // used up so that it can't be matched twice.
+    let mut misplaced = 0;
+    for val in guess_left {
+        if let Some(idx) = secret_left.iter().position(|&x| x == val) {
+            secret_left.swap_remove(idx);
+            misplaced += 1;
+        }
+    }
+    Feedback { exact, misplaced }
+}
+
+///
+/// A code of `size` pegs picked from `palette` at random with
+/// `rng`, every code being as likely as any other. Without
+/// duplicates no peg is picked twice, so `palette` has to have
+/// at least `size` in it.
+///
+pub fn random_code<P: Copy, R: Rng + ?Sized>(
+    palette: &[P],
+    size: usize,
+    allow_duplicates: bool,
+    rng: &mut R,
+) -> Vec<P> {
+    if allow_duplicates {
+        // `gen_range` includes the start, but not the end.
+        (0..size)
+            .map(|_| palette[rng.gen_range(0, palette.len())])
+            .collect()
+    } else {
+        // This shuffles just the first `size` pegs, which is all
+        // we need, and gives back those and the rest. The `.0`
+        // picks the first of the two.
+        let mut palette = palette.to_vec();
+        palette.partial_shuffle(rng, size).0.to_vec()
+    }
+}
+
+///
+/// How a finished game ended.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Outcome {
+    Won,
+    Lost,
+}
+
+impl Outcome {
+    ///
+    /// The name we use for this outcome in save files.
+    ///
+    pub fn name(self) -> &'static str {
+        match self {
+            Outcome::Won => "won",
+            Outcome::Lost => "lost",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "won" => Some(Outcome::Won),
+            "lost" => Some(Outcome::Lost),
+            _ => None,
+        }
+    }
+}
+
+///
+/// A guess made during a game, with the feedback it got. The
+/// feedback is worked out once, when the guess is finished.
+///
+/// `G` is what the guess is kept as, which is a plain `Vec` of
+/// pegs unless something else is given. `mastermind` keeps a
+/// checked `Guess` instead, see its `Attempt`.
+///
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attempt<G = Vec<Colour>> {
+    pub guess: G,
+    pub feedback: Feedback,
+}
+
+///
+/// Why a `Game` turned something away.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameError {
+    ///
+    /// The guess being entered already has a peg in every
+    /// position.
+    ///
+    GuessFull,
+    ///
+    /// The guess being entered only has `got` of its `needed`
+    /// pegs, so it can't be scored yet.
+    ///
+    GuessTooShort { got: usize, needed: usize },
+    ///
+    /// A whole guess with `found` pegs, for a secret of
+    /// `expected`.
+    ///
+    WrongLength { expected: usize, found: usize },
+    ///
+    /// The game has been won or lost, so nothing more can be
+    /// guessed.
+    ///
+    GameOver,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::GuessFull => write!(f, "The guess is full"),
+            GameError::GuessTooShort { got, needed } => {
+                write!(f, "The guess only has {} of its {} pegs", got, needed)
+            }
+            GameError::WrongLength { expected, found } => write!(
+                f,
+                "A guess should have {} pegs, but this one has {}",
+                expected, found
+            ),
+            GameError::GameOver => write!(f, "The game is over"),
+        }
+    }
+}
+
+///
+/// One game on its own: the secret, the guess being entered a
+/// peg at a time, the guesses finished so far, and whether it's
+/// been won or lost. For something without `std` (Like a badge
+/// which takes a peg per button press) this is the whole game,
+/// and `mastermind::State` plays each of its games with one,
+/// with its settings, hints, saves and so on around it.
+///
+/// The only setting it knows about is the limit on tries, so
+/// any peg goes into the guess. Turning away duplicates, or
+/// colours which aren't in play, is up to whatever is in front
+/// of it, the way `State::input_buffer` does.
+///
+/// `G` is what finished guesses are kept as, see `Attempt`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Game<P = Colour, G = Vec<P>> {
+    secret: Vec<P>,
+    buffer: Vec<P>,
+    attempts: Vec<Attempt<G>>,
+    max_tries: Option<usize>,
+}
+
+impl<P: Copy + Eq, G: AsRef<[P]>> Game<P, G> {
+    ///
+    /// A game against `secret`, with `max_tries` guesses or no
+    /// limit if that's `None`.
+    ///
+    pub fn new(secret: Vec<P>, max_tries: Option<usize>) -> Self {
+        Self {
+            buffer: Vec::with_capacity(secret.len()),
+            secret,
+            attempts: Vec::new(),
+            max_tries,
+        }
+    }
+
+    ///
+    /// Starts the next game, against `secret`, with the same
+    /// limit on tries.
+    ///
+    pub fn restart(&mut self, secret: Vec<P>) {
+        self.secret = secret;
+        self.buffer.clear();
+        self.attempts.clear();
+    }
+
+    ///
+    /// Swaps the secret for another one part way through a game,
+    /// for whoever answers the guesses having changed their mind
+    /// (See `mastermind::AdversarialCodeMaker`). The guesses made
+    /// so far keep the feedback they got.
+    ///
+    pub fn set_secret(&mut self, secret: Vec<P>) {
+        self.secret = secret;
+    }
+
+    pub fn secret(&self) -> &[P] {
+        &self.secret
+    }
+
+    ///
+    /// How many pegs the secret has, and so every guess.
+    ///
+    pub fn size(&self) -> usize {
+        self.secret.len()
+    }
+
+    pub fn max_tries(&self) -> Option<usize> {
+        self.max_tries
+    }
+
+    ///
+    /// The pegs entered so far of the guess which isn't finished.
+    ///
+    pub fn buffer(&self) -> &[P] {
+        &self.buffer
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.buffer.len() == self.size()
+    }
+
+    ///
+    /// The guesses finished this game, oldest first.
+    ///
+    pub fn attempts(&self) -> &[Attempt<G>] {
+        &self.attempts
+    }
+
+    ///
+    /// How many more guesses can be made, or `None` if there's no
+    /// limit.
+    ///
+    pub fn tries_left(&self) -> Option<usize> {
+        self.max_tries
+            .map(|x| x.saturating_sub(self.attempts.len()))
+    }
+
+    ///
+    /// How the game ended, or `None` while it's still going. It's
+    /// won once a guess gets every peg in the correct position,
+    /// and lost once the tries run out without that.
+    ///
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self.attempts.last() {
+            Some(x) if x.feedback.is_win(self.size()) => Some(Outcome::Won),
+            _ if self.tries_left() == Some(0) => Some(Outcome::Lost),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Adds `peg` to the end of the guess being entered.
+    ///
+    pub fn push(&mut self, peg: P) -> Result<(), GameError> {
+        if self.outcome().is_some() {
+            return Err(GameError::GameOver);
+        }
+        if self.is_full() {
+            return Err(GameError::GuessFull);
+        }
+        self.buffer.push(peg);
+        Ok(())
+    }
+
+    ///
+    /// Takes the last peg back out of the guess being entered,
+    /// or gives `None` if it's empty.
+    ///
+    pub fn pop(&mut self) -> Option<P> {
+        self.buffer.pop()
+    }
+
+    ///
+    /// Empties the guess being entered.
+    ///
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    ///
+    /// The feedback the guess being entered would get, without
+    /// finishing it, or `None` until it has every peg.
+    ///
+    pub fn score_buffer(&self) -> Option<Feedback> {
+        if self.is_full() {
+            Some(score_guess(&self.secret, &self.buffer))
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Empties out the guess being entered and gives it back,
+    /// once it has every peg. It's left alone until then.
+    ///
+    pub fn take_buffer(&mut self) -> Option<Vec<P>> {
+        if self.is_full() {
+            Some(self.buffer.drain(..).collect())
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Finishes `guess` with `feedback`, however that was worked
+    /// out, giving back how the game ended if this ended it.
+    /// Most of the time the feedback is `score_guess` against the
+    /// secret, which is what `guess` does.
+    ///
+    pub fn record(&mut self, guess: G, feedback: Feedback) -> Result<Option<Outcome>, GameError> {
+        if self.outcome().is_some() {
+            return Err(GameError::GameOver);
+        }
+        let found = guess.as_ref().len();
+        if found != self.size() {
+            return Err(GameError::WrongLength {
+                expected: self.size(),
+                found,
+            });
+        }
+        self.attempts.push(Attempt { guess, feedback });
+        Ok(self.outcome())
+    }
+
+    ///
+    /// Scores `guess` against the secret and finishes it, giving
+    /// back its feedback and how the game ended if this ended it.
+    ///
+    pub fn guess(&mut self, guess: G) -> Result<(Feedback, Option<Outcome>), GameError> {
+        let feedback = score_guess(&self.secret, guess.as_ref());
+        self.record(guess, feedback).map(|x| (feedback, x))
+    }
+}
+
+impl<P: Copy + Eq> Game<P> {
+    ///
+    /// Scores the guess being entered and finishes it, like
+    /// `guess`, once it has every peg.
+    ///
+    pub fn commit(&mut self) -> Result<(Feedback, Option<Outcome>), GameError> {
+        if self.outcome().is_some() {
+            return Err(GameError::GameOver);
+        }
+        let needed = self.size();
+        let got = self.buffer.len();
+        let guess = self
+            .take_buffer()
+            .ok_or(GameError::GuessTooShort { got, needed })?;
+        self.guess(guess)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn each_secret_peg_is_only_matched_once() {
+        use Colour::*;
+        let secret = [Red, Blue, Green, White];
+        let feedback = score_guess(&secret, &[Red, Red, Red, Red]);
+        assert_eq!(
+            feedback,
+            Feedback {
+                exact: 1,
+                misplaced: 0
+            }
+        );
+        let feedback = score_guess(&secret, &[Blue, Red, White, Green]);
+        assert_eq!(
+            feedback,
+            Feedback {
+                exact: 0,
+                misplaced: 4
+            }
+        );
+        assert!(score_guess(&secret, &secret).is_win(secret.len()));
+    }
+
+    // Shorthand for the feedback `score_guess` is expected to give
+    fn pegs(exact: usize, misplaced: usize) -> Feedback {
+        Feedback { exact, misplaced }
+    }
+
+    #[test]
+    fn feedback_is_written_as_key_pegs() {
+        assert_eq!(pegs(0, 0).to_string(), "-");
+        assert_eq!(pegs(1, 0).to_string(), "●");
+        assert_eq!(pegs(0, 1).to_string(), "○");
+        assert_eq!(pegs(2, 1).to_string(), "●●○");
+        assert_eq!(pegs(0, 4).to_string(), "○○○○");
+        assert_eq!(pegs(4, 0).to_string(), "●●●●");
+        assert!(pegs(4, 0).is_win(4));
+        assert!(!pegs(4, 0).is_win(5));
+        assert!(!pegs(2, 2).is_win(4));
+        assert_eq!(pegs(2, 1).colours_found(), 3);
+    }
+
+    #[test]
+    fn repeats_in_the_secret_are_matched_once_each() {
+        use Colour::*;
+        let secret = [Red, Red, Blue, Blue];
+        assert_eq!(
+            score_guess(&secret, &[Red, Green, Green, Green]),
+            pegs(1, 0)
+        );
+        assert_eq!(
+            score_guess(&secret, &[Green, Red, Green, Green]),
+            pegs(1, 0)
+        );
+        assert_eq!(
+            score_guess(&secret, &[Green, Green, Red, Green]),
+            pegs(0, 1)
+        );
+        assert_eq!(score_guess(&secret, &[Blue, Blue, Red, Red]), pegs(0, 4));
+        assert_eq!(score_guess(&secret, &[Red, Blue, Green, White]), pegs(1, 1));
+    }
+
+    #[test]
+    fn repeats_on_both_sides_are_matched_once_each() {
+        use Colour::*;
+        assert_eq!(
+            score_guess(&[Red, Red, Blue, Blue], &[Blue, Red, Red, Green]),
+            pegs(1, 2)
+        );
+        assert_eq!(
+            score_guess(&[Red, Red, Red, Blue], &[Red, Red, Blue, Blue]),
+            pegs(3, 0)
+        );
+        assert_eq!(
+            score_guess(&[Blue, Red, Red, Green], &[Red, Red, Blue, Blue]),
+            pegs(1, 2)
+        );
+    }
+
+    #[test]
+    fn feedback_never_has_more_pegs_than_the_code() {
+        // Every pair of codes with 3 pegs from 6 colours, checked
+        // against the textbook count: the misplaced pegs are every
+        // colour's smaller count, less the exact matches.
+        let codes = (0..6 * 6 * 6)
+            .map(|x| [COLOURS[x % 6], COLOURS[x / 6 % 6], COLOURS[x / 36]])
+            .collect::<Vec<_>>();
+        for secret in &codes {
+            for guess in &codes {
+                let feedback = score_guess(secret, guess);
+                assert!(feedback.exact + feedback.misplaced <= 3);
+                let common = COLOURS[..6]
+                    .iter()
+                    .map(|colour| {
+                        let secret = secret.iter().filter(|&x| x == colour).count();
+                        let guess = guess.iter().filter(|&x| x == colour).count();
+                        secret.min(guess)
+                    })
+                    .sum::<usize>();
+                assert_eq!(feedback.exact + feedback.misplaced, common);
+                assert_eq!(score_guess(guess, secret), feedback);
+            }
+        }
+    }
+
+    #[test]
+    fn random_codes_come_from_the_palette() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for &allow_duplicates in &[false, true] {
+            for _ in 0..100 {
+                let code = random_code(&COLOURS[..6], 4, allow_duplicates, &mut rng);
+                assert_eq!(code.len(), 4);
+                assert!(code.iter().all(|x| COLOURS[..6].contains(x)));
+                if !allow_duplicates {
+                    let mut sorted = code.clone();
+                    sorted.sort();
+                    sorted.dedup();
+                    assert_eq!(sorted.len(), 4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_game_is_won_a_peg_at_a_time() {
+        use Colour::*;
+        let mut game: Game = Game::new(vec![Red, Blue, Green], Some(5));
+        assert_eq!(game.push(Red), Ok(()));
+        assert_eq!(game.push(Green), Ok(()));
+        assert_eq!(game.score_buffer(), None);
+        assert_eq!(
+            game.commit(),
+            Err(GameError::GuessTooShort { got: 2, needed: 3 })
+        );
+        assert_eq!(game.push(Blue), Ok(()));
+        assert_eq!(game.push(Blue), Err(GameError::GuessFull));
+        assert_eq!(game.score_buffer(), Some(pegs(1, 2)));
+        assert_eq!(game.commit(), Ok((pegs(1, 2), None)));
+        assert!(game.buffer().is_empty());
+        assert_eq!(game.tries_left(), Some(4));
+
+        for &colour in &[Red, Blue, White] {
+            game.push(colour).unwrap();
+        }
+        assert_eq!(game.pop(), Some(White));
+        game.push(Green).unwrap();
+        assert_eq!(game.commit(), Ok((pegs(3, 0), Some(Outcome::Won))));
+        assert_eq!(game.outcome(), Some(Outcome::Won));
+        assert_eq!(game.attempts().len(), 2);
+        assert_eq!(game.attempts()[0].guess, vec![Red, Green, Blue]);
+        assert_eq!(game.push(Red), Err(GameError::GameOver));
+        assert_eq!(game.guess(vec![Red, Blue, Green]), Err(GameError::GameOver));
+
+        game.restart(vec![White, Red, Blue]);
+        assert_eq!(game.outcome(), None);
+        assert!(game.attempts().is_empty());
+        assert_eq!(game.tries_left(), Some(5));
+    }
+
+    #[test]
+    fn a_game_is_lost_once_its_tries_run_out() {
+        use Colour::*;
+        let mut game: Game = Game::new(vec![Red, Blue], Some(2));
+        assert_eq!(
+            game.guess(vec![Red]),
+            Err(GameError::WrongLength {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(game.guess(vec![Blue, Red]), Ok((pegs(0, 2), None)));
+        // The feedback can come from somewhere other than the secret
+        assert_eq!(
+            game.record(vec![Red, Green], pegs(0, 0)),
+            Ok(Some(Outcome::Lost))
+        );
+        assert_eq!(game.tries_left(), Some(0));
+        assert_eq!(game.attempts()[1].feedback, pegs(0, 0));
+        assert_eq!(game.push(Red), Err(GameError::GameOver));
+        // Without a limit it's never lost
+        let mut endless: Game = Game::new(vec![Red, Blue], None);
+        for _ in 0..100 {
+            assert_eq!(endless.guess(vec![Blue, Red]).unwrap().1, None);
+        }
+        assert_eq!(endless.tries_left(), None);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for &colour in COLOURS.iter().chain(&[Colour::Blank]) {
+            assert_eq!(Colour::from_name(colour.name()), Some(colour));
+        }
+        assert_eq!(Colour::from_name("Red"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_uses_the_names_of_colours() {
+        for &colour in COLOURS.iter().chain(&[Colour::Blank]) {
+            let text = serde_json::to_string(&colour).unwrap();
+            assert_eq!(text, format!("\"{}\"", colour.name()));
+            assert_eq!(serde_json::from_str::<Colour>(&text).unwrap(), colour);
+        }
+        assert!(serde_json::from_str::<Colour>("0").is_err());
+        assert!(serde_json::from_str::<Colour>("\"Red\"").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn feedback_round_trips_through_serde() {
+        let feedback = Feedback {
+            exact: 1,
+            misplaced: 2,
+        };
+        let text = serde_json::to_string(&feedback).unwrap();
+        assert_eq!(text, r#"{"exact":1,"misplaced":2}"#);
+        assert_eq!(serde_json::from_str::<Feedback>(&text).unwrap(), feedback);
+    }
+}
@@ -0,0 +1,124 @@
+//!
+//! Running the game with input which runs out part way through,
+//! the way a piped file or Ctrl-D does. Each run has a data
+//! directory of its own, so that no profile or autosave from
+//! another run (Or a real one) changes the questions asked.
+//!
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mastermind-eof-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn mastermind(name: &str) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_mastermind"));
+    command.env("MASTERMIND_DATA_DIR", data_dir(name));
+    command
+}
+
+// Runs the game with `input` and nothing more.
+fn play(name: &str, input: &str) -> Output {
+    let mut child = mastermind(name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let _ = std::fs::remove_dir_all(data_dir(name));
+    output
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// An answer to every setup question, in the order they're
+// asked. The blank ones take the default.
+const SETUP: [&str; 7] = [
+    "Sam", // Who is playing?
+    "no",  // A tutorial?
+    "",    // Colours or digits?
+    "",    // Duplicates?
+    "",    // How many colours?
+    "",    // How many pegs?
+    "",    // How many tries?
+];
+
+#[test]
+fn input_running_out_at_any_setup_question_abandons_the_session() {
+    for answered in 0..=SETUP.len() {
+        let input = SETUP[..answered]
+            .iter()
+            .map(|x| format!("{}\n", x))
+            .collect::<String>();
+        let output = play(&format!("setup-{}", answered), &input);
+        let stdout = text(&output.stdout);
+        assert!(
+            output.status.success(),
+            "{}\n{}",
+            stdout,
+            text(&output.stderr)
+        );
+        assert!(
+            stdout.ends_with("Game #1 was abandoned with 0 attempts\n"),
+            "after {} answers:\n{}",
+            answered,
+            stdout
+        );
+        assert!(stdout.contains("Ran out of input, so the session was abandoned"));
+    }
+}
+
+#[test]
+fn input_running_out_mid_game_keeps_the_guesses_made() {
+    let mut input = SETUP.iter().map(|x| format!("{}\n", x)).collect::<String>();
+    input.push_str("rbwy\nrb");
+    let output = play("mid-game", &input);
+    let stdout = text(&output.stdout);
+    assert!(output.status.success(), "{}", text(&output.stderr));
+    // One guess in 360 is the secret
+    assert!(
+        stdout.contains("Game #1 was abandoned with 1 attempts")
+            || stdout.contains("Game #1 with pegs"),
+        "{}",
+        stdout
+    );
+    // A prompt is printed for every line, and one more for the
+    // end of the input, but never any more than that
+    assert!(
+        stdout.matches("Enter next colours").count() <= 3,
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn stdout_closing_stops_the_game_quietly() {
+    let mut child = mastermind("stdout")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Nobody is reading what the game writes any more
+    drop(child.stdout.take());
+    let mut stdin = child.stdin.take().unwrap();
+    let _ = stdin.write_all(SETUP.join("\n").as_bytes());
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    let stderr = text(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
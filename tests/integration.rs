@@ -0,0 +1,125 @@
+//!
+//! Plays whole games through the library, the way another crate
+//! would: only what's brought up to the top of the crate is
+//! used, and nothing is printed or read.
+//!
+
+use mastermind::rules::score_guess;
+use mastermind::{
+    Colour, ConfigError, GameConfig, GameEvent, Guess, GuessError, MastermindError, Outcome, State,
+    StateBuilder, TurnOutcome, COLOURS,
+};
+
+fn seeded(config: GameConfig) -> State<'static> {
+    StateBuilder::new()
+        .config(config)
+        .seed(281)
+        .build()
+        .unwrap()
+}
+
+// Some code the same length as `secret` which isn't it
+fn wrong(secret: &[Colour]) -> Vec<Colour> {
+    let mut code = secret.to_vec();
+    code.rotate_left(1);
+    code
+}
+
+#[test]
+fn a_game_is_won_through_the_public_api() {
+    let config = GameConfig::default();
+    let mut state = seeded(config);
+    let secret = state.reveal_secret().to_vec();
+    assert_eq!(state.size(), 4);
+    assert!(!state.allows_duplicates());
+    assert_eq!(state.remaining_tries(), config.max_tries);
+
+    let guess = Guess::for_config(wrong(&secret), config).unwrap();
+    assert_eq!(state.enter_guess(&guess).unwrap(), None);
+    assert_eq!(state.attempts(), 1);
+    assert_eq!(state.history()[0].feedback, score_guess(&secret, &guess));
+
+    // The rest a colour at a time
+    let (last, first) = secret.split_last().unwrap();
+    for &colour in first {
+        assert_eq!(state.input_buffer(colour).unwrap(), TurnOutcome::Pending);
+    }
+    assert_eq!(state.buffered(), first);
+    assert_eq!(
+        state.input_buffer(*last).unwrap(),
+        TurnOutcome::Won { attempts: 2 }
+    );
+    let record = &state.games()[0];
+    assert_eq!(record.outcome, Outcome::Won);
+    assert_eq!(record.attempts, 2);
+    assert_eq!(record.secret, secret);
+    // And the next game is ready to go
+    assert_eq!(state.attempts(), 0);
+}
+
+#[test]
+fn a_game_is_lost_from_lines_of_text() {
+    let config = GameConfig {
+        max_tries: Some(2),
+        auto_max_tries: false,
+        ..GameConfig::default()
+    };
+    let mut state = seeded(config);
+    let secret = state.reveal_secret().to_vec();
+    let line: String = wrong(&secret)
+        .iter()
+        .map(|x| x.name())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let events = state.push_string_input(&line).unwrap();
+    assert!(!events.iter().any(GameEvent::ends_game));
+    assert_eq!(state.remaining_tries(), Some(1));
+    // Any other wrong code will do for the second
+    let line: String = wrong(&secret)
+        .iter()
+        .rev()
+        .map(|x| x.name())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let events = state.push_string_input(&line).unwrap();
+    assert!(events.iter().any(GameEvent::ends_game));
+    assert_eq!(state.games()[0].outcome, Outcome::Lost);
+    assert_eq!(state.games()[0].attempts, 2);
+}
+
+#[test]
+fn mistakes_come_back_as_errors_to_match_on() {
+    let mut state = seeded(GameConfig::default());
+    let error = state.push_string_input("rrgb").unwrap_err();
+    assert_eq!(
+        error.error,
+        MastermindError::InvalidGuess(GuessError::Duplicate(Colour::Red))
+    );
+    assert_eq!(error.position, Some(1));
+    assert!(state.buffered().is_empty());
+    let error = state.input_buffer(COLOURS[7]).unwrap_err();
+    assert_eq!(error, MastermindError::NotInPlay(COLOURS[7]));
+    let too_many = GameConfig {
+        pegs: 9,
+        ..GameConfig::default()
+    };
+    assert!(matches!(
+        StateBuilder::new().config(too_many).build().err(),
+        Some(ConfigError::TooManyPegs { requested: 9, .. })
+    ));
+}
+
+#[test]
+fn seeded_sessions_play_the_same_games() {
+    let secrets = || {
+        let mut state = seeded(GameConfig::default());
+        (0..5)
+            .map(|_| {
+                let secret = state.reveal_secret().to_vec();
+                state.forfeit();
+                secret
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(secrets(), secrets());
+}
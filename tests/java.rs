@@ -0,0 +1,60 @@
+//!
+//! Runs the smoke test in `main` of `java/Mastermind.java`
+//! against the library, the way a Java program would load it.
+//! This needs a JDK (`javac` and `java` on the `PATH`), and
+//! says it was skipped without one:
+//!
+//! ```text
+//! cargo test --features java --test java
+//! ```
+//!
+
+#![cfg(feature = "java")]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+//
+// Where cargo put the library for this test, which is next to
+// the test itself, in `target/debug/deps`.
+//
+fn library_dir() -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    exe.parent().unwrap().to_path_buf()
+}
+
+#[test]
+fn the_java_smoke_test_passes() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let classes = Path::new(env!("CARGO_TARGET_TMPDIR")).join("java");
+    let compiled = Command::new("javac")
+        .arg("-d")
+        .arg(&classes)
+        .arg(root.join("java").join("Mastermind.java"))
+        .status();
+    match compiled {
+        Err(_) => {
+            eprintln!("Skipped, there's no `javac` to compile the Java side with");
+            return;
+        }
+        Ok(status) => assert!(status.success(), "javac failed"),
+    }
+    let output = Command::new("java")
+        .arg(format!("-Djava.library.path={}", library_dir().display()))
+        .arg("-cp")
+        .arg(&classes)
+        .arg("Mastermind")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}{}", stdout, stderr);
+    assert!(stdout.contains("\"outcome\":\"in_progress\""), "{}", stdout);
+    assert_eq!(
+        stdout.matches("Rejected as expected").count(),
+        5,
+        "{}",
+        stdout
+    );
+    assert!(stdout.trim_end().ends_with("All good"), "{}", stdout);
+}
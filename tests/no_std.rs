@@ -0,0 +1,43 @@
+//!
+//! Builds the library without `std`, for a microcontroller
+//! which doesn't have it, like `src/lib.rs` describes. That
+//! catches anything in `src/rules.rs` which only builds with
+//! `std` by accident, since building for the computer we're on
+//! would quietly use its `std` anyway. This needs the target
+//! installed, and says it was skipped without it:
+//!
+//! ```text
+//! rustup target add thumbv6m-none-eabi
+//! ```
+//!
+
+use std::path::Path;
+use std::process::Command;
+
+const TARGET: &str = "thumbv6m-none-eabi";
+
+#[test]
+fn the_rules_build_for_a_target_without_std() {
+    // A directory of its own, so that this doesn't wait on the
+    // build which is running the tests
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no_std");
+    let output = Command::new(env!("CARGO"))
+        .args([
+            "build",
+            "--lib",
+            "--no-default-features",
+            "--target",
+            TARGET,
+        ])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() && stderr.contains("target may not be installed") {
+        eprintln!("Skipped, the `{}` target isn't installed", TARGET);
+        return;
+    }
+    assert!(output.status.success(), "{}", stderr);
+}
@@ -0,0 +1,131 @@
+//!
+//! Loads the saves in `tests/fixtures`, one for each version
+//! of the save layout, to make sure that files written by
+//! older versions of the game keep loading.
+//!
+//! A new version of the layout should add a fixture here, made
+//! by saving a game with the version which writes it.
+//!
+
+use mastermind::mastermind::json::Json;
+use mastermind::mastermind::save::{self, load_from_file, save_to_file, SaveError, FORMAT_VERSION};
+use mastermind::mastermind::{Colour, Outcome, State, StateBuilder};
+use std::path::PathBuf;
+use Colour::*;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+fn load(name: &str) -> Result<State<'static>, SaveError> {
+    load_from_file(&fixture(name), |_| {}, |_| {}, false)
+}
+
+#[test]
+fn version_1_loads_without_timings() {
+    let state = load("save-v1.json").unwrap();
+    assert_eq!(state.config().max_tries, Some(10));
+    assert_eq!(state.reveal_secret(), &[Red, Blue, Green, Yellow]);
+    assert_eq!(state.attempts(), 1);
+    assert_eq!(state.buffered(), &[Blue]);
+    let games = &state.games();
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].outcome, Outcome::Won);
+    assert_eq!(games[1].outcome, Outcome::Lost);
+    // Version 1 didn't keep any of these
+    assert!(games
+        .iter()
+        .all(|x| x.duration.is_none() && x.settings.is_none()));
+    assert!(games.iter().all(|x| x.guesses.is_empty()));
+}
+
+#[test]
+fn version_2_loads_with_the_classic_colours() {
+    let state = load("save-v2.json").unwrap();
+    assert!(state.config().allow_duplicates && state.config().hardcore);
+    // Version 2 was from before there was a choice of colours
+    assert_eq!(state.config().palette().len(), 6);
+    assert_eq!(state.attempts(), 2);
+    assert_eq!(state.remaining_tries(), Some(10));
+    let games = &state.games();
+    assert_eq!(games[0].duration.map(|x| x.as_secs()), Some(210));
+    assert!(games[0].hardcore);
+    assert_eq!(games[1].duration, None);
+    assert_eq!(games[1].speed_bonus, 0);
+}
+
+#[cfg(feature = "binary")]
+#[test]
+fn version_3_loads_as_json_and_as_binary() {
+    let json = load("save-v3.json").unwrap();
+    let binary = load("save-v3.mmsave").unwrap();
+    let (mut from_json, from_binary) = (json.snapshot(), binary.snapshot());
+    // The time taken goes on from when each was loaded
+    from_json.elapsed = from_binary.elapsed;
+    assert_eq!(from_json, from_binary);
+    assert_eq!(json.config().hint_points, 3);
+    assert_eq!(json.reveal_secret(), &[White, Red, Green, Blue]);
+    assert_eq!(json.buffered(), &[White]);
+    let game = &json.games()[0];
+    assert_eq!(game.outcome, Outcome::Won);
+    assert_eq!(game.guesses.len(), 2);
+    assert!(game.settings.is_some());
+}
+
+#[test]
+fn every_version_up_to_the_newest_has_a_fixture() {
+    for version in 1..=FORMAT_VERSION {
+        let name = format!("save-v{}.json", version);
+        assert!(load(&name).is_ok(), "{} didn't load", name);
+    }
+}
+
+#[test]
+fn a_newer_version_says_so() {
+    match load("save-v99.json") {
+        Err(SaveError::NewerVersion(99)) => {}
+        Err(e) => panic!("expected a newer version error, got {}", e),
+        Ok(_) => panic!("a save from a newer version loaded"),
+    }
+    let message = load("save-v99.json").err().unwrap().to_string();
+    assert!(
+        message.contains("created by a newer version"),
+        "{}",
+        message
+    );
+}
+
+fn version_of(json: &Json) -> Option<usize> {
+    json.get("format_version").and_then(Json::as_usize)
+}
+
+#[test]
+fn saves_are_written_in_the_newest_version() {
+    let state = StateBuilder::new().seed(3).build().unwrap();
+    assert_eq!(version_of(&save::save(&state)), Some(FORMAT_VERSION));
+    let path = std::env::temp_dir().join(format!("mastermind-newest-{}.json", std::process::id()));
+    save_to_file(&state, &path, None).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        version_of(&Json::parse(&text).unwrap()),
+        Some(FORMAT_VERSION)
+    );
+}
+
+#[test]
+fn version_1_is_saved_again_as_the_newest() {
+    let old = load("save-v1.json").unwrap();
+    let json = save::save(&old);
+    assert_eq!(version_of(&json), Some(FORMAT_VERSION));
+    let again = save::load(&json, |_| {}, |_| {}, false).unwrap();
+    assert_eq!(again.reveal_secret(), &[Red, Blue, Green, Yellow]);
+    assert_eq!(again.attempts(), 1);
+    assert_eq!(again.buffered(), &[Blue]);
+    assert_eq!(again.remaining_tries(), old.remaining_tries());
+    assert_eq!(again.config(), old.config());
+    assert_eq!(again.games(), old.games());
+}
@@ -0,0 +1,51 @@
+//!
+//! Save slots, in a data directory of their own (With
+//! `MASTERMIND_DATA_DIR`) so that real saves are never touched.
+//! The environment is shared by the whole process, so this
+//! file has only one test, and its own process.
+//!
+
+use mastermind::mastermind::save::{autosave, clear_autosave, describe, has_autosave};
+use mastermind::mastermind::storage::{
+    autosave_path, data_dir, delete_slot, list_slots, DEFAULT_SLOT,
+};
+use mastermind::mastermind::{GameConfig, StateBuilder};
+
+#[test]
+fn slots_are_kept_apart() {
+    let root = std::env::temp_dir().join(format!("mastermind-slots-{}", std::process::id()));
+    std::env::set_var("MASTERMIND_DATA_DIR", &root);
+    assert_eq!(data_dir(), root);
+    assert_eq!(list_slots().unwrap(), Vec::<String>::new());
+
+    let config = GameConfig {
+        pegs: 5,
+        ..GameConfig::default()
+    };
+    let mut state = StateBuilder::new().config(config).seed(1).build().unwrap();
+    let kid = autosave_path("kid").unwrap();
+    assert!(kid.starts_with(root.join("slots").join("kid")));
+    assert!(!has_autosave(&kid));
+    autosave(&state, &kid).unwrap();
+    state.push_string_input("rbwyg").unwrap();
+    let default = autosave_path(DEFAULT_SLOT).unwrap();
+    autosave(&state, &default).unwrap();
+
+    assert_eq!(list_slots().unwrap(), vec!["default", "kid"]);
+    assert_eq!(describe(&kid).unwrap().attempts, 0);
+    assert_eq!(describe(&default).unwrap().attempts, 1);
+    assert_eq!(describe(&default).unwrap().config.pegs, 5);
+
+    // Saving again keeps the one before as a backup
+    autosave(&state, &kid).unwrap();
+    clear_autosave(&kid);
+    assert!(!has_autosave(&kid));
+    delete_slot("kid").unwrap();
+    assert_eq!(list_slots().unwrap(), vec!["default"]);
+    assert!(has_autosave(&default));
+
+    // A directory which isn't a valid slot name is left out
+    std::fs::create_dir_all(root.join("slots").join("not a slot")).unwrap();
+    assert_eq!(list_slots().unwrap(), vec!["default"]);
+    std::fs::remove_dir_all(&root).unwrap();
+}